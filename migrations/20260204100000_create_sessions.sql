@@ -0,0 +1,23 @@
+-- Server-side login sessions.
+--
+-- The browser only ever holds an opaque random token; we store its SHA-256
+-- hash so a leaked database dump cannot be replayed as a cookie.
+
+CREATE TABLE IF NOT EXISTS sessions
+(
+    id          uuid PRIMARY KEY     DEFAULT gen_random_uuid(),
+    -----------------------------------------------------------
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash  text        NOT NULL UNIQUE,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    last_seen   timestamptz NOT NULL DEFAULT now(),
+    expires_at  timestamptz NOT NULL,
+    ip          text                 DEFAULT NULL,
+    user_agent  text                 DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_user_id
+    ON sessions(user_id);
+
+CREATE INDEX IF NOT EXISTS idx_sessions_expires_at
+    ON sessions(expires_at);
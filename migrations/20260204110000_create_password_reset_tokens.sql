@@ -0,0 +1,19 @@
+-- One-time password reset tokens.
+--
+-- As with sessions, only the SHA-256 hash of the emailed token is stored.
+-- A token is spent by setting used_at; spent or expired rows are purged
+-- opportunistically.
+
+CREATE TABLE IF NOT EXISTS password_reset_tokens
+(
+    id          uuid PRIMARY KEY     DEFAULT gen_random_uuid(),
+    -----------------------------------------------------------
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash  text        NOT NULL UNIQUE,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    expires_at  timestamptz NOT NULL,
+    used_at     timestamptz          DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_reset_tokens_user_id
+    ON password_reset_tokens(user_id);
@@ -0,0 +1,24 @@
+-- Email verification links.
+--
+-- Each token confirms one specific address: on registration it is the
+-- account's own email, on an email change it is the new address, which
+-- only replaces users.email once the link has been followed.
+
+CREATE TABLE IF NOT EXISTS email_verification_tokens
+(
+    id          uuid PRIMARY KEY     DEFAULT gen_random_uuid(),
+    -----------------------------------------------------------
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email       text        NOT NULL,
+    token_hash  text        NOT NULL UNIQUE,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    expires_at  timestamptz NOT NULL,
+    used_at     timestamptz          DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_verification_tokens_user_id
+    ON email_verification_tokens(user_id);
+
+-- Site-wide switch: only users with a verified email may publish.
+ALTER TABLE sites
+    ADD COLUMN IF NOT EXISTS require_verified_email boolean NOT NULL DEFAULT false;
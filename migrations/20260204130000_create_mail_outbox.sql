@@ -0,0 +1,26 @@
+-- Outgoing mail queue.
+--
+-- Handlers only insert rows here; a background worker delivers them and
+-- reschedules failures with backoff. next_attempt_at doubles as a lease:
+-- claiming a row pushes it into the future so a crashed worker's mail is
+-- picked up again later.
+
+CREATE TABLE IF NOT EXISTS mail_outbox
+(
+    id               uuid PRIMARY KEY     DEFAULT gen_random_uuid(),
+    ----------------------------------------------------------------
+    recipient        text        NOT NULL,
+    subject          text        NOT NULL,
+    body             text        NOT NULL,
+    status           text        NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'failed')),
+    attempts         integer     NOT NULL DEFAULT 0,
+    last_error       text                 DEFAULT NULL,
+    created_at       timestamptz NOT NULL DEFAULT now(),
+    next_attempt_at  timestamptz NOT NULL DEFAULT now(),
+    sent_at          timestamptz          DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_mail_outbox_due
+    ON mail_outbox(next_attempt_at)
+    WHERE status = 'pending';
@@ -0,0 +1,50 @@
+-- TOTP two-factor authentication.
+--
+-- user_totp holds one authenticator secret per user. A row with
+-- enabled_at NULL is an enrollment that has not been confirmed with a
+-- code yet. last_used_step records the newest accepted time step so a
+-- code cannot be replayed.
+--
+-- Recovery codes and login challenges store only SHA-256 hashes. A login
+-- challenge is issued after the password check and redeemed with a code
+-- to obtain the actual session.
+
+CREATE TABLE IF NOT EXISTS user_totp
+(
+    user_id         uuid PRIMARY KEY     REFERENCES users(id) ON DELETE CASCADE,
+    --------------------------------------------------------------------
+    secret          text        NOT NULL,
+    enabled_at      timestamptz          DEFAULT NULL,
+    last_used_step  bigint               DEFAULT NULL,
+    created_at      timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS user_recovery_codes
+(
+    id          uuid PRIMARY KEY     DEFAULT gen_random_uuid(),
+    -----------------------------------------------------------
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash   text        NOT NULL,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    used_at     timestamptz          DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_user_recovery_codes_user_id
+    ON user_recovery_codes(user_id);
+
+CREATE TABLE IF NOT EXISTS login_challenges
+(
+    id          uuid PRIMARY KEY     DEFAULT gen_random_uuid(),
+    -----------------------------------------------------------
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash  text        NOT NULL UNIQUE,
+    attempts    integer     NOT NULL DEFAULT 0,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    expires_at  timestamptz NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_challenges_user_id
+    ON login_challenges(user_id);
+
+ALTER TABLE sites
+    ADD COLUMN IF NOT EXISTS require_admin_2fa boolean NOT NULL DEFAULT false;
@@ -0,0 +1,21 @@
+-- Personal API tokens.
+--
+-- Authenticate scripts via `Authorization: Bearer`. As with sessions only
+-- the SHA-256 hash is stored; the token is shown once at creation. Scopes
+-- limit what a token may do on top of its owner's own permissions.
+
+CREATE TABLE IF NOT EXISTS api_tokens
+(
+    id            uuid PRIMARY KEY     DEFAULT gen_random_uuid(),
+    -------------------------------------------------------------
+    user_id       uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name          text        NOT NULL,
+    token_hash    text        NOT NULL UNIQUE,
+    scopes        text[]      NOT NULL DEFAULT '{}',
+    created_at    timestamptz NOT NULL DEFAULT now(),
+    expires_at    timestamptz          DEFAULT NULL,
+    last_used_at  timestamptz          DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_tokens_user_id
+    ON api_tokens(user_id);
@@ -0,0 +1,28 @@
+-- Trash.
+--
+-- Deleting content moves it to the trash by setting `deleted_at`.
+-- Trashed items are hidden everywhere except the trash list and no
+-- longer reserve their slug; they are purged for good once the site's
+-- retention period has passed.
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS deleted_at timestamptz DEFAULT NULL;
+
+DROP INDEX IF EXISTS idx_content_items_global_kind_slug;
+DROP INDEX IF EXISTS idx_content_items_owner_kind_slug;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_items_global_kind_slug
+ON content_items(kind, slug)
+WHERE owner_user_id IS NULL AND deleted_at IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_items_owner_kind_slug
+ON content_items(owner_user_id, kind, slug)
+WHERE owner_user_id IS NOT NULL AND deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_content_items_deleted_at
+ON content_items(deleted_at)
+WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE sites
+    ADD COLUMN IF NOT EXISTS trash_retention_days integer NOT NULL DEFAULT 30
+    CHECK (trash_retention_days BETWEEN 1 AND 3650);
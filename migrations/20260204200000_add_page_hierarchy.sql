@@ -0,0 +1,34 @@
+-- Page hierarchy.
+--
+-- Pages may sit under a parent page and are served at the path made of
+-- their ancestors' slugs (`/about/team/leadership`). `menu_order` sorts
+-- siblings. When a page moves or is renamed the paths it (and its
+-- subtree) used to answer on are kept in `content_slug_history` so old
+-- links can be redirected to the new location.
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS parent_id uuid
+        REFERENCES content_items(id) ON DELETE SET NULL,
+    ADD COLUMN IF NOT EXISTS menu_order integer NOT NULL DEFAULT 0;
+
+ALTER TABLE content_items
+    ADD CONSTRAINT content_items_parent_page_only
+        CHECK (parent_id IS NULL OR kind = 'page'),
+    ADD CONSTRAINT content_items_parent_not_self
+        CHECK (parent_id IS NULL OR parent_id <> id);
+
+CREATE INDEX IF NOT EXISTS idx_content_items_parent_id
+ON content_items(parent_id)
+WHERE parent_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS content_slug_history (
+    kind text NOT NULL CHECK (kind IN ('post', 'page')),
+    path text NOT NULL,
+    content_item_id uuid NOT NULL
+        REFERENCES content_items(id) ON DELETE CASCADE,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (kind, path)
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_slug_history_item
+ON content_slug_history(content_item_id);
@@ -0,0 +1,22 @@
+-- Redirects.
+--
+-- Manual redirects managed from the admin "Redirects" screen. An
+-- `exact` redirect matches one path; a `prefix` redirect matches a path
+-- and everything below it, carrying the rest of the path over to the
+-- target. A 410 has no target and tells clients the page is gone for
+-- good. Automatic redirects for renamed posts and moved pages live in
+-- `content_slug_history`.
+
+CREATE TABLE IF NOT EXISTS redirects (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    source_path text NOT NULL,
+    match_type text NOT NULL DEFAULT 'exact'
+        CHECK (match_type IN ('exact', 'prefix')),
+    target_url text,
+    status_code integer NOT NULL DEFAULT 301
+        CHECK (status_code IN (301, 302, 410)),
+    created_by_user_id uuid REFERENCES users(id) ON DELETE SET NULL,
+    created_at timestamptz NOT NULL DEFAULT now(),
+    CHECK ((status_code = 410) = (target_url IS NULL)),
+    UNIQUE (source_path, match_type)
+);
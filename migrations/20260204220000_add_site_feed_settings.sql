@@ -0,0 +1,7 @@
+-- Feeds.
+--
+-- Whether `/feed.xml` and `/atom.xml` carry the full text of each post
+-- or only an excerpt.
+
+ALTER TABLE sites
+    ADD COLUMN IF NOT EXISTS feed_full_text boolean NOT NULL DEFAULT true;
@@ -0,0 +1,12 @@
+-- Search engines.
+--
+-- `noindex` keeps an item out of the sitemaps and asks crawlers not to
+-- index it. `robots_txt` holds the rules served at `/robots.txt`; a
+-- `Sitemap:` line is appended when it is served.
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS noindex boolean NOT NULL DEFAULT false;
+
+ALTER TABLE sites
+    ADD COLUMN IF NOT EXISTS robots_txt text NOT NULL
+    DEFAULT E'User-agent: *\nDisallow: /admin/\nDisallow: /api/\n';
@@ -0,0 +1,22 @@
+-- SEO metadata.
+--
+-- Optional per-item overrides for the title, description, canonical URL
+-- and Open Graph image used in `{{head_meta}}`. Empty means "derive it
+-- from the content".
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS meta_title text NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS meta_description text NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS canonical_url text NOT NULL DEFAULT '',
+    ADD COLUMN IF NOT EXISTS og_image_url text NOT NULL DEFAULT '';
+
+-- Built-in templates get the generated tags and the SEO title.
+UPDATE site_templates
+SET
+    html = replace(
+        replace(html, '<title>{{title}}', '<title>{{meta_title}}'),
+        '</title>',
+        '</title>{{head_meta}}'
+    ),
+    edited_at = now()
+WHERE is_builtin AND html NOT LIKE '%{{head_meta}}%';
@@ -0,0 +1,17 @@
+-- Markdown as an alternative content format.
+--
+-- `format` says how `content` is written. For Markdown items
+-- `rendered_html` caches the HTML rendered from it when it is saved, so
+-- pages are not rendered again on every request; HTML items leave it
+-- NULL. Revisions keep both, so every revision has its own rendering
+-- and restoring one brings its format back.
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS format text NOT NULL DEFAULT 'html'
+        CHECK (format IN ('html', 'markdown')),
+    ADD COLUMN IF NOT EXISTS rendered_html text;
+
+ALTER TABLE content_item_revisions
+    ADD COLUMN IF NOT EXISTS format text NOT NULL DEFAULT 'html'
+        CHECK (format IN ('html', 'markdown')),
+    ADD COLUMN IF NOT EXISTS rendered_html text;
@@ -0,0 +1,104 @@
+-- Full-text search.
+--
+-- `search_vector` holds the title (weight A) and the text of the content
+-- (weight B), kept current by a trigger whenever either changes. The
+-- text is taken from the stored HTML, or the rendering of Markdown
+-- items, with tags, scripts and styles removed.
+--
+-- Words are stemmed with the text search configuration named by the
+-- default site's `search_language`, e.g. `english` or `german`; `simple`
+-- does no stemming at all. Queries use the same configuration through
+-- `content_search_config()`, and changing the language rebuilds every
+-- vector.
+
+ALTER TABLE sites
+    ADD COLUMN IF NOT EXISTS search_language text NOT NULL DEFAULT 'english';
+
+CREATE OR REPLACE FUNCTION content_search_config() RETURNS regconfig
+LANGUAGE sql STABLE AS $$
+    SELECT COALESCE(
+        (SELECT search_language::regconfig
+         FROM sites
+         WHERE status = 'published'
+         ORDER BY created_at ASC
+         LIMIT 1),
+        'english'::regconfig
+    )
+$$;
+
+-- Inline tags are dropped so words split by them stay whole; every
+-- other tag separates words.
+CREATE OR REPLACE FUNCTION content_search_text(html text) RETURNS text
+LANGUAGE sql IMMUTABLE AS $$
+    SELECT btrim(regexp_replace(
+        replace(replace(replace(replace(replace(replace(
+            regexp_replace(
+                regexp_replace(
+                    regexp_replace(
+                        regexp_replace(
+                            coalesce(html, ''),
+                            '<script[^>]*?>.*?</script\s*>', ' ', 'gi'),
+                        '<style[^>]*?>.*?</style\s*>', ' ', 'gi'),
+                    '</?(a|abbr|b|code|del|em|i|mark|s|small|span|strike|strong|sub|sup|u)\y[^>]*>',
+                    '', 'gi'),
+                '<[^>]*>', ' ', 'g'),
+            '&nbsp;', ' '), '&lt;', '<'), '&gt;', '>'), '&quot;', '"'),
+            '&#39;', ''''), '&amp;', '&'),
+        '\s+', ' ', 'g'))
+$$;
+
+CREATE OR REPLACE FUNCTION content_search_vector(title text, html text)
+RETURNS tsvector
+LANGUAGE sql STABLE AS $$
+    SELECT setweight(
+               to_tsvector(content_search_config(), coalesce(title, '')),
+               'A')
+        || setweight(
+               to_tsvector(content_search_config(),
+                           content_search_text(html)),
+               'B')
+$$;
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS search_vector tsvector;
+
+CREATE OR REPLACE FUNCTION content_items_search_vector_update()
+RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    NEW.search_vector := content_search_vector(
+        NEW.title, COALESCE(NEW.rendered_html, NEW.content));
+    RETURN NEW;
+END
+$$;
+
+DROP TRIGGER IF EXISTS content_items_search_vector ON content_items;
+CREATE TRIGGER content_items_search_vector
+    BEFORE INSERT OR UPDATE OF title, content, rendered_html
+    ON content_items
+    FOR EACH ROW EXECUTE FUNCTION content_items_search_vector_update();
+
+UPDATE content_items
+SET search_vector = content_search_vector(
+    title, COALESCE(rendered_html, content));
+
+CREATE INDEX IF NOT EXISTS content_items_search_vector_idx
+    ON content_items USING gin (search_vector);
+
+CREATE OR REPLACE FUNCTION content_search_reindex() RETURNS trigger
+LANGUAGE plpgsql AS $$
+BEGIN
+    UPDATE content_items
+    SET search_vector = content_search_vector(
+        title, COALESCE(rendered_html, content));
+    RETURN NULL;
+END
+$$;
+
+DROP TRIGGER IF EXISTS sites_search_language ON sites;
+CREATE TRIGGER sites_search_language
+    AFTER UPDATE OF search_language, status ON sites
+    FOR EACH ROW
+    WHEN (OLD.search_language IS DISTINCT FROM NEW.search_language
+          OR OLD.status IS DISTINCT FROM NEW.status)
+    EXECUTE FUNCTION content_search_reindex();
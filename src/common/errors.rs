@@ -34,3 +34,35 @@ pub enum UserError {
     #[error("An unexpected error occurred")]
     Internal,
 }
+
+#[derive(Error, Debug)]
+pub enum MailError {
+    #[error("Invalid email address: {0}")]
+    InvalidAddress(String),
+
+    #[error("Mail transport error: {0}")]
+    Transport(String),
+}
+
+#[derive(Error, Debug)]
+pub enum MediaError {
+    #[error("File is larger than {} MB", .0 / (1024 * 1024))]
+    TooLarge(usize),
+
+    #[error(
+        "Only PNG, JPEG, GIF and WebP images and PDFs can be uploaded"
+    )]
+    UnsupportedType,
+
+    #[error("Invalid storage key: {0}")]
+    InvalidKey(String),
+
+    #[error("Media file not found")]
+    NotFound,
+
+    #[error("Image processing failed: {0}")]
+    Processing(String),
+
+    #[error("Media storage error: {0}")]
+    Storage(#[from] std::io::Error),
+}
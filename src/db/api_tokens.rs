@@ -0,0 +1,101 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{ApiToken, ApiTokenCreate};
+
+pub async fn create_api_token(
+    pool: &PgPool,
+    data: &ApiTokenCreate,
+) -> Result<ApiToken, sqlx::Error> {
+    let scopes: Vec<&str> =
+        data.scopes.iter().map(|s| s.as_str()).collect();
+
+    sqlx::query_as::<_, ApiToken>(
+        r#"
+        INSERT INTO api_tokens (user_id, name, token_hash, scopes, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(data.user_id)
+    .bind(&data.name)
+    .bind(&data.token_hash)
+    .bind(scopes)
+    .bind(data.expires_at)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn list_api_tokens_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<ApiToken>, sqlx::Error> {
+    sqlx::query_as::<_, ApiToken>(
+        r#"
+        SELECT *
+        FROM api_tokens
+        WHERE user_id = $1
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Look up a token that can authenticate: unexpired and owned by a user
+/// that has not been deleted.
+pub async fn get_active_api_token(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<ApiToken>, sqlx::Error> {
+    sqlx::query_as::<_, ApiToken>(
+        r#"
+        SELECT t.*
+        FROM api_tokens t
+        JOIN users u ON u.id = t.user_id
+        WHERE t.token_hash = $1
+          AND (t.expires_at IS NULL OR t.expires_at > now())
+          AND u.deleted_at IS NULL
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record use of a token. Writes at most once a minute per token so a
+/// busy script does not turn every request into an UPDATE.
+pub async fn touch_api_token(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE api_tokens
+        SET last_used_at = now()
+        WHERE id = $1
+          AND (last_used_at IS NULL OR last_used_at < now() - interval '1 minute')
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Revoke a token, scoped to its owner. Returns whether one was deleted.
+pub async fn delete_api_token_for_user(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"DELETE FROM api_tokens WHERE id = $1 AND user_id = $2"#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
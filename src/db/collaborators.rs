@@ -2,7 +2,8 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::models::{
-    ContentCollaborator, ContentItem, ContentKind, RoleName,
+    ContentCollaborator, ContentFilter, ContentItem, ContentKind,
+    ContentStatus, RoleName,
 };
 
 pub async fn can_view_content(
@@ -18,6 +19,13 @@ pub async fn can_view_content(
         return Ok(true);
     }
 
+    // Reviewers need to open and touch up submitted work.
+    if item.status == ContentStatus::Pending
+        && super::user_can_publish(pool, uid).await?
+    {
+        return Ok(true);
+    }
+
     let exists = sqlx::query_scalar::<_, bool>(
         r#"
         SELECT EXISTS(
@@ -48,6 +56,13 @@ pub async fn can_edit_content(
         return Ok(true);
     }
 
+    // Reviewers need to open and touch up submitted work.
+    if item.status == ContentStatus::Pending
+        && super::user_can_publish(pool, uid).await?
+    {
+        return Ok(true);
+    }
+
     let role = sqlx::query_scalar::<_, Option<RoleName>>(
         r#"
         SELECT role
@@ -87,6 +102,7 @@ pub async fn list_content_for_user(
               ON col.content_item_id = c.id
              AND col.user_id = $2
             WHERE c.kind = $1
+              AND c.deleted_at IS NULL
               AND (c.owner_user_id IS NULL OR c.owner_user_id = $2 OR col.user_id IS NOT NULL)
             ORDER BY c.created_at DESC
             "#,
@@ -105,6 +121,8 @@ pub async fn list_content_for_user(
              AND col.user_id = $2
             WHERE c.kind = $1
               AND c.status = 'published'
+              AND (c.unpublish_at IS NULL OR c.unpublish_at > now())
+              AND c.deleted_at IS NULL
               AND (c.owner_user_id IS NULL OR c.owner_user_id = $2 OR col.user_id IS NOT NULL)
             ORDER BY c.published_at DESC NULLS LAST, c.created_at DESC
             "#,
@@ -116,6 +134,98 @@ pub async fn list_content_for_user(
     }
 }
 
+/// Trashed items of `kind` that `uid` could edit, most recently
+/// trashed first.
+pub async fn list_trashed_content_for_user(
+    pool: &PgPool,
+    kind: ContentKind,
+    uid: Uuid,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT c.*
+        FROM content_items c
+        WHERE c.kind = $1
+          AND c.deleted_at IS NOT NULL
+          AND (c.owner_user_id IS NULL OR c.owner_user_id = $2 OR EXISTS(
+                SELECT 1 FROM content_item_collaborators col
+                WHERE col.content_item_id = c.id
+                  AND col.user_id = $2
+                  AND col.role = 'editor'))
+        ORDER BY c.deleted_at DESC, c.id DESC
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(uid)
+    .fetch_all(pool)
+    .await
+}
+
+/// Items of `kind` visible to `uid` that match `filter`, newest first.
+pub async fn list_filtered_content_for_user(
+    pool: &PgPool,
+    kind: ContentKind,
+    filter: &ContentFilter,
+    uid: Uuid,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT c.*
+        FROM content_items c
+        WHERE c.kind = $1
+          AND c.deleted_at IS NULL
+          AND (c.owner_user_id IS NULL OR c.owner_user_id = $2 OR EXISTS(
+                SELECT 1 FROM content_item_collaborators col
+                WHERE col.content_item_id = c.id AND col.user_id = $2))
+          AND ($3::text IS NULL OR c.status = $3)
+          AND ($4::uuid IS NULL OR c.owner_user_id = $4)
+          AND ($5::text IS NULL OR c.slug = $5)
+        ORDER BY c.created_at DESC, c.id DESC
+        LIMIT $6 OFFSET $7
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(uid)
+    .bind(filter.status.as_ref().map(ContentStatus::as_str))
+    .bind(filter.owner_user_id)
+    .bind(filter.slug.as_deref())
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_filtered_content_for_user(
+    pool: &PgPool,
+    kind: ContentKind,
+    filter: &ContentFilter,
+    uid: Uuid,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*)
+        FROM content_items c
+        WHERE c.kind = $1
+          AND c.deleted_at IS NULL
+          AND (c.owner_user_id IS NULL OR c.owner_user_id = $2 OR EXISTS(
+                SELECT 1 FROM content_item_collaborators col
+                WHERE col.content_item_id = c.id AND col.user_id = $2))
+          AND ($3::text IS NULL OR c.status = $3)
+          AND ($4::uuid IS NULL OR c.owner_user_id = $4)
+          AND ($5::text IS NULL OR c.slug = $5)
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(uid)
+    .bind(filter.status.as_ref().map(ContentStatus::as_str))
+    .bind(filter.owner_user_id)
+    .bind(filter.slug.as_deref())
+    .fetch_one(pool)
+    .await
+}
+
 pub async fn list_collaborators(
     pool: &PgPool,
     content_item_id: Uuid,
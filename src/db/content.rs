@@ -1,10 +1,13 @@
-use chrono::Utc;
-use sqlx::PgPool;
+use chrono::{DateTime, Utc};
+use sqlx::{PgPool, Postgres, QueryBuilder};
 use uuid::Uuid;
 
+use super::{record_page_paths, record_slug_history};
 use crate::models::{
-    ContentCreate, ContentItem, ContentKind, ContentStatus,
-    ContentUpdate,
+    ContentCreate, ContentCursor, ContentFormat, ContentItem,
+    ContentKind, ContentPage, ContentSchedule, ContentSeo,
+    ContentStatus, ContentUpdate, PageAnchor, PageRequest,
+    PostListFilter,
 };
 
 pub async fn create_content(
@@ -13,8 +16,8 @@ pub async fn create_content(
 ) -> Result<ContentItem, sqlx::Error> {
     sqlx::query_as::<_, ContentItem>(
         r#"
-        INSERT INTO content_items (owner_user_id, kind, status, title, slug, content, template)
-        VALUES ($1, $2, 'draft', $3, $4, $5, $6)
+        INSERT INTO content_items (owner_user_id, kind, status, title, slug, content, template, unfiltered_html, format, rendered_html)
+        VALUES ($1, $2, 'draft', $3, $4, $5, $6, $7, $8, $9)
         RETURNING *
         "#,
     )
@@ -24,6 +27,9 @@ pub async fn create_content(
     .bind(&data.slug)
     .bind(&data.content)
     .bind(&data.template)
+    .bind(data.unfiltered_html)
+    .bind(data.format.as_str())
+    .bind(data.rendered_html.as_deref())
     .fetch_one(pool)
     .await
 }
@@ -38,7 +44,7 @@ pub async fn list_content(
             r#"
             SELECT *
             FROM content_items
-            WHERE kind = $1
+            WHERE kind = $1 AND deleted_at IS NULL
             ORDER BY created_at DESC
             "#,
         )
@@ -51,6 +57,8 @@ pub async fn list_content(
             SELECT *
             FROM content_items
             WHERE kind = $1 AND status = 'published'
+              AND (unpublish_at IS NULL OR unpublish_at > now())
+              AND deleted_at IS NULL
             ORDER BY published_at DESC NULLS LAST, created_at DESC
             "#,
         )
@@ -60,6 +68,168 @@ pub async fn list_content(
     }
 }
 
+/// Sort key of the public post listings.
+const PUBLISHED_ORDER_KEY: &str =
+    "COALESCE(c.published_at, c.created_at)";
+
+/// Finish a listing query whose items are ordered newest first by
+/// `key`, then id, and load the page `page` asks for.
+async fn fetch_content_page(
+    pool: &PgPool,
+    mut query: QueryBuilder<'_, Postgres>,
+    key: &str,
+    page: &PageRequest,
+    cursor: impl Fn(&ContentItem) -> ContentCursor,
+) -> Result<ContentPage, sqlx::Error> {
+    let limit = page.limit.max(1);
+    // One extra row tells whether there is another page.
+    match page.anchor {
+        PageAnchor::Offset(_) => {
+            query.push(format!(" ORDER BY {key} DESC, c.id DESC"));
+        }
+        PageAnchor::After(after) => {
+            query
+                .push(format!(" AND ({key}, c.id) < ("))
+                .push_bind(after.at)
+                .push(", ")
+                .push_bind(after.id)
+                .push(format!(") ORDER BY {key} DESC, c.id DESC"));
+        }
+        PageAnchor::Before(before) => {
+            query
+                .push(format!(" AND ({key}, c.id) > ("))
+                .push_bind(before.at)
+                .push(", ")
+                .push_bind(before.id)
+                .push(format!(") ORDER BY {key} ASC, c.id ASC"));
+        }
+    }
+    query.push(" LIMIT ").push_bind(limit + 1);
+    if let PageAnchor::Offset(offset) = page.anchor {
+        query.push(" OFFSET ").push_bind(offset.max(0));
+    }
+
+    let mut items = query
+        .build_query_as::<ContentItem>()
+        .fetch_all(pool)
+        .await?;
+    let more = items.len() as i64 > limit;
+    items.truncate(limit as usize);
+    let (has_prev, has_next) = match page.anchor {
+        PageAnchor::Offset(offset) => (offset > 0, more),
+        PageAnchor::After(_) => (true, more),
+        PageAnchor::Before(_) => {
+            items.reverse();
+            (more, true)
+        }
+    };
+    Ok(ContentPage {
+        prev: items.first().filter(|_| has_prev).map(&cursor),
+        next: items.last().filter(|_| has_next).map(&cursor),
+        items,
+    })
+}
+
+/// One page of published posts matching `filter`, newest first.
+pub async fn list_published_posts(
+    pool: &PgPool,
+    filter: &PostListFilter,
+    page: &PageRequest,
+) -> Result<ContentPage, sqlx::Error> {
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT c.*
+        FROM content_items c
+        WHERE c.kind = 'post'
+          AND c.status = 'published'
+          AND (c.unpublish_at IS NULL OR c.unpublish_at > now())
+          AND c.deleted_at IS NULL
+        "#,
+    );
+    if let Some(term_id) = filter.term_id {
+        query
+            .push(
+                r#"
+          AND EXISTS (
+              WITH RECURSIVE subtree AS (
+                  SELECT id FROM terms WHERE id = "#,
+            )
+            .push_bind(term_id)
+            .push(
+                r#"
+                  UNION
+                  SELECT t.id
+                  FROM terms t
+                  JOIN subtree s ON t.parent_id = s.id
+              )
+              SELECT 1
+              FROM content_terms ct
+              JOIN subtree s ON s.id = ct.term_id
+              WHERE ct.content_item_id = c.id
+          )"#,
+            );
+    }
+    if let Some(from) = filter.published_from {
+        query
+            .push(format!(" AND {PUBLISHED_ORDER_KEY} >= "))
+            .push_bind(from);
+    }
+    if let Some(before) = filter.published_before {
+        query
+            .push(format!(" AND {PUBLISHED_ORDER_KEY} < "))
+            .push_bind(before);
+    }
+    fetch_content_page(
+        pool,
+        query,
+        PUBLISHED_ORDER_KEY,
+        page,
+        |item| ContentCursor {
+            at: item.published_at.unwrap_or(item.created_at),
+            id: item.id,
+        },
+    )
+    .await
+}
+
+/// One page of the items of `kind` visible to `uid`, in any status,
+/// newest first.
+pub async fn list_content_page_for_user(
+    pool: &PgPool,
+    kind: ContentKind,
+    uid: Uuid,
+    page: &PageRequest,
+) -> Result<ContentPage, sqlx::Error> {
+    let mut query = QueryBuilder::new(
+        r#"
+        SELECT c.*
+        FROM content_items c
+        WHERE c.deleted_at IS NULL
+          AND c.kind = "#,
+    );
+    query
+        .push_bind(kind.as_str())
+        .push(
+            r#"
+          AND (c.owner_user_id IS NULL OR c.owner_user_id = "#,
+        )
+        .push_bind(uid)
+        .push(
+            r#" OR EXISTS(
+                SELECT 1 FROM content_item_collaborators col
+                WHERE col.content_item_id = c.id AND col.user_id = "#,
+        )
+        .push_bind(uid)
+        .push("))");
+    fetch_content_page(pool, query, "c.created_at", page, |item| {
+        ContentCursor {
+            at: item.created_at,
+            id: item.id,
+        }
+    })
+    .await
+}
+
 pub async fn get_content_by_id(
     pool: &PgPool,
     id: Uuid,
@@ -68,7 +238,7 @@ pub async fn get_content_by_id(
         r#"
         SELECT *
         FROM content_items
-        WHERE id = $1
+        WHERE id = $1 AND deleted_at IS NULL
         "#,
     )
     .bind(id)
@@ -81,35 +251,129 @@ pub async fn get_published_by_slug(
     kind: ContentKind,
     slug: &str,
 ) -> Result<Option<ContentItem>, sqlx::Error> {
+    get_live_by_slug(pool, kind, slug, &[ContentStatus::Published])
+        .await
+}
+
+/// The item at `slug` as `viewer` may see it on the public site: a
+/// published item, or a private one that `viewer` can view.
+pub async fn get_viewable_by_slug(
+    pool: &PgPool,
+    kind: ContentKind,
+    slug: &str,
+    viewer: Option<Uuid>,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    let statuses = match viewer {
+        Some(_) => {
+            &[ContentStatus::Published, ContentStatus::Private][..]
+        }
+        None => &[ContentStatus::Published][..],
+    };
+    let item = get_live_by_slug(pool, kind, slug, statuses).await?;
+    viewable_by(pool, item, viewer).await
+}
+
+/// `item`, unless it is private and `viewer` cannot view it.
+pub(crate) async fn viewable_by(
+    pool: &PgPool,
+    item: Option<ContentItem>,
+    viewer: Option<Uuid>,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    let Some(item) = item else {
+        return Ok(None);
+    };
+    if item.status != ContentStatus::Private {
+        return Ok(Some(item));
+    }
+    match viewer {
+        Some(uid)
+            if super::can_view_content(pool, &item, uid).await? =>
+        {
+            Ok(Some(item))
+        }
+        _ => Ok(None),
+    }
+}
+
+async fn get_live_by_slug(
+    pool: &PgPool,
+    kind: ContentKind,
+    slug: &str,
+    statuses: &[ContentStatus],
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    let statuses: Vec<&str> =
+        statuses.iter().map(ContentStatus::as_str).collect();
     sqlx::query_as::<_, ContentItem>(
         r#"
         SELECT *
         FROM content_items
-        WHERE kind = $1 AND slug = $2 AND status = 'published'
+        WHERE kind = $1 AND slug = $2 AND status = ANY($3)
+          AND (unpublish_at IS NULL OR unpublish_at > now())
+          AND deleted_at IS NULL
         "#,
     )
     .bind(kind.as_str())
     .bind(slug)
+    .bind(statuses)
     .fetch_optional(pool)
     .await
 }
 
+/// Items waiting for review, oldest submission first.
+pub async fn list_pending_content(
+    pool: &PgPool,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT *
+        FROM content_items
+        WHERE status = 'pending' AND deleted_at IS NULL
+        ORDER BY edited_at ASC
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Apply the fields of `data` that are set. An item that goes live as
+/// published or private keeps its first `published_at`, or takes now.
 pub async fn update_content(
     pool: &PgPool,
     id: Uuid,
     data: &ContentUpdate,
 ) -> Result<Option<ContentItem>, sqlx::Error> {
-    sqlx::query_as::<_, ContentItem>(
+    let mut tx = pool.begin().await?;
+    if let Some(slug) = data.slug.as_deref() {
+        record_slug_history(&mut tx, id, slug).await?;
+    }
+    let item = sqlx::query_as::<_, ContentItem>(
         r#"
         UPDATE content_items
         SET
             title = COALESCE($1, title),
             slug = COALESCE($2, slug),
             content = COALESCE($3, content),
+            unfiltered_html = CASE
+                WHEN $3 IS NULL THEN unfiltered_html ELSE $7
+            END,
+            format = CASE
+                WHEN $3 IS NULL THEN format ELSE COALESCE($8, format)
+            END,
+            rendered_html = CASE
+                WHEN $3 IS NULL THEN rendered_html ELSE $9
+            END,
             template = COALESCE($4, template),
             status = COALESCE($5, status),
+            published_at = CASE
+                WHEN $5 IN ('published', 'private')
+                THEN COALESCE(published_at, now())
+                ELSE published_at
+            END,
+            publish_at = CASE
+                WHEN $5 IS NULL OR $5 = 'scheduled' THEN publish_at
+            END,
             edited_at = now()
-        WHERE id = $6
+        WHERE id = $6 AND deleted_at IS NULL
         RETURNING *
         "#,
     )
@@ -119,10 +383,137 @@ pub async fn update_content(
     .bind(data.template.as_deref())
     .bind(data.status.as_ref().map(ContentStatus::as_str))
     .bind(id)
+    .bind(data.unfiltered_html)
+    .bind(data.format.as_ref().map(ContentFormat::as_str))
+    .bind(data.rendered_html.as_deref())
+    .fetch_optional(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(item)
+}
+
+/// Move an item to the trash. It disappears from every other query and
+/// its slug becomes free for new content. Subpages of a trashed page
+/// move up to its parent, keeping their old paths as redirects.
+pub async fn trash_content(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let children = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        SELECT id
+        FROM content_items
+        WHERE parent_id = $1 AND deleted_at IS NULL
+        "#,
+    )
+    .bind(id)
+    .fetch_all(&mut *tx)
+    .await?;
+    for child in children {
+        record_page_paths(&mut tx, child, None).await?;
+    }
+
+    let item = sqlx::query_as::<_, ContentItem>(
+        r#"
+        UPDATE content_items
+        SET deleted_at = now()
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    if let Some(item) = &item {
+        sqlx::query(
+            r#"
+            UPDATE content_items
+            SET parent_id = $1
+            WHERE parent_id = $2 AND deleted_at IS NULL
+            "#,
+        )
+        .bind(item.parent_id)
+        .bind(item.id)
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+    Ok(item)
+}
+
+pub async fn get_trashed_content_by_id(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT *
+        FROM content_items
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Take an item out of the trash as a draft, so nothing goes live
+/// again by accident. A page whose parent is gone comes back at the top
+/// level. Fails with a unique violation when another item
+/// has taken the slug in the meantime; see [`find_slug_conflict`].
+pub async fn restore_trashed_content(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        UPDATE content_items
+        SET
+            deleted_at = NULL,
+            status = 'draft',
+            publish_at = NULL,
+            parent_id = (
+                SELECT p.id
+                FROM content_items p
+                WHERE p.id = content_items.parent_id
+                  AND p.deleted_at IS NULL
+            ),
+            edited_at = now()
+        WHERE id = $1 AND deleted_at IS NOT NULL
+        RETURNING *
+        "#,
+    )
+    .bind(id)
     .fetch_optional(pool)
     .await
 }
 
+/// The live item that holds `item`'s slug, if any. Uses the same scope
+/// as the slug unique indexes.
+pub async fn find_slug_conflict(
+    pool: &PgPool,
+    item: &ContentItem,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT *
+        FROM content_items
+        WHERE kind = $1 AND slug = $2
+          AND owner_user_id IS NOT DISTINCT FROM $3
+          AND deleted_at IS NULL
+          AND id <> $4
+        "#,
+    )
+    .bind(item.kind.as_str())
+    .bind(&item.slug)
+    .bind(item.owner_user_id)
+    .bind(item.id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Delete an item and its revisions for good.
 pub async fn delete_content(
     pool: &PgPool,
     id: Uuid,
@@ -139,6 +530,24 @@ pub async fn delete_content(
     Ok(result.rows_affected() > 0)
 }
 
+/// Permanently delete items trashed at or before `cutoff`. Returns how
+/// many were removed.
+pub async fn purge_trashed_content(
+    pool: &PgPool,
+    cutoff: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM content_items
+        WHERE deleted_at <= $1
+        "#,
+    )
+    .bind(cutoff)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
 pub async fn publish_content(
     pool: &PgPool,
     id: Uuid,
@@ -150,8 +559,10 @@ pub async fn publish_content(
         SET
             status = 'published',
             published_at = COALESCE(published_at, $1),
+            publish_at = NULL,
+            unpublish_at = CASE WHEN unpublish_at > $1 THEN unpublish_at END,
             edited_at = now()
-        WHERE id = $2
+        WHERE id = $2 AND deleted_at IS NULL
         RETURNING *
         "#,
     )
@@ -160,3 +571,135 @@ pub async fn publish_content(
     .fetch_optional(pool)
     .await
 }
+
+/// Set the publish and unpublish times of an item. A `publish_at` makes
+/// the item scheduled; without one the status is left alone.
+pub async fn schedule_content(
+    pool: &PgPool,
+    id: Uuid,
+    schedule: &ContentSchedule,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        UPDATE content_items
+        SET
+            status = CASE
+                WHEN $1::timestamptz IS NULL THEN status
+                ELSE 'scheduled'
+            END,
+            publish_at = $1,
+            unpublish_at = $2,
+            edited_at = now()
+        WHERE id = $3 AND deleted_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(schedule.publish_at)
+    .bind(schedule.unpublish_at)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Replace an item's search engine and sharing settings.
+pub async fn update_content_seo(
+    pool: &PgPool,
+    id: Uuid,
+    seo: &ContentSeo,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    let seo = seo.trimmed();
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        UPDATE content_items
+        SET
+            meta_title = $1,
+            meta_description = $2,
+            canonical_url = $3,
+            og_image_url = $4,
+            noindex = $5,
+            edited_at = now()
+        WHERE id = $6 AND deleted_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(&seo.meta_title)
+    .bind(&seo.meta_description)
+    .bind(&seo.canonical_url)
+    .bind(&seo.og_image_url)
+    .bind(seo.noindex)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Drop both schedule times. A scheduled item goes back to draft.
+pub async fn clear_schedule(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        UPDATE content_items
+        SET
+            status = CASE
+                WHEN status = 'scheduled' THEN 'draft'
+                ELSE status
+            END,
+            publish_at = NULL,
+            unpublish_at = NULL,
+            edited_at = now()
+        WHERE id = $1 AND deleted_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Publish every scheduled item whose `publish_at` is at or before
+/// `now`. The scheduled time becomes `published_at` unless the item was
+/// published before.
+pub async fn promote_due_content(
+    pool: &PgPool,
+    now: DateTime<Utc>,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        UPDATE content_items
+        SET
+            status = 'published',
+            published_at = COALESCE(published_at, publish_at),
+            publish_at = NULL
+        WHERE status = 'scheduled' AND publish_at <= $1
+          AND deleted_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+}
+
+/// Revert every published item whose `unpublish_at` is at or before
+/// `now` to draft. Public queries already hide these items; this makes
+/// the change visible in the admin.
+pub async fn unpublish_due_content(
+    pool: &PgPool,
+    now: DateTime<Utc>,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        UPDATE content_items
+        SET
+            status = 'draft',
+            unpublish_at = NULL
+        WHERE status = 'published' AND unpublish_at <= $1
+          AND deleted_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,134 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    EmailVerificationToken, EmailVerificationTokenCreate, User,
+};
+
+/// Store a new verification token, discarding earlier unused tokens of
+/// the same user so that only the most recent link works.
+pub async fn create_email_verification_token(
+    pool: &PgPool,
+    data: &EmailVerificationTokenCreate,
+) -> Result<EmailVerificationToken, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"DELETE FROM email_verification_tokens WHERE user_id = $1 AND used_at IS NULL"#,
+    )
+    .bind(data.user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let token = sqlx::query_as::<_, EmailVerificationToken>(
+        r#"
+        INSERT INTO email_verification_tokens (user_id, email, token_hash, expires_at)
+        VALUES ($1, $2, $3, $4)
+        RETURNING *
+        "#,
+    )
+    .bind(data.user_id)
+    .bind(&data.email)
+    .bind(&data.token_hash)
+    .bind(data.expires_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(token)
+}
+
+/// The user's outstanding verification, if a link is still waiting to be
+/// followed.
+pub async fn get_pending_email_verification(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<EmailVerificationToken>, sqlx::Error> {
+    sqlx::query_as::<_, EmailVerificationToken>(
+        r#"
+        SELECT *
+        FROM email_verification_tokens
+        WHERE user_id = $1
+          AND used_at IS NULL
+          AND expires_at > now()
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Redeem a verification token.
+///
+/// Sets the user's email to the address the token was issued for and
+/// marks it verified. Returns the updated user, or `None` if the token is
+/// unknown, used, expired or belongs to a deleted user. Fails with a
+/// unique violation if another account took the address in the meantime.
+pub async fn verify_email_with_token(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<User>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let token = sqlx::query_as::<_, EmailVerificationToken>(
+        r#"
+        UPDATE email_verification_tokens t
+        SET used_at = now()
+        FROM users u
+        WHERE t.token_hash = $1
+          AND t.used_at IS NULL
+          AND t.expires_at > now()
+          AND u.id = t.user_id
+          AND u.deleted_at IS NULL
+        RETURNING t.*
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(token) = token else {
+        return Ok(None);
+    };
+
+    let user = sqlx::query_as::<_, User>(
+        r#"
+        UPDATE users
+        SET email = $1, email_verified_at = now(), edited_at = now()
+        WHERE id = $2
+        RETURNING *
+        "#,
+    )
+    .bind(&token.email)
+    .bind(token.user_id)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(Some(user))
+}
+
+pub async fn delete_stale_email_verification_tokens(
+    pool: &PgPool,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"DELETE FROM email_verification_tokens WHERE used_at IS NOT NULL OR expires_at <= now()"#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn is_email_verified(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"SELECT EXISTS(SELECT 1 FROM users WHERE id = $1 AND email_verified_at IS NOT NULL)"#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
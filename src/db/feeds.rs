@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::ContentItem;
+
+/// The `limit` newest live posts, only those owned by `author` when
+/// one is given.
+pub async fn list_feed_posts(
+    pool: &PgPool,
+    author: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT *
+        FROM content_items
+        WHERE kind = 'post' AND status = 'published'
+          AND (unpublish_at IS NULL OR unpublish_at > now())
+          AND deleted_at IS NULL
+          AND ($1::uuid IS NULL OR owner_user_id = $1)
+        ORDER BY published_at DESC NULLS LAST, created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(author)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Id and last change of each post `list_feed_posts` returns, as
+/// `feed_updated` computes it, without loading their bodies.
+pub async fn list_feed_stamps(
+    pool: &PgPool,
+    author: Option<Uuid>,
+    limit: i64,
+) -> Result<Vec<(Uuid, DateTime<Utc>)>, sqlx::Error> {
+    sqlx::query_as::<_, (Uuid, DateTime<Utc>)>(
+        r#"
+        SELECT id,
+               GREATEST(edited_at, COALESCE(published_at, created_at))
+        FROM content_items
+        WHERE kind = 'post' AND status = 'published'
+          AND (unpublish_at IS NULL OR unpublish_at > now())
+          AND deleted_at IS NULL
+          AND ($1::uuid IS NULL OR owner_user_id = $1)
+        ORDER BY published_at DESC NULLS LAST, created_at DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(author)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
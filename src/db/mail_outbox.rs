@@ -0,0 +1,116 @@
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{OutboxMail, OutboxMailCreate};
+
+/// Queue a message for delivery by the outbox worker.
+pub async fn enqueue_mail(
+    pool: &PgPool,
+    data: &OutboxMailCreate,
+) -> Result<OutboxMail, sqlx::Error> {
+    sqlx::query_as::<_, OutboxMail>(
+        r#"
+        INSERT INTO mail_outbox (recipient, subject, body)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+    )
+    .bind(&data.recipient)
+    .bind(&data.subject)
+    .bind(&data.body)
+    .fetch_one(pool)
+    .await
+}
+
+/// Claim up to `limit` pending messages that are due.
+///
+/// Each claimed row has its attempt counter bumped and `next_attempt_at`
+/// pushed out by `lease`, so concurrent workers skip it and a worker that
+/// dies mid-send leaves it to be retried once the lease runs out.
+pub async fn claim_due_mail(
+    pool: &PgPool,
+    limit: i64,
+    lease: Duration,
+) -> Result<Vec<OutboxMail>, sqlx::Error> {
+    sqlx::query_as::<_, OutboxMail>(
+        r#"
+        UPDATE mail_outbox
+        SET attempts = attempts + 1,
+            next_attempt_at = now() + $2
+        WHERE id IN (
+            SELECT id FROM mail_outbox
+            WHERE status = 'pending' AND next_attempt_at <= now()
+            ORDER BY next_attempt_at
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING *
+        "#,
+    )
+    .bind(limit)
+    .bind(lease)
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a delivery. The body is cleared, since links in it can carry
+/// live tokens that are only stored hashed anywhere else.
+pub async fn mark_mail_sent(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE mail_outbox SET status = 'sent', sent_at = now(), last_error = NULL, body = '' WHERE id = $1"#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a failed delivery. With `retry_at` the message stays pending
+/// until then; without it the message is given up on and, as with
+/// [`mark_mail_sent`], its body is cleared.
+pub async fn mark_mail_failed(
+    pool: &PgPool,
+    id: Uuid,
+    error: &str,
+    retry_at: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE mail_outbox
+        SET last_error = $2,
+            status = CASE WHEN $3::timestamptz IS NULL THEN 'failed' ELSE 'pending' END,
+            body = CASE WHEN $3::timestamptz IS NULL THEN '' ELSE body END,
+            next_attempt_at = COALESCE($3, next_attempt_at)
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .bind(error)
+    .bind(retry_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Drop messages sent before `before`, and failed ones queued before
+/// it. Pending messages are kept.
+pub async fn delete_finished_mail(
+    pool: &PgPool,
+    before: DateTime<Utc>,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM mail_outbox
+        WHERE (status = 'sent' AND sent_at < $1)
+           OR (status = 'failed' AND created_at < $1)
+        "#,
+    )
+    .bind(before)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,109 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use super::escape_sql_like;
+use crate::models::{Media, MediaCreate, MediaFilter};
+
+pub async fn create_media(
+    pool: &PgPool,
+    data: &MediaCreate,
+) -> Result<Media, sqlx::Error> {
+    sqlx::query_as::<_, Media>(
+        r#"
+        INSERT INTO media (
+            owner_user_id, filename, storage_key, mime_type,
+            size_bytes, width, height, alt_text
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING *
+        "#,
+    )
+    .bind(data.owner_user_id)
+    .bind(&data.filename)
+    .bind(&data.storage_key)
+    .bind(&data.mime_type)
+    .bind(data.size_bytes)
+    .bind(data.width)
+    .bind(data.height)
+    .bind(&data.alt_text)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn get_media(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<Media>, sqlx::Error> {
+    sqlx::query_as::<_, Media>("SELECT * FROM media WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+pub async fn list_media_by_ids(
+    pool: &PgPool,
+    ids: &[Uuid],
+) -> Result<Vec<Media>, sqlx::Error> {
+    sqlx::query_as::<_, Media>(
+        "SELECT * FROM media WHERE id = ANY($1)",
+    )
+    .bind(ids)
+    .fetch_all(pool)
+    .await
+}
+
+/// Newest first, at most `filter.limit` files.
+pub async fn list_media(
+    pool: &PgPool,
+    filter: &MediaFilter,
+) -> Result<Vec<Media>, sqlx::Error> {
+    let pattern = filter
+        .query
+        .as_deref()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(|q| format!("%{}%", escape_sql_like(q)));
+    sqlx::query_as::<_, Media>(
+        r#"
+        SELECT *
+        FROM media
+        WHERE ($1::text IS NULL
+               OR filename ILIKE $1 OR alt_text ILIKE $1)
+          AND (NOT $2 OR mime_type LIKE 'image/%')
+        ORDER BY created_at DESC, id
+        LIMIT $3
+        "#,
+    )
+    .bind(pattern)
+    .bind(filter.images_only)
+    .bind(filter.limit)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn update_media_alt(
+    pool: &PgPool,
+    id: Uuid,
+    alt_text: &str,
+) -> Result<Option<Media>, sqlx::Error> {
+    sqlx::query_as::<_, Media>(
+        "UPDATE media SET alt_text = $2 WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .bind(alt_text)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Remove the row and return it, so the caller can delete the file.
+pub async fn delete_media(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<Media>, sqlx::Error> {
+    sqlx::query_as::<_, Media>(
+        "DELETE FROM media WHERE id = $1 RETURNING *",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
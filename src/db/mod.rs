@@ -1,17 +1,44 @@
 pub use sqlx::PgPool;
 
+pub use api_tokens::*;
 pub use collaborators::*;
 pub use content::*;
 pub use db::*;
+pub use email_verifications::*;
+pub use feeds::*;
+pub use mail_outbox::*;
+pub use media::*;
+pub use pages::*;
+pub use password_resets::*;
+pub use redirects::*;
 pub use revisions::*;
 pub use roles::*;
+pub use search::*;
+pub use sessions::*;
 pub use site_templates::*;
+pub use sitemaps::*;
 pub use sites::*;
+pub use terms::*;
+pub use two_factor::*;
 
+mod api_tokens;
 mod collaborators;
 mod content;
+#[allow(clippy::module_inception)]
 mod db;
+mod email_verifications;
+mod feeds;
+mod mail_outbox;
+mod media;
+mod pages;
+mod password_resets;
+mod redirects;
 mod revisions;
 mod roles;
+mod search;
+mod sessions;
 mod site_templates;
+mod sitemaps;
 mod sites;
+mod terms;
+mod two_factor;
@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use crate::models::{
+    ContentItem, ContentKind, ContentStatus, MAX_PAGE_DEPTH,
+    PageMove, PageTreeRow,
+};
+
+/// Remember the paths a page and its subpages answer on now, so they
+/// can be redirected after the page moves or is renamed to `new_slug`.
+/// A rename that keeps the slug, or an id that is not a live page,
+/// records nothing.
+pub(crate) async fn record_page_paths(
+    conn: &mut PgConnection,
+    id: Uuid,
+    new_slug: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        WITH RECURSIVE up AS (
+            SELECT id, parent_id, slug::text AS path, 0 AS depth
+            FROM content_items
+            WHERE id = $1 AND kind = 'page' AND deleted_at IS NULL
+              AND ($2::text IS NULL OR slug <> $2)
+            UNION ALL
+            SELECT p.id, p.parent_id, p.slug || '/' || up.path,
+                   up.depth + 1
+            FROM content_items p
+            JOIN up ON p.id = up.parent_id
+            WHERE up.depth < $3
+        ),
+        root AS (
+            SELECT path FROM up ORDER BY depth DESC LIMIT 1
+        ),
+        down AS (
+            SELECT $1::uuid AS id, root.path, 0 AS depth FROM root
+            UNION ALL
+            SELECT c.id, down.path || '/' || c.slug, down.depth + 1
+            FROM content_items c
+            JOIN down ON c.parent_id = down.id
+            WHERE c.deleted_at IS NULL AND down.depth < $3
+        )
+        INSERT INTO content_slug_history (kind, path, content_item_id)
+        SELECT 'page', path, id FROM down
+        ON CONFLICT (kind, path) DO UPDATE
+        SET content_item_id = EXCLUDED.content_item_id,
+            created_at = now()
+        "#,
+    )
+    .bind(id)
+    .bind(new_slug)
+    .bind(MAX_PAGE_DEPTH as i32)
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+/// The published page served at `path` (slugs joined by `/`, no
+/// leading slash): its slug is the last segment and its ancestors'
+/// slugs make up the rest.
+pub async fn get_published_page_by_path(
+    pool: &PgPool,
+    path: &str,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    get_live_page_by_path(pool, path, &[ContentStatus::Published])
+        .await
+}
+
+/// The page at `path` as `viewer` may see it on the public site: a
+/// published page, or a private one that `viewer` can view.
+pub async fn get_viewable_page_by_path(
+    pool: &PgPool,
+    path: &str,
+    viewer: Option<Uuid>,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    let statuses = match viewer {
+        Some(_) => {
+            &[ContentStatus::Published, ContentStatus::Private][..]
+        }
+        None => &[ContentStatus::Published][..],
+    };
+    let page = get_live_page_by_path(pool, path, statuses).await?;
+    super::viewable_by(pool, page, viewer).await
+}
+
+async fn get_live_page_by_path(
+    pool: &PgPool,
+    path: &str,
+    statuses: &[ContentStatus],
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    let statuses: Vec<&str> =
+        statuses.iter().map(ContentStatus::as_str).collect();
+    let Some(slug) = path.rsplit('/').next() else {
+        return Ok(None);
+    };
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        WITH RECURSIVE up AS (
+            SELECT id AS page_id, parent_id, slug::text AS path,
+                   0 AS depth
+            FROM content_items
+            WHERE kind = 'page' AND slug = $1 AND status = ANY($4)
+              AND (unpublish_at IS NULL OR unpublish_at > now())
+              AND deleted_at IS NULL
+            UNION ALL
+            SELECT up.page_id, p.parent_id, p.slug || '/' || up.path,
+                   up.depth + 1
+            FROM content_items p
+            JOIN up ON p.id = up.parent_id
+            WHERE p.deleted_at IS NULL AND up.depth < $3
+        )
+        SELECT c.*
+        FROM up
+        JOIN content_items c ON c.id = up.page_id
+        WHERE up.parent_id IS NULL AND up.path = $2
+        LIMIT 1
+        "#,
+    )
+    .bind(slug)
+    .bind(path)
+    .bind(MAX_PAGE_DEPTH as i32)
+    .bind(statuses)
+    .fetch_optional(pool)
+    .await
+}
+
+/// A page's ancestors, top-level page first. The page itself is not
+/// included.
+pub async fn list_page_ancestors(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        WITH RECURSIVE up AS (
+            SELECT parent_id AS id, 1 AS depth
+            FROM content_items
+            WHERE id = $1
+            UNION ALL
+            SELECT p.parent_id, up.depth + 1
+            FROM content_items p
+            JOIN up ON p.id = up.id
+            WHERE p.parent_id IS NOT NULL AND up.depth < $2
+        )
+        SELECT c.*
+        FROM up
+        JOIN content_items c ON c.id = up.id
+        WHERE c.deleted_at IS NULL
+        ORDER BY up.depth DESC
+        "#,
+    )
+    .bind(id)
+    .bind(MAX_PAGE_DEPTH as i32)
+    .fetch_all(pool)
+    .await
+}
+
+/// The path a page is served at, without the leading slash.
+pub async fn get_page_path(
+    pool: &PgPool,
+    page: &ContentItem,
+) -> Result<String, sqlx::Error> {
+    let mut segments: Vec<String> =
+        list_page_ancestors(pool, page.id)
+            .await?
+            .into_iter()
+            .map(|p| p.slug)
+            .collect();
+    segments.push(page.slug.clone());
+    Ok(segments.join("/"))
+}
+
+/// Paths of every live page, keyed by id.
+pub async fn list_page_paths(
+    pool: &PgPool,
+) -> Result<HashMap<Uuid, String>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (Uuid, String)>(
+        r#"
+        WITH RECURSIVE tree AS (
+            SELECT id, slug::text AS path, 0 AS depth
+            FROM content_items
+            WHERE kind = 'page' AND parent_id IS NULL
+              AND deleted_at IS NULL
+            UNION ALL
+            SELECT c.id, tree.path || '/' || c.slug, tree.depth + 1
+            FROM content_items c
+            JOIN tree ON c.parent_id = tree.id
+            WHERE c.deleted_at IS NULL AND tree.depth < $1
+        )
+        SELECT id, path FROM tree
+        "#,
+    )
+    .bind(MAX_PAGE_DEPTH as i32)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().collect())
+}
+
+/// One page of the tree of pages `uid` can open in the admin, in the
+/// order of [`page_tree`](crate::models::page_tree): depth-first, with
+/// siblings by `menu_order` then title. Pages whose parent `uid` cannot
+/// see are top-level; pages in a parent cycle come last.
+pub async fn list_page_tree_for_user(
+    pool: &PgPool,
+    uid: Uuid,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<PageTreeRow>, sqlx::Error> {
+    sqlx::query_as::<_, PageTreeRow>(
+        r#"
+        WITH RECURSIVE visible AS (
+            SELECT c.id, c.parent_id, c.menu_order, c.title
+            FROM content_items c
+            WHERE c.kind = 'page'
+              AND c.deleted_at IS NULL
+              AND (c.owner_user_id IS NULL OR c.owner_user_id = $1 OR EXISTS(
+                    SELECT 1 FROM content_item_collaborators col
+                    WHERE col.content_item_id = c.id AND col.user_id = $1))
+        ),
+        tree AS (
+            SELECT v.id, 0 AS depth,
+                   ARRAY[ROW(v.menu_order, v.title COLLATE "C", v.id)]
+                       AS sort_key
+            FROM visible v
+            WHERE v.parent_id IS NULL
+               OR v.parent_id NOT IN (SELECT id FROM visible)
+            UNION ALL
+            SELECT v.id, tree.depth + 1,
+                   tree.sort_key
+                       || ROW(v.menu_order, v.title COLLATE "C", v.id)
+            FROM visible v
+            JOIN tree ON v.parent_id = tree.id
+            WHERE tree.depth < $2
+        ),
+        ordered AS (
+            SELECT id, depth, false AS stray, sort_key FROM tree
+            UNION ALL
+            SELECT v.id, 0, true,
+                   ARRAY[ROW(v.menu_order, v.title COLLATE "C", v.id)]
+            FROM visible v
+            WHERE v.id NOT IN (SELECT id FROM tree)
+        )
+        SELECT c.*, ordered.depth
+        FROM ordered
+        JOIN content_items c ON c.id = ordered.id
+        ORDER BY ordered.stray, ordered.sort_key
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(uid)
+    .bind(MAX_PAGE_DEPTH as i32)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn is_page_ancestor_or_self(
+    pool: &PgPool,
+    ancestor_id: Uuid,
+    page_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        WITH RECURSIVE ancestors AS (
+            SELECT id, parent_id FROM content_items WHERE id = $2
+            UNION
+            SELECT c.id, c.parent_id
+            FROM content_items c
+            JOIN ancestors a ON c.id = a.parent_id
+        )
+        SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = $1)
+        "#,
+    )
+    .bind(ancestor_id)
+    .bind(page_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// How many levels of parents sit above `id`, counting the page itself
+/// as one.
+pub async fn get_page_depth(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<usize, sqlx::Error> {
+    let ancestors = list_page_ancestors(pool, id).await?;
+    Ok(ancestors.len() + 1)
+}
+
+/// The deepest chain of subpages below `id`; zero for a leaf.
+pub async fn get_subtree_height(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<usize, sqlx::Error> {
+    let height = sqlx::query_scalar::<_, i32>(
+        r#"
+        WITH RECURSIVE down AS (
+            SELECT id, 0 AS depth FROM content_items WHERE id = $1
+            UNION ALL
+            SELECT c.id, down.depth + 1
+            FROM content_items c
+            JOIN down ON c.parent_id = down.id
+            WHERE c.deleted_at IS NULL AND down.depth < $2
+        )
+        SELECT COALESCE(MAX(depth), 0) FROM down
+        "#,
+    )
+    .bind(id)
+    .bind(MAX_PAGE_DEPTH as i32)
+    .fetch_one(pool)
+    .await?;
+    Ok(height as usize)
+}
+
+/// Put a page under a new parent and/or position. The paths it used to
+/// answer on are remembered when the parent changes. Callers check the
+/// parent is a live page outside the moved subtree.
+pub async fn move_page(
+    pool: &PgPool,
+    id: Uuid,
+    data: &PageMove,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    let current = sqlx::query_scalar::<_, Option<Uuid>>(
+        r#"
+        SELECT parent_id
+        FROM content_items
+        WHERE id = $1 AND kind = 'page' AND deleted_at IS NULL
+        FOR UPDATE
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    let Some(current_parent) = current else {
+        return Ok(None);
+    };
+    if current_parent != data.parent_id {
+        record_page_paths(&mut tx, id, None).await?;
+    }
+
+    let item = sqlx::query_as::<_, ContentItem>(
+        r#"
+        UPDATE content_items
+        SET
+            parent_id = $1,
+            menu_order = COALESCE($2, menu_order),
+            edited_at = now()
+        WHERE id = $3
+        RETURNING *
+        "#,
+    )
+    .bind(data.parent_id)
+    .bind(data.menu_order)
+    .bind(id)
+    .fetch_optional(&mut *tx)
+    .await?;
+    tx.commit().await?;
+    Ok(item)
+}
+
+/// The live item of `kind` that used to be served at `path`.
+pub async fn find_moved_content(
+    pool: &PgPool,
+    kind: ContentKind,
+    path: &str,
+) -> Result<Option<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT c.*
+        FROM content_slug_history h
+        JOIN content_items c ON c.id = h.content_item_id
+        WHERE h.kind = $1 AND h.path = $2 AND c.deleted_at IS NULL
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(path)
+    .fetch_optional(pool)
+    .await
+}
@@ -0,0 +1,119 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{PasswordResetToken, PasswordResetTokenCreate};
+
+/// Store a new reset token, discarding any earlier unused tokens of the
+/// same user so that only the most recent email link works.
+pub async fn create_password_reset_token(
+    pool: &PgPool,
+    data: &PasswordResetTokenCreate,
+) -> Result<PasswordResetToken, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"DELETE FROM password_reset_tokens WHERE user_id = $1 AND used_at IS NULL"#,
+    )
+    .bind(data.user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let token = sqlx::query_as::<_, PasswordResetToken>(
+        r#"
+        INSERT INTO password_reset_tokens (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+    )
+    .bind(data.user_id)
+    .bind(&data.token_hash)
+    .bind(data.expires_at)
+    .fetch_one(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(token)
+}
+
+/// Look up a token that can still be redeemed: unused, unexpired and
+/// belonging to a user that has not been deleted.
+pub async fn get_usable_password_reset_token(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<PasswordResetToken>, sqlx::Error> {
+    sqlx::query_as::<_, PasswordResetToken>(
+        r#"
+        SELECT t.*
+        FROM password_reset_tokens t
+        JOIN users u ON u.id = t.user_id
+        WHERE t.token_hash = $1
+          AND t.used_at IS NULL
+          AND t.expires_at > now()
+          AND u.deleted_at IS NULL
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Redeem a token and set the new password in one transaction.
+///
+/// The token is marked as used, the password replaced and every session
+/// of the user revoked. Returns the user id, or `None` if the token was
+/// not usable (in which case nothing is changed).
+pub async fn reset_password_with_token(
+    pool: &PgPool,
+    token_hash: &str,
+    password_hash: &str,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let user_id = sqlx::query_scalar::<_, Uuid>(
+        r#"
+        UPDATE password_reset_tokens t
+        SET used_at = now()
+        FROM users u
+        WHERE t.token_hash = $1
+          AND t.used_at IS NULL
+          AND t.expires_at > now()
+          AND u.id = t.user_id
+          AND u.deleted_at IS NULL
+        RETURNING t.user_id
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let Some(user_id) = user_id else {
+        return Ok(None);
+    };
+
+    sqlx::query(
+        r#"UPDATE users SET password_hash = $1, edited_at = now() WHERE id = $2"#,
+    )
+    .bind(password_hash)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(r#"DELETE FROM sessions WHERE user_id = $1"#)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await?;
+    Ok(Some(user_id))
+}
+
+pub async fn delete_stale_password_reset_tokens(
+    pool: &PgPool,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"DELETE FROM password_reset_tokens WHERE used_at IS NOT NULL OR expires_at <= now()"#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,145 @@
+use sqlx::{PgConnection, PgPool};
+use uuid::Uuid;
+
+use super::record_page_paths;
+use crate::models::{
+    ContentKind, Redirect, RedirectCreate, SlugHistoryEntry,
+};
+
+/// Remember the addresses an item answers on now, before its slug
+/// changes to `new_slug`: the post slug, or the paths of a page and
+/// its subpages. Keeping the slug records nothing.
+pub(crate) async fn record_slug_history(
+    conn: &mut PgConnection,
+    id: Uuid,
+    new_slug: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO content_slug_history (kind, path, content_item_id)
+        SELECT 'post', slug, id
+        FROM content_items
+        WHERE id = $1 AND kind = 'post' AND deleted_at IS NULL
+          AND slug <> $2
+        ON CONFLICT (kind, path) DO UPDATE
+        SET content_item_id = EXCLUDED.content_item_id,
+            created_at = now()
+        "#,
+    )
+    .bind(id)
+    .bind(new_slug)
+    .execute(&mut *conn)
+    .await?;
+    record_page_paths(conn, id, Some(new_slug)).await
+}
+
+/// Old addresses of live items, newest first.
+pub async fn list_slug_history(
+    pool: &PgPool,
+) -> Result<Vec<SlugHistoryEntry>, sqlx::Error> {
+    sqlx::query_as::<_, SlugHistoryEntry>(
+        r#"
+        SELECT h.kind, h.path, h.content_item_id, c.title, h.created_at
+        FROM content_slug_history h
+        JOIN content_items c ON c.id = h.content_item_id
+        WHERE c.deleted_at IS NULL
+        ORDER BY h.created_at DESC, h.path
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn delete_slug_history(
+    pool: &PgPool,
+    kind: ContentKind,
+    path: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM content_slug_history
+        WHERE kind = $1 AND path = $2
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(path)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn list_redirects(
+    pool: &PgPool,
+) -> Result<Vec<Redirect>, sqlx::Error> {
+    sqlx::query_as::<_, Redirect>(
+        r#"
+        SELECT *
+        FROM redirects
+        ORDER BY source_path, match_type
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn create_redirect(
+    pool: &PgPool,
+    data: &RedirectCreate,
+) -> Result<Redirect, sqlx::Error> {
+    sqlx::query_as::<_, Redirect>(
+        r#"
+        INSERT INTO redirects
+            (source_path, match_type, target_url, status_code, created_by_user_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(&data.source_path)
+    .bind(data.match_type.as_str())
+    .bind(data.target_url.as_deref())
+    .bind(data.status_code)
+    .bind(data.created_by_user_id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn delete_redirect(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM redirects
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// The redirect that applies to `path`: an exact match first, else the
+/// longest prefix. A prefix matches the path itself and anything below
+/// it, never a sibling that merely starts with the same letters.
+pub async fn find_redirect(
+    pool: &PgPool,
+    path: &str,
+) -> Result<Option<Redirect>, sqlx::Error> {
+    sqlx::query_as::<_, Redirect>(
+        r#"
+        SELECT *
+        FROM redirects
+        WHERE (match_type = 'exact' AND source_path = $1)
+           OR (match_type = 'prefix' AND (
+                rtrim(source_path, '/') = rtrim($1, '/')
+                OR starts_with($1, rtrim(source_path, '/') || '/')
+           ))
+        ORDER BY match_type = 'exact' DESC, length(source_path) DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(path)
+    .fetch_optional(pool)
+    .await
+}
@@ -1,6 +1,7 @@
 use sqlx::{PgPool, Postgres, Transaction};
 use uuid::Uuid;
 
+use super::record_slug_history;
 use crate::models::{
     ContentItem, ContentItemRevision, ContentItemRevisionMeta,
     ContentStatus,
@@ -40,8 +41,14 @@ pub async fn ensure_initial_revision(
     .await?;
 
     if !exists {
-        insert_revision_snapshot(&mut tx, &item, 1, actor_user_id)
-            .await?;
+        insert_revision_snapshot(
+            &mut tx,
+            &item,
+            1,
+            actor_user_id,
+            None,
+        )
+        .await?;
     }
 
     // Ensure pointer is sane.
@@ -66,6 +73,38 @@ pub async fn record_revision(
     pool: &PgPool,
     item: &ContentItem,
     actor_user_id: Option<Uuid>,
+) -> Result<i32, sqlx::Error> {
+    insert_next_revision(pool, item, actor_user_id, None).await
+}
+
+/// Record a revision for `item`, noting the status change when it
+/// differs from `previous`.
+pub async fn record_change(
+    pool: &PgPool,
+    item: &ContentItem,
+    previous: ContentStatus,
+    actor_user_id: Option<Uuid>,
+) -> Result<i32, sqlx::Error> {
+    let note = previous.transition_note(item.status);
+    insert_next_revision(pool, item, actor_user_id, note).await
+}
+
+/// Record a revision for a status transition such as a review decision.
+/// `note` is shown next to the revision in the history.
+pub async fn record_transition(
+    pool: &PgPool,
+    item: &ContentItem,
+    actor_user_id: Option<Uuid>,
+    note: &str,
+) -> Result<i32, sqlx::Error> {
+    insert_next_revision(pool, item, actor_user_id, Some(note)).await
+}
+
+async fn insert_next_revision(
+    pool: &PgPool,
+    item: &ContentItem,
+    actor_user_id: Option<Uuid>,
+    note: Option<&str>,
 ) -> Result<i32, sqlx::Error> {
     let mut tx = pool.begin().await?;
 
@@ -86,8 +125,14 @@ pub async fn record_revision(
     .await?;
 
     if !rev1_exists {
-        insert_revision_snapshot(&mut tx, item, 1, actor_user_id)
-            .await?;
+        insert_revision_snapshot(
+            &mut tx,
+            item,
+            1,
+            actor_user_id,
+            None,
+        )
+        .await?;
         sqlx::query(
             r#"
             UPDATE content_items
@@ -124,8 +169,14 @@ pub async fn record_revision(
         ));
     }
 
-    insert_revision_snapshot(&mut tx, item, next, actor_user_id)
-        .await?;
+    insert_revision_snapshot(
+        &mut tx,
+        item,
+        next,
+        actor_user_id,
+        note,
+    )
+    .await?;
 
     sqlx::query(
         r#"
@@ -155,7 +206,8 @@ pub async fn list_revisions(
             created_by_user_id,
             created_at,
             title,
-            status
+            status,
+            note
         FROM content_item_revisions
         WHERE content_item_id = $1
         ORDER BY rev DESC
@@ -221,12 +273,9 @@ async fn restore_revision_in_tx(
         return Ok(None);
     }
 
-    let rev = sqlx::query_as::<
-        _,
-        (String, String, String, String, ContentStatus),
-    >(
+    let rev = sqlx::query_as::<_, ContentItemRevision>(
         r#"
-        SELECT title, slug, content, template, status
+        SELECT *
         FROM content_item_revisions
         WHERE content_item_id = $1 AND rev = $2
         "#,
@@ -236,11 +285,13 @@ async fn restore_revision_in_tx(
     .fetch_optional(&mut **tx)
     .await?;
 
-    let Some((title, slug, content, template, status)) = rev else {
+    let Some(rev) = rev else {
         return Ok(None);
     };
+    record_slug_history(tx, content_item_id, &rev.slug).await?;
 
     // Keep published_at if present; restoring a draft shouldn't implicitly clear it.
+    // A scheduled revision only stays scheduled while a publish time is set.
     let item = sqlx::query_as::<_, ContentItem>(
         r#"
         UPDATE content_items
@@ -249,20 +300,30 @@ async fn restore_revision_in_tx(
             slug = $2,
             content = $3,
             template = $4,
-            status = $5,
+            status = CASE
+                WHEN $5 = 'scheduled' AND publish_at IS NULL THEN 'draft'
+                ELSE $5
+            END,
+            publish_at = CASE WHEN $5 = 'scheduled' THEN publish_at END,
             edited_at = now(),
-            current_rev = $6
+            current_rev = $6,
+            unfiltered_html = $8,
+            format = $9,
+            rendered_html = $10
         WHERE id = $7
         RETURNING *
         "#,
     )
-    .bind(title)
-    .bind(slug)
-    .bind(content)
-    .bind(template)
-    .bind(status.as_str())
+    .bind(rev.title)
+    .bind(rev.slug)
+    .bind(rev.content)
+    .bind(rev.template)
+    .bind(rev.status.as_str())
     .bind(rev_num)
     .bind(content_item_id)
+    .bind(rev.unfiltered_html)
+    .bind(rev.format.as_str())
+    .bind(rev.rendered_html)
     .fetch_optional(&mut **tx)
     .await?;
 
@@ -386,6 +447,7 @@ async fn insert_revision_snapshot(
     item: &ContentItem,
     rev_num: i32,
     actor_user_id: Option<Uuid>,
+    note: Option<&str>,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
         r#"
@@ -397,9 +459,13 @@ async fn insert_revision_snapshot(
             content,
             template,
             status,
-            created_by_user_id
+            created_by_user_id,
+            note,
+            unfiltered_html,
+            format,
+            rendered_html
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
         "#,
     )
     .bind(item.id)
@@ -410,6 +476,10 @@ async fn insert_revision_snapshot(
     .bind(&item.template)
     .bind(item.status.as_str())
     .bind(actor_user_id)
+    .bind(note)
+    .bind(item.unfiltered_html)
+    .bind(item.format.as_str())
+    .bind(item.rendered_html.as_deref())
     .execute(&mut **tx)
     .await?;
 
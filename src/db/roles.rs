@@ -3,7 +3,7 @@ use serde::Serialize;
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::models::{RoleName, User};
+use crate::models::{Capability, RoleName, User};
 
 #[derive(Debug, Serialize, sqlx::FromRow)]
 pub struct Role {
@@ -124,13 +124,129 @@ pub async fn set_user_role(
     Ok(())
 }
 
+pub async fn user_has_capability(
+    pool: &PgPool,
+    user_id: Uuid,
+    capability: Capability,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM user_capabilities
+            WHERE user_id = $1 AND capability = $2
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(capability.as_str())
+    .fetch_one(pool)
+    .await
+}
+
+/// Grant or revoke `capability`. Admins hold every capability anyway.
+pub async fn set_user_capability(
+    pool: &PgPool,
+    user_id: Uuid,
+    capability: Capability,
+    granted: bool,
+) -> Result<(), sqlx::Error> {
+    if granted {
+        sqlx::query(
+            r#"
+            INSERT INTO user_capabilities (user_id, capability)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+        )
+        .bind(user_id)
+        .bind(capability.as_str())
+        .execute(pool)
+        .await?;
+    } else {
+        sqlx::query(
+            r#"
+            DELETE FROM user_capabilities
+            WHERE user_id = $1 AND capability = $2
+            "#,
+        )
+        .bind(user_id)
+        .bind(capability.as_str())
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+/// Whether the user may publish and review content: admins, and users
+/// holding [`Capability::Publish`]. Everyone else submits for review.
+pub async fn user_can_publish(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM user_roles ur
+            JOIN roles r ON r.id = ur.role_id
+            WHERE ur.user_id = $1 AND r.name = 'admin'
+        )
+        OR EXISTS(
+            SELECT 1
+            FROM user_capabilities
+            WHERE user_id = $1 AND capability = 'publish'
+        )
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Whether content the user saves is kept as written: admins, and
+/// users holding [`Capability::UnfilteredHtml`]. Everyone else's
+/// content is sanitized.
+pub async fn user_can_use_unfiltered_html(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT EXISTS(
+            SELECT 1
+            FROM user_roles ur
+            JOIN roles r ON r.id = ur.role_id
+            WHERE ur.user_id = $1 AND r.name = 'admin'
+        )
+        OR EXISTS(
+            SELECT 1
+            FROM user_capabilities
+            WHERE user_id = $1 AND capability = 'unfiltered_html'
+        )
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Change a user's email directly. A changed address loses its verified
+/// status; self-service changes go through
+/// [`verify_email_with_token`](super::verify_email_with_token) instead.
 pub async fn update_user_email(
     pool: &PgPool,
     user_id: Uuid,
     email: &str,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
-        r#"UPDATE users SET email = $1, edited_at = now() WHERE id = $2"#,
+        r#"
+        UPDATE users
+        SET email = $1,
+            email_verified_at = CASE WHEN email = $1 THEN email_verified_at ELSE NULL END,
+            edited_at = now()
+        WHERE id = $2
+        "#,
     )
     .bind(email)
     .bind(user_id)
@@ -223,23 +339,6 @@ pub async fn get_user_by_email(
     .await
 }
 
-pub async fn mark_email_verified(
-    pool: &PgPool,
-    user_id: Uuid,
-) -> Result<User, sqlx::Error> {
-    sqlx::query_as::<_, User>(
-        r#"
-        UPDATE users
-        SET email_verified_at = COALESCE(email_verified_at, now()), edited_at = now()
-        WHERE id = $1
-        RETURNING *
-        "#,
-    )
-    .bind(user_id)
-    .fetch_one(pool)
-    .await
-}
-
 pub async fn list_roles(
     pool: &PgPool,
 ) -> Result<Vec<Role>, sqlx::Error> {
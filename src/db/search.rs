@@ -0,0 +1,143 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    ContentItem, ContentKind, SNIPPET_MATCH_END, SNIPPET_MATCH_START,
+    SearchHit,
+};
+
+/// `ts_headline` options for result snippets.
+fn headline_options() -> String {
+    format!(
+        "StartSel={SNIPPET_MATCH_START}, StopSel={SNIPPET_MATCH_END}, \
+         MaxWords=35, MinWords=15, MaxFragments=2, \
+         FragmentDelimiter=\" … \""
+    )
+}
+
+/// Published posts and pages matching `query`, best first. `query`
+/// uses web search syntax: `"quoted phrases"`, `or` and `-excluded`
+/// words.
+pub async fn search_published_content(
+    pool: &PgPool,
+    query: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<SearchHit>, sqlx::Error> {
+    // Snippets are only made for the page of results shown.
+    sqlx::query_as::<_, SearchHit>(
+        r#"
+        WITH hits AS (
+            SELECT c.*,
+                   ts_rank(c.search_vector, websearch_to_tsquery(
+                       content_search_config(), $1)) AS rank
+            FROM content_items c
+            WHERE c.search_vector @@ websearch_to_tsquery(
+                      content_search_config(), $1)
+              AND c.status = 'published'
+              AND (c.unpublish_at IS NULL OR c.unpublish_at > now())
+              AND c.deleted_at IS NULL
+            ORDER BY rank DESC, c.published_at DESC NULLS LAST, c.id
+            LIMIT $2 OFFSET $3
+        )
+        SELECT hits.*,
+               ts_headline(
+                   content_search_config(),
+                   content_search_text(
+                       COALESCE(hits.rendered_html, hits.content)),
+                   websearch_to_tsquery(content_search_config(), $1),
+                   $4) AS snippet
+        FROM hits
+        ORDER BY hits.rank DESC, hits.published_at DESC NULLS LAST,
+                 hits.id
+        "#,
+    )
+    .bind(query)
+    .bind(limit)
+    .bind(offset)
+    .bind(headline_options())
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_published_search_results(
+    pool: &PgPool,
+    query: &str,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*)
+        FROM content_items c
+        WHERE c.search_vector @@ websearch_to_tsquery(
+                  content_search_config(), $1)
+          AND c.status = 'published'
+          AND (c.unpublish_at IS NULL OR c.unpublish_at > now())
+          AND c.deleted_at IS NULL
+        "#,
+    )
+    .bind(query)
+    .fetch_one(pool)
+    .await
+}
+
+/// Items of `kind` visible to `uid` that match `query`, in any status,
+/// best first. The admin counterpart of [`search_published_content`].
+pub async fn search_content_for_user(
+    pool: &PgPool,
+    kind: ContentKind,
+    uid: Uuid,
+    query: &str,
+    limit: i64,
+    offset: i64,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT c.*
+        FROM content_items c
+        WHERE c.kind = $1
+          AND c.deleted_at IS NULL
+          AND (c.owner_user_id IS NULL OR c.owner_user_id = $2 OR EXISTS(
+                SELECT 1 FROM content_item_collaborators col
+                WHERE col.content_item_id = c.id AND col.user_id = $2))
+          AND c.search_vector @@ websearch_to_tsquery(
+                  content_search_config(), $3)
+        ORDER BY ts_rank(c.search_vector, websearch_to_tsquery(
+                     content_search_config(), $3)) DESC,
+                 c.created_at DESC, c.id
+        LIMIT $4 OFFSET $5
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(uid)
+    .bind(query)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn count_content_search_results_for_user(
+    pool: &PgPool,
+    kind: ContentKind,
+    uid: Uuid,
+    query: &str,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*)
+        FROM content_items c
+        WHERE c.kind = $1
+          AND c.deleted_at IS NULL
+          AND (c.owner_user_id IS NULL OR c.owner_user_id = $2 OR EXISTS(
+                SELECT 1 FROM content_item_collaborators col
+                WHERE col.content_item_id = c.id AND col.user_id = $2))
+          AND c.search_vector @@ websearch_to_tsquery(
+                  content_search_config(), $3)
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(uid)
+    .bind(query)
+    .fetch_one(pool)
+    .await
+}
@@ -0,0 +1,153 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{Session, SessionCreate};
+
+pub async fn create_session(
+    pool: &PgPool,
+    data: &SessionCreate,
+) -> Result<Session, sqlx::Error> {
+    sqlx::query_as::<_, Session>(
+        r#"
+        INSERT INTO sessions (user_id, token_hash, expires_at, ip, user_agent)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(data.user_id)
+    .bind(&data.token_hash)
+    .bind(data.expires_at)
+    .bind(data.ip.as_deref())
+    .bind(data.user_agent.as_deref())
+    .fetch_one(pool)
+    .await
+}
+
+/// Look up a live session by token hash.
+///
+/// Expired sessions and sessions belonging to deleted users are treated as
+/// if they did not exist.
+pub async fn get_active_session(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<Session>, sqlx::Error> {
+    sqlx::query_as::<_, Session>(
+        r#"
+        SELECT s.*
+        FROM sessions s
+        JOIN users u ON u.id = s.user_id
+        WHERE s.token_hash = $1
+          AND s.expires_at > now()
+          AND u.deleted_at IS NULL
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Record activity on a session and push its expiry forward (sliding renewal).
+pub async fn touch_session(
+    pool: &PgPool,
+    id: Uuid,
+    expires_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE sessions
+        SET last_seen = now(), expires_at = $1
+        WHERE id = $2
+        "#,
+    )
+    .bind(expires_at)
+    .bind(id)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+pub async fn delete_session(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(r#"DELETE FROM sessions WHERE id = $1"#)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+pub async fn delete_sessions_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result =
+        sqlx::query(r#"DELETE FROM sessions WHERE user_id = $1"#)
+            .bind(user_id)
+            .execute(pool)
+            .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn delete_expired_sessions(
+    pool: &PgPool,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"DELETE FROM sessions WHERE expires_at <= now()"#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+/// Active sessions of a user, most recently used first.
+pub async fn list_sessions_for_user(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Vec<Session>, sqlx::Error> {
+    sqlx::query_as::<_, Session>(
+        r#"
+        SELECT *
+        FROM sessions
+        WHERE user_id = $1 AND expires_at > now()
+        ORDER BY last_seen DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Revoke a single session, but only if it belongs to `user_id`.
+pub async fn delete_session_for_user(
+    pool: &PgPool,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"DELETE FROM sessions WHERE id = $1 AND user_id = $2"#,
+    )
+    .bind(id)
+    .bind(user_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Revoke every session of `user_id` except `keep_id`.
+pub async fn delete_other_sessions(
+    pool: &PgPool,
+    user_id: Uuid,
+    keep_id: Uuid,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"DELETE FROM sessions WHERE user_id = $1 AND id <> $2"#,
+    )
+    .bind(user_id)
+    .bind(keep_id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
@@ -0,0 +1,57 @@
+use sqlx::PgPool;
+
+use crate::models::{ContentItem, ContentKind, SitemapChunk};
+
+/// The files a kind's sitemap is split into, `per_page` items each.
+/// Empty when nothing of the kind is listed.
+pub async fn list_sitemap_chunks(
+    pool: &PgPool,
+    kind: ContentKind,
+    per_page: i64,
+) -> Result<Vec<SitemapChunk>, sqlx::Error> {
+    sqlx::query_as::<_, SitemapChunk>(
+        r#"
+        SELECT (n - 1) / $2 + 1 AS page, max(edited_at) AS lastmod
+        FROM (
+            SELECT edited_at,
+                   row_number() OVER (ORDER BY created_at, id) AS n
+            FROM content_items
+            WHERE kind = $1 AND status = 'published'
+              AND (unpublish_at IS NULL OR unpublish_at > now())
+              AND deleted_at IS NULL AND NOT noindex
+        ) listed
+        GROUP BY page
+        ORDER BY page
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(per_page)
+    .fetch_all(pool)
+    .await
+}
+
+/// Items listed in file `page` (1-based) of a kind's sitemap: live and
+/// not marked `noindex`, oldest first so files stay stable.
+pub async fn list_sitemap_content(
+    pool: &PgPool,
+    kind: ContentKind,
+    page: i64,
+    per_page: i64,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        SELECT *
+        FROM content_items
+        WHERE kind = $1 AND status = 'published'
+          AND (unpublish_at IS NULL OR unpublish_at > now())
+          AND deleted_at IS NULL AND NOT noindex
+        ORDER BY created_at, id
+        LIMIT $2 OFFSET $3
+        "#,
+    )
+    .bind(kind.as_str())
+    .bind(per_page)
+    .bind((page - 1).max(0) * per_page)
+    .fetch_all(pool)
+    .await
+}
@@ -5,7 +5,7 @@ use uuid::Uuid;
 use crate::models::{Site, SiteCreate, SiteUpdate};
 
 /// Escape SQL LIKE wildcards
-fn escape_sql_like(input: &str) -> String {
+pub(crate) fn escape_sql_like(input: &str) -> String {
     input
         .replace('\\', "\\\\")
         .replace('%', "\\%")
@@ -120,8 +120,15 @@ pub async fn update_site(
             default_template = COALESCE($4, default_template),
             homepage_type = COALESCE($5, homepage_type),
             homepage_page_id = CASE WHEN $6 THEN $7 ELSE homepage_page_id END,
+            require_verified_email = COALESCE($8, require_verified_email),
+            require_admin_2fa = COALESCE($9, require_admin_2fa),
+            trash_retention_days = COALESCE($10, trash_retention_days),
+            feed_full_text = COALESCE($11, feed_full_text),
+            robots_txt = COALESCE($12, robots_txt),
+            search_language = COALESCE($13, search_language),
+            posts_per_page = COALESCE($14, posts_per_page),
             edited_at = now()
-        WHERE id = $8 AND owner_user_id = $9
+        WHERE id = $15 AND owner_user_id = $16
         RETURNING *
         "#,
     )
@@ -132,6 +139,13 @@ pub async fn update_site(
     .bind(data.homepage_type)
     .bind(update_homepage_page)
     .bind(homepage_page_value)
+    .bind(data.require_verified_email)
+    .bind(data.require_admin_2fa)
+    .bind(data.trash_retention_days)
+    .bind(data.feed_full_text)
+    .bind(data.robots_txt.as_deref())
+    .bind(data.search_language.as_deref())
+    .bind(data.posts_per_page)
     .bind(id)
     .bind(owner_user_id)
     .fetch_optional(pool)
@@ -161,3 +175,14 @@ pub async fn publish_site(
     .fetch_optional(pool)
     .await
 }
+
+/// Names of the text search configurations a site can search in.
+pub async fn list_search_languages(
+    pool: &PgPool,
+) -> Result<Vec<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, String>(
+        "SELECT cfgname::text FROM pg_ts_config ORDER BY cfgname",
+    )
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,268 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{
+    ContentItem, Taxonomy, Term, TermCreate, TermUpdate,
+};
+
+pub async fn list_terms(
+    pool: &PgPool,
+    taxonomy: Taxonomy,
+) -> Result<Vec<Term>, sqlx::Error> {
+    sqlx::query_as::<_, Term>(
+        r#"
+        SELECT *
+        FROM terms
+        WHERE taxonomy = $1
+        ORDER BY name ASC
+        "#,
+    )
+    .bind(taxonomy)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_term_by_id(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<Option<Term>, sqlx::Error> {
+    sqlx::query_as::<_, Term>(
+        r#"
+        SELECT *
+        FROM terms
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_term_by_slug(
+    pool: &PgPool,
+    taxonomy: Taxonomy,
+    slug: &str,
+) -> Result<Option<Term>, sqlx::Error> {
+    sqlx::query_as::<_, Term>(
+        r#"
+        SELECT *
+        FROM terms
+        WHERE taxonomy = $1 AND slug = $2
+        "#,
+    )
+    .bind(taxonomy)
+    .bind(slug)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn create_term(
+    pool: &PgPool,
+    data: &TermCreate,
+) -> Result<Term, sqlx::Error> {
+    sqlx::query_as::<_, Term>(
+        r#"
+        INSERT INTO terms (taxonomy, slug, name, description, parent_id)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#,
+    )
+    .bind(data.taxonomy)
+    .bind(&data.slug)
+    .bind(&data.name)
+    .bind(&data.description)
+    .bind(data.parent_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Fetch the tag with `slug`, creating it named `name` if missing.
+pub async fn get_or_create_tag(
+    pool: &PgPool,
+    slug: &str,
+    name: &str,
+) -> Result<Term, sqlx::Error> {
+    // The no-op update makes RETURNING yield the existing row too.
+    sqlx::query_as::<_, Term>(
+        r#"
+        INSERT INTO terms (taxonomy, slug, name)
+        VALUES ('tag', $1, $2)
+        ON CONFLICT (taxonomy, slug)
+        DO UPDATE SET slug = EXCLUDED.slug
+        RETURNING *
+        "#,
+    )
+    .bind(slug)
+    .bind(name)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn update_term(
+    pool: &PgPool,
+    id: Uuid,
+    data: &TermUpdate,
+) -> Result<Option<Term>, sqlx::Error> {
+    let update_parent = data.parent_id.is_some();
+    let parent_value = data.parent_id.flatten();
+
+    sqlx::query_as::<_, Term>(
+        r#"
+        UPDATE terms
+        SET
+            slug = COALESCE($1, slug),
+            name = COALESCE($2, name),
+            description = COALESCE($3, description),
+            parent_id = CASE WHEN $4 THEN $5 ELSE parent_id END
+        WHERE id = $6
+        RETURNING *
+        "#,
+    )
+    .bind(data.slug.as_deref())
+    .bind(data.name.as_deref())
+    .bind(data.description.as_deref())
+    .bind(update_parent)
+    .bind(parent_value)
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Delete a term. Its children move up to the top level and posts
+/// simply lose the assignment.
+pub async fn delete_term(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        DELETE FROM terms
+        WHERE id = $1
+        "#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Whether `ancestor_id` is `term_id` itself or one of its ancestors.
+/// Making such a term the parent of `ancestor_id` would form a cycle.
+pub async fn is_term_ancestor_or_self(
+    pool: &PgPool,
+    ancestor_id: Uuid,
+    term_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        WITH RECURSIVE ancestors AS (
+            SELECT id, parent_id FROM terms WHERE id = $2
+            UNION
+            SELECT t.id, t.parent_id
+            FROM terms t
+            JOIN ancestors a ON t.id = a.parent_id
+        )
+        SELECT EXISTS (SELECT 1 FROM ancestors WHERE id = $1)
+        "#,
+    )
+    .bind(ancestor_id)
+    .bind(term_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// Terms assigned to a content item, categories first.
+pub async fn list_terms_for_content(
+    pool: &PgPool,
+    content_item_id: Uuid,
+) -> Result<Vec<Term>, sqlx::Error> {
+    sqlx::query_as::<_, Term>(
+        r#"
+        SELECT t.*
+        FROM terms t
+        JOIN content_terms ct ON ct.term_id = t.id
+        WHERE ct.content_item_id = $1
+        ORDER BY t.taxonomy ASC, t.name ASC
+        "#,
+    )
+    .bind(content_item_id)
+    .fetch_all(pool)
+    .await
+}
+
+/// Replace the item's terms of one taxonomy with `term_ids`. Ids of
+/// terms from another taxonomy are ignored.
+pub async fn set_content_terms(
+    pool: &PgPool,
+    content_item_id: Uuid,
+    taxonomy: Taxonomy,
+    term_ids: &[Uuid],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        DELETE FROM content_terms ct
+        USING terms t
+        WHERE ct.term_id = t.id
+          AND ct.content_item_id = $1
+          AND t.taxonomy = $2
+        "#,
+    )
+    .bind(content_item_id)
+    .bind(taxonomy)
+    .execute(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO content_terms (content_item_id, term_id)
+        SELECT $1, t.id
+        FROM terms t
+        WHERE t.id = ANY($2) AND t.taxonomy = $3
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(content_item_id)
+    .bind(term_ids)
+    .bind(taxonomy)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await
+}
+
+/// Published posts filed under a term, newest first. A category also
+/// lists the posts of its subcategories.
+pub async fn list_published_posts_for_term(
+    pool: &PgPool,
+    term_id: Uuid,
+) -> Result<Vec<ContentItem>, sqlx::Error> {
+    sqlx::query_as::<_, ContentItem>(
+        r#"
+        WITH RECURSIVE subtree AS (
+            SELECT id FROM terms WHERE id = $1
+            UNION
+            SELECT t.id
+            FROM terms t
+            JOIN subtree s ON t.parent_id = s.id
+        )
+        SELECT c.*
+        FROM content_items c
+        WHERE c.kind = 'post'
+          AND c.status = 'published'
+          AND (c.unpublish_at IS NULL OR c.unpublish_at > now())
+          AND c.deleted_at IS NULL
+          AND EXISTS (
+              SELECT 1
+              FROM content_terms ct
+              JOIN subtree s ON s.id = ct.term_id
+              WHERE ct.content_item_id = c.id
+          )
+        ORDER BY c.published_at DESC NULLS LAST, c.created_at DESC
+        "#,
+    )
+    .bind(term_id)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,298 @@
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::models::{LoginChallenge, LoginChallengeCreate, UserTotp};
+
+pub async fn get_user_totp(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<Option<UserTotp>, sqlx::Error> {
+    sqlx::query_as::<_, UserTotp>(
+        r#"SELECT * FROM user_totp WHERE user_id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Store a fresh secret for an enrollment that is not yet confirmed.
+///
+/// Returns `None` if two-factor is already enabled for the user; an
+/// active secret is never overwritten.
+pub async fn start_totp_enrollment(
+    pool: &PgPool,
+    user_id: Uuid,
+    secret: &str,
+) -> Result<Option<UserTotp>, sqlx::Error> {
+    sqlx::query_as::<_, UserTotp>(
+        r#"
+        INSERT INTO user_totp (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE
+        SET secret = EXCLUDED.secret,
+            last_used_step = NULL,
+            created_at = now()
+        WHERE user_totp.enabled_at IS NULL
+        RETURNING *
+        "#,
+    )
+    .bind(user_id)
+    .bind(secret)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Confirm a pending enrollment and store its recovery codes in one
+/// transaction. `step` is the time step of the code that confirmed it.
+/// Returns `false` if there was no pending enrollment.
+pub async fn enable_totp(
+    pool: &PgPool,
+    user_id: Uuid,
+    step: i64,
+    recovery_code_hashes: &[String],
+) -> Result<bool, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE user_totp
+        SET enabled_at = now(), last_used_step = $2
+        WHERE user_id = $1 AND enabled_at IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(&mut *tx)
+    .await?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    insert_recovery_codes(&mut tx, user_id, recovery_code_hashes)
+        .await?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Remove the secret and all recovery codes of a user.
+pub async fn disable_totp(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"DELETE FROM user_recovery_codes WHERE user_id = $1"#,
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await?;
+    sqlx::query(r#"DELETE FROM user_totp WHERE user_id = $1"#)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+    tx.commit().await
+}
+
+/// Accept a code's time step if it is newer than the last accepted one.
+///
+/// Returns `false` for a replayed (or older) step, so each code can sign
+/// in only once.
+pub async fn record_totp_step(
+    pool: &PgPool,
+    user_id: Uuid,
+    step: i64,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_totp
+        SET last_used_step = $2
+        WHERE user_id = $1
+          AND enabled_at IS NOT NULL
+          AND (last_used_step IS NULL OR last_used_step < $2)
+        "#,
+    )
+    .bind(user_id)
+    .bind(step)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+async fn insert_recovery_codes(
+    tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: Uuid,
+    code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"DELETE FROM user_recovery_codes WHERE user_id = $1"#,
+    )
+    .bind(user_id)
+    .execute(&mut **tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_recovery_codes (user_id, code_hash)
+        SELECT $1, unnest($2::text[])
+        "#,
+    )
+    .bind(user_id)
+    .bind(code_hashes)
+    .execute(&mut **tx)
+    .await?;
+
+    Ok(())
+}
+
+/// Replace all recovery codes of a user, invalidating the old set.
+pub async fn replace_recovery_codes(
+    pool: &PgPool,
+    user_id: Uuid,
+    code_hashes: &[String],
+) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    insert_recovery_codes(&mut tx, user_id, code_hashes).await?;
+    tx.commit().await
+}
+
+/// Spend a recovery code. Returns `false` if it is unknown or used.
+pub async fn use_recovery_code(
+    pool: &PgPool,
+    user_id: Uuid,
+    code_hash: &str,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        r#"
+        UPDATE user_recovery_codes
+        SET used_at = now()
+        WHERE id = (
+            SELECT id FROM user_recovery_codes
+            WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL
+            LIMIT 1
+        )
+        "#,
+    )
+    .bind(user_id)
+    .bind(code_hash)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() == 1)
+}
+
+pub async fn count_unused_recovery_codes(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar::<_, i64>(
+        r#"SELECT COUNT(*) FROM user_recovery_codes WHERE user_id = $1 AND used_at IS NULL"#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+/// True if the site requires two-factor for admins and `user_id` has not
+/// enabled it. The caller checks that the user is an admin.
+pub async fn admin_two_factor_missing(
+    pool: &PgPool,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar::<_, bool>(
+        r#"
+        SELECT COALESCE((
+            SELECT require_admin_2fa
+            FROM sites
+            WHERE status = 'published'
+            ORDER BY created_at ASC
+            LIMIT 1
+        ), false)
+        AND NOT EXISTS(
+            SELECT 1 FROM user_totp
+            WHERE user_id = $1 AND enabled_at IS NOT NULL
+        )
+        "#,
+    )
+    .bind(user_id)
+    .fetch_one(pool)
+    .await
+}
+
+pub async fn create_login_challenge(
+    pool: &PgPool,
+    data: &LoginChallengeCreate,
+) -> Result<LoginChallenge, sqlx::Error> {
+    sqlx::query_as::<_, LoginChallenge>(
+        r#"
+        INSERT INTO login_challenges (user_id, token_hash, expires_at)
+        VALUES ($1, $2, $3)
+        RETURNING *
+        "#,
+    )
+    .bind(data.user_id)
+    .bind(&data.token_hash)
+    .bind(data.expires_at)
+    .fetch_one(pool)
+    .await
+}
+
+/// Look up an unexpired challenge, counting this lookup as an attempt.
+///
+/// Returns `None` once `max_attempts` is exceeded, which burns the
+/// challenge and sends the user back to the password step.
+pub async fn attempt_login_challenge(
+    pool: &PgPool,
+    token_hash: &str,
+    max_attempts: i32,
+) -> Result<Option<LoginChallenge>, sqlx::Error> {
+    sqlx::query_as::<_, LoginChallenge>(
+        r#"
+        UPDATE login_challenges
+        SET attempts = attempts + 1
+        WHERE token_hash = $1
+          AND expires_at > now()
+          AND attempts < $2
+        RETURNING *
+        "#,
+    )
+    .bind(token_hash)
+    .bind(max_attempts)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn get_login_challenge(
+    pool: &PgPool,
+    token_hash: &str,
+) -> Result<Option<LoginChallenge>, sqlx::Error> {
+    sqlx::query_as::<_, LoginChallenge>(
+        r#"SELECT * FROM login_challenges WHERE token_hash = $1 AND expires_at > now()"#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn delete_login_challenge(
+    pool: &PgPool,
+    id: Uuid,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(r#"DELETE FROM login_challenges WHERE id = $1"#)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn delete_expired_login_challenges(
+    pool: &PgPool,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        r#"DELETE FROM login_challenges WHERE expires_at <= now()"#,
+    )
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
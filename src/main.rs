@@ -2,77 +2,242 @@ mod web;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
+    // `rustpress openapi` prints the API description for client
+    // generators and exits without touching the database.
+    if std::env::args().nth(1).as_deref() == Some("openapi") {
+        println!(
+            "{}",
+            web::handlers::api::openapi::openapi_json_string()
+        );
+        return Ok(());
+    }
+
     dotenvy::dotenv().ok();
 
     env_logger::init_from_env(
         env_logger::Env::default().default_filter_or("info"),
     );
 
-    use crate::web::helpers::{AdminStatus, render_unauthorized};
+    use crate::web::api_token::{
+        ApiTokenAuth, bearer_error, bearer_token, required_scope,
+    };
+    use crate::web::handlers::api::{OPENAPI_PATH, is_api_path};
+    use crate::web::handlers::two_factor::TWO_FACTOR_SETUP_PATH;
+    use crate::web::helpers::{
+        AdminStatus, is_htmx, render_unauthorized,
+    };
+    use crate::web::session::{
+        CurrentSession, SESSION_COOKIE, SESSION_TOUCH_INTERVAL_SECS,
+        session_cookie, session_expiry,
+    };
     use crate::web::{AppState, handlers};
     use actix_files::Files;
     use actix_web::body::BoxBody;
     use actix_web::dev::{ServiceRequest, ServiceResponse};
+    use actix_web::http::StatusCode;
     use actix_web::http::header::LOCATION;
     use actix_web::middleware::{Next, from_fn};
     use actix_web::{
         App, Error, HttpMessage, HttpResponse, HttpServer,
     };
-    use rustpress::db::Database;
-    use rustpress::db::user_is_admin;
+    use chrono::Utc;
+    use rustpress::db::{self, Database};
+    use rustpress::services::{
+        ContentScheduler, DEFAULT_IMAGE_WIDTHS, FileMailer,
+        HtmlPolicy, LocalMediaStorage, LogMailer, MailOutbox, Mailer,
+        MediaStorage, SmtpConfig, SmtpMailer, TokenManager,
+        TrashPurger, parse_image_widths,
+    };
+    use std::sync::Arc;
+
+    /// Routes under /admin that are reachable without a session.
+    const PUBLIC_ADMIN_PREFIXES: &[&str] = &[
+        "/admin/login",
+        "/admin/register",
+        "/admin/forgot-password",
+        "/admin/reset-password",
+        "/admin/verify-email",
+    ];
+
+    /// Routes an admin can reach while enrollment in required 2FA is
+    /// still pending.
+    const TWO_FACTOR_EXEMPT_PREFIXES: &[&str] =
+        &[TWO_FACTOR_SETUP_PATH, "/admin/logout"];
 
     /// Routes that require the admin role (non-admins get 403).
-    const ADMIN_ONLY_PREFIXES: &[&str] =
-        &["/admin/configuration", "/admin/users"];
+    const ADMIN_ONLY_PREFIXES: &[&str] = &[
+        "/admin/configuration",
+        "/admin/users",
+        "/admin/terms",
+        "/admin/redirects",
+    ];
 
     async fn admin_auth_guard(
         req: ServiceRequest,
         next: Next<BoxBody>,
     ) -> Result<ServiceResponse<BoxBody>, Error> {
         let path = req.path().to_owned();
+        let mut renewed_cookie = None;
 
-        if path.starts_with("/admin")
-            && !path.starts_with("/admin/login")
-            && !path.starts_with("/admin/register")
+        let is_api = is_api_path(&path) && path != OPENAPI_PATH;
+        if is_api
+            || path.starts_with("/admin")
+                && !PUBLIC_ADMIN_PREFIXES
+                    .iter()
+                    .any(|p| path.starts_with(p))
         {
             // --- authentication check ---
-            let uid = req.cookie("rp_uid").and_then(|c| {
-                uuid::Uuid::parse_str(c.value().trim()).ok()
-            });
-
-            if uid.is_none() {
-                let is_htmx = req
-                    .headers()
-                    .get("HX-Request")
-                    .and_then(|v| v.to_str().ok())
-                    .is_some_and(|s| s.eq_ignore_ascii_case("true"));
-
-                if is_htmx {
-                    return Ok(req.into_response(
+            let pool = req
+                .app_data::<actix_web::web::Data<AppState>>()
+                .map(|s| s.pool.clone());
+
+            let bearer = bearer_token(req.headers());
+            let uid = if let Some(raw) = &bearer {
+                // --- API token authentication ---
+                // A bearer header means a script is calling; any session
+                // cookie sent alongside it is ignored.
+                let Some(scope) = required_scope(req.method(), &path)
+                else {
+                    let response = bearer_error(
+                        &path,
+                        StatusCode::FORBIDDEN,
+                        "error=\"insufficient_scope\"",
+                        "This route requires a signed-in session",
+                    );
+                    return Ok(req.into_response(response));
+                };
+
+                let api_token = match &pool {
+                    Some(pool) => db::get_active_api_token(
+                        pool,
+                        &TokenManager::hash(raw),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::error!("API token lookup failed: {}", e);
+                        None
+                    }),
+                    None => None,
+                };
+
+                let Some(api_token) = api_token else {
+                    let response = bearer_error(
+                        &path,
+                        StatusCode::UNAUTHORIZED,
+                        "error=\"invalid_token\"",
+                        "Invalid or expired API token",
+                    );
+                    return Ok(req.into_response(response));
+                };
+
+                if !api_token.has_scope(scope) {
+                    let response = bearer_error(
+                        &path,
+                        StatusCode::FORBIDDEN,
+                        &format!(
+                            "error=\"insufficient_scope\", scope=\"{scope}\""
+                        ),
+                        &format!("API token lacks the {scope} scope"),
+                    );
+                    return Ok(req.into_response(response));
+                }
+
+                if let Some(pool) = &pool
+                    && let Err(e) =
+                        db::touch_api_token(pool, api_token.id).await
+                {
+                    log::error!("API token touch failed: {}", e);
+                }
+
+                req.extensions_mut().insert(ApiTokenAuth {
+                    user_id: api_token.user_id,
+                });
+                api_token.user_id
+            } else if is_api {
+                // The JSON API only accepts tokens.
+                let response = bearer_error(
+                    &path,
+                    StatusCode::UNAUTHORIZED,
+                    "",
+                    "Authentication required",
+                );
+                return Ok(req.into_response(response));
+            } else {
+                let token = req
+                    .cookie(SESSION_COOKIE)
+                    .map(|c| c.value().trim().to_string())
+                    .filter(|s| !s.is_empty());
+
+                let session =
+                    match (&pool, &token) {
+                        (Some(pool), Some(token)) => {
+                            db::get_active_session(
+                                pool,
+                                &TokenManager::hash(token),
+                            )
+                            .await
+                            .unwrap_or_else(|e| {
+                                log::error!(
+                                    "Session lookup failed: {}",
+                                    e
+                                );
+                                None
+                            })
+                        }
+                        _ => None,
+                    };
+
+                let Some(session) = session else {
+                    let response = if is_htmx(req.request()) {
                         HttpResponse::Unauthorized()
                             .insert_header((
                                 "HX-Redirect",
                                 "/admin/login",
                             ))
-                            .finish(),
-                    ));
+                            .finish()
+                    } else {
+                        HttpResponse::SeeOther()
+                            .insert_header((LOCATION, "/admin/login"))
+                            .finish()
+                    };
+                    return Ok(req.into_response(response));
+                };
+
+                // --- sliding renewal ---
+                let idle = Utc::now() - session.last_seen;
+                if idle.num_seconds() >= SESSION_TOUCH_INTERVAL_SECS
+                    && let Some(pool) = &pool
+                {
+                    match db::touch_session(
+                        pool,
+                        session.id,
+                        session_expiry(),
+                    )
+                    .await
+                    {
+                        Ok(()) => {
+                            renewed_cookie =
+                                token.as_deref().map(session_cookie);
+                        }
+                        Err(e) => {
+                            log::error!(
+                                "Session renewal failed: {}",
+                                e
+                            )
+                        }
+                    }
                 }
 
-                return Ok(req.into_response(
-                    HttpResponse::SeeOther()
-                        .insert_header((LOCATION, "/admin/login"))
-                        .finish(),
-                ));
-            }
+                req.extensions_mut().insert(CurrentSession {
+                    id: session.id,
+                    user_id: session.user_id,
+                });
+                session.user_id
+            };
 
             // --- role check (compute once per request) ---
-            let uid = uid.unwrap();
-            let pool = req
-                .app_data::<actix_web::web::Data<AppState>>()
-                .map(|s| s.pool.clone());
-
-            let is_admin = if let Some(pool) = pool {
-                user_is_admin(&pool, uid).await.unwrap_or(false)
+            let is_admin = if let Some(pool) = &pool {
+                db::user_is_admin(pool, uid).await.unwrap_or(false)
             } else {
                 false
             };
@@ -80,6 +245,49 @@ async fn main() -> std::io::Result<()> {
             // Store for handlers to read via get_is_admin()
             req.extensions_mut().insert(AdminStatus(is_admin));
 
+            // --- two-factor policy ---
+            // Admins without 2FA on a site that requires it may only
+            // reach the enrollment pages until they finish setup.
+            if is_admin
+                && !TWO_FACTOR_EXEMPT_PREFIXES
+                    .iter()
+                    .any(|p| path.starts_with(p))
+                && let Some(pool) = &pool
+                && db::admin_two_factor_missing(pool, uid)
+                    .await
+                    .unwrap_or_else(|e| {
+                        log::error!(
+                            "Two-factor policy check failed: {}",
+                            e
+                        );
+                        false
+                    })
+            {
+                let response = if bearer.is_some() {
+                    bearer_error(
+                        &path,
+                        StatusCode::FORBIDDEN,
+                        "error=\"insufficient_scope\"",
+                        "Two-factor enrollment is required first",
+                    )
+                } else if is_htmx(req.request()) {
+                    HttpResponse::Ok()
+                        .insert_header((
+                            "HX-Redirect",
+                            TWO_FACTOR_SETUP_PATH,
+                        ))
+                        .finish()
+                } else {
+                    HttpResponse::SeeOther()
+                        .insert_header((
+                            LOCATION,
+                            TWO_FACTOR_SETUP_PATH,
+                        ))
+                        .finish()
+                };
+                return Ok(req.into_response(response));
+            }
+
             // Block non-admins from admin-only routes - show 401 template
             if !is_admin
                 && ADMIN_ONLY_PREFIXES
@@ -91,7 +299,65 @@ async fn main() -> std::io::Result<()> {
             }
         }
 
-        next.call(req).await
+        let mut res = next.call(req).await?;
+        if let Some(cookie) = renewed_cookie {
+            res.response_mut().add_cookie(&cookie)?;
+        }
+        Ok(res)
+    }
+
+    /// Pick the mail transport from MAIL_TRANSPORT (log, file or smtp).
+    fn mailer_from_env() -> Arc<dyn Mailer> {
+        let var = |name: &str| std::env::var(name).ok();
+        let from = var("MAIL_FROM").unwrap_or_else(|| {
+            "RustPress <noreply@localhost>".into()
+        });
+
+        match var("MAIL_TRANSPORT").as_deref().unwrap_or("log") {
+            "log" => Arc::new(LogMailer),
+            "file" => Arc::new(
+                FileMailer::new(
+                    var("MAIL_DIR").unwrap_or_else(|| "./mail".into()),
+                    &from,
+                )
+                .expect("Failed to set up file mail transport"),
+            ),
+            "smtp" => Arc::new(
+                SmtpMailer::new(&SmtpConfig {
+                    host: var("SMTP_HOST").expect(
+                        "SMTP_HOST must be set for MAIL_TRANSPORT=smtp",
+                    ),
+                    port: var("SMTP_PORT").map(|p| {
+                        p.parse().expect("SMTP_PORT must be a port")
+                    }),
+                    security: var("SMTP_SECURITY")
+                        .as_deref()
+                        .unwrap_or("starttls")
+                        .parse()
+                        .expect("Invalid SMTP_SECURITY"),
+                    username: var("SMTP_USERNAME"),
+                    password: var("SMTP_PASSWORD"),
+                    from,
+                })
+                .expect("Failed to set up SMTP transport"),
+            ),
+            other => panic!("Unknown MAIL_TRANSPORT: {other}"),
+        }
+    }
+
+    /// Pick the media storage from MEDIA_STORAGE (only local for now).
+    fn media_storage_from_env() -> Arc<dyn MediaStorage> {
+        let var = |name: &str| std::env::var(name).ok();
+        match var("MEDIA_STORAGE").as_deref().unwrap_or("local") {
+            "local" => Arc::new(
+                LocalMediaStorage::new(
+                    var("MEDIA_DIR")
+                        .unwrap_or_else(|| "./uploads".into()),
+                )
+                .expect("Failed to set up local media storage"),
+            ),
+            other => panic!("Unknown MEDIA_STORAGE: {other}"),
+        }
     }
 
     let database_url = std::env::var("DATABASE_URL")
@@ -99,15 +365,65 @@ async fn main() -> std::io::Result<()> {
     let bind_addr = std::env::var("BIND_ADDR")
         .unwrap_or_else(|_| "127.0.0.1:8080".to_string());
 
+    let public_url = std::env::var("PUBLIC_URL")
+        .unwrap_or_else(|_| format!("http://{bind_addr}"))
+        .trim_end_matches('/')
+        .to_string();
+
+    let image_widths = match std::env::var("MEDIA_IMAGE_WIDTHS") {
+        Ok(value) => parse_image_widths(&value)
+            .expect("Invalid MEDIA_IMAGE_WIDTHS"),
+        Err(_) => DEFAULT_IMAGE_WIDTHS.to_vec(),
+    };
+
+    let html_policy = HtmlPolicy::default()
+        .allow_tags(
+            &std::env::var("HTML_ALLOWED_TAGS").unwrap_or_default(),
+        )
+        .expect("Invalid HTML_ALLOWED_TAGS")
+        .allow_attributes(
+            &std::env::var("HTML_ALLOWED_ATTRIBUTES")
+                .unwrap_or_default(),
+        )
+        .expect("Invalid HTML_ALLOWED_ATTRIBUTES");
+
+    let trust_proxy = std::env::var("TRUST_PROXY")
+        .map(|value| {
+            value.parse().expect("TRUST_PROXY must be true or false")
+        })
+        .unwrap_or(false);
+
     let db = Database::new(&database_url)
         .await
         .expect("Failed to initialize database");
 
+    let outbox = MailOutbox::new(db.pool.clone());
+    actix_web::rt::spawn(
+        outbox.clone().run(
+            mailer_from_env(),
+            std::time::Duration::from_secs(30),
+        ),
+    );
+
+    actix_web::rt::spawn(
+        ContentScheduler::new(db.pool.clone())
+            .run(std::time::Duration::from_secs(30)),
+    );
+
+    actix_web::rt::spawn(
+        TrashPurger::new(db.pool.clone())
+            .run(std::time::Duration::from_secs(60 * 60)),
+    );
+
     let state = actix_web::web::Data::new(AppState {
         pool: db.pool.clone(),
-        rate_limiter: std::sync::Arc::new(
-            web::security::RateLimiter::new(),
-        ),
+        rate_limiter: Arc::new(web::security::RateLimiter::new()),
+        outbox,
+        public_url,
+        media: media_storage_from_env(),
+        image_widths,
+        html_policy: Arc::new(html_policy),
+        trust_proxy,
     });
 
     println!("Starting RustPress (Actix + Askama + HTMX)");
@@ -117,6 +433,7 @@ async fn main() -> std::io::Result<()> {
     HttpServer::new(move || {
         App::new()
             .app_data(state.clone())
+            .wrap(web::middleware::CsrfProtection)
             .wrap(web::middleware::SecurityHeaders)
             .wrap(from_fn(admin_auth_guard))
             .service(Files::new("/static", "./static"))
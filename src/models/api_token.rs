@@ -0,0 +1,105 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// What a personal API token may be used for.
+#[derive(
+    Debug, Clone, Copy, Eq, PartialEq, Hash, Serialize, Deserialize,
+)]
+pub enum ApiScope {
+    #[serde(rename = "content:read")]
+    ContentRead,
+    #[serde(rename = "content:write")]
+    ContentWrite,
+    #[serde(rename = "templates:write")]
+    TemplatesWrite,
+    #[serde(rename = "users:admin")]
+    UsersAdmin,
+}
+
+impl ApiScope {
+    pub const ALL: [ApiScope; 4] = [
+        Self::ContentRead,
+        Self::ContentWrite,
+        Self::TemplatesWrite,
+        Self::UsersAdmin,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ContentRead => "content:read",
+            Self::ContentWrite => "content:write",
+            Self::TemplatesWrite => "templates:write",
+            Self::UsersAdmin => "users:admin",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::ContentRead => "Read posts, pages and templates",
+            Self::ContentWrite => "Create, edit and publish content",
+            Self::TemplatesWrite => "Create and edit templates",
+            Self::UsersAdmin => "Manage users and site configuration",
+        }
+    }
+}
+
+impl std::fmt::Display for ApiScope {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for ApiScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|scope| scope.as_str() == s)
+            .ok_or_else(|| format!("invalid API scope: {}", s))
+    }
+}
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Serialize, Deserialize, FromRow,
+)]
+pub struct ApiToken {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub name: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    /// Scope names; unknown entries are ignored.
+    pub scopes: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl ApiToken {
+    pub fn scopes(&self) -> Vec<ApiScope> {
+        self.scopes.iter().filter_map(|s| s.parse().ok()).collect()
+    }
+
+    pub fn has_scope(&self, scope: ApiScope) -> bool {
+        self.scopes.iter().any(|s| s == scope.as_str())
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|at| at <= Utc::now())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiTokenCreate {
+    pub user_id: Uuid,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
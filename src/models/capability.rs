@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// Permission granted to a user on top of their role. Admins hold every
+/// capability implicitly.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    sqlx::Type,
+)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Capability {
+    /// Publish content and review what others submit.
+    Publish,
+    /// Save content as written, without HTML sanitization.
+    #[sqlx(rename = "unfiltered_html")]
+    #[serde(rename = "unfiltered_html")]
+    UnfilteredHtml,
+}
+
+impl Capability {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Publish => "publish",
+            Self::UnfilteredHtml => "unfiltered_html",
+        }
+    }
+}
+
+impl std::fmt::Display for Capability {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
@@ -1,11 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use super::{ContentKind, ContentStatus};
+use super::{ContentFormat, ContentKind, ContentStatus};
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct ContentItem {
     pub id: Uuid,
     pub owner_user_id: Option<Uuid>,
@@ -19,6 +20,140 @@ pub struct ContentItem {
     pub created_at: DateTime<Utc>,
     pub edited_at: DateTime<Utc>,
     pub published_at: Option<DateTime<Utc>>,
+    /// When a scheduled item goes live.
+    pub publish_at: Option<DateTime<Utc>>,
+    /// When a published item is taken down again.
+    pub unpublish_at: Option<DateTime<Utc>>,
+    /// When the item was moved to the trash.
+    pub deleted_at: Option<DateTime<Utc>>,
+    /// Parent page; always `None` for posts.
+    pub parent_id: Option<Uuid>,
+    /// Position among sibling pages, lowest first.
+    pub menu_order: i32,
+    /// Kept out of the sitemaps and served with `X-Robots-Tag: noindex`.
+    pub noindex: bool,
+    /// Title for search results and sharing; empty uses `title`.
+    pub meta_title: String,
+    /// Empty derives a description from the content.
+    pub meta_description: String,
+    /// Empty uses the item's own URL.
+    pub canonical_url: String,
+    /// Sharing image; empty uses the first image in the content.
+    pub og_image_url: String,
+    /// `content` was saved as written by a user allowed to post
+    /// unfiltered HTML. Other content is sanitized again when shown.
+    pub unfiltered_html: bool,
+    pub format: ContentFormat,
+    /// HTML rendered from a Markdown `content` when it was saved;
+    /// `None` for HTML items.
+    pub rendered_html: Option<String>,
+}
+
+impl ContentItem {
+    /// The item's body as HTML: `content` itself, or its rendering for
+    /// Markdown items.
+    pub fn html(&self) -> &str {
+        self.rendered_html.as_deref().unwrap_or(&self.content)
+    }
+
+    /// Whether the public may see the item at `now`: published and not
+    /// past its `unpublish_at`. Mirrors the filter in the public queries.
+    pub fn is_live(&self, now: DateTime<Utc>) -> bool {
+        self.status == ContentStatus::Published
+            && self.unpublish_at.is_none_or(|at| at > now)
+    }
+}
+
+pub const MAX_META_TITLE_LENGTH: usize = 200;
+pub const MAX_META_DESCRIPTION_LENGTH: usize = 500;
+pub const MAX_SEO_URL_LENGTH: usize = 2000;
+
+/// Search engine and sharing settings of an item. Empty strings fall
+/// back to values derived from the content.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ContentSeo {
+    #[serde(default)]
+    #[schema(max_length = 200)]
+    pub meta_title: String,
+    #[serde(default)]
+    #[schema(max_length = 500)]
+    pub meta_description: String,
+    /// An `http(s)` URL or a path on this site.
+    #[serde(default)]
+    #[schema(max_length = 2000)]
+    pub canonical_url: String,
+    /// An `http(s)` URL or a path on this site.
+    #[serde(default)]
+    #[schema(max_length = 2000)]
+    pub og_image_url: String,
+    /// Keep the item out of the sitemaps and search results.
+    #[serde(default)]
+    pub noindex: bool,
+}
+
+impl ContentSeo {
+    pub fn from_item(item: &ContentItem) -> Self {
+        Self {
+            meta_title: item.meta_title.clone(),
+            meta_description: item.meta_description.clone(),
+            canonical_url: item.canonical_url.clone(),
+            og_image_url: item.og_image_url.clone(),
+            noindex: item.noindex,
+        }
+    }
+
+    /// The same settings with surrounding whitespace removed.
+    pub fn trimmed(&self) -> Self {
+        Self {
+            meta_title: self.meta_title.trim().to_string(),
+            meta_description: self
+                .meta_description
+                .trim()
+                .to_string(),
+            canonical_url: self.canonical_url.trim().to_string(),
+            og_image_url: self.og_image_url.trim().to_string(),
+            noindex: self.noindex,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.meta_title.chars().count() > MAX_META_TITLE_LENGTH {
+            return Err(format!(
+                "Meta title must be at most {MAX_META_TITLE_LENGTH} characters"
+            ));
+        }
+        if self.meta_description.chars().count()
+            > MAX_META_DESCRIPTION_LENGTH
+        {
+            return Err(format!(
+                "Meta description must be at most {MAX_META_DESCRIPTION_LENGTH} characters"
+            ));
+        }
+        for (label, url) in [
+            ("Canonical URL", &self.canonical_url),
+            ("Open Graph image", &self.og_image_url),
+        ] {
+            if !is_seo_url(url) {
+                return Err(format!(
+                    "{label} must be an http(s) URL or a path starting with /"
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Empty, an `http(s)` URL, or a site path (but not `//host`).
+fn is_seo_url(url: &str) -> bool {
+    if url.is_empty() {
+        return true;
+    }
+    let well_formed = url.starts_with("https://")
+        || url.starts_with("http://")
+        || (url.starts_with('/') && !url.starts_with("//"));
+    well_formed
+        && url.len() <= MAX_SEO_URL_LENGTH
+        && !url.chars().any(|c| c.is_whitespace() || c.is_control())
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,13 +164,47 @@ pub struct ContentCreate {
     pub slug: String,
     pub content: String,
     pub template: String,
+    /// `content` is stored as written rather than sanitized.
+    pub unfiltered_html: bool,
+    pub format: ContentFormat,
+    /// Required for Markdown content; see [`ContentItem::rendered_html`].
+    pub rendered_html: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct ContentUpdate {
     pub title: Option<String>,
     pub slug: Option<String>,
     pub content: Option<String>,
     pub template: Option<String>,
     pub status: Option<ContentStatus>,
+    /// `content` is stored as written rather than sanitized. Set from
+    /// the author's capabilities, never by clients; ignored when
+    /// `content` is `None`.
+    #[serde(skip)]
+    pub unfiltered_html: bool,
+    /// Format of `content`. A new format sent without `content`
+    /// converts the stored content; the database update itself ignores
+    /// the format when `content` is `None`.
+    pub format: Option<ContentFormat>,
+    /// Rendering of a Markdown `content`. Set by the server.
+    #[serde(skip)]
+    pub rendered_html: Option<String>,
+}
+
+/// Publish and unpublish times set on an item. A `publish_at` moves a
+/// draft to [`ContentStatus::Scheduled`]; `unpublish_at` alone only sets
+/// an expiry on an item that is (or will be) published.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct ContentSchedule {
+    pub publish_at: Option<DateTime<Utc>>,
+    pub unpublish_at: Option<DateTime<Utc>>,
+}
+
+/// Narrows a content listing; `None` fields match everything.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContentFilter {
+    pub status: Option<ContentStatus>,
+    pub owner_user_id: Option<Uuid>,
+    pub slug: Option<String>,
 }
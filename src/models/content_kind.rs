@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 #[derive(
     Debug,
@@ -10,6 +11,7 @@ use serde::{Deserialize, Serialize};
     Serialize,
     Deserialize,
     sqlx::Type,
+    ToSchema,
 )]
 #[sqlx(type_name = "text", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
@@ -1,11 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use super::ContentStatus;
+use super::{ContentFormat, ContentStatus};
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct ContentItemRevision {
     pub id: Uuid,
     pub content_item_id: Uuid,
@@ -17,15 +18,31 @@ pub struct ContentItemRevision {
     pub content: String,
     pub template: String,
     pub status: ContentStatus,
+    /// Review decision or status transition recorded with this revision.
+    pub note: Option<String>,
+    /// Whether `content` was saved unfiltered; see
+    /// [`ContentItem::unfiltered_html`](super::ContentItem).
+    pub unfiltered_html: bool,
+    pub format: ContentFormat,
+    /// HTML rendered from a Markdown `content` for this revision.
+    pub rendered_html: Option<String>,
 
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+impl ContentItemRevision {
+    /// See [`ContentItem::html`](super::ContentItem::html).
+    pub fn html(&self) -> &str {
+        self.rendered_html.as_deref().unwrap_or(&self.content)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct ContentItemRevisionMeta {
     pub rev: i32,
     pub created_by_user_id: Option<Uuid>,
     pub created_at: DateTime<Utc>,
     pub title: String,
     pub status: ContentStatus,
+    pub note: Option<String>,
 }
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
 #[derive(
     Debug,
@@ -10,19 +11,65 @@ use serde::{Deserialize, Serialize};
     Serialize,
     Deserialize,
     sqlx::Type,
+    ToSchema,
 )]
 #[sqlx(type_name = "text", rename_all = "lowercase")]
 #[serde(rename_all = "lowercase")]
 pub enum ContentStatus {
     #[default]
     Draft,
+    /// Submitted for review by someone who may not publish.
+    Pending,
+    /// Waiting for `publish_at`; not publicly visible yet.
+    Scheduled,
+    /// Published to signed-in users who can see the item, not the public.
+    Private,
     Published,
 }
 
 impl ContentStatus {
+    /// Statuses that only users allowed to publish may set.
+    pub fn needs_publish_permission(&self) -> bool {
+        matches!(
+            self,
+            Self::Scheduled | Self::Private | Self::Published
+        )
+    }
+
+    /// Whether saving an item in this status as `next` needs the
+    /// permission to publish. Changing a live item would skip review just
+    /// like publishing it.
+    pub fn save_needs_publish_permission(self, next: Self) -> bool {
+        self.needs_publish_permission()
+            || next.needs_publish_permission()
+    }
+
+    /// History note for a move from `self` to `next`; `None` if the
+    /// status did not change.
+    pub fn transition_note(self, next: Self) -> Option<&'static str> {
+        if self == next {
+            return None;
+        }
+        Some(match (self, next) {
+            (_, Self::Pending) => "Submitted for review",
+            (_, Self::Scheduled) => "Scheduled",
+            (Self::Pending, Self::Published) => {
+                "Approved and published"
+            }
+            (_, Self::Private) => "Published privately",
+            (_, Self::Published) => "Published",
+            (Self::Pending, Self::Draft) => "Withdrawn from review",
+            (Self::Scheduled, Self::Draft) => "Schedule cleared",
+            (_, Self::Draft) => "Unpublished",
+        })
+    }
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Self::Draft => "draft",
+            Self::Pending => "pending",
+            Self::Scheduled => "scheduled",
+            Self::Private => "private",
             Self::Published => "published",
         }
     }
@@ -49,6 +96,9 @@ impl std::str::FromStr for ContentStatus {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
             "draft" => Ok(Self::Draft),
+            "pending" => Ok(Self::Pending),
+            "scheduled" => Ok(Self::Scheduled),
+            "private" => Ok(Self::Private),
             "published" => Ok(Self::Published),
             _ => Err(format!("invalid content status: {}", s)),
         }
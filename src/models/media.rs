@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+/// Largest file accepted by an upload.
+pub const MAX_MEDIA_UPLOAD_BYTES: usize = 10 * 1024 * 1024;
+
+/// Files accepted by one upload request.
+pub const MAX_MEDIA_FILES_PER_UPLOAD: usize = 20;
+
+pub const MAX_MEDIA_ALT_LENGTH: usize = 500;
+
+/// Longest file name kept for display; longer names are shortened.
+pub const MAX_MEDIA_FILENAME_LENGTH: usize = 255;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Media {
+    pub id: Uuid,
+    pub owner_user_id: Option<Uuid>,
+    /// The uploader's file name, for display and search only.
+    pub filename: String,
+    /// Name of the file in the storage backend.
+    pub storage_key: String,
+    /// Sniffed from the file's contents, not taken from the upload.
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub alt_text: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Media {
+    /// Where the file is served.
+    pub fn url(&self) -> String {
+        format!("/media/{}", self.id)
+    }
+
+    pub fn is_image(&self) -> bool {
+        self.mime_type.starts_with("image/")
+    }
+
+    /// The size for people, e.g. `1.4 MB`.
+    pub fn display_size(&self) -> String {
+        let bytes = self.size_bytes as f64;
+        if bytes >= 1024.0 * 1024.0 {
+            format!("{:.1} MB", bytes / (1024.0 * 1024.0))
+        } else if bytes >= 1024.0 {
+            format!("{:.0} KB", bytes / 1024.0)
+        } else {
+            format!("{} bytes", self.size_bytes)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaCreate {
+    pub owner_user_id: Option<Uuid>,
+    pub filename: String,
+    pub storage_key: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub alt_text: String,
+}
+
+/// Narrows the media library listing.
+#[derive(Debug, Clone, Default)]
+pub struct MediaFilter {
+    /// Matched against the file name and alt text.
+    pub query: Option<String>,
+    pub images_only: bool,
+    pub limit: i64,
+}
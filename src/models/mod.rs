@@ -1,19 +1,49 @@
+pub use api_token::*;
+pub use capability::*;
 pub use collaborator::*;
 pub use content::*;
+pub use content_format::*;
 pub use content_kind::*;
 pub use content_revision::*;
 pub use content_status::*;
+pub use email_verification::*;
 pub use homepage_type::*;
+pub use mail_outbox::*;
+pub use media::*;
+pub use page::*;
+pub use pagination::*;
+pub use password_reset::*;
+pub use redirect::*;
+pub use search::*;
+pub use session::*;
 pub use site::*;
 pub use site_template::*;
+pub use sitemap::*;
+pub use term::*;
+pub use two_factor::*;
 pub use user::*;
 
+mod api_token;
+mod capability;
 mod collaborator;
 mod content;
+mod content_format;
 mod content_kind;
 mod content_revision;
 mod content_status;
+mod email_verification;
 mod homepage_type;
+mod mail_outbox;
+mod media;
+mod page;
+mod pagination;
+mod password_reset;
+mod redirect;
+mod search;
+mod session;
 mod site;
 mod site_template;
+mod sitemap;
+mod term;
+mod two_factor;
 mod user;
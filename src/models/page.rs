@@ -0,0 +1,145 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::ContentItem;
+
+/// How deep the page tree may nest. Path lookups stop here, which also
+/// keeps a corrupted parent cycle from looping forever.
+pub const MAX_PAGE_DEPTH: usize = 16;
+
+/// Where a page sits in the tree. A missing `parent_id` makes it a
+/// top-level page; a missing `menu_order` keeps the current one.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct PageMove {
+    pub parent_id: Option<Uuid>,
+    pub menu_order: Option<i32>,
+}
+
+/// A page with its depth below the top level and its public path, for
+/// the indented admin listing.
+#[derive(Debug, Clone)]
+pub struct PageNode {
+    pub page: ContentItem,
+    pub depth: usize,
+    pub path: String,
+}
+
+impl PageNode {
+    pub fn url(&self) -> String {
+        format!("/{}", self.path)
+    }
+}
+
+/// A page and its depth below the top level, as listed by
+/// [`list_page_tree_for_user`](crate::db::list_page_tree_for_user).
+#[derive(Debug, Clone, FromRow)]
+pub struct PageTreeRow {
+    #[sqlx(flatten)]
+    pub page: ContentItem,
+    pub depth: i32,
+}
+
+/// Pages already in tree order, e.g. one page of the admin listing.
+pub fn page_nodes(
+    rows: Vec<PageTreeRow>,
+    paths: &HashMap<Uuid, String>,
+) -> Vec<PageNode> {
+    rows.into_iter()
+        .map(|row| {
+            let path = paths
+                .get(&row.page.id)
+                .cloned()
+                .unwrap_or_else(|| row.page.slug.clone());
+            PageNode {
+                page: row.page,
+                depth: row.depth as usize,
+                path,
+            }
+        })
+        .collect()
+}
+
+/// Pages in the order given, all at the top level, e.g. search results
+/// ranked by relevance.
+pub fn page_list(
+    pages: Vec<ContentItem>,
+    paths: &HashMap<Uuid, String>,
+) -> Vec<PageNode> {
+    pages
+        .into_iter()
+        .map(|page| {
+            let path = paths
+                .get(&page.id)
+                .cloned()
+                .unwrap_or_else(|| page.slug.clone());
+            PageNode {
+                page,
+                depth: 0,
+                path,
+            }
+        })
+        .collect()
+}
+
+/// Order pages depth-first so children follow their parent, siblings
+/// sorted by `menu_order` then title. Pages whose parent is not in the
+/// list are treated as top-level. `paths` maps page ids to their full
+/// path; pages missing from it fall back to their slug.
+pub fn page_tree(
+    mut pages: Vec<ContentItem>,
+    paths: &HashMap<Uuid, String>,
+) -> Vec<PageNode> {
+    fn walk(
+        parent: Option<Uuid>,
+        depth: usize,
+        remaining: &mut Vec<ContentItem>,
+        paths: &HashMap<Uuid, String>,
+        nodes: &mut Vec<PageNode>,
+    ) {
+        let (children, rest): (Vec<_>, Vec<_>) =
+            remaining.drain(..).partition(|p| p.parent_id == parent);
+        *remaining = rest;
+        for page in children {
+            let id = page.id;
+            nodes.push(node(page, depth, paths));
+            walk(Some(id), depth + 1, remaining, paths, nodes);
+        }
+    }
+
+    fn node(
+        page: ContentItem,
+        depth: usize,
+        paths: &HashMap<Uuid, String>,
+    ) -> PageNode {
+        let path = paths
+            .get(&page.id)
+            .cloned()
+            .unwrap_or_else(|| page.slug.clone());
+        PageNode { page, depth, path }
+    }
+
+    pages.sort_by(|a, b| {
+        (a.menu_order, &a.title).cmp(&(b.menu_order, &b.title))
+    });
+    let ids: HashSet<Uuid> = pages.iter().map(|p| p.id).collect();
+    let mut remaining: Vec<ContentItem> = pages
+        .into_iter()
+        .map(|mut p| {
+            if p.parent_id.is_some_and(|id| !ids.contains(&id)) {
+                p.parent_id = None;
+            }
+            p
+        })
+        .collect();
+    let mut nodes = Vec::with_capacity(remaining.len());
+    walk(None, 0, &mut remaining, paths, &mut nodes);
+    // Anything left sits in a parent cycle; list it rather than drop it.
+    nodes.extend(
+        remaining.into_iter().map(|page| node(page, 0, paths)),
+    );
+    nodes
+}
@@ -0,0 +1,106 @@
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use super::ContentItem;
+
+/// Posts per page of the blog and its archives when no site is
+/// configured.
+pub const DEFAULT_POSTS_PER_PAGE: i32 = 10;
+pub const MAX_POSTS_PER_PAGE: i32 = 100;
+
+/// Items per page of the admin lists.
+pub const ADMIN_ITEMS_PER_PAGE: i64 = 50;
+
+/// Where an item sits in a listing: its sort time, with the id to break
+/// ties. Written as `{microseconds}_{id}` in URLs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContentCursor {
+    pub at: DateTime<Utc>,
+    pub id: Uuid,
+}
+
+impl fmt::Display for ContentCursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}_{}", self.at.timestamp_micros(), self.id)
+    }
+}
+
+impl FromStr for ContentCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid cursor: {s}");
+        let (micros, id) = s.split_once('_').ok_or_else(invalid)?;
+        let at = micros
+            .parse()
+            .ok()
+            .and_then(DateTime::from_timestamp_micros)
+            .ok_or_else(invalid)?;
+        let id = id.parse().map_err(|_| invalid())?;
+        Ok(Self { at, id })
+    }
+}
+
+/// Which page of a listing to load. Cursors are cheap at any depth;
+/// offsets serve `?page=` links that carry no cursor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageAnchor {
+    /// Skip this many items from the start.
+    Offset(i64),
+    /// The items listed after this one.
+    After(ContentCursor),
+    /// The items listed before this one.
+    Before(ContentCursor),
+}
+
+impl Default for PageAnchor {
+    fn default() -> Self {
+        Self::Offset(0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageRequest {
+    pub anchor: PageAnchor,
+    pub limit: i64,
+}
+
+impl PageRequest {
+    pub fn first(limit: i64) -> Self {
+        Self {
+            anchor: PageAnchor::default(),
+            limit,
+        }
+    }
+}
+
+/// Items before one-based page `number` of `per_page` items. Saturates
+/// rather than overflowing on page numbers no listing reaches.
+pub fn page_offset(number: i64, per_page: i64) -> i64 {
+    (number.max(1) - 1).saturating_mul(per_page)
+}
+
+/// One page of a listing. `prev` and `next` are set when there are
+/// items before or after it, and anchor the neighbouring pages as
+/// [`PageAnchor::Before`] and [`PageAnchor::After`].
+#[derive(Debug, Clone, Default)]
+pub struct ContentPage {
+    pub items: Vec<ContentItem>,
+    pub prev: Option<ContentCursor>,
+    pub next: Option<ContentCursor>,
+}
+
+/// Narrows the published posts listed by
+/// [`list_published_posts`](crate::db::list_published_posts).
+#[derive(Debug, Clone, Default)]
+pub struct PostListFilter {
+    /// Only posts filed under this term or one of its descendants.
+    pub term_id: Option<Uuid>,
+    /// Only posts published at or after this time.
+    pub published_from: Option<DateTime<Utc>>,
+    /// Only posts published before this time.
+    pub published_before: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+use super::ContentKind;
+
+/// Status codes a manual redirect may answer with.
+pub const REDIRECT_STATUS_CODES: [i32; 3] = [301, 302, 410];
+
+/// How a redirect's source is compared with the request path.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    sqlx::Type,
+)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum RedirectMatch {
+    /// Only the path itself.
+    #[default]
+    Exact,
+    /// The path and everything below it.
+    Prefix,
+}
+
+impl RedirectMatch {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Exact => "exact",
+            Self::Prefix => "prefix",
+        }
+    }
+}
+
+impl std::fmt::Display for RedirectMatch {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl std::str::FromStr for RedirectMatch {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "exact" => Ok(Self::Exact),
+            "prefix" => Ok(Self::Prefix),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Redirect {
+    pub id: Uuid,
+    pub source_path: String,
+    pub match_type: RedirectMatch,
+    /// Where to send the client; `None` for a 410.
+    pub target_url: Option<String>,
+    pub status_code: i32,
+    pub created_by_user_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Redirect {
+    /// The `Location` for a request to `path` with `query` (without the
+    /// `?`). A prefix redirect carries the rest of the path over, and
+    /// the query string is kept unless the target sets its own. `None`
+    /// for a 410.
+    pub fn location(
+        &self,
+        path: &str,
+        query: &str,
+    ) -> Option<String> {
+        let target = self.target_url.as_deref()?;
+        let mut location = match self.match_type {
+            RedirectMatch::Exact => target.to_string(),
+            RedirectMatch::Prefix => {
+                let source = self.source_path.trim_end_matches('/');
+                let rest =
+                    path.get(source.len()..).unwrap_or_default();
+                // Browsers read `//host` and `/\host` as another site,
+                // so the carried-over path keeps a single leading slash.
+                let tail = rest.trim_start_matches(['/', '\\']);
+                let sep =
+                    if tail.len() < rest.len() { "/" } else { "" };
+                format!("{}{sep}{tail}", target.trim_end_matches('/'))
+            }
+        };
+        if location.is_empty() {
+            location.push('/');
+        }
+        if !query.is_empty() && !location.contains('?') {
+            location.push('?');
+            location.push_str(query);
+        }
+        Some(location)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedirectCreate {
+    pub source_path: String,
+    pub match_type: RedirectMatch,
+    pub target_url: Option<String>,
+    pub status_code: i32,
+    pub created_by_user_id: Option<Uuid>,
+}
+
+/// An old address of a renamed post or moved page, kept so it can be
+/// redirected to the item's current one.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SlugHistoryEntry {
+    pub kind: ContentKind,
+    /// Post slug, or page path without the leading slash.
+    pub path: String,
+    pub content_item_id: Uuid,
+    pub title: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl SlugHistoryEntry {
+    /// The public URL this entry redirects from.
+    pub fn url(&self) -> String {
+        match self.kind {
+            ContentKind::Post => format!("/blog/{}", self.path),
+            ContentKind::Page => format!("/{}", self.path),
+        }
+    }
+}
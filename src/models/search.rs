@@ -0,0 +1,56 @@
+use sqlx::FromRow;
+
+use super::ContentItem;
+
+/// Results shown on each page of `/search`.
+pub const SEARCH_RESULTS_PER_PAGE: i64 = 10;
+
+/// Queries are cut to this many characters before they are parsed.
+pub const MAX_SEARCH_QUERY_LENGTH: usize = 200;
+
+/// Put around each matched word in [`SearchHit::snippet`]. Control
+/// characters never occur in the text, so they cannot be forged.
+pub const SNIPPET_MATCH_START: char = '\u{2}';
+pub const SNIPPET_MATCH_END: char = '\u{3}';
+
+/// A published item matching a search, best matches first.
+#[derive(Debug, Clone, FromRow)]
+pub struct SearchHit {
+    #[sqlx(flatten)]
+    pub item: ContentItem,
+    pub rank: f32,
+    /// Plain text around the matches, which are marked with
+    /// [`SNIPPET_MATCH_START`] and [`SNIPPET_MATCH_END`].
+    pub snippet: String,
+}
+
+impl SearchHit {
+    /// The snippet as `(matched, text)` runs, for highlighting the
+    /// matches without trusting the text as HTML.
+    pub fn snippet_parts(&self) -> Vec<(bool, &str)> {
+        let mut parts = Vec::new();
+        for (i, run) in
+            self.snippet.split(SNIPPET_MATCH_START).enumerate()
+        {
+            match run.split_once(SNIPPET_MATCH_END) {
+                Some((matched, rest)) if i > 0 => {
+                    parts.push((true, matched));
+                    parts.push((false, rest));
+                }
+                _ => parts.push((false, run)),
+            }
+        }
+        parts.retain(|(_, text)| !text.is_empty());
+        parts
+    }
+}
+
+/// The normalized form of a search box's contents: trimmed and cut to
+/// [`MAX_SEARCH_QUERY_LENGTH`]. `None` when nothing is left to search.
+pub fn search_terms(query: Option<&str>) -> Option<String> {
+    let query = query?.trim();
+    if query.is_empty() {
+        return None;
+    }
+    Some(query.chars().take(MAX_SEARCH_QUERY_LENGTH).collect())
+}
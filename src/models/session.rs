@@ -0,0 +1,75 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use uuid::Uuid;
+
+#[derive(
+    Debug, Clone, Eq, PartialEq, Serialize, Deserialize, FromRow,
+)]
+pub struct Session {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SessionCreate {
+    pub user_id: Uuid,
+    pub token_hash: String,
+    pub expires_at: DateTime<Utc>,
+    pub ip: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl Session {
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= Utc::now()
+    }
+
+    /// Short human-readable description of the client, e.g. "Firefox on Linux".
+    pub fn device_label(&self) -> String {
+        let Some(ua) = self.user_agent.as_deref() else {
+            return "Unknown device".to_string();
+        };
+
+        // Order matters: Edge and Opera also advertise "Chrome", and
+        // Chrome advertises "Safari".
+        let browser = [
+            ("Edg/", "Edge"),
+            ("OPR/", "Opera"),
+            ("Firefox/", "Firefox"),
+            ("Chrome/", "Chrome"),
+            ("Safari/", "Safari"),
+            ("curl/", "curl"),
+        ]
+        .iter()
+        .find(|(needle, _)| ua.contains(needle))
+        .map(|(_, name)| *name);
+
+        // Android and iOS user agents also mention Linux / Mac OS X.
+        let os = [
+            ("Android", "Android"),
+            ("iPhone", "iOS"),
+            ("iPad", "iPadOS"),
+            ("Windows", "Windows"),
+            ("Mac OS X", "macOS"),
+            ("Linux", "Linux"),
+        ]
+        .iter()
+        .find(|(needle, _)| ua.contains(needle))
+        .map(|(_, name)| *name);
+
+        match (browser, os) {
+            (Some(b), Some(o)) => format!("{b} on {o}"),
+            (Some(b), None) => b.to_string(),
+            (None, Some(o)) => o.to_string(),
+            (None, None) => "Unknown device".to_string(),
+        }
+    }
+}
@@ -1,11 +1,20 @@
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-use super::HomepageType;
+use super::{HomepageType, MAX_POSTS_PER_PAGE};
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+/// Used when no site is configured.
+pub const DEFAULT_TRASH_RETENTION_DAYS: i32 = 30;
+pub const MAX_TRASH_RETENTION_DAYS: i32 = 3650;
+pub const MAX_ROBOTS_TXT_LENGTH: usize = 10_000;
+/// Served at `/robots.txt` when no site is configured.
+pub const DEFAULT_ROBOTS_TXT: &str =
+    "User-agent: *\nDisallow: /admin/\nDisallow: /api/\n";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct Site {
     pub id: Uuid,
     pub owner_user_id: Uuid,
@@ -15,6 +24,22 @@ pub struct Site {
     pub default_template: String,
     pub homepage_type: HomepageType,
     pub homepage_page_id: Option<Uuid>,
+    /// Only users with a verified email may publish content.
+    pub require_verified_email: bool,
+    /// Users holding the admin role must enroll in two-factor
+    /// authentication before using the admin console.
+    pub require_admin_2fa: bool,
+    /// Days a trashed item is kept before it is deleted for good.
+    pub trash_retention_days: i32,
+    /// Feeds carry each post's full text rather than an excerpt.
+    pub feed_full_text: bool,
+    /// Rules served at `/robots.txt`, before the `Sitemap:` line.
+    pub robots_txt: String,
+    /// Postgres text search configuration used to stem words for
+    /// search, e.g. `english` or `simple`.
+    pub search_language: String,
+    /// Posts on each page of the blog and its archives.
+    pub posts_per_page: i32,
     pub created_at: DateTime<Utc>,
     pub edited_at: DateTime<Utc>,
     pub published_at: Option<DateTime<Utc>>,
@@ -37,17 +62,82 @@ pub struct SiteCreate {
     pub default_template: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SiteUpdate {
     pub name: Option<String>,
     pub slug: Option<String>,
     pub status: Option<String>,
     pub default_template: Option<String>,
     pub homepage_type: Option<HomepageType>,
+    /// `null` clears the page; leaving the field out keeps it.
+    #[serde(default, deserialize_with = "deserialize_some")]
+    #[schema(value_type = Option<Uuid>)]
     pub homepage_page_id: Option<Option<Uuid>>,
+    pub require_verified_email: Option<bool>,
+    pub require_admin_2fa: Option<bool>,
+    #[schema(minimum = 1, maximum = 3650)]
+    pub trash_retention_days: Option<i32>,
+    pub feed_full_text: Option<bool>,
+    #[schema(max_length = 10000)]
+    pub robots_txt: Option<String>,
+    /// One of the server's text search configurations; changing it
+    /// rebuilds the search index.
+    pub search_language: Option<String>,
+    #[schema(minimum = 1, maximum = 100)]
+    pub posts_per_page: Option<i32>,
 }
 
 impl SiteUpdate {
+    pub fn validate_trash_retention(&self) -> Result<(), String> {
+        match self.trash_retention_days {
+            Some(days)
+                if !(1..=MAX_TRASH_RETENTION_DAYS)
+                    .contains(&days) =>
+            {
+                Err(format!(
+                    "Trash retention must be between 1 and {MAX_TRASH_RETENTION_DAYS} days"
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn validate_robots_txt(&self) -> Result<(), String> {
+        match &self.robots_txt {
+            Some(rules) if rules.len() > MAX_ROBOTS_TXT_LENGTH => {
+                Err(format!(
+                    "robots.txt must be at most {MAX_ROBOTS_TXT_LENGTH} characters"
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn validate_posts_per_page(&self) -> Result<(), String> {
+        match self.posts_per_page {
+            Some(n) if !(1..=MAX_POSTS_PER_PAGE).contains(&n) => {
+                Err(format!(
+                    "Posts per page must be between 1 and {MAX_POSTS_PER_PAGE}"
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// `available` lists the configurations the database knows, see
+    /// [`list_search_languages`](crate::db::list_search_languages).
+    pub fn validate_search_language(
+        &self,
+        available: &[String],
+    ) -> Result<(), String> {
+        match &self.search_language {
+            Some(language) if !available.contains(language) => {
+                Err(format!("Unknown search language: {language}"))
+            }
+            _ => Ok(()),
+        }
+    }
+
     pub fn validate_homepage(&self) -> Result<(), String> {
         if let Some(typ) = self.homepage_type {
             HomepageType::validate(
@@ -59,3 +149,12 @@ impl SiteUpdate {
         }
     }
 }
+
+/// Tell an explicit `null` (`Some(None)`) apart from a missing field.
+fn deserialize_some<'de, D, T>(d: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(d).map(Some)
+}
@@ -1,9 +1,10 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
+use utoipa::ToSchema;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
 pub struct SiteTemplate {
     pub id: Uuid,
     pub owner_user_id: Option<Uuid>,
@@ -23,7 +24,7 @@ pub struct SiteTemplateCreate {
     pub html: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct SiteTemplateUpdate {
     pub name: Option<String>,
     pub description: Option<String>,
@@ -0,0 +1,15 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+/// Most URLs one sitemap file may list.
+pub const SITEMAP_MAX_URLS: i64 = 50_000;
+
+/// One file of a kind's sitemap, as listed in the sitemap index.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SitemapChunk {
+    /// 1-based file number.
+    pub page: i64,
+    /// Latest `edited_at` among the file's items.
+    pub lastmod: DateTime<Utc>,
+}
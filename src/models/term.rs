@@ -0,0 +1,176 @@
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    Eq,
+    PartialEq,
+    Serialize,
+    Deserialize,
+    sqlx::Type,
+    ToSchema,
+)]
+#[sqlx(type_name = "text", rename_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Taxonomy {
+    #[default]
+    Category,
+    Tag,
+}
+
+impl Taxonomy {
+    pub const ALL: [Taxonomy; 2] = [Self::Category, Self::Tag];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Category => "category",
+            Self::Tag => "tag",
+        }
+    }
+
+    /// Human-readable plural, e.g. for headings.
+    pub fn plural_label(&self) -> &'static str {
+        match self {
+            Self::Category => "Categories",
+            Self::Tag => "Tags",
+        }
+    }
+
+    /// Only categories nest.
+    pub fn is_hierarchical(&self) -> bool {
+        matches!(self, Self::Category)
+    }
+}
+
+impl std::fmt::Display for Taxonomy {
+    fn fmt(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+    ) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl PartialEq<&str> for Taxonomy {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl std::str::FromStr for Taxonomy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "category" => Ok(Self::Category),
+            "tag" => Ok(Self::Tag),
+            _ => Err(format!("invalid taxonomy: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow, ToSchema)]
+pub struct Term {
+    pub id: Uuid,
+    pub taxonomy: Taxonomy,
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    pub parent_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Term {
+    /// Public archive listing the posts filed under this term.
+    pub fn url(&self) -> String {
+        format!("/{}/{}", self.taxonomy, self.slug)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermCreate {
+    pub taxonomy: Taxonomy,
+    pub slug: String,
+    pub name: String,
+    pub description: String,
+    pub parent_id: Option<Uuid>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TermUpdate {
+    pub slug: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    /// `Some(None)` detaches the term from its parent.
+    pub parent_id: Option<Option<Uuid>>,
+}
+
+/// A term with its depth below the top level, for indented listings.
+#[derive(Debug, Clone)]
+pub struct TermNode {
+    pub term: Term,
+    pub depth: usize,
+}
+
+/// Order terms depth-first so children follow their parent. Siblings
+/// keep their input order; terms whose parent is missing are treated as
+/// top-level.
+pub fn term_tree(terms: Vec<Term>) -> Vec<TermNode> {
+    fn walk(
+        parent: Option<Uuid>,
+        depth: usize,
+        remaining: &mut Vec<Term>,
+        nodes: &mut Vec<TermNode>,
+    ) {
+        let (children, rest): (Vec<_>, Vec<_>) =
+            remaining.drain(..).partition(|t| t.parent_id == parent);
+        *remaining = rest;
+        for term in children {
+            let id = term.id;
+            nodes.push(TermNode { term, depth });
+            walk(Some(id), depth + 1, remaining, nodes);
+        }
+    }
+
+    let ids: HashSet<Uuid> = terms.iter().map(|t| t.id).collect();
+    let mut remaining: Vec<Term> = terms
+        .into_iter()
+        .map(|mut t| {
+            if t.parent_id.is_some_and(|p| !ids.contains(&p)) {
+                t.parent_id = None;
+            }
+            t
+        })
+        .collect();
+    let mut nodes = Vec::with_capacity(remaining.len());
+    walk(None, 0, &mut remaining, &mut nodes);
+    // Anything left sits in a parent cycle; list it rather than drop it.
+    nodes.extend(
+        remaining
+            .into_iter()
+            .map(|term| TermNode { term, depth: 0 }),
+    );
+    nodes
+}
+
+/// Derive a URL slug from a display name: lowercase ASCII letters and
+/// digits, with runs of anything else collapsed to a single hyphen.
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::with_capacity(name.len());
+    for c in name.trim().chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::models::ContentItem;
+
+/// Posts listed in a feed, newest first.
+pub const FEED_ITEM_LIMIT: i64 = 20;
+
+/// Words kept when a feed carries excerpts instead of the full text.
+pub const FEED_EXCERPT_WORDS: usize = 55;
+
+/// When a post went out: its publish date, or its creation date for
+/// posts published before that was recorded.
+pub fn feed_published(item: &ContentItem) -> DateTime<Utc> {
+    item.published_at.unwrap_or(item.created_at)
+}
+
+/// When a post last changed, never earlier than its publish date.
+pub fn feed_updated(item: &ContentItem) -> DateTime<Utc> {
+    item.edited_at.max(feed_published(item))
+}
+
+/// The first `max_words` words of `html` with the markup removed.
+/// Entities are kept as written, so the result is still valid HTML.
+pub fn excerpt(html: &str, max_words: usize) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            // Tags separate words, e.g. `</p><p>`.
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.len() <= max_words {
+        return words.join(" ");
+    }
+    format!("{}…", words[..max_words].join(" "))
+}
+
+/// Point root-relative links and images in `html` at `base_url`, so
+/// they still work when a feed reader shows the post.
+pub fn absolute_urls(html: &str, base_url: &str) -> String {
+    let mut out = html.to_string();
+    for attr in ["href", "src"] {
+        for quote in ['"', '\''] {
+            let relative = format!("{attr}={quote}/");
+            let absolute = format!("{attr}={quote}{base_url}/");
+            let mut result = String::with_capacity(out.len());
+            let mut rest = out.as_str();
+            while let Some(pos) = rest.find(&relative) {
+                let after = &rest[pos + relative.len()..];
+                result.push_str(&rest[..pos]);
+                // `//host/path` is protocol-relative, not ours to touch.
+                if after.starts_with('/') {
+                    result.push_str(&relative);
+                } else {
+                    result.push_str(&absolute);
+                }
+                rest = after;
+            }
+            result.push_str(rest);
+            out = result;
+        }
+    }
+    out
+}
+
+/// A strong `ETag` (quotes included) for a response body.
+pub fn etag_for(body: &str) -> String {
+    let digest = Sha256::digest(body.as_bytes());
+    format!("\"{}\"", hex::encode(&digest[..16]))
+}
+
+/// A strong `ETag` for a feed, from the id and last change of each
+/// post it lists and `version`, which stands for everything else the
+/// feed is built from. Unlike `etag_for`, it needs nothing rendered.
+pub fn feed_etag(
+    version: &str,
+    posts: impl IntoIterator<Item = (Uuid, DateTime<Utc>)>,
+) -> String {
+    let mut key = version.to_string();
+    for (id, updated) in posts {
+        key.push_str(&format!(
+            "\n{id} {}",
+            updated.timestamp_micros()
+        ));
+    }
+    etag_for(&key)
+}
+
+/// Whether an `If-None-Match` header value matches `etag`. Weak
+/// validators compare equal to their strong form, as RFC 9110 allows
+/// for `GET`.
+pub fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    if_none_match.split(',').map(str::trim).any(|tag| {
+        tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag
+    })
+}
@@ -0,0 +1,284 @@
+use std::collections::HashMap;
+use std::io::Cursor;
+
+use image::codecs::jpeg::JpegEncoder;
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use uuid::Uuid;
+
+use crate::common::MediaError;
+use crate::models::Media;
+
+/// Widths of the resized copies offered for an image, used unless
+/// `MEDIA_IMAGE_WIDTHS` says otherwise.
+pub const DEFAULT_IMAGE_WIDTHS: [u32; 4] = [320, 640, 1024, 1600];
+
+const JPEG_VARIANT_QUALITY: u8 = 82;
+
+/// A comma-separated list of widths in pixels, e.g. `320,640,1024`.
+pub fn parse_image_widths(value: &str) -> Result<Vec<u32>, String> {
+    let mut widths = value
+        .split(',')
+        .map(str::trim)
+        .filter(|w| !w.is_empty())
+        .map(|w| match w.parse::<u32>() {
+            Ok(width) if (16..=8192).contains(&width) => Ok(width),
+            _ => Err(format!(
+                "Image width must be between 16 and 8192: {w}"
+            )),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    widths.sort_unstable();
+    widths.dedup();
+    Ok(widths)
+}
+
+/// The format resized copies of a file are written in. Only JPEG and
+/// PNG get copies; GIFs may be animated and WebP is already small.
+fn variant_format(mime_type: &str) -> Option<ImageFormat> {
+    match mime_type {
+        "image/jpeg" => Some(ImageFormat::Jpeg),
+        "image/png" => Some(ImageFormat::Png),
+        _ => None,
+    }
+}
+
+/// The configured widths a file has a resized copy at: those narrower
+/// than the original.
+pub fn variant_widths(media: &Media, widths: &[u32]) -> Vec<u32> {
+    let Some(original) =
+        media.width.and_then(|w| u32::try_from(w).ok())
+    else {
+        return Vec::new();
+    };
+    if variant_format(&media.mime_type).is_none() {
+        return Vec::new();
+    }
+    widths.iter().copied().filter(|&w| w < original).collect()
+}
+
+/// Storage key of the copy of `storage_key` resized to `width`, e.g.
+/// `<uuid>-640w.jpg`.
+pub fn media_variant_key(storage_key: &str, width: u32) -> String {
+    match storage_key.rsplit_once('.') {
+        Some((stem, ext)) => format!("{stem}-{width}w.{ext}"),
+        None => format!("{storage_key}-{width}w"),
+    }
+}
+
+/// `data`, a JPEG or PNG file, scaled down to `width` pixels wide and
+/// encoded in the same format.
+pub fn resize_image(
+    data: &[u8],
+    mime_type: &str,
+    width: u32,
+) -> Result<Vec<u8>, MediaError> {
+    let format = variant_format(mime_type)
+        .ok_or(MediaError::UnsupportedType)?;
+    let original = image::load_from_memory_with_format(data, format)
+        .map_err(|e| MediaError::Processing(e.to_string()))?;
+    let resized =
+        original.resize(width, u32::MAX, FilterType::Lanczos3);
+
+    let mut out = Cursor::new(Vec::new());
+    let encoded = match format {
+        ImageFormat::Jpeg => {
+            let rgb = DynamicImage::ImageRgb8(resized.to_rgb8());
+            rgb.write_with_encoder(JpegEncoder::new_with_quality(
+                &mut out,
+                JPEG_VARIANT_QUALITY,
+            ))
+        }
+        _ => resized.write_to(&mut out, format),
+    };
+    encoded.map_err(|e| MediaError::Processing(e.to_string()))?;
+    Ok(out.into_inner())
+}
+
+/// One parsed attribute of a start tag.
+struct Attribute<'a> {
+    name: String,
+    value: Option<&'a str>,
+}
+
+/// The attributes of a start tag's inside, i.e. the text between the
+/// tag name and the closing `>`.
+fn parse_attributes(mut rest: &str) -> Vec<Attribute<'_>> {
+    let mut attributes = Vec::new();
+    loop {
+        rest = rest.trim_start_matches(|c: char| {
+            c.is_ascii_whitespace() || c == '/'
+        });
+        let name_end = rest
+            .find(|c: char| {
+                c.is_ascii_whitespace()
+                    || matches!(c, '=' | '/' | '>')
+            })
+            .unwrap_or(rest.len());
+        if name_end == 0 {
+            return attributes;
+        }
+        let name = rest[..name_end].to_ascii_lowercase();
+        rest = rest[name_end..].trim_start();
+
+        let mut value = None;
+        if let Some(after) = rest.strip_prefix('=') {
+            let after = after.trim_start();
+            let (v, next) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => {
+                    let inner = &after[1..];
+                    let end =
+                        inner.find(quote).unwrap_or(inner.len());
+                    (
+                        &inner[..end],
+                        inner.get(end + 1..).unwrap_or(""),
+                    )
+                }
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_ascii_whitespace())
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = Some(v);
+            rest = next;
+        }
+        attributes.push(Attribute { name, value });
+    }
+}
+
+/// The end of the tag starting at `html[0]`, skipping `>` inside quoted
+/// attribute values.
+fn tag_end(html: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in html.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The media id in a `src` that points at the library, like
+/// `/media/<id>`.
+fn media_id_in_src(src: &str) -> Option<Uuid> {
+    src.strip_prefix("/media/")?.parse().ok()
+}
+
+/// Calls `f` with each `<img>` tag in `html` and its attributes, and
+/// replaces the tag with what `f` returns.
+fn map_img_tags(
+    html: &str,
+    mut f: impl FnMut(&str, &[Attribute<'_>]) -> Option<String>,
+) -> String {
+    let mut out = String::with_capacity(html.len());
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let is_img = rest
+            .get(1..4)
+            .is_some_and(|name| name.eq_ignore_ascii_case("img"))
+            && rest[4..].starts_with(|c: char| {
+                c.is_ascii_whitespace() || c == '/' || c == '>'
+            });
+        let end = if is_img { tag_end(rest) } else { None };
+        let Some(end) = end else {
+            out.push('<');
+            rest = &rest[1..];
+            continue;
+        };
+        let tag = &rest[..=end];
+        let attributes = parse_attributes(&rest[4..end]);
+        match f(tag, &attributes) {
+            Some(replacement) => out.push_str(&replacement),
+            None => out.push_str(tag),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Ids of library files shown by `<img>` tags in `html`.
+pub fn media_ids_in(html: &str) -> Vec<Uuid> {
+    let mut ids = Vec::new();
+    map_img_tags(html, |_, attributes| {
+        let src = attributes.iter().find(|a| a.name == "src")?;
+        if let Some(id) = src.value.and_then(media_id_in_src)
+            && !ids.contains(&id)
+        {
+            ids.push(id);
+        }
+        None
+    });
+    ids
+}
+
+/// Let browsers pick a suitably sized copy of library images and load
+/// images only as they scroll into view.
+///
+/// Every `<img>` without a `loading` attribute gets `loading="lazy"`.
+/// Images from `media` also get `srcset` and `sizes` listing their
+/// resized copies, plus `width` and `height` so the page does not jump
+/// while they load. Attributes the author set are left alone.
+pub fn add_responsive_images(
+    html: &str,
+    media: &HashMap<Uuid, Media>,
+    widths: &[u32],
+) -> String {
+    map_img_tags(html, |tag, attributes| {
+        let has =
+            |name: &str| attributes.iter().any(|a| a.name == name);
+        let mut extra = String::new();
+        if !has("loading") {
+            extra.push_str(" loading=\"lazy\"");
+        }
+
+        let file = attributes
+            .iter()
+            .find(|a| a.name == "src")
+            .and_then(|a| a.value)
+            .and_then(media_id_in_src)
+            .and_then(|id| media.get(&id));
+        if let Some(file) = file
+            && let (Some(w), Some(h)) = (file.width, file.height)
+        {
+            let variants = variant_widths(file, widths);
+            if !variants.is_empty() && !has("srcset") {
+                let mut srcset: Vec<String> = variants
+                    .iter()
+                    .map(|v| format!("{}/{v} {v}w", file.url()))
+                    .collect();
+                srcset.push(format!("{} {w}w", file.url()));
+                extra.push_str(&format!(
+                    " srcset=\"{}\"",
+                    srcset.join(", ")
+                ));
+                if !has("sizes") {
+                    extra.push_str(&format!(
+                        " sizes=\"(max-width: {w}px) 100vw, {w}px\""
+                    ));
+                }
+            }
+            if !has("width") && !has("height") {
+                extra.push_str(&format!(
+                    " width=\"{w}\" height=\"{h}\""
+                ));
+            }
+        }
+
+        if extra.is_empty() {
+            return None;
+        }
+        let body = tag[..tag.len() - 1].trim_end();
+        Some(match body.strip_suffix('/') {
+            Some(body) => format!("{}{extra} />", body.trim_end()),
+            None => format!("{body}{extra}>"),
+        })
+    })
+}
@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{FileTransport, Message, SmtpTransport, Transport};
+
+use crate::common::MailError;
+
+/// A plain-text email ready to be handed to a [`Mailer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmailMessage {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Outgoing mail delivery.
+///
+/// Handlers never call a transport directly; they queue messages in the
+/// [`MailOutbox`](super::MailOutbox), whose worker hands them to the
+/// configured `Mailer`.
+pub trait Mailer: Send + Sync {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailError>;
+}
+
+fn parse_mailbox(address: &str) -> Result<Mailbox, MailError> {
+    address
+        .parse()
+        .map_err(|_| MailError::InvalidAddress(address.to_string()))
+}
+
+fn build_message(
+    from: &Mailbox,
+    message: &EmailMessage,
+) -> Result<Message, MailError> {
+    Message::builder()
+        .from(from.clone())
+        .to(parse_mailbox(&message.to)?)
+        .subject(&message.subject)
+        .header(ContentType::TEXT_PLAIN)
+        .body(message.body.clone())
+        .map_err(|e| MailError::Transport(e.to_string()))
+}
+
+/// Writes messages to the application log instead of delivering them.
+///
+/// Default transport for development. Only the recipient and subject
+/// are logged at info level; bodies hold live reset and verification
+/// links, so they are logged at debug level only.
+pub struct LogMailer;
+
+impl Mailer for LogMailer {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailError> {
+        log::info!("Email to {} | {}", message.to, message.subject);
+        log::debug!(
+            "Body of email to {} (contains live links, keep this log \
+             private):\n{}",
+            message.to,
+            message.body
+        );
+        Ok(())
+    }
+}
+
+/// Writes each message as an `<uuid>.eml` file into a directory.
+///
+/// Useful for development and tests: the files are complete RFC 5322
+/// messages that any mail client can open.
+pub struct FileMailer {
+    transport: FileTransport,
+    from: Mailbox,
+}
+
+impl FileMailer {
+    /// Creates `dir` if it does not exist yet.
+    pub fn new(
+        dir: impl Into<PathBuf>,
+        from: &str,
+    ) -> Result<Self, MailError> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| MailError::Transport(e.to_string()))?;
+        Ok(Self {
+            transport: FileTransport::new(dir),
+            from: parse_mailbox(from)?,
+        })
+    }
+}
+
+impl Mailer for FileMailer {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailError> {
+        let email = build_message(&self.from, message)?;
+        self.transport
+            .send(&email)
+            .map(|_| ())
+            .map_err(|e| MailError::Transport(e.to_string()))
+    }
+}
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SmtpSecurity {
+    /// Implicit TLS, usually port 465.
+    Tls,
+    /// Plain connection upgraded with STARTTLS, usually port 587.
+    StartTls,
+    /// No encryption. Only for local relays and test servers.
+    None,
+}
+
+impl std::str::FromStr for SmtpSecurity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "tls" => Ok(Self::Tls),
+            "starttls" => Ok(Self::StartTls),
+            "none" => Ok(Self::None),
+            _ => Err(format!("invalid SMTP security mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// Defaults to the standard port of `security` when unset.
+    pub port: Option<u16>,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+}
+
+/// Delivers messages through an SMTP relay.
+///
+/// Sending blocks on network I/O; the outbox worker runs it on the
+/// blocking thread pool.
+pub struct SmtpMailer {
+    transport: SmtpTransport,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(config: &SmtpConfig) -> Result<Self, MailError> {
+        let transport_err = |e: lettre::transport::smtp::Error| {
+            MailError::Transport(e.to_string())
+        };
+
+        let mut builder = match config.security {
+            SmtpSecurity::Tls => SmtpTransport::relay(&config.host)
+                .map_err(transport_err)?,
+            SmtpSecurity::StartTls => {
+                SmtpTransport::starttls_relay(&config.host)
+                    .map_err(transport_err)?
+            }
+            SmtpSecurity::None => {
+                SmtpTransport::builder_dangerous(&config.host)
+            }
+        };
+        if let Some(port) = config.port {
+            builder = builder.port(port);
+        }
+        if let Some(username) = &config.username {
+            builder = builder.credentials(Credentials::new(
+                username.clone(),
+                config.password.clone().unwrap_or_default(),
+            ));
+        }
+
+        Ok(Self {
+            transport: builder.build(),
+            from: parse_mailbox(&config.from)?,
+        })
+    }
+}
+
+impl Mailer for SmtpMailer {
+    fn send(&self, message: &EmailMessage) -> Result<(), MailError> {
+        let email = build_message(&self.from, message)?;
+        self.transport
+            .send(&email)
+            .map(|_| ())
+            .map_err(|e| MailError::Transport(e.to_string()))
+    }
+}
@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use pulldown_cmark::{CowStr, Event, Options, Parser, Tag, TagEnd};
+
+use super::HtmlPolicy;
+use crate::models::ContentFormat;
+
+/// CommonMark plus the GitHub extensions authors expect.
+const MARKDOWN_OPTIONS: Options = Options::ENABLE_TABLES
+    .union(Options::ENABLE_FOOTNOTES)
+    .union(Options::ENABLE_TASKLISTS)
+    .union(Options::ENABLE_STRIKETHROUGH);
+
+/// Render Markdown to HTML. Footnotes are numbered in order of first
+/// use and linked as `#fn-1`, `#fn-2`, … rather than by their labels,
+/// which may contain anything. Raw HTML is passed through; sanitizing
+/// the result is up to the caller.
+pub fn render_markdown(source: &str) -> String {
+    let mut numbers: HashMap<String, usize> = HashMap::new();
+    let mut number = |label: CowStr<'_>| {
+        let next = numbers.len() + 1;
+        *numbers.entry(label.into_string()).or_insert(next)
+    };
+    let events = Parser::new_ext(source, MARKDOWN_OPTIONS).map(
+        |event| match event {
+            Event::FootnoteReference(label) => {
+                let n = number(label);
+                Event::InlineHtml(
+                    format!(
+                        "<sup class=\"footnote-reference\">\
+                         <a href=\"#fn-{n}\">{n}</a></sup>"
+                    )
+                    .into(),
+                )
+            }
+            Event::Start(Tag::FootnoteDefinition(label)) => {
+                let n = number(label);
+                Event::Html(
+                    format!(
+                        "<div class=\"footnote-definition\" \
+                         id=\"fn-{n}\"><sup \
+                         class=\"footnote-definition-label\">{n}</sup>"
+                    )
+                    .into(),
+                )
+            }
+            Event::End(TagEnd::FootnoteDefinition) => {
+                Event::Html("</div>\n".into())
+            }
+            event => event,
+        },
+    );
+    let mut html = String::with_capacity(source.len() * 3 / 2);
+    pulldown_cmark::html::push_html(&mut html, events);
+    html
+}
+
+/// Convert HTML to Markdown for switching an item's format. Markup
+/// Markdown has no syntax for, such as underlines or alignment, is
+/// dropped. HTML is valid Markdown, so it is kept as is if it cannot be
+/// converted.
+pub fn html_to_markdown(html: &str) -> String {
+    let converter = htmd::HtmlToMarkdown::builder()
+        .options(htmd::options::Options {
+            bullet_list_marker: htmd::options::BulletListMarker::Dash,
+            hr_style: htmd::options::HrStyle::Dashes,
+            br_style: htmd::options::BrStyle::Backslash,
+            ul_bullet_spacing: 1,
+            ol_number_spacing: 1,
+            ..Default::default()
+        })
+        .skip_tags(vec!["script", "style"])
+        .build();
+    let Ok(markdown) = converter.convert(html) else {
+        return html.to_string();
+    };
+
+    // The editor wraps list items in paragraphs, which leaves
+    // whitespace-only lines between them; keep single blank lines.
+    let mut out = String::with_capacity(markdown.len() + 1);
+    for line in markdown.lines().map(str::trim_end) {
+        if line.is_empty()
+            && (out.is_empty() || out.ends_with("\n\n"))
+        {
+            continue;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out.truncate(out.trim_end().len());
+    out.push('\n');
+    out
+}
+
+/// `content` written in `from`, rewritten in `to`.
+pub fn convert_content(
+    content: &str,
+    from: ContentFormat,
+    to: ContentFormat,
+) -> String {
+    match (from, to) {
+        (ContentFormat::Html, ContentFormat::Markdown) => {
+            html_to_markdown(content)
+        }
+        (ContentFormat::Markdown, ContentFormat::Html) => {
+            render_markdown(content)
+        }
+        _ => content.to_string(),
+    }
+}
+
+/// Content as it is stored when saved: the `content` column, its
+/// format, the cached rendering for Markdown and whether it was kept
+/// unfiltered.
+#[derive(Debug, Clone)]
+pub struct AuthoredContent {
+    pub content: String,
+    pub format: ContentFormat,
+    pub rendered_html: Option<String>,
+    pub unfiltered_html: bool,
+}
+
+impl AuthoredContent {
+    /// Prepare `source` for saving. Unless `unfiltered`, HTML is
+    /// sanitized against `policy`; Markdown is kept as written and its
+    /// rendering is sanitized instead, so the source survives a round
+    /// trip through the editor.
+    pub fn new(
+        policy: &HtmlPolicy,
+        format: ContentFormat,
+        source: &str,
+        unfiltered: bool,
+    ) -> Self {
+        let (content, rendered_html) = match format {
+            ContentFormat::Html if unfiltered => {
+                (source.to_string(), None)
+            }
+            ContentFormat::Html => (policy.clean(source), None),
+            ContentFormat::Markdown => {
+                let html = render_markdown(source);
+                let html = if unfiltered {
+                    html
+                } else {
+                    policy.clean_as(&html, format)
+                };
+                (source.to_string(), Some(html))
+            }
+        };
+        Self {
+            content,
+            format,
+            rendered_html,
+            unfiltered_html: unfiltered,
+        }
+    }
+
+    /// The content as HTML; see [`ContentItem::html`](crate::models::ContentItem::html).
+    pub fn html(&self) -> &str {
+        self.rendered_html.as_deref().unwrap_or(&self.content)
+    }
+}
@@ -0,0 +1,146 @@
+use std::io::{Cursor, ErrorKind};
+use std::path::PathBuf;
+
+use image::{ImageFormat, ImageReader};
+use uuid::Uuid;
+
+use crate::common::MediaError;
+use crate::models::MAX_MEDIA_FILENAME_LENGTH;
+
+/// Where uploaded media files are kept.
+///
+/// Keys are chosen by RustPress (see [`media_storage_key`]), never by
+/// the uploader. Calls block, so handlers run them on the blocking
+/// thread pool. An S3-compatible backend only has to implement these
+/// three calls.
+pub trait MediaStorage: Send + Sync {
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), MediaError>;
+    fn get(&self, key: &str) -> Result<Vec<u8>, MediaError>;
+    /// Deleting a missing file is not an error.
+    fn delete(&self, key: &str) -> Result<(), MediaError>;
+}
+
+/// Keeps media files in a directory on the local disk.
+pub struct LocalMediaStorage {
+    root: PathBuf,
+}
+
+impl LocalMediaStorage {
+    /// Creates `dir` if it does not exist yet.
+    pub fn new(dir: impl Into<PathBuf>) -> Result<Self, MediaError> {
+        let root = dir.into();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, MediaError> {
+        let valid = !key.is_empty()
+            && !key.starts_with('.')
+            && key.chars().all(|c| {
+                c.is_ascii_alphanumeric()
+                    || matches!(c, '.' | '-' | '_')
+            });
+        if !valid {
+            return Err(MediaError::InvalidKey(key.to_string()));
+        }
+        Ok(self.root.join(key))
+    }
+}
+
+impl MediaStorage for LocalMediaStorage {
+    fn put(&self, key: &str, data: &[u8]) -> Result<(), MediaError> {
+        let path = self.path(key)?;
+        // Write under a temporary name first so a half-written file
+        // is never served, and two writers of one key do not mix.
+        let partial = self
+            .root
+            .join(format!(".{key}.{}.partial", Uuid::new_v4()));
+        std::fs::write(&partial, data)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(())
+    }
+
+    fn get(&self, key: &str) -> Result<Vec<u8>, MediaError> {
+        std::fs::read(self.path(key)?).map_err(|e| {
+            if e.kind() == ErrorKind::NotFound {
+                MediaError::NotFound
+            } else {
+                e.into()
+            }
+        })
+    }
+
+    fn delete(&self, key: &str) -> Result<(), MediaError> {
+        match std::fs::remove_file(self.path(key)?) {
+            Err(e) if e.kind() != ErrorKind::NotFound => {
+                Err(e.into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// What an upload turned out to be, judged by its first bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SniffedMedia {
+    pub mime_type: &'static str,
+    pub extension: &'static str,
+    /// Pixel size, for images.
+    pub dimensions: Option<(u32, u32)>,
+}
+
+/// Identify an upload from its contents. The name and content type the
+/// browser sent are ignored, so a script renamed to `.png` is turned
+/// away. SVG is not accepted, as it can carry scripts.
+pub fn sniff_media(data: &[u8]) -> Result<SniffedMedia, MediaError> {
+    if data.starts_with(b"%PDF-") {
+        return Ok(SniffedMedia {
+            mime_type: "application/pdf",
+            extension: "pdf",
+            dimensions: None,
+        });
+    }
+
+    let format = image::guess_format(data)
+        .map_err(|_| MediaError::UnsupportedType)?;
+    let (mime_type, extension) = match format {
+        ImageFormat::Png => ("image/png", "png"),
+        ImageFormat::Jpeg => ("image/jpeg", "jpg"),
+        ImageFormat::Gif => ("image/gif", "gif"),
+        ImageFormat::WebP => ("image/webp", "webp"),
+        _ => return Err(MediaError::UnsupportedType),
+    };
+    // Only the header is read; a file that claims to be an image but
+    // has no readable size is rejected.
+    let dimensions =
+        ImageReader::with_format(Cursor::new(data), format)
+            .into_dimensions()
+            .map_err(|_| MediaError::UnsupportedType)?;
+    Ok(SniffedMedia {
+        mime_type,
+        extension,
+        dimensions: Some(dimensions),
+    })
+}
+
+/// A fresh storage key for a file of the given type.
+pub fn media_storage_key(id: Uuid, extension: &str) -> String {
+    format!("{id}.{extension}")
+}
+
+/// The uploader's file name without any directory part or control
+/// characters, shortened to [`MAX_MEDIA_FILENAME_LENGTH`] characters.
+pub fn clean_media_filename(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or_default();
+    let cleaned: String = base
+        .chars()
+        .filter(|c| !c.is_control())
+        .take(MAX_MEDIA_FILENAME_LENGTH)
+        .collect();
+    let cleaned = cleaned.trim();
+    if cleaned.is_empty() {
+        "upload".to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
@@ -1,3 +1,27 @@
 pub use auth::*;
+pub use feed::*;
+pub use images::*;
+pub use mailer::*;
+pub use markdown::*;
+pub use media::*;
+pub use outbox::*;
+pub use sanitize::*;
+pub use scheduler::*;
+pub use seo::*;
+pub use token::*;
+pub use totp::*;
+pub use trash::*;
 
 mod auth;
+mod feed;
+mod images;
+mod mailer;
+mod markdown;
+mod media;
+mod outbox;
+mod sanitize;
+mod scheduler;
+mod seo;
+mod token;
+mod totp;
+mod trash;
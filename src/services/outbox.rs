@@ -0,0 +1,171 @@
+use std::sync::Arc;
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+use tokio::sync::Notify;
+
+use super::{EmailMessage, Mailer};
+use crate::common::MailError;
+use crate::db;
+use crate::models::{OutboxMail, OutboxMailCreate};
+
+/// Attempts after which a message is marked as failed.
+pub const MAX_MAIL_ATTEMPTS: i32 = 6;
+
+/// Messages claimed per worker pass.
+const BATCH_SIZE: i64 = 20;
+
+/// How long a claimed message is reserved for the worker sending it.
+const CLAIM_LEASE_MINUTES: i64 = 5;
+
+/// Delivered and failed messages are kept this long, without their
+/// bodies, before being purged.
+const FINISHED_RETENTION_DAYS: i64 = 7;
+
+/// Wait before the next attempt after `attempts` failed ones: one minute,
+/// doubling each time, capped at an hour.
+pub fn mail_retry_delay(attempts: i32) -> Duration {
+    let exponent = attempts.clamp(1, 7) as u32 - 1;
+    Duration::minutes(2i64.pow(exponent)).min(Duration::hours(1))
+}
+
+/// Database-backed queue for outgoing mail.
+///
+/// Request handlers call [`MailOutbox::enqueue`], which only inserts a
+/// row, so a slow or unreachable mail server never holds up a response.
+/// [`MailOutbox::run`] delivers queued mail in the background.
+#[derive(Clone)]
+pub struct MailOutbox {
+    pool: PgPool,
+    wake: Arc<Notify>,
+}
+
+impl MailOutbox {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    pub async fn enqueue(
+        &self,
+        message: &EmailMessage,
+    ) -> Result<OutboxMail, sqlx::Error> {
+        let mail = db::enqueue_mail(
+            &self.pool,
+            &OutboxMailCreate {
+                recipient: message.to.clone(),
+                subject: message.subject.clone(),
+                body: message.body.clone(),
+            },
+        )
+        .await?;
+        self.wake.notify_one();
+        Ok(mail)
+    }
+
+    /// Send every message that is currently due and return how many were
+    /// delivered. Failures are rescheduled with [`mail_retry_delay`] until
+    /// [`MAX_MAIL_ATTEMPTS`] is reached.
+    pub async fn deliver_due(
+        &self,
+        mailer: &Arc<dyn Mailer>,
+    ) -> Result<usize, sqlx::Error> {
+        let mut delivered = 0;
+
+        loop {
+            let batch = db::claim_due_mail(
+                &self.pool,
+                BATCH_SIZE,
+                Duration::minutes(CLAIM_LEASE_MINUTES),
+            )
+            .await?;
+            if batch.is_empty() {
+                return Ok(delivered);
+            }
+
+            for mail in batch {
+                let message = EmailMessage {
+                    to: mail.recipient,
+                    subject: mail.subject,
+                    body: mail.body,
+                };
+                let sender = mailer.clone();
+                let result = tokio::task::spawn_blocking(move || {
+                    sender.send(&message)
+                })
+                .await
+                .unwrap_or_else(|e| {
+                    Err(MailError::Transport(e.to_string()))
+                });
+
+                match result {
+                    Ok(()) => {
+                        db::mark_mail_sent(&self.pool, mail.id)
+                            .await?;
+                        delivered += 1;
+                    }
+                    Err(e) => {
+                        let retry_at = (mail.attempts
+                            < MAX_MAIL_ATTEMPTS)
+                            .then(|| {
+                                Utc::now()
+                                    + mail_retry_delay(mail.attempts)
+                            });
+                        if retry_at.is_some() {
+                            log::warn!(
+                                "Mail {} failed (attempt {}): {}",
+                                mail.id,
+                                mail.attempts,
+                                e
+                            );
+                        } else {
+                            log::error!(
+                                "Giving up on mail {} after {} attempts: {}",
+                                mail.id,
+                                mail.attempts,
+                                e
+                            );
+                        }
+                        db::mark_mail_failed(
+                            &self.pool,
+                            mail.id,
+                            &e.to_string(),
+                            retry_at,
+                        )
+                        .await?;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Deliver mail until the process exits. Wakes up as soon as a message
+    /// is enqueued, and every `poll_interval` to pick up retries.
+    pub async fn run(
+        self,
+        mailer: Arc<dyn Mailer>,
+        poll_interval: StdDuration,
+    ) {
+        loop {
+            if let Err(e) = self.deliver_due(&mailer).await {
+                log::error!("Mail outbox delivery failed: {}", e);
+            }
+            if let Err(e) = db::delete_finished_mail(
+                &self.pool,
+                Utc::now() - Duration::days(FINISHED_RETENTION_DAYS),
+            )
+            .await
+            {
+                log::warn!("Failed to purge finished mail: {}", e);
+            }
+
+            tokio::select! {
+                _ = self.wake.notified() => {}
+                _ = tokio::time::sleep(poll_interval) => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,378 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::models::ContentFormat;
+
+/// Tags the editor toolbar produces: headings, paragraphs, inline
+/// formatting, lists, quotes, code, links and images.
+const DEFAULT_ALLOWED_TAGS: &[&str] = &[
+    "a",
+    "b",
+    "blockquote",
+    "br",
+    "code",
+    "del",
+    "em",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "hr",
+    "i",
+    "img",
+    "li",
+    "ol",
+    "p",
+    "pre",
+    "s",
+    "strike",
+    "strong",
+    "u",
+    "ul",
+];
+
+/// `tag:attribute` pairs allowed by default. `style` only ever keeps a
+/// `text-align`, `class` only `language-*` names on `code`, and
+/// `target` only `_blank`.
+const DEFAULT_ALLOWED_ATTRIBUTES: &[(&str, &str)] = &[
+    ("a", "href"),
+    ("a", "target"),
+    ("a", "title"),
+    ("code", "class"),
+    ("h1", "style"),
+    ("h2", "style"),
+    ("h3", "style"),
+    ("h4", "style"),
+    ("img", "alt"),
+    ("img", "height"),
+    ("img", "loading"),
+    ("img", "src"),
+    ("img", "title"),
+    ("img", "width"),
+    ("ol", "start"),
+    ("ol", "type"),
+    ("p", "style"),
+];
+
+/// Also allowed in HTML rendered from Markdown: GFM tables, footnotes,
+/// task list checkboxes and the headings the editor lacks.
+const MARKDOWN_TAGS: &[&str] = &[
+    "div", "h5", "h6", "input", "sup", "table", "tbody", "td", "th",
+    "thead", "tr",
+];
+
+/// Attributes allowed on [`MARKDOWN_TAGS`]. In rendered Markdown `id`
+/// and `class` only keep footnote names and `type` only `checkbox`.
+const MARKDOWN_ATTRIBUTES: &[(&str, &str)] = &[
+    ("div", "class"),
+    ("div", "id"),
+    ("input", "checked"),
+    ("input", "disabled"),
+    ("input", "type"),
+    ("sup", "class"),
+    ("td", "style"),
+    ("th", "style"),
+];
+
+/// Tags that can run scripts, load other documents or submit forms.
+/// They cannot be allowed, not even by configuration.
+const FORBIDDEN_TAGS: &[&str] = &[
+    "base",
+    "button",
+    "embed",
+    "form",
+    "frame",
+    "frameset",
+    "iframe",
+    "input",
+    "link",
+    "math",
+    "meta",
+    "noembed",
+    "noframes",
+    "noscript",
+    "object",
+    "option",
+    "plaintext",
+    "script",
+    "select",
+    "style",
+    "svg",
+    "template",
+    "textarea",
+    "title",
+    "xmp",
+];
+
+/// Attributes that cannot be allowed, besides event handlers (`on*`).
+/// `rel` is set on every link instead.
+const FORBIDDEN_ATTRIBUTES: &[&str] = &[
+    "formaction",
+    "http-equiv",
+    "rel",
+    "sizes",
+    "srcdoc",
+    "srcset",
+    "style",
+    "xmlns",
+];
+
+const URL_SCHEMES: &[&str] = &["http", "https", "mailto", "tel"];
+
+/// Added to every link in sanitized content.
+const LINK_REL: &str = "noopener noreferrer nofollow";
+
+/// Which HTML stored content may contain.
+///
+/// Content is cleaned against the policy when a user without the
+/// `unfiltered_html` capability saves it, and again when it is shown,
+/// so older rows and a narrowed policy are covered too. Everything not
+/// allowed is removed; the text inside removed tags is kept, except for
+/// `<script>` and `<style>`. Link and image URLs must be relative or use
+/// `http`, `https`, `mailto` or `tel`.
+#[derive(Debug, Clone)]
+pub struct HtmlPolicy {
+    tags: BTreeSet<String>,
+    /// Allowed attributes by tag; `*` applies to every tag.
+    attributes: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Default for HtmlPolicy {
+    fn default() -> Self {
+        let mut attributes: BTreeMap<String, BTreeSet<String>> =
+            BTreeMap::new();
+        for &(tag, attribute) in DEFAULT_ALLOWED_ATTRIBUTES {
+            attributes
+                .entry(tag.to_string())
+                .or_default()
+                .insert(attribute.to_string());
+        }
+        Self {
+            tags: DEFAULT_ALLOWED_TAGS
+                .iter()
+                .map(|t| t.to_string())
+                .collect(),
+            attributes,
+        }
+    }
+}
+
+fn is_html_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+}
+
+/// Whether `name` is a valid, allowable attribute name.
+fn is_allowable_attribute(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with("on")
+        && !FORBIDDEN_ATTRIBUTES.contains(&name)
+        && name.chars().all(|c| c.is_ascii_lowercase() || c == '-')
+}
+
+impl HtmlPolicy {
+    /// Also allow the comma-separated `tags`, e.g. `table,tr,td`.
+    pub fn allow_tags(mut self, tags: &str) -> Result<Self, String> {
+        for tag in tags.split(',').map(str::trim) {
+            if tag.is_empty() {
+                continue;
+            }
+            let tag = tag.to_ascii_lowercase();
+            if !is_html_name(&tag) {
+                return Err(format!("Invalid tag name: {tag}"));
+            }
+            if FORBIDDEN_TAGS.contains(&tag.as_str()) {
+                return Err(format!(
+                    "The {tag} tag cannot be allowed"
+                ));
+            }
+            self.tags.insert(tag);
+        }
+        Ok(self)
+    }
+
+    /// Also allow the comma-separated `tag:attribute` pairs, e.g.
+    /// `th:scope,*:lang`. A `*` tag allows the attribute on every tag.
+    pub fn allow_attributes(
+        mut self,
+        attributes: &str,
+    ) -> Result<Self, String> {
+        for pair in attributes.split(',').map(str::trim) {
+            if pair.is_empty() {
+                continue;
+            }
+            let pair = pair.to_ascii_lowercase();
+            let Some((tag, attribute)) = pair.split_once(':') else {
+                return Err(format!(
+                    "Expected tag:attribute, got {pair}"
+                ));
+            };
+            let (tag, attribute) = (tag.trim(), attribute.trim());
+            if tag != "*" && !is_html_name(tag) {
+                return Err(format!("Invalid tag name: {tag}"));
+            }
+            if !is_allowable_attribute(attribute) {
+                return Err(format!(
+                    "The {attribute} attribute cannot be allowed"
+                ));
+            }
+            self.attributes
+                .entry(tag.to_string())
+                .or_default()
+                .insert(attribute.to_string());
+        }
+        Ok(self)
+    }
+
+    /// `html` with everything the policy does not allow removed.
+    pub fn clean(&self, html: &str) -> String {
+        self.clean_as(html, ContentFormat::Html)
+    }
+
+    /// Like [`clean`](Self::clean), for the HTML of content written in
+    /// `format`. HTML rendered from Markdown may also contain the
+    /// tables, footnotes and task lists Markdown produces.
+    pub fn clean_as(
+        &self,
+        html: &str,
+        format: ContentFormat,
+    ) -> String {
+        let markdown = format == ContentFormat::Markdown;
+        let mut tags: HashSet<&str> =
+            self.tags.iter().map(String::as_str).collect();
+        let mut tag_attributes: HashMap<&str, HashSet<&str>> =
+            HashMap::new();
+        if markdown {
+            tags.extend(MARKDOWN_TAGS);
+            for &(tag, attribute) in MARKDOWN_ATTRIBUTES {
+                tag_attributes
+                    .entry(tag)
+                    .or_default()
+                    .insert(attribute);
+            }
+        }
+        let mut generic_attributes = HashSet::new();
+        for (tag, attributes) in &self.attributes {
+            let attributes = attributes.iter().map(String::as_str);
+            if tag == "*" {
+                generic_attributes.extend(attributes);
+            } else if tags.contains(tag.as_str()) {
+                tag_attributes
+                    .entry(tag.as_str())
+                    .or_default()
+                    .extend(attributes);
+            }
+        }
+
+        ammonia::Builder::empty()
+            .tags(tags)
+            .tag_attributes(tag_attributes)
+            .generic_attributes(generic_attributes)
+            .url_schemes(URL_SCHEMES.iter().copied().collect())
+            .link_rel(Some(LINK_REL))
+            .attribute_filter(move |element, attribute, value| {
+                if markdown {
+                    filter_markdown_attribute(
+                        element, attribute, value,
+                    )
+                } else {
+                    filter_attribute(element, attribute, value)
+                }
+            })
+            .strip_comments(true)
+            .clean(html)
+            .to_string()
+    }
+
+    /// Stored HTML of content written in `format`, ready to show: as
+    /// saved when it was saved unfiltered, cleaned otherwise.
+    pub fn for_display<'a>(
+        &self,
+        html: &'a str,
+        format: ContentFormat,
+        unfiltered: bool,
+    ) -> Cow<'a, str> {
+        if unfiltered {
+            Cow::Borrowed(html)
+        } else {
+            Cow::Owned(self.clean_as(html, format))
+        }
+    }
+}
+
+/// [`filter_attribute`] plus the narrowing of [`MARKDOWN_ATTRIBUTES`].
+fn filter_markdown_attribute<'u>(
+    element: &str,
+    attribute: &str,
+    value: &'u str,
+) -> Option<Cow<'u, str>> {
+    match (element, attribute) {
+        (_, "id") => value
+            .strip_prefix("fn-")
+            .is_some_and(|n| {
+                !n.is_empty() && n.chars().all(|c| c.is_ascii_digit())
+            })
+            .then_some(Cow::Borrowed(value)),
+        ("div" | "sup", "class") => matches!(
+            value,
+            "footnote-definition"
+                | "footnote-definition-label"
+                | "footnote-reference"
+        )
+        .then_some(Cow::Borrowed(value)),
+        ("input", "type") => {
+            (value == "checkbox").then_some(Cow::Borrowed(value))
+        }
+        _ => filter_attribute(element, attribute, value),
+    }
+}
+
+/// Narrows the values of attributes that are only partly safe.
+fn filter_attribute<'u>(
+    element: &str,
+    attribute: &str,
+    value: &'u str,
+) -> Option<Cow<'u, str>> {
+    match attribute {
+        "style" => text_align_style(value).map(Cow::Owned),
+        "class" if element == "code" => {
+            let names: Vec<&str> = value
+                .split_ascii_whitespace()
+                .filter(|name| {
+                    name.strip_prefix("language-").is_some_and(
+                        |lang| {
+                            lang.chars().all(|c| {
+                                c.is_ascii_alphanumeric()
+                                    || matches!(c, '-' | '_' | '+')
+                            })
+                        },
+                    )
+                })
+                .collect();
+            (!names.is_empty()).then(|| Cow::Owned(names.join(" ")))
+        }
+        "target" => {
+            (value == "_blank").then_some(Cow::Borrowed(value))
+        }
+        _ => Some(Cow::Borrowed(value)),
+    }
+}
+
+/// The `text-align` declaration of a `style` value, if it has a valid
+/// one. Every other declaration is dropped.
+fn text_align_style(style: &str) -> Option<String> {
+    style.split(';').rev().find_map(|declaration| {
+        let (property, value) = declaration.split_once(':')?;
+        if !property.trim().eq_ignore_ascii_case("text-align") {
+            return None;
+        }
+        let value = value.trim().to_ascii_lowercase();
+        matches!(
+            value.as_str(),
+            "left" | "center" | "right" | "justify"
+        )
+        .then(|| format!("text-align: {value}"))
+    })
+}
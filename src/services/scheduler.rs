@@ -0,0 +1,74 @@
+use std::time::Duration as StdDuration;
+
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::db;
+use crate::models::{ContentItem, ContentStatus};
+
+/// Background task that carries out publish and unpublish schedules.
+///
+/// Public queries never show a scheduled item or one past its
+/// `unpublish_at`, so a late pass only delays the change; it never leaks
+/// content early.
+#[derive(Clone)]
+pub struct ContentScheduler {
+    pool: PgPool,
+}
+
+impl ContentScheduler {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Publish due scheduled items and unpublish expired ones, recording
+    /// a revision for each. Returns the items that changed.
+    pub async fn apply_due(
+        &self,
+    ) -> Result<Vec<ContentItem>, sqlx::Error> {
+        let now = Utc::now();
+        let promoted =
+            db::promote_due_content(&self.pool, now).await?;
+        let expired =
+            db::unpublish_due_content(&self.pool, now).await?;
+
+        let mut changed =
+            Vec::with_capacity(promoted.len() + expired.len());
+        for (previous, mut item) in promoted
+            .into_iter()
+            .map(|item| (ContentStatus::Scheduled, item))
+            .chain(
+                expired
+                    .into_iter()
+                    .map(|item| (ContentStatus::Published, item)),
+            )
+        {
+            db::ensure_initial_revision(&self.pool, item.id, None)
+                .await?;
+            item.current_rev =
+                db::record_change(&self.pool, &item, previous, None)
+                    .await?;
+            log::info!(
+                "Scheduled {} {} is now {}",
+                item.kind,
+                item.id,
+                item.status
+            );
+            changed.push(item);
+        }
+        Ok(changed)
+    }
+
+    /// Apply schedules every `poll_interval` until the process exits.
+    pub async fn run(self, poll_interval: StdDuration) {
+        loop {
+            if let Err(e) = self.apply_due().await {
+                log::error!(
+                    "Applying content schedules failed: {}",
+                    e
+                );
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use chrono::{DateTime, Utc};
+
+use crate::models::{ContentItem, ContentKind, ContentStatus};
+
+/// Characters kept in a description derived from the content.
+pub const META_DESCRIPTION_CHARS: usize = 160;
+
+/// The readable text of `html`: tags removed, common entities decoded
+/// and whitespace collapsed.
+pub fn html_to_text(html: &str) -> String {
+    let mut text = String::with_capacity(html.len());
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => {
+                in_tag = false;
+                text.push(' ');
+            }
+            _ if !in_tag => text.push(c),
+            _ => {}
+        }
+    }
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find('&') {
+        out.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+        let decoded =
+            rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+                let c = match &rest[1..end] {
+                    "amp" => Some('&'),
+                    "lt" => Some('<'),
+                    "gt" => Some('>'),
+                    "quot" => Some('"'),
+                    "apos" => Some('\''),
+                    "nbsp" => Some(' '),
+                    entity => entity
+                        .strip_prefix("#x")
+                        .or_else(|| entity.strip_prefix("#X"))
+                        .and_then(|hex| {
+                            u32::from_str_radix(hex, 16).ok()
+                        })
+                        .or_else(|| {
+                            entity.strip_prefix('#')?.parse().ok()
+                        })
+                        .and_then(char::from_u32),
+                };
+                c.map(|c| (c, end))
+            });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `text` cut to at most `max_chars` characters at a word boundary,
+/// with an ellipsis when anything was dropped.
+pub fn truncate_text(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let cut: String = text.chars().take(max_chars).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 => &cut[..space],
+        _ => cut.as_str(),
+    };
+    format!("{}…", cut.trim_end_matches([',', '.', ';', ':']))
+}
+
+/// The `src` of the first `<img>` in `html`.
+pub fn first_image_src(html: &str) -> Option<String> {
+    let lower = html.to_ascii_lowercase();
+    let tag_start = lower.find("<img")?;
+    let tag_end = tag_start + lower[tag_start..].find('>')?;
+    let tag = &html[tag_start..tag_end];
+    let src_at = tag.to_ascii_lowercase().find("src=")? + 4;
+    let value = &tag[src_at..];
+    let src = match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next()?,
+        _ => value
+            .split(char::is_whitespace)
+            .next()?
+            .trim_end_matches('/'),
+    };
+    (!src.is_empty()).then(|| src.to_string())
+}
+
+/// `url` made absolute against `base_url` when it is a site path.
+pub fn absolute_url(url: &str, base_url: &str) -> String {
+    if url.starts_with('/') && !url.starts_with("//") {
+        format!("{base_url}{url}")
+    } else {
+        url.to_string()
+    }
+}
+
+/// What `{{head_meta}}` and the SEO placeholders are filled from: an
+/// item's own settings where it has them, otherwise values derived from
+/// its title and content.
+#[derive(Debug, Clone)]
+pub struct SeoMeta {
+    pub title: String,
+    /// Plain text.
+    pub description: String,
+    pub canonical_url: String,
+    pub image_url: Option<String>,
+    pub noindex: bool,
+    pub kind: ContentKind,
+    pub published_at: Option<DateTime<Utc>>,
+    pub modified_at: Option<DateTime<Utc>>,
+}
+
+impl SeoMeta {
+    /// Metadata from a title and body alone, for the page at `url`.
+    pub fn derived(
+        kind: ContentKind,
+        title: &str,
+        content_html: &str,
+        url: &str,
+        base_url: &str,
+    ) -> Self {
+        Self {
+            title: title.to_string(),
+            description: truncate_text(
+                &html_to_text(content_html),
+                META_DESCRIPTION_CHARS,
+            ),
+            canonical_url: url.to_string(),
+            image_url: first_image_src(content_html)
+                .map(|src| absolute_url(&src, base_url)),
+            noindex: false,
+            kind,
+            published_at: None,
+            modified_at: None,
+        }
+    }
+
+    /// Metadata for `item`, served at the absolute `url`.
+    pub fn for_item(
+        item: &ContentItem,
+        url: &str,
+        base_url: &str,
+    ) -> Self {
+        let mut meta = Self::derived(
+            item.kind,
+            &item.title,
+            item.html(),
+            url,
+            base_url,
+        );
+        if !item.meta_title.is_empty() {
+            meta.title = item.meta_title.clone();
+        }
+        if !item.meta_description.is_empty() {
+            meta.description = item.meta_description.clone();
+        }
+        if !item.canonical_url.is_empty() {
+            meta.canonical_url =
+                absolute_url(&item.canonical_url, base_url);
+        }
+        if !item.og_image_url.is_empty() {
+            meta.image_url =
+                Some(absolute_url(&item.og_image_url, base_url));
+        }
+        meta.noindex =
+            item.noindex || item.status == ContentStatus::Private;
+        meta.published_at = item.published_at;
+        meta.modified_at = Some(item.edited_at);
+        meta
+    }
+}
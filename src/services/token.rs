@@ -0,0 +1,25 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+/// Opaque bearer tokens (sessions, one-time links).
+///
+/// Tokens are handed to the client in plain form and only their SHA-256
+/// digest is persisted. A fast hash is fine here: unlike passwords the
+/// input already carries 256 bits of entropy.
+pub struct TokenManager;
+
+impl TokenManager {
+    const TOKEN_BYTES: usize = 32;
+
+    /// Generate a new random token, hex encoded.
+    pub fn generate() -> String {
+        let mut bytes = [0u8; Self::TOKEN_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        hex::encode(bytes)
+    }
+
+    /// Digest of a token as stored in the database.
+    pub fn hash(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+}
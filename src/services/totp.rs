@@ -0,0 +1,119 @@
+use argon2::password_hash::rand_core::{OsRng, RngCore};
+use qrcode::QrCode;
+use qrcode::render::svg;
+use totp_rs::{Algorithm, Secret, TOTP};
+
+use super::TokenManager;
+
+/// Time-based one-time passwords (RFC 6238) and recovery codes.
+///
+/// Uses the parameters every authenticator app understands: SHA-1,
+/// 6 digits, 30 second steps.
+pub struct TotpManager;
+
+impl TotpManager {
+    const ISSUER: &'static str = "RustPress";
+    const SECRET_BYTES: usize = 20;
+    const STEP_SECS: u64 = 30;
+    const RECOVERY_CODE_COUNT: usize = 10;
+
+    /// Generate a new shared secret, base32 encoded.
+    pub fn generate_secret() -> String {
+        let mut bytes = [0u8; Self::SECRET_BYTES];
+        OsRng.fill_bytes(&mut bytes);
+        Secret::Raw(bytes.to_vec()).to_encoded().to_string()
+    }
+
+    fn totp(secret: &str, account: &str) -> Result<TOTP, String> {
+        let bytes = Secret::Encoded(secret.to_string())
+            .to_bytes()
+            .map_err(|e| e.to_string())?;
+        // ':' separates issuer and account in the otpauth label.
+        TOTP::new(
+            Algorithm::SHA1,
+            6,
+            0,
+            Self::STEP_SECS,
+            bytes,
+            Some(Self::ISSUER.to_string()),
+            account.replace(':', ""),
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    /// `otpauth://` URI for enrolling `secret` in an authenticator app.
+    pub fn provisioning_uri(
+        secret: &str,
+        account: &str,
+    ) -> Result<String, String> {
+        Ok(Self::totp(secret, account)?.get_url())
+    }
+
+    /// Render `uri` as an inline SVG QR code.
+    pub fn qr_svg(uri: &str) -> Result<String, String> {
+        let code =
+            QrCode::new(uri.as_bytes()).map_err(|e| e.to_string())?;
+        Ok(code
+            .render::<svg::Color>()
+            .min_dimensions(200, 200)
+            .build())
+    }
+
+    /// Check `code` against the steps around `unix_time`, allowing one
+    /// step of clock drift either way. Returns the matching time step.
+    pub fn verify_at(
+        secret: &str,
+        code: &str,
+        unix_time: u64,
+    ) -> Option<i64> {
+        let code = code.trim().replace(' ', "");
+        if code.len() != 6
+            || !code.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let totp = Self::totp(secret, "").ok()?;
+        let current = unix_time / Self::STEP_SECS;
+
+        [current.saturating_sub(1), current, current + 1]
+            .into_iter()
+            .find(|step| totp.check(&code, step * Self::STEP_SECS))
+            .map(|step| step as i64)
+    }
+
+    pub fn verify(secret: &str, code: &str) -> Option<i64> {
+        let now = chrono::Utc::now().timestamp().max(0) as u64;
+        Self::verify_at(secret, code, now)
+    }
+
+    /// Code for `secret` at `unix_time`.
+    pub fn generate_code_at(
+        secret: &str,
+        unix_time: u64,
+    ) -> Result<String, String> {
+        Ok(Self::totp(secret, "")?.generate(unix_time))
+    }
+
+    /// A fresh set of single-use recovery codes, formatted `xxxxx-xxxxx`.
+    pub fn generate_recovery_codes() -> Vec<String> {
+        (0..Self::RECOVERY_CODE_COUNT)
+            .map(|_| {
+                let mut bytes = [0u8; 5];
+                OsRng.fill_bytes(&mut bytes);
+                let hex = hex::encode(bytes);
+                format!("{}-{}", &hex[..5], &hex[5..])
+            })
+            .collect()
+    }
+
+    /// Digest of a recovery code as stored in the database. Case, spaces
+    /// and dashes are ignored so the code can be typed loosely.
+    pub fn hash_recovery_code(code: &str) -> String {
+        let normalized: String = code
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        TokenManager::hash(&normalized)
+    }
+}
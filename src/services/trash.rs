@@ -0,0 +1,54 @@
+use std::time::Duration as StdDuration;
+
+use chrono::{Duration, Utc};
+use sqlx::PgPool;
+
+use crate::db;
+use crate::models::DEFAULT_TRASH_RETENTION_DAYS;
+
+/// Background task that empties the trash. Items are deleted for good
+/// once they have been trashed for longer than the site's
+/// `trash_retention_days`.
+#[derive(Clone)]
+pub struct TrashPurger {
+    pool: PgPool,
+}
+
+impl TrashPurger {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Days trashed items are kept, from the default site's settings.
+    pub async fn retention_days(&self) -> Result<i32, sqlx::Error> {
+        Ok(db::get_default_site(&self.pool)
+            .await?
+            .map_or(DEFAULT_TRASH_RETENTION_DAYS, |s| {
+                s.trash_retention_days
+            }))
+    }
+
+    /// Delete every item past the retention period. Returns how many
+    /// were removed.
+    pub async fn purge_expired(&self) -> Result<u64, sqlx::Error> {
+        let days = self.retention_days().await?;
+        let cutoff = Utc::now() - Duration::days(days.into());
+        let purged =
+            db::purge_trashed_content(&self.pool, cutoff).await?;
+        if purged > 0 {
+            log::info!("Purged {} item(s) from the trash", purged);
+        }
+        Ok(purged)
+    }
+
+    /// Purge expired items every `poll_interval` until the process
+    /// exits.
+    pub async fn run(self, poll_interval: StdDuration) {
+        loop {
+            if let Err(e) = self.purge_expired().await {
+                log::error!("Emptying the trash failed: {}", e);
+            }
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use actix_web::http::header::{
+    AUTHORIZATION, HeaderMap, HeaderValue, WWW_AUTHENTICATE,
+};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{HttpResponse, ResponseError};
+use uuid::Uuid;
+
+use rustpress::models::ApiScope;
+use rustpress::services::TokenManager;
+
+use crate::web::handlers::api::{ApiError, is_api_path};
+
+/// Prefix of every personal API token, so leaked tokens are easy to
+/// recognise in logs and secret scanners.
+pub const API_TOKEN_PREFIX: &str = "rp_pat_";
+
+/// Routes a token can never reach: account, security and sign-out pages
+/// need an interactive session.
+const SESSION_ONLY_PREFIXES: &[&str] =
+    &["/admin/me", "/admin/logout"];
+
+/// Routes that manage users and site-wide settings.
+const USERS_ADMIN_PREFIXES: &[&str] = &[
+    "/admin/users",
+    "/admin/roles",
+    "/admin/configuration",
+    "/admin/redirects",
+];
+
+/// Routes that change templates and themes.
+const TEMPLATE_PREFIXES: &[&str] =
+    &["/admin/templates", "/admin/themes", "/api/v1/templates"];
+
+/// API routes whose writes change site-wide settings.
+const SITE_PREFIXES: &[&str] = &["/api/v1/sites"];
+
+/// Stored in request extensions by the admin middleware once a bearer
+/// token has been validated against the database.
+#[derive(Clone, Debug)]
+pub struct ApiTokenAuth {
+    pub user_id: Uuid,
+}
+
+/// Mint a new token. Returns the plaintext (shown once) and its hash.
+pub fn generate_api_token() -> (String, String) {
+    let token =
+        format!("{API_TOKEN_PREFIX}{}", TokenManager::generate());
+    let hash = TokenManager::hash(&token);
+    (token, hash)
+}
+
+/// The token from an `Authorization: Bearer` header, if any.
+pub fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+    let token = token.trim();
+    (scheme.eq_ignore_ascii_case("bearer") && !token.is_empty())
+        .then(|| token.to_string())
+}
+
+/// The scope a token needs for an admin or API route, or `None` if the
+/// route only accepts a session.
+pub fn required_scope(
+    method: &Method,
+    path: &str,
+) -> Option<ApiScope> {
+    let matches = |prefixes: &[&str]| {
+        prefixes.iter().any(|p| path.starts_with(p))
+    };
+    let read_only = matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS
+    );
+
+    if matches(SESSION_ONLY_PREFIXES) {
+        None
+    } else if matches(USERS_ADMIN_PREFIXES) {
+        Some(ApiScope::UsersAdmin)
+    } else if read_only {
+        Some(ApiScope::ContentRead)
+    } else if matches(TEMPLATE_PREFIXES) {
+        Some(ApiScope::TemplatesWrite)
+    } else if matches(SITE_PREFIXES) {
+        Some(ApiScope::UsersAdmin)
+    } else {
+        Some(ApiScope::ContentWrite)
+    }
+}
+
+/// Rejection for a token-authenticated request: a JSON error under the
+/// API, plain text elsewhere. `challenge` holds the `WWW-Authenticate`
+/// parameters, if any.
+pub fn bearer_error(
+    path: &str,
+    status: StatusCode,
+    challenge: &str,
+    message: &str,
+) -> HttpResponse {
+    let mut response = if is_api_path(path) {
+        ApiError::new(status, message).error_response()
+    } else {
+        HttpResponse::build(status)
+            .content_type("text/plain; charset=utf-8")
+            .body(message.to_string())
+    };
+    let value = format!("Bearer {challenge}");
+    if let Ok(value) = HeaderValue::from_str(value.trim_end()) {
+        response.headers_mut().insert(WWW_AUTHENTICATE, value);
+    }
+    response
+}
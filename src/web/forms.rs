@@ -1,5 +1,14 @@
-use rustpress::models::RoleName;
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+use rustpress::models::{
+    ApiScope, ContentFormat, ContentSchedule, ContentSeo,
+    ContentStatus, ContentUpdate, MAX_MEDIA_ALT_LENGTH, PageAnchor,
+    PageMove, PageRequest, REDIRECT_STATUS_CODES, RedirectCreate,
+    RedirectMatch, RoleName, SiteTemplateUpdate, Taxonomy,
+    page_offset, slugify,
+};
 use serde::Deserialize;
+use utoipa::{IntoParams, ToSchema};
+use uuid::Uuid;
 
 use crate::web::security::validate_slug;
 
@@ -8,6 +17,7 @@ pub const MAX_PASSWORD_LENGTH: usize = 128; // Prevent DoS via huge passwords
 pub const MAX_EMAIL_LENGTH: usize = 255;
 pub const MAX_CONTENT_LENGTH: usize = 10_000_000; // 10MB
 pub const MAX_TEMPLATE_LENGTH: usize = 1_000_000; // 1MB
+pub const MAX_MENU_ORDER: i32 = 1_000_000;
 
 #[derive(Deserialize)]
 pub struct LoginForm {
@@ -36,6 +46,7 @@ impl LoginForm {
 #[derive(Deserialize)]
 pub struct AuthQuery {
     pub error: Option<String>,
+    pub notice: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -87,6 +98,29 @@ impl RegisterForm {
     }
 }
 
+#[derive(Deserialize)]
+pub struct ForgotPasswordForm {
+    pub email: String,
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordForm {
+    pub password: String,
+    pub password_confirm: String,
+}
+
+impl ResetPasswordForm {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.password.len() > MAX_PASSWORD_LENGTH {
+            return Err("Password must not exceed 128 characters");
+        }
+        if self.password != self.password_confirm {
+            return Err("Passwords do not match");
+        }
+        Ok(())
+    }
+}
+
 #[derive(Deserialize)]
 pub struct AccountEmailForm {
     pub email: String,
@@ -143,18 +177,116 @@ pub struct SearchQuery {
     pub q: Option<String>,
 }
 
+/// Which page of a listing to show. Pages count from 1; `after` and
+/// `before` hold the cursor of the item the page continues from, and
+/// without one the page number is used as an offset.
+#[derive(Deserialize)]
+pub struct PageQuery {
+    pub page: Option<i64>,
+    pub after: Option<String>,
+    pub before: Option<String>,
+}
+
+impl PageQuery {
+    pub fn number(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn request(&self, limit: i64) -> Result<PageRequest, String> {
+        let anchor = match (&self.after, &self.before) {
+            (Some(after), _) => PageAnchor::After(after.parse()?),
+            (None, Some(before)) => {
+                PageAnchor::Before(before.parse()?)
+            }
+            (None, None) => {
+                PageAnchor::Offset(page_offset(self.number(), limit))
+            }
+        };
+        Ok(PageRequest { anchor, limit })
+    }
+}
+
+/// `/search?q=…&page=…`; pages count from 1.
+#[derive(Deserialize)]
+pub struct PublicSearchQuery {
+    pub q: Option<String>,
+    pub page: Option<i64>,
+}
+
+/// Format the new-content editor starts in.
+#[derive(Deserialize)]
+pub struct AdminNewQuery {
+    #[serde(default)]
+    pub format: ContentFormat,
+}
+
+/// Which listing an upload answers with: the media library (default)
+/// or the editor's image picker.
+#[derive(Deserialize)]
+pub struct MediaUploadQuery {
+    pub view: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AdminMediaAltForm {
+    #[serde(default)]
+    pub alt_text: String,
+}
+
+impl AdminMediaAltForm {
+    pub fn alt_text(&self) -> Result<String, String> {
+        let alt = self.alt_text.trim();
+        if alt.chars().count() > MAX_MEDIA_ALT_LENGTH {
+            return Err(format!(
+                "Alt text must be at most {MAX_MEDIA_ALT_LENGTH} characters"
+            ));
+        }
+        Ok(alt.to_string())
+    }
+}
+
+pub const API_DEFAULT_PER_PAGE: i64 = 20;
+pub const API_MAX_PER_PAGE: i64 = 100;
+
+/// Filters and paging for `GET /api/v1/{posts|pages}`.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ApiContentListQuery {
+    pub status: Option<ContentStatus>,
+    pub owner: Option<Uuid>,
+    pub slug: Option<String>,
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+impl ApiContentListQuery {
+    /// One-based page number.
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn per_page(&self) -> i64 {
+        self.per_page
+            .unwrap_or(API_DEFAULT_PER_PAGE)
+            .clamp(1, API_MAX_PER_PAGE)
+    }
+}
+
 #[derive(Deserialize)]
 pub struct ThemesQuery {
     pub q: Option<String>,
     pub category: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
+#[schema(as = NewContent)]
 pub struct AdminCreateForm {
     pub title: String,
     pub slug: String,
     pub content: String,
     pub template: Option<String>,
+    #[serde(default)]
+    pub format: ContentFormat,
 }
 
 impl AdminCreateForm {
@@ -192,6 +324,302 @@ pub struct AdminUpdateForm {
     pub status: Option<String>,
 }
 
+/// `ContentUpdate` checks matching [`AdminCreateForm::validate`] for
+/// the fields that are present.
+pub fn validate_content_update(
+    update: &ContentUpdate,
+) -> Result<(), &'static str> {
+    if let Some(title) = &update.title {
+        if title.trim().is_empty() {
+            return Err("Title is required");
+        }
+        if title.len() > 500 {
+            return Err("Title must not exceed 500 characters");
+        }
+    }
+    if let Some(slug) = &update.slug
+        && !validate_slug(slug.trim(), Some(200))
+    {
+        return Err(
+            "Slug must be lowercase alphanumeric with hyphens/underscores only and not exceed 200 characters",
+        );
+    }
+    if update
+        .content
+        .as_ref()
+        .is_some_and(|c| c.len() > MAX_CONTENT_LENGTH)
+    {
+        return Err("Content must not exceed 10MB");
+    }
+    if update.status == Some(ContentStatus::Scheduled) {
+        return Err("Set a publish time to schedule content");
+    }
+    Ok(())
+}
+
+/// Schedule picked on the edit page. `datetime-local` inputs carry no
+/// zone, so the page also posts the browser's `getTimezoneOffset()`.
+#[derive(Deserialize)]
+pub struct AdminScheduleForm {
+    #[serde(default)]
+    pub publish_at: String,
+    #[serde(default)]
+    pub unpublish_at: String,
+    /// Minutes to add to local time to get UTC; empty means UTC.
+    #[serde(default)]
+    pub tz_offset: String,
+}
+
+impl AdminScheduleForm {
+    pub fn schedule(&self) -> Result<ContentSchedule, &'static str> {
+        let offset = match self.tz_offset.trim() {
+            "" => 0,
+            minutes => minutes
+                .parse::<i64>()
+                .ok()
+                .filter(|m| m.abs() <= 14 * 60)
+                .ok_or("Invalid time zone")?,
+        };
+        let parse = |value: &str| {
+            let value = value.trim();
+            if value.is_empty() {
+                return Ok(None);
+            }
+            NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M")
+                .or_else(|_| {
+                    NaiveDateTime::parse_from_str(
+                        value,
+                        "%Y-%m-%dT%H:%M:%S",
+                    )
+                })
+                .map(|local| {
+                    Some(local.and_utc() + Duration::minutes(offset))
+                })
+                .map_err(|_| "Invalid date and time")
+        };
+        Ok(ContentSchedule {
+            publish_at: parse(&self.publish_at)?,
+            unpublish_at: parse(&self.unpublish_at)?,
+        })
+    }
+}
+
+/// SEO settings from the edit screen. Empty fields fall back to values
+/// derived from the content.
+#[derive(Deserialize)]
+pub struct AdminSeoForm {
+    #[serde(default)]
+    pub meta_title: String,
+    #[serde(default)]
+    pub meta_description: String,
+    #[serde(default)]
+    pub canonical_url: String,
+    #[serde(default)]
+    pub og_image_url: String,
+    /// Checkbox: present when ticked, absent otherwise.
+    pub noindex: Option<String>,
+}
+
+impl AdminSeoForm {
+    pub fn seo(&self) -> Result<ContentSeo, String> {
+        let seo = ContentSeo {
+            meta_title: self.meta_title.clone(),
+            meta_description: self.meta_description.clone(),
+            canonical_url: self.canonical_url.clone(),
+            og_image_url: self.og_image_url.clone(),
+            noindex: self.noindex.is_some(),
+        }
+        .trimmed();
+        seo.validate()?;
+        Ok(seo)
+    }
+}
+
+/// Parent and position picked on a page's edit screen. An empty
+/// `parent_id` makes the page top-level.
+#[derive(Deserialize)]
+pub struct AdminPageParentForm {
+    #[serde(default)]
+    pub parent_id: String,
+    #[serde(default)]
+    pub menu_order: String,
+}
+
+impl AdminPageParentForm {
+    pub fn page_move(&self) -> Result<PageMove, &'static str> {
+        let parent_id = match self.parent_id.trim() {
+            "" => None,
+            id => {
+                Some(id.parse().map_err(|_| "Invalid parent page")?)
+            }
+        };
+        let menu_order = match self.menu_order.trim() {
+            "" => None,
+            order => Some(
+                order
+                    .parse::<i32>()
+                    .ok()
+                    .filter(|o| o.abs() <= MAX_MENU_ORDER)
+                    .ok_or("Order must be a whole number")?,
+            ),
+        };
+        Ok(PageMove {
+            parent_id,
+            menu_order,
+        })
+    }
+}
+
+/// Paths the public redirects never apply to.
+const RESERVED_REDIRECT_PREFIXES: [&str; 3] =
+    ["/admin", "/api", "/static"];
+pub const MAX_REDIRECT_URL_LENGTH: usize = 2000;
+
+/// A manual redirect from the admin Redirects screen.
+#[derive(Deserialize)]
+pub struct AdminRedirectForm {
+    pub source_path: String,
+    #[serde(default)]
+    pub match_type: String,
+    #[serde(default)]
+    pub target_url: String,
+    #[serde(default)]
+    pub status_code: String,
+}
+
+impl AdminRedirectForm {
+    pub fn redirect(&self) -> Result<RedirectCreate, &'static str> {
+        let source = self.source_path.trim();
+        if !source.starts_with('/') || source == "/" {
+            return Err(
+                "Source must be a path starting with / (not the home page)",
+            );
+        }
+        if source.len() > MAX_REDIRECT_URL_LENGTH {
+            return Err("Source must not exceed 2000 characters");
+        }
+        if source.contains(|c: char| {
+            c.is_whitespace() || c == '?' || c == '#'
+        }) {
+            return Err(
+                "Source must be a plain path without spaces, query or fragment",
+            );
+        }
+        if RESERVED_REDIRECT_PREFIXES.iter().any(|prefix| {
+            source == *prefix
+                || source.starts_with(&format!("{prefix}/"))
+        }) {
+            return Err(
+                "Admin, API and static paths cannot be redirected",
+            );
+        }
+
+        let match_type = match self.match_type.trim() {
+            "" => RedirectMatch::Exact,
+            m => m.parse().map_err(|_| "Invalid match type")?,
+        };
+        let status_code = match self.status_code.trim() {
+            "" => 301,
+            code => code
+                .parse::<i32>()
+                .ok()
+                .filter(|c| REDIRECT_STATUS_CODES.contains(c))
+                .ok_or("Status must be 301, 302 or 410")?,
+        };
+
+        let target = self.target_url.trim();
+        let target_url = if status_code == 410 {
+            None
+        } else {
+            if target.is_empty() {
+                return Err(
+                    "Target is required unless the status is 410",
+                );
+            }
+            if target.len() > MAX_REDIRECT_URL_LENGTH {
+                return Err("Target must not exceed 2000 characters");
+            }
+            let is_path =
+                target.starts_with('/') && !target.starts_with("//");
+            let is_url = target.starts_with("https://")
+                || target.starts_with("http://");
+            if !(is_path || is_url)
+                || target.contains(char::is_whitespace)
+            {
+                return Err(
+                    "Target must be a path starting with / or an http(s) URL",
+                );
+            }
+            if match_type == RedirectMatch::Exact && target == source
+            {
+                return Err("A redirect cannot point to itself");
+            }
+            Some(target.to_string())
+        };
+
+        Ok(RedirectCreate {
+            source_path: source.to_string(),
+            match_type,
+            target_url,
+            status_code,
+            created_by_user_id: None,
+        })
+    }
+}
+
+/// Check a schedule for an item currently in `status`. Only unpublished
+/// items can be scheduled; a published one may only get an expiry.
+pub fn validate_schedule(
+    status: ContentStatus,
+    schedule: &ContentSchedule,
+    now: DateTime<Utc>,
+) -> Result<(), &'static str> {
+    match schedule.publish_at {
+        Some(_) if status == ContentStatus::Published => {
+            return Err("This item is already published");
+        }
+        Some(publish_at) if publish_at <= now => {
+            return Err("Publish time must be in the future");
+        }
+        None if status != ContentStatus::Published => {
+            return Err("Choose when to publish");
+        }
+        _ => {}
+    }
+    match (schedule.publish_at, schedule.unpublish_at) {
+        (Some(publish_at), Some(unpublish_at))
+            if unpublish_at <= publish_at =>
+        {
+            Err("Unpublish time must be after the publish time")
+        }
+        (None, Some(unpublish_at)) if unpublish_at <= now => {
+            Err("Unpublish time must be in the future")
+        }
+        (None, None) => Err("Choose when to unpublish"),
+        _ => Ok(()),
+    }
+}
+
+pub const MAX_REVIEW_NOTE_LENGTH: usize = 2_000;
+
+/// Reviewer decision on a pending item. The note is optional when
+/// approving and required when requesting changes.
+#[derive(Deserialize)]
+pub struct AdminReviewForm {
+    #[serde(default)]
+    pub note: String,
+}
+
+impl AdminReviewForm {
+    pub fn note(&self) -> Result<Option<&str>, &'static str> {
+        let note = self.note.trim();
+        if note.chars().count() > MAX_REVIEW_NOTE_LENGTH {
+            return Err("Review note too long");
+        }
+        Ok(Some(note).filter(|n| !n.is_empty()))
+    }
+}
+
 #[derive(Deserialize)]
 pub struct AdminLiveForm {
     pub title: Option<String>,
@@ -207,9 +635,20 @@ pub struct AdminNewPreviewForm {
     pub slug: Option<String>,
     pub content: Option<String>,
     pub template: Option<String>,
+    #[serde(default)]
+    pub format: ContentFormat,
 }
 
+/// Switch an item to `format`, converting `content` (the editor's
+/// unsaved text) or else the stored content.
 #[derive(Deserialize)]
+pub struct AdminFormatForm {
+    pub format: ContentFormat,
+    pub content: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema)]
+#[schema(as = NewTemplate)]
 pub struct AdminTemplateCreateForm {
     pub name: String,
     pub description: Option<String>,
@@ -233,6 +672,31 @@ impl AdminTemplateCreateForm {
     }
 }
 
+/// `SiteTemplateUpdate` checks matching
+/// [`AdminTemplateCreateForm::validate`] for the fields that are present.
+pub fn validate_template_update(
+    update: &SiteTemplateUpdate,
+) -> Result<(), &'static str> {
+    if let Some(name) = &update.name {
+        if name.trim().is_empty() {
+            return Err("Template name is required");
+        }
+        if name.len() > 200 {
+            return Err(
+                "Template name must not exceed 200 characters",
+            );
+        }
+    }
+    if update
+        .html
+        .as_ref()
+        .is_some_and(|h| h.len() > MAX_TEMPLATE_LENGTH)
+    {
+        return Err("Template HTML must not exceed 1MB");
+    }
+    Ok(())
+}
+
 #[derive(Deserialize)]
 pub struct AdminTemplateUpdateForm {
     pub name: Option<String>,
@@ -302,9 +766,194 @@ pub struct AdminUpdateUserForm {
     pub email: String,
     pub role: RoleName,
     pub new_password: Option<String>,
+    /// "Can publish without review" checkbox; admins always can.
+    pub can_publish: Option<String>,
+    /// "Can post unfiltered HTML" checkbox; admins always can.
+    pub unfiltered_html: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct DeleteAccountForm {
     pub password: String,
 }
+
+/// Authenticator code or recovery code.
+#[derive(Deserialize)]
+pub struct TwoFactorCodeForm {
+    pub code: String,
+}
+
+/// Current password, confirming a change to two-factor settings.
+#[derive(Deserialize)]
+pub struct TwoFactorPasswordForm {
+    pub password: String,
+}
+
+pub const MAX_API_TOKEN_NAME_LENGTH: usize = 100;
+
+/// New personal API token. Each scope is a checkbox named after it.
+#[derive(Deserialize)]
+pub struct ApiTokenCreateForm {
+    pub name: String,
+    #[serde(rename = "content:read")]
+    pub content_read: Option<String>,
+    #[serde(rename = "content:write")]
+    pub content_write: Option<String>,
+    #[serde(rename = "templates:write")]
+    pub templates_write: Option<String>,
+    #[serde(rename = "users:admin")]
+    pub users_admin: Option<String>,
+    /// Lifetime in days; empty for a token that never expires.
+    #[serde(default)]
+    pub expires_in_days: String,
+}
+
+impl ApiTokenCreateForm {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Token name is required");
+        }
+        if name.chars().count() > MAX_API_TOKEN_NAME_LENGTH {
+            return Err("Token name too long");
+        }
+        if self.scopes().is_empty() {
+            return Err("Select at least one scope");
+        }
+        self.expires_in_days().map(|_| ())
+    }
+
+    pub fn scopes(&self) -> Vec<ApiScope> {
+        [
+            (&self.content_read, ApiScope::ContentRead),
+            (&self.content_write, ApiScope::ContentWrite),
+            (&self.templates_write, ApiScope::TemplatesWrite),
+            (&self.users_admin, ApiScope::UsersAdmin),
+        ]
+        .into_iter()
+        .filter(|(field, _)| field.is_some())
+        .map(|(_, scope)| scope)
+        .collect()
+    }
+
+    pub fn expires_in_days(
+        &self,
+    ) -> Result<Option<i64>, &'static str> {
+        match self.expires_in_days.trim() {
+            "" => Ok(None),
+            days => match days.parse::<i64>() {
+                Ok(days @ 1..=365) => Ok(Some(days)),
+                _ => Err("Invalid token expiry"),
+            },
+        }
+    }
+}
+
+pub const MAX_TERM_NAME_LENGTH: usize = 200;
+pub const MAX_TERM_DESCRIPTION_LENGTH: usize = 2_000;
+pub const MAX_TAGS_PER_ITEM: usize = 50;
+
+/// Create or edit a category or tag. `taxonomy` is only read on
+/// create; an empty slug is derived from the name.
+#[derive(Deserialize)]
+pub struct AdminTermForm {
+    pub taxonomy: Option<Taxonomy>,
+    pub name: String,
+    #[serde(default)]
+    pub slug: String,
+    #[serde(default)]
+    pub description: String,
+    /// Empty for a top-level term.
+    #[serde(default)]
+    pub parent_id: String,
+}
+
+impl AdminTermForm {
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            return Err("Name is required");
+        }
+        if name.len() > MAX_TERM_NAME_LENGTH {
+            return Err("Name must not exceed 200 characters");
+        }
+        if !validate_slug(&self.slug(), Some(200)) {
+            return Err(
+                "Slug must be lowercase alphanumeric with hyphens/underscores only and not exceed 200 characters",
+            );
+        }
+        if self.description.len() > MAX_TERM_DESCRIPTION_LENGTH {
+            return Err(
+                "Description must not exceed 2000 characters",
+            );
+        }
+        self.parent_id().map(|_| ())
+    }
+
+    pub fn slug(&self) -> String {
+        match self.slug.trim() {
+            "" => slugify(&self.name),
+            slug => slug.to_string(),
+        }
+    }
+
+    pub fn parent_id(&self) -> Result<Option<Uuid>, &'static str> {
+        match self.parent_id.trim() {
+            "" => Ok(None),
+            id => id
+                .parse()
+                .map(Some)
+                .map_err(|_| "Invalid parent category"),
+        }
+    }
+}
+
+/// Categories and tags chosen on the edit page. The form repeats the
+/// `category` field once per ticked box, so it is read from raw pairs.
+pub struct ContentTermsForm {
+    pub categories: Vec<Uuid>,
+    /// `(slug, name)` for each distinct tag typed in.
+    pub tags: Vec<(String, String)>,
+}
+
+impl ContentTermsForm {
+    pub fn from_pairs(
+        pairs: &[(String, String)],
+    ) -> Result<Self, &'static str> {
+        let mut categories = Vec::new();
+        let mut tags: Vec<(String, String)> = Vec::new();
+        for (key, value) in pairs {
+            match key.as_str() {
+                "category" => categories.push(
+                    value.parse().map_err(|_| "Invalid category")?,
+                ),
+                "tags" => {
+                    for name in value.split(',').map(str::trim) {
+                        if name.len() > MAX_TERM_NAME_LENGTH {
+                            return Err(
+                                "Tag names must not exceed 200 characters",
+                            );
+                        }
+                        if name.is_empty() {
+                            continue;
+                        }
+                        let slug = slugify(name);
+                        if slug.is_empty() {
+                            return Err(
+                                "Tag names need at least one ASCII letter or digit",
+                            );
+                        }
+                        if !tags.iter().any(|(s, _)| *s == slug) {
+                            tags.push((slug, name.to_string()));
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        if tags.len() > MAX_TAGS_PER_ITEM {
+            return Err("Too many tags");
+        }
+        Ok(Self { categories, tags })
+    }
+}
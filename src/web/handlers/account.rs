@@ -1,40 +1,85 @@
-use actix_web::cookie::{Cookie, SameSite};
 use actix_web::{
     HttpRequest, HttpResponse, Responder, get, post, web,
 };
-use chrono::Utc;
+use std::time::Duration;
+use uuid::Uuid;
 
 use rustpress::db;
-use rustpress::models::User;
+use rustpress::models::{ApiScope, User};
 use rustpress::services::PasswordManager;
 
 use crate::web::forms::{
     AccountEmailForm, ChangePasswordForm, DeleteAccountForm,
 };
+use crate::web::handlers::email_verification::send_verification_email;
 use crate::web::helpers::{
-    get_is_admin, is_htmx, is_unique_violation, load_user, render,
-    require_user,
+    csrf_token, current_session, get_is_admin, is_htmx, load_user,
+    render, require_user,
 };
 use crate::web::security::{
     PasswordValidator, generic_error_message, validate_email,
 };
+use crate::web::session::session_removal_cookie;
 use crate::web::state::AppState;
 use crate::web::templates::{MeAccountTemplate, MeSecurityTemplate};
 
-fn render_account(
+async fn render_account(
+    pool: &db::PgPool,
+    req: &HttpRequest,
     user: User,
-    is_admin: bool,
     error: Option<String>,
     success: Option<String>,
 ) -> HttpResponse {
+    render_account_page(pool, req, user, error, success, None).await
+}
+
+/// Render the account page, optionally revealing a freshly created API
+/// token.
+pub async fn render_account_page(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    user: User,
+    error: Option<String>,
+    success: Option<String>,
+    new_api_token: Option<String>,
+) -> HttpResponse {
+    // Only an email change leaves a pending address different from the
+    // current one; a pending check of the current address is shown on
+    // the security page instead.
+    let pending_email =
+        db::get_pending_email_verification(pool, user.id)
+            .await
+            .ok()
+            .flatten()
+            .map(|t| t.email)
+            .filter(|email| *email != user.email);
+
+    let api_tokens = db::list_api_tokens_for_user(pool, user.id)
+        .await
+        .unwrap_or_default();
+    let is_admin = get_is_admin(req);
+
     render(MeAccountTemplate {
         user,
+        pending_email,
+        api_tokens,
+        api_scopes: grantable_api_scopes(is_admin),
+        new_api_token,
         error,
         success,
         is_admin,
+        csrf_token: csrf_token(req),
     })
 }
 
+/// Scopes a user may put on a token; `users:admin` is for admins only.
+pub fn grantable_api_scopes(is_admin: bool) -> Vec<ApiScope> {
+    ApiScope::ALL
+        .into_iter()
+        .filter(|s| is_admin || *s != ApiScope::UsersAdmin)
+        .collect()
+}
+
 #[get("/admin/me/account")]
 pub async fn me_account(
     state: web::Data<AppState>,
@@ -48,7 +93,7 @@ pub async fn me_account(
         Ok(u) => u,
         Err(resp) => return resp,
     };
-    render_account(user, get_is_admin(&req), None, None)
+    render_account(&state.pool, &req, user, None, None).await
 }
 
 #[post("/admin/me/account/email")]
@@ -62,53 +107,59 @@ pub async fn me_account_update_email(
         Err(resp) => return resp,
     };
 
-    let is_admin = get_is_admin(&req);
+    let user = match load_user(&state.pool, uid).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+
     let new_email = form.email.trim().to_string();
 
-    if !validate_email(&new_email) {
-        let user = match load_user(&state.pool, uid).await {
-            Ok(u) => u,
-            Err(resp) => return resp,
-        };
+    let error = if !validate_email(&new_email) {
+        Some("Invalid email address".to_string())
+    } else if new_email == user.email {
+        Some("This is already your email address".to_string())
+    } else {
+        match db::get_user_by_email(&state.pool, &new_email).await {
+            Ok(Some(_)) => Some(
+                "A user with this email already exists".to_string(),
+            ),
+            Ok(None) => None,
+            Err(e) => {
+                log::error!("Email lookup failed: {}", e);
+                Some(generic_error_message("email update"))
+            }
+        }
+    };
+    if let Some(error) = error {
         return render_account(
+            &state.pool,
+            &req,
             user,
-            is_admin,
-            Some("Invalid email address".into()),
+            Some(error),
             None,
-        );
+        )
+        .await;
     }
 
-    match db::update_user_email(&state.pool, uid, &new_email).await {
+    // The current address stays in place until the new one is confirmed.
+    match send_verification_email(&state, uid, &new_email).await {
         Ok(()) => {
-            let user = match load_user(&state.pool, uid).await {
-                Ok(u) => u,
-                Err(resp) => return resp,
-            };
+            let msg = format!(
+                "We sent a confirmation link to {new_email}. Your current address stays active until you follow it."
+            );
+            render_account(&state.pool, &req, user, None, Some(msg))
+                .await
+        }
+        Err(e) => {
+            log::error!("Failed to send verification email: {}", e);
             render_account(
+                &state.pool,
+                &req,
                 user,
-                is_admin,
+                Some(generic_error_message("email update")),
                 None,
-                Some("Email updated".into()),
             )
-        }
-        Err(e) => {
-            let user = load_user(&state.pool, uid)
-                .await
-                .unwrap_or_else(|_| User {
-                    id: uid,
-                    email: new_email,
-                    password_hash: "".into(),
-                    email_verified_at: None,
-                    created_at: Utc::now(),
-                    edited_at: Utc::now(),
-                    deleted_at: None,
-                });
-            let msg = if is_unique_violation(&e) {
-                "A user with this email already exists".into()
-            } else {
-                generic_error_message("email update")
-            };
-            render_account(user, is_admin, Some(msg), None)
+            .await
         }
     }
 }
@@ -130,13 +181,14 @@ pub async fn me_account_change_password(
             Ok(u) => u,
             Err(resp) => return resp,
         };
-        let is_admin = get_is_admin(&req);
         return render_account(
+            &state.pool,
+            &req,
             user,
-            is_admin,
             Some(e.to_string()),
             None,
-        );
+        )
+        .await;
     }
 
     let user = match load_user(&state.pool, uid).await {
@@ -144,8 +196,6 @@ pub async fn me_account_change_password(
         Err(resp) => return resp,
     };
 
-    let is_admin = get_is_admin(&req);
-
     let ok = match PasswordManager::verify_password(
         &form.current_password,
         &user.password_hash,
@@ -154,21 +204,25 @@ pub async fn me_account_change_password(
         Err(e) => {
             log::error!("Password verification error: {}", e);
             return render_account(
+                &state.pool,
+                &req,
                 user,
-                is_admin,
                 Some(generic_error_message("password verification")),
                 None,
-            );
+            )
+            .await;
         }
     };
 
     if !ok {
         return render_account(
+            &state.pool,
+            &req,
             user,
-            is_admin,
             Some("Current password is incorrect".into()),
             None,
-        );
+        )
+        .await;
     }
 
     let new_hash =
@@ -177,35 +231,100 @@ pub async fn me_account_change_password(
             Err(e) => {
                 log::error!("Password hashing error: {}", e);
                 return render_account(
+                    &state.pool,
+                    &req,
                     user,
-                    is_admin,
                     Some(generic_error_message("password hashing")),
                     None,
-                );
+                )
+                .await;
             }
         };
 
     match db::update_user_password(&state.pool, uid, &new_hash).await
     {
         Ok(()) => {
+            revoke_other_sessions(&state.pool, &req, uid).await;
             let user =
                 load_user(&state.pool, uid).await.unwrap_or(user);
             render_account(
+                &state.pool,
+                &req,
                 user,
-                is_admin,
                 None,
-                Some("Password updated".into()),
+                Some(
+                    "Password updated. Other sessions were signed out."
+                        .into(),
+                ),
             )
+.await
         }
         Err(e) => {
             log::error!("Password update failed: {}", e);
             render_account(
+                &state.pool,
+                &req,
                 user,
-                is_admin,
                 Some(generic_error_message("password update")),
                 None,
             )
+            .await
+        }
+    }
+}
+
+pub async fn render_security(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    user: &User,
+    error: Option<String>,
+    success: Option<String>,
+) -> HttpResponse {
+    let sessions = db::list_sessions_for_user(pool, user.id)
+        .await
+        .unwrap_or_default();
+
+    let two_factor_enabled = db::get_user_totp(pool, user.id)
+        .await
+        .ok()
+        .flatten()
+        .is_some_and(|t| t.is_enabled());
+    let recovery_codes_left = if two_factor_enabled {
+        db::count_unused_recovery_codes(pool, user.id)
+            .await
+            .unwrap_or(0)
+    } else {
+        0
+    };
+
+    render(MeSecurityTemplate {
+        password_set: !user.password_hash.trim().is_empty(),
+        email_verified: user.email_verified_at.is_some(),
+        two_factor_enabled,
+        recovery_codes_left,
+        sessions,
+        current_session_id: current_session(req).map(|s| s.id),
+        error,
+        success,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
+    })
+}
+
+/// After a password change, keep only the session that made the change.
+async fn revoke_other_sessions(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    uid: Uuid,
+) {
+    let result = match current_session(req) {
+        Some(session) => {
+            db::delete_other_sessions(pool, uid, session.id).await
         }
+        None => db::delete_sessions_for_user(pool, uid).await,
+    };
+    if let Err(e) = result {
+        log::error!("Failed to revoke sessions for {}: {}", uid, e);
     }
 }
 
@@ -224,14 +343,7 @@ pub async fn me_security(
         Err(resp) => return resp,
     };
 
-    let is_admin = get_is_admin(&req);
-    render(MeSecurityTemplate {
-        password_set: !user.password_hash.trim().is_empty(),
-        email_verified: user.email_verified_at.is_some(),
-        error: None,
-        success: None,
-        is_admin,
-    })
+    render_security(&state.pool, &req, &user, None, None).await
 }
 
 #[post("/admin/me/security/password")]
@@ -250,18 +362,17 @@ pub async fn me_security_change_password(
         Err(resp) => return resp,
     };
 
-    let is_admin = get_is_admin(&req);
-
     // Validate new password strength
     if let Err(msg) = PasswordValidator::validate(&form.new_password)
     {
-        return render(MeSecurityTemplate {
-            password_set: !user.password_hash.trim().is_empty(),
-            email_verified: user.email_verified_at.is_some(),
-            error: Some(msg),
-            success: None,
-            is_admin,
-        });
+        return render_security(
+            &state.pool,
+            &req,
+            &user,
+            Some(msg),
+            None,
+        )
+        .await;
     }
 
     let ok = match PasswordManager::verify_password(
@@ -271,26 +382,26 @@ pub async fn me_security_change_password(
         Ok(v) => v,
         Err(e) => {
             log::error!("Password verification error: {}", e);
-            return render(MeSecurityTemplate {
-                password_set: !user.password_hash.trim().is_empty(),
-                email_verified: user.email_verified_at.is_some(),
-                error: Some(generic_error_message(
-                    "password verification",
-                )),
-                success: None,
-                is_admin,
-            });
+            return render_security(
+                &state.pool,
+                &req,
+                &user,
+                Some(generic_error_message("password verification")),
+                None,
+            )
+            .await;
         }
     };
 
     if !ok {
-        return render(MeSecurityTemplate {
-            password_set: !user.password_hash.trim().is_empty(),
-            email_verified: user.email_verified_at.is_some(),
-            error: Some("Current password is incorrect".to_string()),
-            success: None,
-            is_admin,
-        });
+        return render_security(
+            &state.pool,
+            &req,
+            &user,
+            Some("Current password is incorrect".to_string()),
+            None,
+        )
+        .await;
     }
 
     let new_hash =
@@ -298,49 +409,51 @@ pub async fn me_security_change_password(
             Ok(h) => h,
             Err(e) => {
                 log::error!("Password hashing error: {}", e);
-                return render(MeSecurityTemplate {
-                    password_set: !user
-                        .password_hash
-                        .trim()
-                        .is_empty(),
-                    email_verified: user.email_verified_at.is_some(),
-                    error: Some(generic_error_message(
-                        "password hashing",
-                    )),
-                    success: None,
-                    is_admin,
-                });
+                return render_security(
+                    &state.pool,
+                    &req,
+                    &user,
+                    Some(generic_error_message("password hashing")),
+                    None,
+                )
+                .await;
             }
         };
 
     match db::update_user_password(&state.pool, uid, &new_hash).await
     {
         Ok(()) => {
+            revoke_other_sessions(&state.pool, &req, uid).await;
             let user =
                 load_user(&state.pool, uid).await.unwrap_or(user);
-            render(MeSecurityTemplate {
-                password_set: !user.password_hash.trim().is_empty(),
-                email_verified: user.email_verified_at.is_some(),
-                error: None,
-                success: Some("Password updated".to_string()),
-                is_admin,
-            })
+            render_security(
+                &state.pool,
+                &req,
+                &user,
+                None,
+                Some(
+                    "Password updated. Other sessions were signed out."
+                        .to_string(),
+                ),
+            )
+            .await
         }
         Err(e) => {
             log::error!("Password update failed: {}", e);
-            render(MeSecurityTemplate {
-                password_set: !user.password_hash.trim().is_empty(),
-                email_verified: user.email_verified_at.is_some(),
-                error: Some(generic_error_message("password update")),
-                success: None,
-                is_admin,
-            })
+            render_security(
+                &state.pool,
+                &req,
+                &user,
+                Some(generic_error_message("password update")),
+                None,
+            )
+            .await
         }
     }
 }
 
 #[post("/admin/me/security/verify-email")]
-pub async fn me_security_mark_email_verified(
+pub async fn me_security_send_verification(
     state: web::Data<AppState>,
     req: HttpRequest,
 ) -> impl Responder {
@@ -349,22 +462,185 @@ pub async fn me_security_mark_email_verified(
         Err(resp) => return resp,
     };
 
-    let is_admin = get_is_admin(&req);
+    let user = match load_user(&state.pool, uid).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
 
-    let updated = db::mark_email_verified(&state.pool, uid).await;
+    if user.email_verified_at.is_some() {
+        return render_security(
+            &state.pool,
+            &req,
+            &user,
+            None,
+            Some("Your email is already verified".to_string()),
+        )
+        .await;
+    }
 
-    match updated {
-        Ok(user) => render(MeSecurityTemplate {
-            password_set: !user.password_hash.trim().is_empty(),
-            email_verified: user.email_verified_at.is_some(),
-            error: None,
-            success: Some(
-                "Email marked as verified (dev)".to_string(),
+    if !state.rate_limiter.check_rate_limit(
+        &format!("verify_email:{}", uid),
+        3,                         // 3 emails
+        Duration::from_secs(3600), // per hour
+    ) {
+        return render_security(
+            &state.pool,
+            &req,
+            &user,
+            Some(
+                "Too many verification emails. Please try again later."
+                    .to_string(),
             ),
-            is_admin,
-        }),
-        Err(e) => HttpResponse::InternalServerError()
-            .body(format!("Database error: {e}")),
+            None,
+        )
+        .await;
+    }
+
+    let (error, success) =
+        match send_verification_email(&state, uid, &user.email).await
+        {
+            Ok(()) => (
+                None,
+                Some(format!(
+                    "We sent a verification link to {}",
+                    user.email
+                )),
+            ),
+            Err(e) => {
+                log::error!(
+                    "Failed to send verification email: {}",
+                    e
+                );
+                (
+                    Some(generic_error_message(
+                        "verification request",
+                    )),
+                    None,
+                )
+            }
+        };
+
+    render_security(&state.pool, &req, &user, error, success).await
+}
+
+#[post("/admin/me/security/sessions/{id}/revoke")]
+pub async fn me_security_revoke_session(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+
+    let session_id = path.into_inner();
+    let revoked = match db::delete_session_for_user(
+        &state.pool,
+        session_id,
+        uid,
+    )
+    .await
+    {
+        Ok(v) => v,
+        Err(e) => {
+            log::error!("Failed to revoke session: {}", e);
+            false
+        }
+    };
+
+    // Signing out the current device is a plain logout.
+    if revoked
+        && current_session(&req).is_some_and(|s| s.id == session_id)
+    {
+        return logged_out_response(&req);
+    }
+
+    let user = match load_user(&state.pool, uid).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+
+    if revoked {
+        render_security(
+            &state.pool,
+            &req,
+            &user,
+            None,
+            Some("Session signed out".to_string()),
+        )
+        .await
+    } else {
+        render_security(
+            &state.pool,
+            &req,
+            &user,
+            Some("Session not found".to_string()),
+            None,
+        )
+        .await
+    }
+}
+
+#[post("/admin/me/security/sessions/revoke-others")]
+pub async fn me_security_revoke_other_sessions(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+
+    let user = match load_user(&state.pool, uid).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+
+    let Some(current) = current_session(&req) else {
+        return logged_out_response(&req);
+    };
+
+    match db::delete_other_sessions(&state.pool, uid, current.id)
+        .await
+    {
+        Ok(n) => {
+            render_security(
+                &state.pool,
+                &req,
+                &user,
+                None,
+                Some(format!("Signed out {n} other session(s)")),
+            )
+            .await
+        }
+        Err(e) => {
+            log::error!("Failed to revoke sessions: {}", e);
+            render_security(
+                &state.pool,
+                &req,
+                &user,
+                Some(generic_error_message("sign out")),
+                None,
+            )
+            .await
+        }
+    }
+}
+
+fn logged_out_response(req: &HttpRequest) -> HttpResponse {
+    let cookie = session_removal_cookie();
+
+    if is_htmx(req) {
+        HttpResponse::Ok()
+            .cookie(cookie)
+            .insert_header(("HX-Redirect", "/admin/login"))
+            .finish()
+    } else {
+        HttpResponse::SeeOther()
+            .cookie(cookie)
+            .insert_header(("Location", "/admin/login"))
+            .finish()
     }
 }
 
@@ -393,61 +669,58 @@ pub async fn me_account_delete(
         Ok(v) => v,
         Err(e) => {
             return render_account(
+                &state.pool,
+                &req,
                 user,
-                is_admin,
                 Some(format!("Password verification error: {e}")),
                 None,
-            );
+            )
+            .await;
         }
     };
 
     if !ok {
         return render_account(
+            &state.pool,
+            &req,
             user,
-            is_admin,
             Some("Password is incorrect".into()),
             None,
-        );
+        )
+        .await;
     }
 
     if is_admin
         && db::count_admins(&state.pool).await.unwrap_or(0) <= 1
     {
         return render_account(
+            &state.pool,
+            &req,
             user,
-            is_admin,
             Some("Cannot delete the last admin account".into()),
             None,
-        );
+        )
+        .await;
     }
 
     if let Err(e) = db::soft_delete_user(&state.pool, uid).await {
         return render_account(
+            &state.pool,
+            &req,
             user,
-            is_admin,
             Some(format!("Failed to delete account: {e}")),
             None,
-        );
+        )
+        .await;
     }
 
-    let mut cookie = Cookie::build("rp_uid", "")
-        .path("/")
-        .http_only(true)
-        .same_site(SameSite::Lax)
-        .finish();
-    cookie.make_removal();
-
-    if is_htmx(&req) {
-        HttpResponse::Ok()
-            .cookie(cookie)
-            .insert_header(("HX-Redirect", "/admin/login"))
-            .finish()
-    } else {
-        HttpResponse::SeeOther()
-            .cookie(cookie)
-            .insert_header(("Location", "/admin/login"))
-            .finish()
+    if let Err(e) =
+        db::delete_sessions_for_user(&state.pool, uid).await
+    {
+        log::error!("Failed to revoke sessions for {}: {}", uid, e);
     }
+
+    logged_out_response(&req)
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
@@ -457,5 +730,7 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .service(me_account_delete)
         .service(me_security)
         .service(me_security_change_password)
-        .service(me_security_mark_email_verified);
+        .service(me_security_send_verification)
+        .service(me_security_revoke_session)
+        .service(me_security_revoke_other_sessions);
 }
@@ -6,18 +6,27 @@ use uuid::Uuid;
 
 use rustpress::db;
 use rustpress::models::{
-    ContentCreate, ContentKind, ContentStatus, ContentUpdate,
+    ADMIN_ITEMS_PER_PAGE, ContentCreate, ContentFilter,
+    ContentFormat, ContentItem, ContentKind, ContentStatus,
+    ContentUpdate, MAX_PAGE_DEPTH, PageNode, Taxonomy, page_list,
+    page_nodes, page_offset, page_tree, search_terms, term_tree,
 };
+use rustpress::services::{SeoMeta, convert_content};
 
 use serde::Deserialize;
 
 use crate::web::forms::{
-    AdminCreateForm, AdminLiveForm, AdminNewPreviewForm,
-    AdminUpdateForm, SearchQuery,
+    AdminCreateForm, AdminFormatForm, AdminLiveForm,
+    AdminNewPreviewForm, AdminNewQuery, AdminPageParentForm,
+    AdminScheduleForm, AdminSeoForm, AdminUpdateForm,
+    MAX_CONTENT_LENGTH, PageQuery, SearchQuery, validate_schedule,
 };
 use crate::web::helpers::{
-    apply_site_template, escape_html, get_is_admin, iframe_srcdoc,
-    is_htmx, is_unique_violation, normalize_builtin_template_html,
+    apply_breadcrumb_placeholder, apply_seo_placeholders,
+    apply_site_template, apply_term_placeholders, authored_content,
+    content_public_url, csrf_token, escape_html, get_is_admin,
+    iframe_srcdoc, is_htmx, is_unique_violation,
+    normalize_builtin_template_html, numbered_page_links, page_links,
     render, render_not_found, require_user,
 };
 use crate::web::state::AppState;
@@ -27,6 +36,115 @@ use crate::web::templates::{
     AdminRevisionPreviewTemplate,
 };
 
+/// Enforce the site's "verified email required to publish" setting.
+pub const VERIFY_BEFORE_PUBLISH_MESSAGE: &str = "Verify your email address before publishing. \
+     You can request a link on the security page.";
+
+/// Shown to users who may only submit their work for review.
+pub const REVIEW_REQUIRED_MESSAGE: &str = "You don't have permission to publish. \
+     Submit the item for review instead.";
+
+/// Shown to users without the publish permission who try to change a
+/// live item.
+pub const LIVE_CONTENT_MESSAGE: &str = "You don't have permission to change published content. \
+     Ask someone who can publish to make the change.";
+
+/// Prefix of the revision note left when a reviewer sends an item back.
+pub const CHANGES_REQUESTED_NOTE: &str = "Changes requested";
+
+pub const TRASHED_NOTE: &str = "Moved to trash";
+pub const RESTORED_NOTE: &str = "Restored from trash";
+
+/// Record a revision carrying `note`, such as a review decision or a
+/// move into or out of the trash, so the history shows who did it.
+pub async fn record_note(
+    pool: &db::PgPool,
+    item: &ContentItem,
+    uid: Uuid,
+    note: &str,
+) -> Result<i32, sqlx::Error> {
+    db::ensure_initial_revision(pool, item.id, Some(uid)).await?;
+    db::record_transition(pool, item, Some(uid), note).await
+}
+
+/// Why `uid` may not publish, or `None` if they may. Publishing needs
+/// the admin role or the publish capability, plus a verified email when
+/// the site requires one.
+pub async fn publish_denied_reason(
+    pool: &db::PgPool,
+    uid: Uuid,
+) -> Result<Option<&'static str>, sqlx::Error> {
+    if !db::user_can_publish(pool, uid).await? {
+        return Ok(Some(REVIEW_REQUIRED_MESSAGE));
+    }
+    let required = db::get_default_site(pool)
+        .await?
+        .is_some_and(|s| s.require_verified_email);
+    if required && !db::is_email_verified(pool, uid).await? {
+        return Ok(Some(VERIFY_BEFORE_PUBLISH_MESSAGE));
+    }
+    Ok(None)
+}
+
+/// Why `uid` may not save an item in the `current` status as `next`,
+/// or `None` if they may. See
+/// [`ContentStatus::save_needs_publish_permission`].
+pub async fn save_denied_reason(
+    pool: &db::PgPool,
+    uid: Uuid,
+    current: ContentStatus,
+    next: ContentStatus,
+) -> Result<Option<&'static str>, sqlx::Error> {
+    if !current.save_needs_publish_permission(next) {
+        return Ok(None);
+    }
+    Ok(match publish_denied_reason(pool, uid).await? {
+        Some(REVIEW_REQUIRED_MESSAGE)
+            if current.needs_publish_permission() =>
+        {
+            Some(LIVE_CONTENT_MESSAGE)
+        }
+        reason => reason,
+    })
+}
+
+/// Refuse changes to `item` that need the publish permission `uid`
+/// lacks; `next` is the status it is saved with, if that changes.
+pub async fn ensure_can_save(
+    pool: &db::PgPool,
+    uid: Uuid,
+    item: &ContentItem,
+    next: Option<ContentStatus>,
+) -> Result<(), HttpResponse> {
+    let next = next.unwrap_or(item.status);
+    match save_denied_reason(pool, uid, item.status, next).await {
+        Ok(None) => Ok(()),
+        Ok(Some(reason)) => Err(HttpResponse::Forbidden()
+            .content_type("text/plain; charset=utf-8")
+            .body(reason)),
+        Err(e) => {
+            Err(HttpResponse::InternalServerError()
+                .body(e.to_string()))
+        }
+    }
+}
+
+pub async fn ensure_can_publish(
+    pool: &db::PgPool,
+    uid: Uuid,
+) -> Result<(), HttpResponse> {
+    match publish_denied_reason(pool, uid).await {
+        Ok(None) => Ok(()),
+        Ok(Some(reason)) => Err(HttpResponse::Forbidden()
+            .content_type("text/plain; charset=utf-8")
+            .body(reason)),
+        Err(e) => {
+            Err(HttpResponse::InternalServerError()
+                .body(e.to_string()))
+        }
+    }
+}
+
 #[get("/admin")]
 pub async fn admin_dashboard(
     state: web::Data<AppState>,
@@ -59,6 +177,7 @@ pub async fn admin_dashboard(
         posts,
         pages,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
@@ -67,6 +186,7 @@ pub async fn admin_posts_list(
     state: web::Data<AppState>,
     req: HttpRequest,
     query: web::Query<SearchQuery>,
+    page_query: web::Query<PageQuery>,
 ) -> impl Responder {
     let uid = match require_user(&req) {
         Ok(uid) => uid,
@@ -75,22 +195,66 @@ pub async fn admin_posts_list(
 
     let is_admin = get_is_admin(&req);
     let q = query.q.clone().unwrap_or_default();
-    let posts = db::list_content_for_user(
-        &state.pool,
-        ContentKind::Post,
-        true,
-        uid,
-    )
-    .await
-    .unwrap_or_default();
-
-    let posts: Vec<_> = posts
-        .into_iter()
-        .filter(|c| {
-            q.is_empty()
-                || c.title.to_lowercase().contains(&q.to_lowercase())
-        })
-        .collect();
+    // Search results are ranked, so they are paged by offset.
+    let (posts, total, prev_url, next_url) =
+        match search_terms(Some(&q)) {
+            Some(terms) => {
+                let (posts, total) = match search_page(
+                    &state.pool,
+                    ContentKind::Post,
+                    uid,
+                    &terms,
+                    page_query.number(),
+                )
+                .await
+                {
+                    Ok(found) => found,
+                    Err(e) => {
+                        return HttpResponse::InternalServerError()
+                            .body(e.to_string());
+                    }
+                };
+                let (prev_url, next_url) = search_page_links(
+                    req.path(),
+                    &terms,
+                    page_query.number(),
+                    total,
+                );
+                (posts, total, prev_url, next_url)
+            }
+            None => {
+                let page = match page_query
+                    .request(ADMIN_ITEMS_PER_PAGE)
+                {
+                    Ok(page) => page,
+                    Err(e) => {
+                        return HttpResponse::BadRequest().body(e);
+                    }
+                };
+                let posts = db::list_content_page_for_user(
+                    &state.pool,
+                    ContentKind::Post,
+                    uid,
+                    &page,
+                )
+                .await
+                .unwrap_or_default();
+                let total = db::count_filtered_content_for_user(
+                    &state.pool,
+                    ContentKind::Post,
+                    &ContentFilter::default(),
+                    uid,
+                )
+                .await
+                .unwrap_or_default();
+                let (prev_url, next_url) = page_links(
+                    req.path(),
+                    page_query.number(),
+                    &posts,
+                );
+                (posts.items, total, prev_url, next_url)
+            }
+        };
 
     let owner_ids: Vec<_> =
         posts.iter().filter_map(|p| p.owner_user_id).collect();
@@ -102,15 +266,60 @@ pub async fn admin_posts_list(
         posts,
         authors,
         query: q,
+        total,
+        page: page_query.number(),
+        prev_url,
+        next_url,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
+/// One page of `uid`'s items of `kind` matching `terms`, best first,
+/// and the number of matches.
+async fn search_page(
+    pool: &db::PgPool,
+    kind: ContentKind,
+    uid: Uuid,
+    terms: &str,
+    number: i64,
+) -> Result<(Vec<ContentItem>, i64), sqlx::Error> {
+    let items = db::search_content_for_user(
+        pool,
+        kind,
+        uid,
+        terms,
+        ADMIN_ITEMS_PER_PAGE,
+        page_offset(number, ADMIN_ITEMS_PER_PAGE),
+    )
+    .await?;
+    let total = db::count_content_search_results_for_user(
+        pool, kind, uid, terms,
+    )
+    .await?;
+    Ok((items, total))
+}
+
+fn search_page_links(
+    path: &str,
+    terms: &str,
+    number: i64,
+    total: i64,
+) -> (Option<String>, Option<String>) {
+    numbered_page_links(
+        &format!("{path}?q={}", urlencoding::encode(terms)),
+        number,
+        total,
+        ADMIN_ITEMS_PER_PAGE,
+    )
+}
+
 #[get("/admin/pages")]
 pub async fn admin_pages_list(
     state: web::Data<AppState>,
     req: HttpRequest,
     query: web::Query<SearchQuery>,
+    page_query: web::Query<PageQuery>,
 ) -> impl Responder {
     let uid = match require_user(&req) {
         Ok(uid) => uid,
@@ -119,25 +328,73 @@ pub async fn admin_pages_list(
 
     let is_admin = get_is_admin(&req);
     let q = query.q.clone().unwrap_or_default();
-    let pages = db::list_content_for_user(
-        &state.pool,
-        ContentKind::Page,
-        true,
-        uid,
-    )
-    .await
-    .unwrap_or_default();
+    let paths =
+        db::list_page_paths(&state.pool).await.unwrap_or_default();
+    // Matches stay in order of relevance rather than in the tree.
+    let (pages, total, prev_url, next_url) =
+        match search_terms(Some(&q)) {
+            Some(terms) => {
+                let (pages, total) = match search_page(
+                    &state.pool,
+                    ContentKind::Page,
+                    uid,
+                    &terms,
+                    page_query.number(),
+                )
+                .await
+                {
+                    Ok(found) => found,
+                    Err(e) => {
+                        return HttpResponse::InternalServerError()
+                            .body(e.to_string());
+                    }
+                };
+                let (prev_url, next_url) = search_page_links(
+                    req.path(),
+                    &terms,
+                    page_query.number(),
+                    total,
+                );
+                (page_list(pages, &paths), total, prev_url, next_url)
+            }
+            None => {
+                let number = page_query.number();
+                let rows = match db::list_page_tree_for_user(
+                    &state.pool,
+                    uid,
+                    ADMIN_ITEMS_PER_PAGE,
+                    page_offset(number, ADMIN_ITEMS_PER_PAGE),
+                )
+                .await
+                {
+                    Ok(rows) => rows,
+                    Err(e) => {
+                        return HttpResponse::InternalServerError()
+                            .body(e.to_string());
+                    }
+                };
+                let total = db::count_filtered_content_for_user(
+                    &state.pool,
+                    ContentKind::Page,
+                    &ContentFilter::default(),
+                    uid,
+                )
+                .await
+                .unwrap_or_default();
+                let (prev_url, next_url) = numbered_page_links(
+                    req.path(),
+                    number,
+                    total,
+                    ADMIN_ITEMS_PER_PAGE,
+                );
+                (page_nodes(rows, &paths), total, prev_url, next_url)
+            }
+        };
 
-    let pages: Vec<_> = pages
-        .into_iter()
-        .filter(|c| {
-            q.is_empty()
-                || c.title.to_lowercase().contains(&q.to_lowercase())
-        })
+    let owner_ids: Vec<_> = pages
+        .iter()
+        .filter_map(|node| node.page.owner_user_id)
         .collect();
-
-    let owner_ids: Vec<_> =
-        pages.iter().filter_map(|p| p.owner_user_id).collect();
     let authors = db::get_user_email_map(&state.pool, &owner_ids)
         .await
         .unwrap_or_default();
@@ -146,7 +403,12 @@ pub async fn admin_pages_list(
         pages,
         authors,
         query: q,
+        total,
+        page: page_query.number(),
+        prev_url,
+        next_url,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
@@ -155,6 +417,7 @@ pub async fn admin_new(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<String>,
+    query: web::Query<AdminNewQuery>,
 ) -> impl Responder {
     let uid = match require_user(&req) {
         Ok(uid) => uid,
@@ -171,9 +434,11 @@ pub async fn admin_new(
 
     render(AdminNewTemplate {
         kind,
+        format: query.format,
         default_template: "default".to_string(),
         templates,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
@@ -202,16 +467,33 @@ pub async fn admin_create(
         _ => return render_not_found(&req),
     };
 
+    let authored = match authored_content(
+        &state,
+        uid,
+        form.format,
+        &form.content,
+    )
+    .await
+    {
+        Ok(authored) => authored,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
     let data = ContentCreate {
         owner_user_id: Some(uid),
         kind,
         title: form.title.trim().to_string(),
         slug: form.slug.trim().to_string(),
-        content: form.content.to_string(),
+        content: authored.content,
         template: form
             .template
             .clone()
             .unwrap_or_else(|| "default".to_string()),
+        unfiltered_html: authored.unfiltered_html,
+        format: authored.format,
+        rendered_html: authored.rendered_html,
     };
 
     let created = match db::create_content(&state.pool, &data).await {
@@ -261,7 +543,8 @@ pub async fn admin_create(
     }
 }
 
-/// Resolve a site template and render preview HTML as an `<iframe srcdoc>`.
+/// Resolve a site template and render the page for a preview. Term
+/// placeholders are left for the caller.
 async fn compute_preview_html(
     pool: &sqlx::PgPool,
     owner_user_id: Option<Uuid>,
@@ -300,7 +583,7 @@ async fn compute_preview_html(
         };
     }
 
-    let html = match tpl {
+    match tpl {
         Some(tpl) => {
             let tpl_html = if tpl.is_builtin {
                 normalize_builtin_template_html(&tpl.html)
@@ -316,15 +599,219 @@ async fn compute_preview_html(
             )
         }
         None => apply_site_template(
-            "<!doctype html><html><head><meta charset=\"utf-8\"><title>{{title}}</title></head><body><h1>{{title}}</h1>{{content}}</body></html>",
+            "<!doctype html><html><head><meta charset=\"utf-8\"><title>{{title}}</title>{{head_meta}}</head><body><h1>{{title}}</h1>{{content}}</body></html>",
             title,
             content,
             slug,
             kind_str,
         ),
+    }
+}
+
+/// SEO metadata for previewing `item` with an unsaved title and body.
+async fn preview_seo(
+    state: &AppState,
+    item: &ContentItem,
+    title: &str,
+    content: &str,
+) -> SeoMeta {
+    let url =
+        content_public_url(&state.pool, item, &state.public_url)
+            .await;
+    let mut draft = item.clone();
+    draft.title = title.to_string();
+    draft.content = content.to_string();
+    draft.format = ContentFormat::Html;
+    draft.rendered_html = None;
+    SeoMeta::for_item(&draft, &url, &state.public_url)
+}
+
+/// Fill in the item's terms, breadcrumbs and SEO tags and wrap the page
+/// as an `<iframe srcdoc>`.
+async fn preview_iframe(
+    pool: &sqlx::PgPool,
+    content_id: Option<Uuid>,
+    title: &str,
+    html: &str,
+    seo: &SeoMeta,
+) -> String {
+    let (terms, ancestors) = match content_id {
+        Some(id) => (
+            db::list_terms_for_content(pool, id)
+                .await
+                .unwrap_or_default(),
+            db::list_page_ancestors(pool, id)
+                .await
+                .unwrap_or_default(),
+        ),
+        None => (Vec::new(), Vec::new()),
+    };
+    let html = apply_term_placeholders(html, &terms);
+    let html = apply_breadcrumb_placeholder(&html, &ancestors, title);
+    iframe_srcdoc(&apply_seo_placeholders(&html, seo))
+}
+
+/// Pages `page` may be moved under: those `uid` can see, minus the
+/// page itself and its subpages.
+async fn page_parent_options(
+    pool: &db::PgPool,
+    page: &ContentItem,
+    uid: Uuid,
+) -> Vec<PageNode> {
+    let pages =
+        db::list_content_for_user(pool, ContentKind::Page, true, uid)
+            .await
+            .unwrap_or_default();
+    let paths = db::list_page_paths(pool).await.unwrap_or_default();
+    let mut subtree_depth = None;
+    page_tree(pages, &paths)
+        .into_iter()
+        .filter(|node| {
+            if let Some(depth) = subtree_depth {
+                if node.depth > depth {
+                    return false;
+                }
+                subtree_depth = None;
+            }
+            if node.page.id == page.id {
+                subtree_depth = Some(node.depth);
+                return false;
+            }
+            true
+        })
+        .collect()
+}
+
+/// Check a requested parent for `page`: a live page `uid` can see that
+/// is not the page itself or one of its subpages, and that keeps the
+/// tree within [`MAX_PAGE_DEPTH`] levels.
+pub async fn validate_page_parent(
+    pool: &db::PgPool,
+    page: &ContentItem,
+    parent_id: Option<Uuid>,
+    uid: Uuid,
+) -> Result<(), String> {
+    if page.kind != ContentKind::Page {
+        return Err("Only pages can have a parent".into());
+    }
+    let Some(parent_id) = parent_id else {
+        return Ok(());
     };
+    let parent = db::get_content_by_id(pool, parent_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .filter(|p| p.kind == ContentKind::Page)
+        .ok_or("Parent page not found")?;
+    if !db::can_view_content(pool, &parent, uid)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Err("Parent page not found".into());
+    }
+    if db::is_page_ancestor_or_self(pool, page.id, parent.id)
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        return Err(
+            "A page cannot be moved below itself or its subpages"
+                .into(),
+        );
+    }
+    let depth = db::get_page_depth(pool, parent.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let height = db::get_subtree_height(pool, page.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    if depth + 1 + height > MAX_PAGE_DEPTH {
+        return Err(format!(
+            "Pages cannot be nested more than {MAX_PAGE_DEPTH} levels deep"
+        ));
+    }
+    Ok(())
+}
 
-    iframe_srcdoc(&html)
+pub async fn render_edit_page(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    uid: Uuid,
+    item: ContentItem,
+) -> HttpResponse {
+    let author = match item.owner_user_id {
+        Some(oid) => db::get_user_email_map(pool, &[oid])
+            .await
+            .ok()
+            .and_then(|m| m.into_values().next())
+            .unwrap_or_else(|| "Unknown".to_string()),
+        None => "Unknown".to_string(),
+    };
+    let templates = db::list_site_templates_for_user(pool, uid)
+        .await
+        .unwrap_or_default();
+    let categories = db::list_terms(pool, Taxonomy::Category)
+        .await
+        .map(term_tree)
+        .unwrap_or_default();
+    let (item_categories, item_tags): (Vec<_>, Vec<_>) =
+        db::list_terms_for_content(pool, item.id)
+            .await
+            .unwrap_or_default()
+            .into_iter()
+            .partition(|t| t.taxonomy == Taxonomy::Category);
+    let can_publish =
+        db::user_can_publish(pool, uid).await.unwrap_or(false);
+    let review = match item.status {
+        ContentStatus::Draft => {
+            db::get_revision(pool, item.id, item.current_rev)
+                .await
+                .ok()
+                .flatten()
+                .filter(|rev| {
+                    rev.note.as_deref().is_some_and(|n| {
+                        n.starts_with(CHANGES_REQUESTED_NOTE)
+                    })
+                })
+        }
+        _ => None,
+    };
+    let review_note_by = match review
+        .as_ref()
+        .and_then(|rev| rev.created_by_user_id)
+    {
+        Some(rid) => db::get_user_email_map(pool, &[rid])
+            .await
+            .ok()
+            .and_then(|m| m.into_values().next())
+            .unwrap_or_else(|| "Unknown".to_string()),
+        None => String::new(),
+    };
+    let parent_options = match item.kind {
+        ContentKind::Page => {
+            page_parent_options(pool, &item, uid).await
+        }
+        ContentKind::Post => Vec::new(),
+    };
+    render(AdminEditTemplate {
+        parent_options,
+        item,
+        author,
+        templates,
+        categories,
+        assigned_category_ids: item_categories
+            .iter()
+            .map(|t| t.id)
+            .collect(),
+        tags_input: item_tags
+            .iter()
+            .map(|t| t.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        can_publish,
+        review_note: review.and_then(|rev| rev.note),
+        review_note_by,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
+    })
 }
 
 #[get("/admin/edit/{id}")]
@@ -406,16 +893,32 @@ pub async fn admin_edit(
             None => "System".to_string(),
         };
 
+        let content = state.html_policy.for_display(
+            revision.html(),
+            revision.format,
+            revision.unfiltered_html,
+        );
         let preview_html = compute_preview_html(
             &state.pool,
             item.owner_user_id,
             &revision.template,
             &revision.title,
-            &revision.content,
+            &content,
             &revision.slug,
             item.kind.as_str(),
         )
         .await;
+        let seo =
+            preview_seo(&state, &item, &revision.title, &content)
+                .await;
+        let preview_html = preview_iframe(
+            &state.pool,
+            Some(item.id),
+            &revision.title,
+            &preview_html,
+            &seo,
+        )
+        .await;
 
         return render(AdminRevisionPreviewTemplate {
             item,
@@ -423,27 +926,11 @@ pub async fn admin_edit(
             revision_author,
             preview_html,
             is_admin,
+            csrf_token: csrf_token(&req),
         });
     }
 
-    let author = match item.owner_user_id {
-        Some(oid) => db::get_user_email_map(&state.pool, &[oid])
-            .await
-            .ok()
-            .and_then(|m| m.into_values().next())
-            .unwrap_or_else(|| "Unknown".to_string()),
-        None => "Unknown".to_string(),
-    };
-    let templates =
-        db::list_site_templates_for_user(&state.pool, uid)
-            .await
-            .unwrap_or_default();
-    render(AdminEditTemplate {
-        item,
-        author,
-        templates,
-        is_admin,
-    })
+    render_edit_page(&state.pool, &req, uid, item).await
 }
 
 #[post("/admin/edit/{id}")]
@@ -486,23 +973,70 @@ pub async fn admin_update(
     }
 
     let status = match form.status.as_deref().map(|s| s.trim()) {
-        Some("draft") => Some(ContentStatus::Draft),
-        Some("published") => Some(ContentStatus::Published),
         Some("") | None => None,
-        Some(_) => {
-            return HttpResponse::BadRequest().body("Invalid status");
+        // Shown while scheduled; the schedule itself is set separately.
+        Some("scheduled")
+            if existing.status == ContentStatus::Scheduled =>
+        {
+            None
+        }
+        Some("scheduled") => {
+            return HttpResponse::BadRequest()
+                .body("Set a publish time to schedule content");
+        }
+        Some(s) => match s.parse::<ContentStatus>() {
+            Ok(status) => Some(status),
+            Err(_) => {
+                return HttpResponse::BadRequest()
+                    .body("Invalid status");
+            }
+        },
+    };
+
+    if let Err(resp) =
+        ensure_can_save(&state.pool, uid, &existing, status).await
+    {
+        return resp;
+    }
+
+    let authored = match form.content.as_deref() {
+        Some(source) => {
+            match authored_content(
+                &state,
+                uid,
+                existing.format,
+                source,
+            )
+            .await
+            {
+                Ok(authored) => Some(authored),
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .body(e.to_string());
+                }
+            }
+        }
+        None => None,
+    };
+    let (content, unfiltered_html, rendered_html) = match authored {
+        Some(a) => {
+            (Some(a.content), a.unfiltered_html, a.rendered_html)
         }
+        None => (None, false, None),
     };
 
     let update = ContentUpdate {
         title: form.title.as_ref().map(|s| s.trim().to_string()),
         slug: form.slug.as_ref().map(|s| s.trim().to_string()),
-        content: form.content.clone(),
+        content,
         template: form
             .template
             .as_ref()
             .map(|s| s.trim().to_string()),
         status,
+        unfiltered_html,
+        format: None,
+        rendered_html,
     };
 
     let updated =
@@ -531,33 +1065,20 @@ pub async fn admin_update(
         return HttpResponse::InternalServerError()
             .body(e.to_string());
     }
-    if let Err(e) =
-        db::record_revision(&state.pool, &updated, Some(uid)).await
+    if let Err(e) = db::record_change(
+        &state.pool,
+        &updated,
+        existing.status,
+        Some(uid),
+    )
+    .await
     {
         return HttpResponse::InternalServerError()
             .body(e.to_string());
     }
 
     if is_htmx(&req) {
-        let is_admin = get_is_admin(&req);
-        let author = match updated.owner_user_id {
-            Some(oid) => db::get_user_email_map(&state.pool, &[oid])
-                .await
-                .ok()
-                .and_then(|m| m.into_values().next())
-                .unwrap_or_else(|| "Unknown".to_string()),
-            None => "Unknown".to_string(),
-        };
-        let templates =
-            db::list_site_templates_for_user(&state.pool, uid)
-                .await
-                .unwrap_or_default();
-        render(AdminEditTemplate {
-            item: updated,
-            author,
-            templates,
-            is_admin,
-        })
+        render_edit_page(&state.pool, &req, uid, updated).await
     } else {
         HttpResponse::SeeOther()
             .insert_header((
@@ -606,6 +1127,10 @@ pub async fn admin_publish(
         return HttpResponse::Forbidden().body("Forbidden");
     }
 
+    if let Err(resp) = ensure_can_publish(&state.pool, uid).await {
+        return resp;
+    }
+
     let published = match db::publish_content(&state.pool, id).await {
         Ok(Some(item)) => item,
         Ok(None) => return render_not_found(&req),
@@ -622,33 +1147,20 @@ pub async fn admin_publish(
         return HttpResponse::InternalServerError()
             .body(e.to_string());
     }
-    if let Err(e) =
-        db::record_revision(&state.pool, &published, Some(uid)).await
+    if let Err(e) = db::record_change(
+        &state.pool,
+        &published,
+        existing.status,
+        Some(uid),
+    )
+    .await
     {
         return HttpResponse::InternalServerError()
             .body(e.to_string());
     }
 
     if is_htmx(&req) {
-        let is_admin = get_is_admin(&req);
-        let author = match published.owner_user_id {
-            Some(oid) => db::get_user_email_map(&state.pool, &[oid])
-                .await
-                .ok()
-                .and_then(|m| m.into_values().next())
-                .unwrap_or_else(|| "Unknown".to_string()),
-            None => "Unknown".to_string(),
-        };
-        let templates =
-            db::list_site_templates_for_user(&state.pool, uid)
-                .await
-                .unwrap_or_default();
-        render(AdminEditTemplate {
-            item: published,
-            author,
-            templates,
-            is_admin,
-        })
+        render_edit_page(&state.pool, &req, uid, published).await
     } else {
         HttpResponse::SeeOther()
             .insert_header((
@@ -659,8 +1171,9 @@ pub async fn admin_publish(
     }
 }
 
-#[post("/admin/edit/{id}/delete")]
-pub async fn admin_delete(
+/// Submit a draft for review. Used by authors who cannot publish.
+#[post("/admin/edit/{id}/submit")]
+pub async fn admin_submit_for_review(
     state: web::Data<AppState>,
     req: HttpRequest,
     path: web::Path<Uuid>,
@@ -669,42 +1182,476 @@ pub async fn admin_delete(
         Ok(uid) => uid,
         Err(resp) => return resp,
     };
-
-    let id = path.into_inner();
-    let item = match db::get_content_by_id(&state.pool, id).await {
-        Ok(Some(item)) => item,
-        Ok(None) => return render_not_found(&req),
-        Err(e) => {
-            return HttpResponse::InternalServerError()
-                .body(e.to_string());
-        }
+    let existing = match load_editable_item(
+        &state.pool,
+        &req,
+        path.into_inner(),
+        uid,
+    )
+    .await
+    {
+        Ok(item) => item,
+        Err(resp) => return resp,
     };
+    if existing.status != ContentStatus::Draft {
+        return HttpResponse::BadRequest()
+            .content_type("text/plain; charset=utf-8")
+            .body("Only drafts can be submitted for review.");
+    }
 
-    let can_edit =
-        match db::can_edit_content(&state.pool, &item, uid).await {
-            Ok(v) => v,
+    let update = ContentUpdate {
+        title: None,
+        slug: None,
+        content: None,
+        template: None,
+        status: Some(ContentStatus::Pending),
+        unfiltered_html: false,
+        format: None,
+        rendered_html: None,
+    };
+    let submitted =
+        match db::update_content(&state.pool, existing.id, &update)
+            .await
+        {
+            Ok(Some(item)) => item,
+            Ok(None) => return render_not_found(&req),
             Err(e) => {
                 return HttpResponse::InternalServerError()
                     .body(e.to_string());
             }
         };
 
-    if !can_edit {
-        return HttpResponse::Forbidden().body("Forbidden");
+    if let Err(e) = db::ensure_initial_revision(
+        &state.pool,
+        submitted.id,
+        Some(uid),
+    )
+    .await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+    if let Err(e) = db::record_change(
+        &state.pool,
+        &submitted,
+        existing.status,
+        Some(uid),
+    )
+    .await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+
+    if is_htmx(&req) {
+        render_edit_page(&state.pool, &req, uid, submitted).await
+    } else {
+        HttpResponse::SeeOther()
+            .insert_header((
+                "Location",
+                format!("/admin/edit/{}", submitted.id),
+            ))
+            .finish()
+    }
+}
+
+/// Load an item for a change by `uid`, or the response refusing it.
+pub async fn load_editable_item(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<ContentItem, HttpResponse> {
+    let item = match db::get_content_by_id(pool, id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return Err(render_not_found(req)),
+        Err(e) => {
+            return Err(HttpResponse::InternalServerError()
+                .body(e.to_string()));
+        }
+    };
+    match db::can_edit_content(pool, &item, uid).await {
+        Ok(true) => Ok(item),
+        Ok(false) => Err(HttpResponse::Forbidden().body("Forbidden")),
+        Err(e) => {
+            Err(HttpResponse::InternalServerError()
+                .body(e.to_string()))
+        }
+    }
+}
+
+/// Load an item for a change by `uid` that leaves its status alone,
+/// such as its SEO fields, schedule or place in the page tree. Live
+/// items also need the publish permission; see [`ensure_can_save`].
+pub async fn load_changeable_item(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<ContentItem, HttpResponse> {
+    let item = load_editable_item(pool, req, id, uid).await?;
+    ensure_can_save(pool, uid, &item, None).await?;
+    Ok(item)
+}
+
+/// Record a schedule change in the history and return to the editor.
+async fn finish_schedule_change(
+    pool: &db::PgPool,
+    item: &ContentItem,
+    previous: ContentStatus,
+    uid: Uuid,
+) -> HttpResponse {
+    if let Err(e) =
+        db::ensure_initial_revision(pool, item.id, Some(uid)).await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+    if let Err(e) =
+        db::record_change(pool, item, previous, Some(uid)).await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+    HttpResponse::SeeOther()
+        .insert_header((
+            "Location",
+            format!("/admin/edit/{}", item.id),
+        ))
+        .finish()
+}
+
+/// Schedule an unpublished item, or set when a published one expires.
+#[post("/admin/edit/{id}/schedule")]
+pub async fn admin_schedule(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<AdminScheduleForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let id = path.into_inner();
+
+    let existing = match load_changeable_item(
+        &state.pool,
+        &req,
+        id,
+        uid,
+    )
+    .await
+    {
+        Ok(item) => item,
+        Err(resp) => return resp,
+    };
+
+    let schedule = match form.schedule().and_then(|schedule| {
+        validate_schedule(existing.status, &schedule, Utc::now())
+            .map(|()| schedule)
+    }) {
+        Ok(schedule) => schedule,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .content_type("text/plain; charset=utf-8")
+                .body(e);
+        }
+    };
+
+    if schedule.publish_at.is_some()
+        && let Err(resp) = ensure_can_publish(&state.pool, uid).await
+    {
+        return resp;
+    }
+
+    match db::schedule_content(&state.pool, id, &schedule).await {
+        Ok(Some(item)) => {
+            finish_schedule_change(
+                &state.pool,
+                &item,
+                existing.status,
+                uid,
+            )
+            .await
+        }
+        Ok(None) => render_not_found(&req),
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
     }
+}
+
+/// Move a page under another page, or to the top level, and set its
+/// position among its siblings.
+#[post("/admin/edit/{id}/parent")]
+pub async fn admin_page_parent(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<AdminPageParentForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let id = path.into_inner();
+
+    let existing = match load_changeable_item(
+        &state.pool,
+        &req,
+        id,
+        uid,
+    )
+    .await
+    {
+        Ok(item) => item,
+        Err(resp) => return resp,
+    };
+
+    let page_move = match form.page_move() {
+        Ok(page_move) => page_move,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .content_type("text/plain; charset=utf-8")
+                .body(e);
+        }
+    };
+    if let Err(e) = validate_page_parent(
+        &state.pool,
+        &existing,
+        page_move.parent_id,
+        uid,
+    )
+    .await
+    {
+        return HttpResponse::BadRequest()
+            .content_type("text/plain; charset=utf-8")
+            .body(e);
+    }
+
+    match db::move_page(&state.pool, id, &page_move).await {
+        Ok(Some(_)) => HttpResponse::SeeOther()
+            .insert_header(("Location", format!("/admin/edit/{id}")))
+            .finish(),
+        Ok(None) => render_not_found(&req),
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+/// Save an item's SEO title, description, canonical URL, sharing image
+/// and search engine visibility.
+#[post("/admin/edit/{id}/seo")]
+pub async fn admin_seo(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<AdminSeoForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let id = path.into_inner();
+
+    if let Err(resp) =
+        load_changeable_item(&state.pool, &req, id, uid).await
+    {
+        return resp;
+    }
+
+    let seo = match form.seo() {
+        Ok(seo) => seo,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .content_type("text/plain; charset=utf-8")
+                .body(e);
+        }
+    };
+
+    match db::update_content_seo(&state.pool, id, &seo).await {
+        Ok(Some(_)) => HttpResponse::SeeOther()
+            .insert_header(("Location", format!("/admin/edit/{id}")))
+            .finish(),
+        Ok(None) => render_not_found(&req),
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+/// Switch an item between HTML and Markdown. The editor's unsaved text
+/// is converted when posted, otherwise the stored content; either way
+/// the result is recorded as a revision so it can be undone.
+#[post("/admin/edit/{id}/format")]
+pub async fn admin_format(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<AdminFormatForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let id = path.into_inner();
+
+    let existing =
+        match load_editable_item(&state.pool, &req, id, uid).await {
+            Ok(item) => item,
+            Err(resp) => return resp,
+        };
+    if form.format == existing.format {
+        return HttpResponse::SeeOther()
+            .insert_header(("Location", format!("/admin/edit/{id}")))
+            .finish();
+    }
+    if let Err(resp) =
+        ensure_can_save(&state.pool, uid, &existing, None).await
+    {
+        return resp;
+    }
+    if form
+        .content
+        .as_ref()
+        .is_some_and(|c| c.len() > MAX_CONTENT_LENGTH)
+    {
+        return HttpResponse::BadRequest()
+            .content_type("text/plain; charset=utf-8")
+            .body("Content must not exceed 10MB");
+    }
+
+    let source = form.content.as_deref().unwrap_or(&existing.content);
+    let converted =
+        convert_content(source, existing.format, form.format);
+    let authored =
+        match authored_content(&state, uid, form.format, &converted)
+            .await
+        {
+            Ok(authored) => authored,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+    let update = ContentUpdate {
+        title: None,
+        slug: None,
+        content: Some(authored.content),
+        template: None,
+        status: None,
+        unfiltered_html: authored.unfiltered_html,
+        format: Some(authored.format),
+        rendered_html: authored.rendered_html,
+    };
+
+    let updated =
+        match db::update_content(&state.pool, id, &update).await {
+            Ok(Some(item)) => item,
+            Ok(None) => return render_not_found(&req),
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+    let note = format!("Converted to {}", form.format.label());
+    if let Err(e) =
+        record_note(&state.pool, &updated, uid, &note).await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+
+    if is_htmx(&req) {
+        render_edit_page(&state.pool, &req, uid, updated).await
+    } else {
+        HttpResponse::SeeOther()
+            .insert_header(("Location", format!("/admin/edit/{id}")))
+            .finish()
+    }
+}
+
+/// Drop the schedule; a scheduled item goes back to draft.
+#[post("/admin/edit/{id}/schedule/clear")]
+pub async fn admin_schedule_clear(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let id = path.into_inner();
+
+    let existing = match load_changeable_item(
+        &state.pool,
+        &req,
+        id,
+        uid,
+    )
+    .await
+    {
+        Ok(item) => item,
+        Err(resp) => return resp,
+    };
+
+    match db::clear_schedule(&state.pool, id).await {
+        Ok(Some(item)) => {
+            finish_schedule_change(
+                &state.pool,
+                &item,
+                existing.status,
+                uid,
+            )
+            .await
+        }
+        Ok(None) => render_not_found(&req),
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+#[post("/admin/edit/{id}/delete")]
+pub async fn admin_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+
+    let id = path.into_inner();
+    let item = match load_changeable_item(&state.pool, &req, id, uid)
+        .await
+    {
+        Ok(item) => item,
+        Err(resp) => return resp,
+    };
 
     let redirect = match item.kind {
         ContentKind::Post => "/admin/posts",
         ContentKind::Page => "/admin/pages",
     };
 
-    match db::delete_content(&state.pool, id).await {
-        Ok(true) => {}
-        Ok(false) => return render_not_found(&req),
+    let trashed = match db::trash_content(&state.pool, id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return render_not_found(&req),
         Err(e) => {
             return HttpResponse::InternalServerError()
                 .body(e.to_string());
         }
+    };
+    if let Err(e) =
+        record_note(&state.pool, &trashed, uid, TRASHED_NOTE).await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
     }
 
     if is_htmx(&req) {
@@ -753,17 +1700,62 @@ pub async fn admin_autosave(
     if !can_edit {
         return HttpResponse::Forbidden().body("Forbidden");
     }
+    match save_denied_reason(
+        &state.pool,
+        uid,
+        item.status,
+        item.status,
+    )
+    .await
+    {
+        Ok(None) => {}
+        Ok(Some(reason)) => {
+            return HttpResponse::Forbidden()
+                .content_type("text/html; charset=utf-8")
+                .body(format!(
+                    "<span class=\"muted\">Not autosaved: {}</span>",
+                    escape_html(reason)
+                ));
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    }
 
+    let authored = match form.content.as_deref() {
+        Some(source) => {
+            match authored_content(&state, uid, item.format, source)
+                .await
+            {
+                Ok(authored) => Some(authored),
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .body(e.to_string());
+                }
+            }
+        }
+        None => None,
+    };
+    let (content, unfiltered_html, rendered_html) = match authored {
+        Some(a) => {
+            (Some(a.content), a.unfiltered_html, a.rendered_html)
+        }
+        None => (None, false, None),
+    };
     // Autosave should never implicitly publish.
     let update = ContentUpdate {
         title: form.title.as_ref().map(|s| s.trim().to_string()),
         slug: form.slug.as_ref().map(|s| s.trim().to_string()),
-        content: form.content.clone(),
+        content,
         template: form
             .template
             .as_ref()
             .map(|s| s.trim().to_string()),
         status: None,
+        unfiltered_html,
+        format: None,
+        rendered_html,
     };
 
     // Avoid creating a new revision when nothing actually changed.
@@ -882,8 +1874,28 @@ pub async fn admin_preview(
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty())
         .unwrap_or_else(|| item.slug.clone());
-    let content =
-        form.content.clone().unwrap_or_else(|| item.content.clone());
+    // Show the content as it would be saved or is shown.
+    let content = match form.content.as_deref() {
+        Some(source) => {
+            match authored_content(&state, uid, item.format, source)
+                .await
+            {
+                Ok(authored) => authored.html().to_string(),
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .body(e.to_string());
+                }
+            }
+        }
+        None => state
+            .html_policy
+            .for_display(
+                item.html(),
+                item.format,
+                item.unfiltered_html,
+            )
+            .into_owned(),
+    };
     let template_name = form
         .template
         .as_ref()
@@ -901,6 +1913,10 @@ pub async fn admin_preview(
         item.kind.as_str(),
     )
     .await;
+    let seo = preview_seo(&state, &item, &title, &content).await;
+    let preview =
+        preview_iframe(&state.pool, Some(id), &title, &preview, &seo)
+            .await;
 
     HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
@@ -950,14 +1966,18 @@ pub async fn admin_preview_fullpage(
     }
 
     // If a specific revision is requested, use its content instead.
-    let (title, slug, content, template_name) =
+    let (title, slug, content, template_name, format, unfiltered) =
         if let Some(rev) = query.rev {
             match db::get_revision(&state.pool, id, rev).await {
                 Ok(Some(revision)) => (
                     revision.title,
                     revision.slug,
-                    revision.content,
+                    revision
+                        .rendered_html
+                        .unwrap_or(revision.content),
                     revision.template,
+                    revision.format,
+                    revision.unfiltered_html,
                 ),
                 Ok(None) => return render_not_found(&req),
                 Err(e) => {
@@ -969,10 +1989,18 @@ pub async fn admin_preview_fullpage(
             (
                 item.title.clone(),
                 item.slug.clone(),
-                item.content.clone(),
+                item.html().to_string(),
                 item.template.clone(),
+                item.format,
+                item.unfiltered_html,
             )
         };
+    // This page is not sandboxed, so the content must be cleaned just
+    // as on the public site.
+    let content = state
+        .html_policy
+        .for_display(&content, format, unfiltered)
+        .into_owned();
 
     // compute_preview_html wraps in iframe_srcdoc; we need raw HTML here.
     let mut tpl = match item.owner_user_id {
@@ -1026,13 +2054,24 @@ pub async fn admin_preview_fullpage(
             )
         }
         None => apply_site_template(
-            "<!doctype html><html><head><meta charset=\"utf-8\"><title>{{title}}</title></head><body><h1>{{title}}</h1>{{content}}</body></html>",
+            "<!doctype html><html><head><meta charset=\"utf-8\"><title>{{title}}</title>{{head_meta}}</head><body><h1>{{title}}</h1>{{content}}</body></html>",
             &title,
             &content,
             &slug,
             item.kind.as_str(),
         ),
     };
+    let terms = db::list_terms_for_content(&state.pool, item.id)
+        .await
+        .unwrap_or_default();
+    let html = apply_term_placeholders(&html, &terms);
+    let ancestors = db::list_page_ancestors(&state.pool, item.id)
+        .await
+        .unwrap_or_default();
+    let html =
+        apply_breadcrumb_placeholder(&html, &ancestors, &title);
+    let seo = preview_seo(&state, &item, &title, &content).await;
+    let html = apply_seo_placeholders(&html, &seo);
 
     HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
@@ -1068,7 +2107,20 @@ pub async fn admin_preview_new(
         .map(|s| s.trim().to_string())
         .filter(|s| !s.is_empty())
         .unwrap_or_else(|| "untitled".to_string());
-    let content = form.content.clone().unwrap_or_default();
+    let content = match authored_content(
+        &state,
+        uid,
+        form.format,
+        form.content.as_deref().unwrap_or_default(),
+    )
+    .await
+    {
+        Ok(authored) => authored.html().to_string(),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
     let template_name = form
         .template
         .as_ref()
@@ -1086,6 +2138,15 @@ pub async fn admin_preview_new(
         kind,
     )
     .await;
+    let base = &state.public_url;
+    let (kind, url) = match kind {
+        "page" => (ContentKind::Page, format!("{base}/{slug}")),
+        _ => (ContentKind::Post, format!("{base}/blog/{slug}")),
+    };
+    let seo = SeoMeta::derived(kind, &title, &content, &url, base);
+    let preview =
+        preview_iframe(&state.pool, None, &title, &preview, &seo)
+            .await;
 
     HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
@@ -1101,6 +2162,12 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
         .service(admin_edit)
         .service(admin_update)
         .service(admin_publish)
+        .service(admin_submit_for_review)
+        .service(admin_schedule)
+        .service(admin_schedule_clear)
+        .service(admin_page_parent)
+        .service(admin_seo)
+        .service(admin_format)
         .service(admin_delete)
         .service(admin_autosave)
         .service(admin_preview)
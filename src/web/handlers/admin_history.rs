@@ -2,11 +2,13 @@ use actix_web::{
     HttpRequest, HttpResponse, Responder, get, post, web,
 };
 use serde::Deserialize;
+use utoipa::IntoParams;
 use uuid::Uuid;
 
 use rustpress::db;
 use rustpress::models::ContentItem;
 
+use crate::web::handlers::admin_content::ensure_can_save;
 use crate::web::helpers::{
     is_htmx, render, render_not_found, require_user,
 };
@@ -63,7 +65,29 @@ fn internal_server_error(e: impl std::fmt::Display) -> HttpResponse {
     HttpResponse::InternalServerError().body(e.to_string())
 }
 
-#[derive(Deserialize)]
+/// Going back to revision `rev` changes the item and may change its
+/// status; doing either to a live item needs the same permission as
+/// publishing.
+async fn ensure_can_restore(
+    pool: &db::PgPool,
+    uid: Uuid,
+    item: &ContentItem,
+    rev: i32,
+) -> Result<(), HttpResponse> {
+    let target = db::get_revision(pool, item.id, rev)
+        .await
+        .map_err(internal_server_error)?;
+    match target {
+        Some(target) => {
+            ensure_can_save(pool, uid, item, Some(target.status))
+                .await
+        }
+        None => Ok(()),
+    }
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct RevisionsQuery {
     pub limit: Option<i64>,
 }
@@ -164,7 +188,14 @@ pub async fn admin_restore_revision(
     path: web::Path<(Uuid, i32)>,
 ) -> impl Responder {
     let (id, rev) = path.into_inner();
-    if let Err(r) = load_editable(&state.pool, &req, id).await {
+    let (uid, item) = match load_editable(&state.pool, &req, id).await
+    {
+        Ok(v) => v,
+        Err(r) => return r,
+    };
+    if let Err(r) =
+        ensure_can_restore(&state.pool, uid, &item, rev).await
+    {
         return r;
     }
 
@@ -193,17 +224,27 @@ pub async fn admin_undo(
     path: web::Path<Uuid>,
 ) -> impl Responder {
     let id = path.into_inner();
-    let (uid, _item) =
-        match load_editable(&state.pool, &req, id).await {
-            Ok(v) => v,
-            Err(r) => return r,
-        };
+    let (uid, item) = match load_editable(&state.pool, &req, id).await
+    {
+        Ok(v) => v,
+        Err(r) => return r,
+    };
 
     if let Err(e) =
         db::ensure_initial_revision(&state.pool, id, Some(uid)).await
     {
         return internal_server_error(e);
     }
+    if let Err(r) = ensure_can_restore(
+        &state.pool,
+        uid,
+        &item,
+        item.current_rev - 1,
+    )
+    .await
+    {
+        return r;
+    }
 
     match db::undo(&state.pool, id).await {
         Ok(Some(item)) => HttpResponse::Ok().json(item),
@@ -219,17 +260,27 @@ pub async fn admin_redo(
     path: web::Path<Uuid>,
 ) -> impl Responder {
     let id = path.into_inner();
-    let (uid, _item) =
-        match load_editable(&state.pool, &req, id).await {
-            Ok(v) => v,
-            Err(r) => return r,
-        };
+    let (uid, item) = match load_editable(&state.pool, &req, id).await
+    {
+        Ok(v) => v,
+        Err(r) => return r,
+    };
 
     if let Err(e) =
         db::ensure_initial_revision(&state.pool, id, Some(uid)).await
     {
         return internal_server_error(e);
     }
+    if let Err(r) = ensure_can_restore(
+        &state.pool,
+        uid,
+        &item,
+        item.current_rev + 1,
+    )
+    .await
+    {
+        return r;
+    }
 
     match db::redo(&state.pool, id).await {
         Ok(Some(item)) => HttpResponse::Ok().json(item),
@@ -0,0 +1,430 @@
+use actix_multipart::Multipart;
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, get, post, web,
+};
+use futures_util::StreamExt;
+use uuid::Uuid;
+
+use rustpress::common::MediaError;
+use rustpress::db;
+use rustpress::models::{
+    MAX_MEDIA_ALT_LENGTH, MAX_MEDIA_FILES_PER_UPLOAD,
+    MAX_MEDIA_UPLOAD_BYTES, Media, MediaCreate, MediaFilter,
+};
+use rustpress::services::{
+    clean_media_filename, media_storage_key, media_variant_key,
+    sniff_media,
+};
+
+use crate::web::forms::{
+    AdminMediaAltForm, MediaUploadQuery, SearchQuery,
+};
+use crate::web::helpers::{
+    csrf_token, get_is_admin, is_htmx, render, render_not_found,
+    require_user,
+};
+use crate::web::state::AppState;
+use crate::web::templates::{
+    AdminMediaGridTemplate, AdminMediaPickerTemplate,
+    AdminMediaTemplate, MediaEntry,
+};
+
+/// Files listed by the library and the picker; search to reach older
+/// ones.
+const MEDIA_LIST_LIMIT: i64 = 200;
+
+/// A file taken from an upload form, not yet checked.
+struct Upload {
+    filename: String,
+    data: Vec<u8>,
+}
+
+/// The `file` parts and the optional `alt_text` of an upload form.
+/// Stops reading as soon as a file goes over the size limit.
+async fn read_upload_form(
+    mut payload: Multipart,
+) -> Result<(Vec<Upload>, String), String> {
+    let mut uploads = Vec::new();
+    let mut alt_text = String::new();
+    while let Some(field) = payload.next().await {
+        let mut field = field.map_err(|e| e.to_string())?;
+        let name = field.name().unwrap_or_default().to_string();
+        let filename = field
+            .content_disposition()
+            .and_then(|cd| cd.get_filename())
+            .map(clean_media_filename);
+
+        let limit = match name.as_str() {
+            "file" => MAX_MEDIA_UPLOAD_BYTES,
+            "alt_text" => MAX_MEDIA_ALT_LENGTH * 4,
+            _ => 0,
+        };
+        let mut data = Vec::new();
+        while let Some(chunk) = field.next().await {
+            let chunk = chunk.map_err(|e| e.to_string())?;
+            if data.len() + chunk.len() > limit {
+                return Err(match name.as_str() {
+                    "file" => MediaError::TooLarge(limit).to_string(),
+                    "alt_text" => format!(
+                        "Alt text must be at most {MAX_MEDIA_ALT_LENGTH} characters"
+                    ),
+                    _ => format!("Unexpected form field: {name}"),
+                });
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        match name.as_str() {
+            // Browsers send an empty part when no file was picked.
+            "file" if data.is_empty() => {}
+            "file" => {
+                if uploads.len() == MAX_MEDIA_FILES_PER_UPLOAD {
+                    return Err(format!(
+                        "Upload at most {MAX_MEDIA_FILES_PER_UPLOAD} files at a time"
+                    ));
+                }
+                uploads.push(Upload {
+                    filename: filename
+                        .unwrap_or_else(|| "upload".to_string()),
+                    data,
+                });
+            }
+            "alt_text" => {
+                alt_text = String::from_utf8(data).map_err(|_| {
+                    "Alt text must be UTF-8".to_string()
+                })?;
+            }
+            _ => {}
+        }
+    }
+
+    let alt_text = AdminMediaAltForm { alt_text }.alt_text()?;
+    if uploads.is_empty() {
+        return Err("Choose a file to upload".to_string());
+    }
+    Ok((uploads, alt_text))
+}
+
+/// Check, store and record one uploaded file.
+async fn store_upload(
+    state: &AppState,
+    upload: Upload,
+    alt_text: &str,
+    uid: Uuid,
+) -> Result<Media, String> {
+    let sniffed = sniff_media(&upload.data)
+        .map_err(|e| format!("{}: {e}", upload.filename))?;
+    let key = media_storage_key(Uuid::new_v4(), sniffed.extension);
+    let size_bytes = upload.data.len() as i64;
+
+    let storage = state.media.clone();
+    let put_key = key.clone();
+    web::block(move || storage.put(&put_key, &upload.data))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())?;
+
+    let (width, height) =
+        sniffed.dimensions.map_or((None, None), |(w, h)| {
+            (i32::try_from(w).ok(), i32::try_from(h).ok())
+        });
+    let created = db::create_media(
+        &state.pool,
+        &MediaCreate {
+            owner_user_id: Some(uid),
+            filename: upload.filename,
+            storage_key: key.clone(),
+            mime_type: sniffed.mime_type.to_string(),
+            size_bytes,
+            width,
+            height,
+            alt_text: alt_text.to_string(),
+        },
+    )
+    .await;
+    match created {
+        Ok(media) => Ok(media),
+        Err(e) => {
+            // Do not leave a file behind that nothing points to.
+            let storage = state.media.clone();
+            let _ = web::block(move || storage.delete(&key)).await;
+            Err(e.to_string())
+        }
+    }
+}
+
+async fn list_entries(
+    state: &AppState,
+    req: &HttpRequest,
+    uid: Uuid,
+    query: Option<String>,
+) -> Result<(Vec<MediaEntry>, bool), sqlx::Error> {
+    let media = db::list_media(
+        &state.pool,
+        &MediaFilter {
+            query,
+            images_only: false,
+            limit: MEDIA_LIST_LIMIT,
+        },
+    )
+    .await?;
+    let is_admin = get_is_admin(req);
+    let truncated = media.len() as i64 == MEDIA_LIST_LIMIT;
+    let entries = media
+        .into_iter()
+        .map(|media| MediaEntry {
+            can_manage: is_admin || media.owner_user_id == Some(uid),
+            media,
+        })
+        .collect();
+    Ok((entries, truncated))
+}
+
+async fn render_picker(
+    state: &AppState,
+    query: Option<String>,
+    error: Option<String>,
+) -> HttpResponse {
+    let filter = MediaFilter {
+        query,
+        images_only: true,
+        limit: MEDIA_LIST_LIMIT,
+    };
+    match db::list_media(&state.pool, &filter).await {
+        Ok(images) => {
+            render(AdminMediaPickerTemplate { images, error })
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+#[get("/admin/media")]
+pub async fn media_library(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let query = query.into_inner().q.unwrap_or_default();
+    let (entries, truncated) =
+        match list_entries(&state, &req, uid, Some(query.clone()))
+            .await
+        {
+            Ok(listed) => listed,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+    render(AdminMediaTemplate {
+        entries,
+        query,
+        truncated,
+        is_admin: get_is_admin(&req),
+        error: None,
+        success: None,
+        csrf_token: csrf_token(&req),
+    })
+}
+
+/// Images for the editor's picker, filtered by `q`.
+#[get("/admin/media/picker")]
+pub async fn media_picker(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<SearchQuery>,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    render_picker(&state, query.into_inner().q, None).await
+}
+
+/// Upload one or more files from a multipart form. The form must send
+/// the CSRF token in the `X-CSRF-Token` header, as HTMX does. Answers
+/// with the refreshed library grid, or the picker with `?view=picker`.
+#[post("/admin/media")]
+pub async fn media_upload(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    view: web::Query<MediaUploadQuery>,
+    payload: Multipart,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+
+    let mut stored = 0;
+    let mut error = None;
+    match read_upload_form(payload).await {
+        Ok((uploads, alt_text)) => {
+            for upload in uploads {
+                match store_upload(&state, upload, &alt_text, uid)
+                    .await
+                {
+                    Ok(_) => stored += 1,
+                    Err(e) => {
+                        error = Some(e);
+                        break;
+                    }
+                }
+            }
+        }
+        Err(e) => error = Some(e),
+    }
+
+    if !is_htmx(&req) {
+        return match error {
+            Some(e) => HttpResponse::BadRequest()
+                .content_type("text/plain; charset=utf-8")
+                .body(e),
+            None => HttpResponse::SeeOther()
+                .insert_header(("Location", "/admin/media"))
+                .finish(),
+        };
+    }
+
+    // HTMX only swaps successful responses, so errors are shown inside
+    // the refreshed listing.
+    if view.view.as_deref() == Some("picker") {
+        return render_picker(&state, None, error).await;
+    }
+    let (entries, truncated) =
+        match list_entries(&state, &req, uid, None).await {
+            Ok(listed) => listed,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+    let success = (stored > 0).then(|| match stored {
+        1 => "Uploaded 1 file".to_string(),
+        n => format!("Uploaded {n} files"),
+    });
+    render(AdminMediaGridTemplate {
+        entries,
+        truncated,
+        error,
+        success,
+        csrf_token: csrf_token(&req),
+    })
+}
+
+/// The file if the user may change it: its uploader or an admin.
+async fn load_manageable_media(
+    state: &AppState,
+    req: &HttpRequest,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<Media, HttpResponse> {
+    let media = match db::get_media(&state.pool, id).await {
+        Ok(Some(media)) => media,
+        Ok(None) => return Err(render_not_found(req)),
+        Err(e) => {
+            return Err(HttpResponse::InternalServerError()
+                .body(e.to_string()));
+        }
+    };
+    if media.owner_user_id != Some(uid) && !get_is_admin(req) {
+        return Err(HttpResponse::Forbidden().body("Forbidden"));
+    }
+    Ok(media)
+}
+
+#[post("/admin/media/{id}")]
+pub async fn media_update(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<AdminMediaAltForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let id = path.into_inner();
+    if let Err(resp) =
+        load_manageable_media(&state, &req, id, uid).await
+    {
+        return resp;
+    }
+    let alt_text = match form.alt_text() {
+        Ok(alt_text) => alt_text,
+        Err(e) => {
+            return HttpResponse::BadRequest()
+                .content_type("text/plain; charset=utf-8")
+                .body(e);
+        }
+    };
+    match db::update_media_alt(&state.pool, id, &alt_text).await {
+        Ok(Some(_)) => HttpResponse::SeeOther()
+            .insert_header(("Location", "/admin/media"))
+            .finish(),
+        Ok(None) => render_not_found(&req),
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+/// Delete the record, the stored file and its resized copies. Posts
+/// that show the file are left as they are.
+#[post("/admin/media/{id}/delete")]
+pub async fn media_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let id = path.into_inner();
+    if let Err(resp) =
+        load_manageable_media(&state, &req, id, uid).await
+    {
+        return resp;
+    }
+    let deleted = match db::delete_media(&state.pool, id).await {
+        Ok(Some(media)) => media,
+        Ok(None) => return render_not_found(&req),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    // The original and any resized copies of it.
+    let mut keys: Vec<String> = state
+        .image_widths
+        .iter()
+        .map(|&w| media_variant_key(&deleted.storage_key, w))
+        .collect();
+    keys.push(deleted.storage_key);
+    let storage = state.media.clone();
+    let removed = web::block(move || {
+        keys.iter().try_for_each(|key| storage.delete(key))
+    })
+    .await;
+    match removed {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => log::error!("Failed to delete media file: {e}"),
+        Err(e) => log::error!("Failed to delete media file: {e}"),
+    }
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/admin/media"))
+        .finish()
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(media_library)
+        .service(media_picker)
+        .service(media_upload)
+        .service(media_update)
+        .service(media_delete);
+}
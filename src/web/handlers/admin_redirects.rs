@@ -0,0 +1,164 @@
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, get, post, web,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::ContentKind;
+
+use crate::web::forms::AdminRedirectForm;
+use crate::web::helpers::{
+    csrf_token, get_is_admin, is_unique_violation, render,
+    require_user,
+};
+use crate::web::state::AppState;
+use crate::web::templates::AdminRedirectsTemplate;
+
+/// Identifies an automatic redirect to remove.
+#[derive(Deserialize)]
+pub struct SlugHistoryForm {
+    pub kind: ContentKind,
+    pub path: String,
+}
+
+async fn render_list(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    error: Option<String>,
+    success: Option<String>,
+) -> HttpResponse {
+    let redirects = match db::list_redirects(pool).await {
+        Ok(redirects) => redirects,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let history =
+        db::list_slug_history(pool).await.unwrap_or_default();
+    render(AdminRedirectsTemplate {
+        redirects,
+        history,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
+        error,
+        success,
+    })
+}
+
+#[get("/admin/redirects")]
+pub async fn redirects_list(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    render_list(&state.pool, &req, None, None).await
+}
+
+#[post("/admin/redirects")]
+pub async fn redirects_create(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<AdminRedirectForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let mut data = match form.redirect() {
+        Ok(data) => data,
+        Err(e) => {
+            return render_list(
+                &state.pool,
+                &req,
+                Some(e.to_string()),
+                None,
+            )
+            .await;
+        }
+    };
+    data.created_by_user_id = Some(uid);
+
+    match db::create_redirect(&state.pool, &data).await {
+        Ok(redirect) => {
+            render_list(
+                &state.pool,
+                &req,
+                None,
+                Some(format!(
+                    "Added a redirect from {}",
+                    redirect.source_path
+                )),
+            )
+            .await
+        }
+        Err(e) if is_unique_violation(&e) => {
+            render_list(
+                &state.pool,
+                &req,
+                Some(format!(
+                    "A redirect from {} ({} match) already exists",
+                    data.source_path, data.match_type
+                )),
+                None,
+            )
+            .await
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+/// Drop an automatic redirect left behind by a renamed or moved item.
+/// Registered before the `{id}` route so "history" is not read as an id.
+#[post("/admin/redirects/history/delete")]
+pub async fn redirects_history_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<SlugHistoryForm>,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    if let Err(e) =
+        db::delete_slug_history(&state.pool, form.kind, &form.path)
+            .await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/admin/redirects"))
+        .finish()
+}
+
+#[post("/admin/redirects/{id}/delete")]
+pub async fn redirects_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    if let Err(e) =
+        db::delete_redirect(&state.pool, path.into_inner()).await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+    HttpResponse::SeeOther()
+        .insert_header(("Location", "/admin/redirects"))
+        .finish()
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(redirects_list)
+        .service(redirects_create)
+        .service(redirects_history_delete)
+        .service(redirects_delete);
+}
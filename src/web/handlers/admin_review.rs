@@ -0,0 +1,251 @@
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, get, post, web,
+};
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::{ContentItem, ContentStatus, ContentUpdate};
+
+use crate::web::forms::AdminReviewForm;
+use crate::web::handlers::admin_content::{
+    CHANGES_REQUESTED_NOTE, ensure_can_publish, load_editable_item,
+    record_note,
+};
+use crate::web::helpers::{
+    csrf_token, get_is_admin, render, require_user,
+};
+use crate::web::state::AppState;
+use crate::web::templates::AdminReviewQueueTemplate;
+
+async fn render_queue(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    uid: Uuid,
+    error: Option<String>,
+    success: Option<String>,
+) -> HttpResponse {
+    let can_review = match db::user_can_publish(pool, uid).await {
+        Ok(v) => v,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let mut items = match db::list_pending_content(pool).await {
+        Ok(items) => items,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    if !can_review {
+        items.retain(|item| item.owner_user_id == Some(uid));
+    }
+    let owner_ids: Vec<Uuid> =
+        items.iter().filter_map(|i| i.owner_user_id).collect();
+    let authors = db::get_user_email_map(pool, &owner_ids)
+        .await
+        .unwrap_or_default();
+    render(AdminReviewQueueTemplate {
+        items,
+        authors,
+        can_review,
+        is_admin: get_is_admin(req),
+        error,
+        success,
+        csrf_token: csrf_token(req),
+    })
+}
+
+/// Load a pending item for a reviewer decision by `uid`.
+async fn load_pending(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<ContentItem, HttpResponse> {
+    ensure_can_publish(pool, uid).await?;
+    let item = load_editable_item(pool, req, id, uid).await?;
+    if item.status != ContentStatus::Pending {
+        return Err(render_queue(
+            pool,
+            req,
+            uid,
+            Some(format!(
+                "\"{}\" is not waiting for review",
+                item.title
+            )),
+            None,
+        )
+        .await);
+    }
+    Ok(item)
+}
+
+#[get("/admin/review")]
+pub async fn review_queue(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    render_queue(&state.pool, &req, uid, None, None).await
+}
+
+#[post("/admin/review/{id}/approve")]
+pub async fn review_approve(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<AdminReviewForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let note = match form.note() {
+        Ok(note) => note,
+        Err(e) => {
+            return render_queue(
+                &state.pool,
+                &req,
+                uid,
+                Some(e.to_string()),
+                None,
+            )
+            .await;
+        }
+    };
+    let item =
+        match load_pending(&state.pool, &req, path.into_inner(), uid)
+            .await
+        {
+            Ok(item) => item,
+            Err(resp) => return resp,
+        };
+
+    let published =
+        match db::publish_content(&state.pool, item.id).await {
+            Ok(Some(item)) => item,
+            Ok(None) => return HttpResponse::NotFound().finish(),
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+
+    let approved = ContentStatus::Pending
+        .transition_note(ContentStatus::Published)
+        .unwrap_or_default();
+    let note = match note {
+        Some(note) => format!("{approved}: {note}"),
+        None => approved.to_string(),
+    };
+    if let Err(e) =
+        record_note(&state.pool, &published, uid, &note).await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+
+    render_queue(
+        &state.pool,
+        &req,
+        uid,
+        None,
+        Some(format!("Published \"{}\"", published.title)),
+    )
+    .await
+}
+
+#[post("/admin/review/{id}/request-changes")]
+pub async fn review_request_changes(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<AdminReviewForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let note = match form.note() {
+        Ok(Some(note)) => note,
+        Ok(None) => {
+            return render_queue(
+                &state.pool,
+                &req,
+                uid,
+                Some("Explain what needs to change".to_string()),
+                None,
+            )
+            .await;
+        }
+        Err(e) => {
+            return render_queue(
+                &state.pool,
+                &req,
+                uid,
+                Some(e.to_string()),
+                None,
+            )
+            .await;
+        }
+    };
+    let item =
+        match load_pending(&state.pool, &req, path.into_inner(), uid)
+            .await
+        {
+            Ok(item) => item,
+            Err(resp) => return resp,
+        };
+
+    let update = ContentUpdate {
+        title: None,
+        slug: None,
+        content: None,
+        template: None,
+        status: Some(ContentStatus::Draft),
+        unfiltered_html: false,
+        format: None,
+        rendered_html: None,
+    };
+    let returned =
+        match db::update_content(&state.pool, item.id, &update).await
+        {
+            Ok(Some(item)) => item,
+            Ok(None) => return HttpResponse::NotFound().finish(),
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+
+    let note = format!("{CHANGES_REQUESTED_NOTE}: {note}");
+    if let Err(e) =
+        record_note(&state.pool, &returned, uid, &note).await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+
+    render_queue(
+        &state.pool,
+        &req,
+        uid,
+        None,
+        Some(format!(
+            "Sent \"{}\" back to its author",
+            returned.title
+        )),
+    )
+    .await
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(review_queue)
+        .service(review_approve)
+        .service(review_request_changes);
+}
@@ -5,14 +5,17 @@ use uuid::Uuid;
 
 use rustpress::db;
 use rustpress::models::ContentKind;
+use rustpress::services::SeoMeta;
 
 use crate::web::forms::{
     AdminTemplateCreateForm, AdminTemplatePreviewForm,
     AdminTemplateUpdateForm,
 };
 use crate::web::helpers::{
-    apply_site_template, get_is_admin, iframe_srcdoc, is_htmx,
-    is_unique_violation, render, render_not_found, require_user,
+    apply_breadcrumb_placeholder, apply_seo_placeholders,
+    apply_site_template, apply_term_placeholders, csrf_token,
+    get_is_admin, iframe_srcdoc, is_htmx, is_unique_violation,
+    render, render_not_found, require_user,
 };
 
 use crate::web::state::AppState;
@@ -65,6 +68,7 @@ pub async fn admin_templates_list(
     render(AdminTemplatesListTemplate {
         templates,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
@@ -80,11 +84,12 @@ pub async fn admin_template_new(
 
     let is_admin = get_is_admin(&req);
     let content_items = fetch_content_items(&state.pool, uid).await;
-    let starter_html = "<!doctype html>\n<html lang=\"en\">\n  <head>\n    <meta charset=\"utf-8\"/>\n    <meta name=\"viewport\" content=\"width=device-width,initial-scale=1\"/>\n    <title>{{title}}</title>\n    <link rel=\"stylesheet\" href=\"/static/app.css\"/>\n  </head>\n  <body>\n    <header class=\"topbar\">\n      <div class=\"container\">\n        <a class=\"brand\" href=\"/\">RustPress</a>\n        <nav class=\"nav\"><a href=\"/admin\">Admin</a></nav>\n      </div>\n    </header>\n    <main class=\"container\">\n      <article class=\"card\">\n        <h1>{{title}}</h1>\n        <div class=\"prose\">{{content}}</div>\n      </article>\n    </main>\n  </body>\n</html>\n".to_string();
+    let starter_html = "<!doctype html>\n<html lang=\"en\">\n  <head>\n    <meta charset=\"utf-8\"/>\n    <meta name=\"viewport\" content=\"width=device-width,initial-scale=1\"/>\n    <title>{{meta_title}}</title>\n    {{head_meta}}\n    <link rel=\"stylesheet\" href=\"/static/app.css\"/>\n  </head>\n  <body>\n    <header class=\"topbar\">\n      <div class=\"container\">\n        <a class=\"brand\" href=\"/\">RustPress</a>\n        <nav class=\"nav\"><a href=\"/admin\">Admin</a></nav>\n      </div>\n    </header>\n    <main class=\"container\">\n      <article class=\"card\">\n        <h1>{{title}}</h1>\n        <div class=\"prose\">{{content}}</div>\n      </article>\n    </main>\n  </body>\n</html>\n".to_string();
     render(AdminTemplateNewTemplate {
         starter_html,
         content_items,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
@@ -180,7 +185,14 @@ pub async fn admin_template_preview(
                         if can_view {
                             (
                                 item.title.clone(),
-                                item.content.clone(),
+                                state
+                                    .html_policy
+                                    .for_display(
+                                        item.html(),
+                                        item.format,
+                                        item.unfiltered_html,
+                                    )
+                                    .into_owned(),
                                 item.slug.clone(),
                                 item.kind.as_str().to_string(),
                             )
@@ -228,6 +240,16 @@ pub async fn admin_template_preview(
     let html = apply_site_template(
         &form.html, &title, &content, &slug, &kind,
     );
+    let html = apply_term_placeholders(&html, &[]);
+    let html = apply_breadcrumb_placeholder(&html, &[], &title);
+    let base = &state.public_url;
+    let (seo_kind, url) = match kind.as_str() {
+        "page" => (ContentKind::Page, format!("{base}/{slug}")),
+        _ => (ContentKind::Post, format!("{base}/blog/{slug}")),
+    };
+    let seo =
+        SeoMeta::derived(seo_kind, &title, &content, &url, base);
+    let html = apply_seo_placeholders(&html, &seo);
 
     HttpResponse::Ok()
         .content_type("text/html; charset=utf-8")
@@ -265,6 +287,7 @@ pub async fn admin_template_edit(
         template,
         content_items,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
@@ -337,6 +360,7 @@ pub async fn admin_template_update(
             template: updated,
             content_items,
             is_admin,
+            csrf_token: csrf_token(&req),
         })
     } else {
         HttpResponse::SeeOther()
@@ -0,0 +1,383 @@
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, get, post, web,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::{
+    ContentKind, Taxonomy, Term, TermCreate, TermUpdate, term_tree,
+};
+
+use crate::web::forms::{AdminTermForm, ContentTermsForm};
+use crate::web::handlers::admin_content::load_changeable_item;
+use crate::web::helpers::{
+    csrf_token, get_is_admin, is_unique_violation, render,
+    render_not_found, require_user,
+};
+use crate::web::state::AppState;
+use crate::web::templates::{
+    AdminTermEditTemplate, AdminTermsTemplate,
+};
+
+#[derive(Deserialize)]
+pub struct TermsQuery {
+    pub taxonomy: Option<Taxonomy>,
+}
+
+fn slug_taken_message(taxonomy: Taxonomy) -> String {
+    format!("A {taxonomy} with this slug already exists")
+}
+
+async fn render_list(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    taxonomy: Taxonomy,
+    error: Option<String>,
+    success: Option<String>,
+) -> HttpResponse {
+    let terms = db::list_terms(pool, taxonomy)
+        .await
+        .map(term_tree)
+        .unwrap_or_default();
+    render(AdminTermsTemplate {
+        taxonomy,
+        terms,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
+        error,
+        success,
+    })
+}
+
+async fn render_edit(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    term: Term,
+    error: Option<String>,
+    success: Option<String>,
+) -> HttpResponse {
+    // A category cannot sit below itself; deeper cycles are rejected
+    // on save.
+    let parents = if term.taxonomy.is_hierarchical() {
+        db::list_terms(pool, term.taxonomy)
+            .await
+            .map(|terms| {
+                term_tree(terms)
+                    .into_iter()
+                    .filter(|n| n.term.id != term.id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    render(AdminTermEditTemplate {
+        term,
+        parents,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
+        error,
+        success,
+    })
+}
+
+/// Check a requested parent: an existing category other than `term_id`
+/// and not one of its descendants.
+async fn validate_parent(
+    pool: &db::PgPool,
+    taxonomy: Taxonomy,
+    term_id: Option<Uuid>,
+    parent_id: Option<Uuid>,
+) -> Result<Option<Uuid>, String> {
+    let Some(parent_id) = parent_id else {
+        return Ok(None);
+    };
+    if !taxonomy.is_hierarchical() {
+        return Err("Tags cannot have a parent".into());
+    }
+    let parent = db::get_term_by_id(pool, parent_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .filter(|p| p.taxonomy == taxonomy)
+        .ok_or("Parent category not found")?;
+    if let Some(term_id) = term_id
+        && db::is_term_ancestor_or_self(pool, term_id, parent.id)
+            .await
+            .map_err(|e| e.to_string())?
+    {
+        return Err(
+            "A category cannot be moved below itself or its subcategories"
+                .into(),
+        );
+    }
+    Ok(Some(parent.id))
+}
+
+#[get("/admin/terms")]
+pub async fn terms_list(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<TermsQuery>,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    let taxonomy = query.taxonomy.unwrap_or_default();
+    render_list(&state.pool, &req, taxonomy, None, None).await
+}
+
+#[post("/admin/terms")]
+pub async fn terms_create(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<AdminTermForm>,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    let taxonomy = form.taxonomy.unwrap_or_default();
+    let fail = |msg: String| {
+        render_list(&state.pool, &req, taxonomy, Some(msg), None)
+    };
+
+    if let Err(e) = form.validate() {
+        return fail(e.to_string()).await;
+    }
+    let parent_id = match validate_parent(
+        &state.pool,
+        taxonomy,
+        None,
+        form.parent_id().ok().flatten(),
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(e) => return fail(e).await,
+    };
+
+    let data = TermCreate {
+        taxonomy,
+        slug: form.slug(),
+        name: form.name.trim().to_string(),
+        description: form.description.trim().to_string(),
+        parent_id,
+    };
+    match db::create_term(&state.pool, &data).await {
+        Ok(term) => {
+            render_list(
+                &state.pool,
+                &req,
+                taxonomy,
+                None,
+                Some(format!("Created \"{}\"", term.name)),
+            )
+            .await
+        }
+        Err(e) if is_unique_violation(&e) => {
+            fail(slug_taken_message(taxonomy)).await
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+#[get("/admin/terms/{id}")]
+pub async fn terms_edit(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    match db::get_term_by_id(&state.pool, path.into_inner()).await {
+        Ok(Some(term)) => {
+            render_edit(&state.pool, &req, term, None, None).await
+        }
+        Ok(None) => render_not_found(&req),
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+#[post("/admin/terms/{id}")]
+pub async fn terms_update(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<AdminTermForm>,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    let id = path.into_inner();
+    let term = match db::get_term_by_id(&state.pool, id).await {
+        Ok(Some(term)) => term,
+        Ok(None) => return render_not_found(&req),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+
+    if let Err(e) = form.validate() {
+        return render_edit(
+            &state.pool,
+            &req,
+            term,
+            Some(e.to_string()),
+            None,
+        )
+        .await;
+    }
+    let parent_id = match validate_parent(
+        &state.pool,
+        term.taxonomy,
+        Some(id),
+        form.parent_id().ok().flatten(),
+    )
+    .await
+    {
+        Ok(p) => p,
+        Err(e) => {
+            return render_edit(
+                &state.pool,
+                &req,
+                term,
+                Some(e),
+                None,
+            )
+            .await;
+        }
+    };
+
+    let update = TermUpdate {
+        slug: Some(form.slug()),
+        name: Some(form.name.trim().to_string()),
+        description: Some(form.description.trim().to_string()),
+        parent_id: Some(parent_id),
+    };
+    match db::update_term(&state.pool, id, &update).await {
+        Ok(Some(updated)) => {
+            render_edit(
+                &state.pool,
+                &req,
+                updated,
+                None,
+                Some("Saved".to_string()),
+            )
+            .await
+        }
+        Ok(None) => render_not_found(&req),
+        Err(e) if is_unique_violation(&e) => {
+            let message = slug_taken_message(term.taxonomy);
+            render_edit(&state.pool, &req, term, Some(message), None)
+                .await
+        }
+        Err(e) => {
+            HttpResponse::InternalServerError().body(e.to_string())
+        }
+    }
+}
+
+#[post("/admin/terms/{id}/delete")]
+pub async fn terms_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    if let Err(resp) = require_user(&req) {
+        return resp;
+    }
+    let id = path.into_inner();
+    let term = match db::get_term_by_id(&state.pool, id).await {
+        Ok(Some(term)) => term,
+        Ok(None) => return render_not_found(&req),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    if let Err(e) = db::delete_term(&state.pool, id).await {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+    HttpResponse::SeeOther()
+        .insert_header((
+            "Location",
+            format!("/admin/terms?taxonomy={}", term.taxonomy),
+        ))
+        .finish()
+}
+
+/// Assign categories and tags from the edit page. Tags that do not
+/// exist yet are created.
+#[post("/admin/edit/{id}/terms")]
+pub async fn content_terms_update(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    form: web::Form<Vec<(String, String)>>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let id = path.into_inner();
+
+    let item = match load_changeable_item(&state.pool, &req, id, uid)
+        .await
+    {
+        Ok(item) => item,
+        Err(resp) => return resp,
+    };
+    if item.kind != ContentKind::Post {
+        return HttpResponse::BadRequest()
+            .body("Only posts have categories and tags");
+    }
+
+    let terms = match ContentTermsForm::from_pairs(&form) {
+        Ok(terms) => terms,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+
+    let mut tag_ids = Vec::with_capacity(terms.tags.len());
+    for (slug, name) in &terms.tags {
+        match db::get_or_create_tag(&state.pool, slug, name).await {
+            Ok(tag) => tag_ids.push(tag.id),
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        }
+    }
+
+    for (taxonomy, ids) in [
+        (Taxonomy::Category, &terms.categories),
+        (Taxonomy::Tag, &tag_ids),
+    ] {
+        if let Err(e) =
+            db::set_content_terms(&state.pool, id, taxonomy, ids)
+                .await
+        {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    }
+
+    HttpResponse::SeeOther()
+        .insert_header(("Location", format!("/admin/edit/{id}")))
+        .finish()
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(terms_list)
+        .service(terms_create)
+        .service(terms_edit)
+        .service(terms_update)
+        .service(terms_delete)
+        .service(content_terms_update);
+}
@@ -0,0 +1,239 @@
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, get, post, web,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::{
+    ContentItem, ContentKind, DEFAULT_TRASH_RETENTION_DAYS,
+};
+
+use crate::web::handlers::admin_content::{
+    RESTORED_NOTE, ensure_can_save, record_note,
+};
+use crate::web::helpers::{
+    csrf_token, get_is_admin, is_unique_violation, render,
+    render_not_found, require_user,
+};
+use crate::web::state::AppState;
+use crate::web::templates::AdminTrashTemplate;
+
+#[derive(Deserialize)]
+pub struct TrashQuery {
+    pub kind: Option<ContentKind>,
+}
+
+async fn render_trash(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    uid: Uuid,
+    kind: ContentKind,
+    error: Option<String>,
+    success: Option<String>,
+) -> HttpResponse {
+    let items = match db::list_trashed_content_for_user(
+        pool, kind, uid,
+    )
+    .await
+    {
+        Ok(items) => items,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let owner_ids: Vec<Uuid> =
+        items.iter().filter_map(|i| i.owner_user_id).collect();
+    let authors = db::get_user_email_map(pool, &owner_ids)
+        .await
+        .unwrap_or_default();
+    let retention_days = db::get_default_site(pool)
+        .await
+        .ok()
+        .flatten()
+        .map_or(DEFAULT_TRASH_RETENTION_DAYS, |s| {
+            s.trash_retention_days
+        });
+    render(AdminTrashTemplate {
+        kind,
+        items,
+        authors,
+        retention_days,
+        is_admin: get_is_admin(req),
+        error,
+        success,
+        csrf_token: csrf_token(req),
+    })
+}
+
+/// Load a trashed item that `uid` could change before it was trashed.
+async fn load_trashed(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<ContentItem, HttpResponse> {
+    let item = match db::get_trashed_content_by_id(pool, id).await {
+        Ok(Some(item)) => item,
+        Ok(None) => return Err(render_not_found(req)),
+        Err(e) => {
+            return Err(HttpResponse::InternalServerError()
+                .body(e.to_string()));
+        }
+    };
+    match db::can_edit_content(pool, &item, uid).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err(HttpResponse::Forbidden().body("Forbidden"));
+        }
+        Err(e) => {
+            return Err(HttpResponse::InternalServerError()
+                .body(e.to_string()));
+        }
+    }
+    ensure_can_save(pool, uid, &item, None).await?;
+    Ok(item)
+}
+
+fn slug_taken_message(item: &ContentItem, holder: &str) -> String {
+    format!(
+        "\"{holder}\" now uses the slug \"{}\". Change its slug or \
+         move it to the trash, then restore this {} again.",
+        item.slug, item.kind
+    )
+}
+
+#[get("/admin/trash")]
+pub async fn trash_list(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<TrashQuery>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let kind = query.kind.unwrap_or_default();
+    render_trash(&state.pool, &req, uid, kind, None, None).await
+}
+
+#[post("/admin/trash/{id}/restore")]
+pub async fn trash_restore(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let item =
+        match load_trashed(&state.pool, &req, path.into_inner(), uid)
+            .await
+        {
+            Ok(item) => item,
+            Err(resp) => return resp,
+        };
+    let fail = |msg: String| {
+        render_trash(
+            &state.pool,
+            &req,
+            uid,
+            item.kind,
+            Some(msg),
+            None,
+        )
+    };
+
+    // Another item may have taken the slug while this one was trashed.
+    match db::find_slug_conflict(&state.pool, &item).await {
+        Ok(Some(holder)) => {
+            return fail(slug_taken_message(&item, &holder.title))
+                .await;
+        }
+        Ok(None) => {}
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    }
+
+    let restored =
+        match db::restore_trashed_content(&state.pool, item.id).await
+        {
+            Ok(Some(item)) => item,
+            Ok(None) => return render_not_found(&req),
+            Err(e) if is_unique_violation(&e) => {
+                return fail(slug_taken_message(
+                    &item,
+                    "Another item",
+                ))
+                .await;
+            }
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+    if let Err(e) =
+        record_note(&state.pool, &restored, uid, RESTORED_NOTE).await
+    {
+        return HttpResponse::InternalServerError()
+            .body(e.to_string());
+    }
+
+    render_trash(
+        &state.pool,
+        &req,
+        uid,
+        restored.kind,
+        None,
+        Some(format!("Restored \"{}\" as a draft", restored.title)),
+    )
+    .await
+}
+
+#[post("/admin/trash/{id}/delete")]
+pub async fn trash_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let item =
+        match load_trashed(&state.pool, &req, path.into_inner(), uid)
+            .await
+        {
+            Ok(item) => item,
+            Err(resp) => return resp,
+        };
+
+    match db::delete_content(&state.pool, item.id).await {
+        Ok(true) => {}
+        Ok(false) => return render_not_found(&req),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    }
+
+    render_trash(
+        &state.pool,
+        &req,
+        uid,
+        item.kind,
+        None,
+        Some(format!("Deleted \"{}\" permanently", item.title)),
+    )
+    .await
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(trash_list)
+        .service(trash_restore)
+        .service(trash_delete);
+}
@@ -4,12 +4,12 @@ use actix_web::{
 use uuid::Uuid;
 
 use rustpress::db;
-use rustpress::models::User;
+use rustpress::models::{Capability, User};
 use rustpress::services::PasswordManager;
 
 use super::super::forms::{AdminCreateUserForm, AdminUpdateUserForm};
 use super::super::helpers::{
-    get_is_admin, load_user, render, require_user,
+    csrf_token, get_is_admin, load_user, render, require_user,
 };
 use super::super::security::{
     PasswordValidator, generic_error_message, validate_email,
@@ -23,17 +23,31 @@ use super::super::templates::{
 async fn render_edit(
     pool: &db::PgPool,
     user: User,
-    is_admin: bool,
+    req: &HttpRequest,
     error: Option<String>,
     success: Option<String>,
 ) -> HttpResponse {
     let target_roles = db::get_user_role_names(pool, user.id)
         .await
         .unwrap_or_default();
+    let target_can_publish =
+        db::user_has_capability(pool, user.id, Capability::Publish)
+            .await
+            .unwrap_or(false);
+    let target_unfiltered_html = db::user_has_capability(
+        pool,
+        user.id,
+        Capability::UnfilteredHtml,
+    )
+    .await
+    .unwrap_or(false);
     render(AdminUserEditTemplate {
         target_user: user,
         target_roles,
-        is_admin,
+        target_can_publish,
+        target_unfiltered_html,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
         error,
         success,
     })
@@ -42,7 +56,7 @@ async fn render_edit(
 async fn render_list(
     pool: &db::PgPool,
     current_user_id: Uuid,
-    is_admin: bool,
+    req: &HttpRequest,
     error: Option<String>,
     success: Option<String>,
 ) -> HttpResponse {
@@ -52,7 +66,8 @@ async fn render_list(
     render(AdminUsersListTemplate {
         users,
         current_user_id,
-        is_admin,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
         error,
         success,
     })
@@ -67,8 +82,7 @@ pub async fn users_list(
         Ok(uid) => uid,
         Err(resp) => return resp,
     };
-    render_list(&state.pool, uid, get_is_admin(&req), None, None)
-        .await
+    render_list(&state.pool, uid, &req, None, None).await
 }
 
 #[get("/admin/users/new")]
@@ -78,6 +92,7 @@ pub async fn users_new(req: HttpRequest) -> impl Responder {
     }
     render(AdminUserNewTemplate {
         is_admin: get_is_admin(&req),
+        csrf_token: csrf_token(&req),
         error: None,
     })
 }
@@ -98,6 +113,7 @@ pub async fn users_create(
     if let Err(e) = form.validate() {
         return render(AdminUserNewTemplate {
             is_admin,
+            csrf_token: csrf_token(&req),
             error: Some(e.to_string()),
         });
     }
@@ -110,6 +126,7 @@ pub async fn users_create(
             log::error!("Password hashing error: {}", e);
             return render(AdminUserNewTemplate {
                 is_admin,
+                csrf_token: csrf_token(&req),
                 error: Some(generic_error_message("user creation")),
             });
         }
@@ -121,6 +138,7 @@ pub async fn users_create(
         Ok(None) => {
             return render(AdminUserNewTemplate {
                 is_admin,
+                csrf_token: csrf_token(&req),
                 error: Some(
                     "A user with this email already exists".into(),
                 ),
@@ -130,6 +148,7 @@ pub async fn users_create(
             log::error!("Database error creating user: {}", e);
             return render(AdminUserNewTemplate {
                 is_admin,
+                csrf_token: csrf_token(&req),
                 error: Some(generic_error_message("user creation")),
             });
         }
@@ -160,14 +179,7 @@ pub async fn users_edit(
             Ok(u) => u,
             Err(resp) => return resp,
         };
-    render_edit(
-        &state.pool,
-        target_user,
-        get_is_admin(&req),
-        None,
-        None,
-    )
-    .await
+    render_edit(&state.pool, target_user, &req, None, None).await
 }
 
 #[post("/admin/users/{id}/edit")]
@@ -181,7 +193,6 @@ pub async fn users_update(
         return resp;
     }
 
-    let is_admin = get_is_admin(&req);
     let target_id = path.into_inner();
     let target_user = match load_user(&state.pool, target_id).await {
         Ok(u) => u,
@@ -193,7 +204,7 @@ pub async fn users_update(
         return render_edit(
             &state.pool,
             target_user,
-            is_admin,
+            &req,
             Some("Invalid email address".into()),
             None,
         )
@@ -207,7 +218,7 @@ pub async fn users_update(
         return render_edit(
             &state.pool,
             target_user,
-            is_admin,
+            &req,
             Some(generic_error_message("email update")),
             None,
         )
@@ -216,6 +227,20 @@ pub async fn users_update(
 
     let _ =
         db::set_user_role(&state.pool, target_id, form.role).await;
+    let _ = db::set_user_capability(
+        &state.pool,
+        target_id,
+        Capability::Publish,
+        form.can_publish.is_some(),
+    )
+    .await;
+    let _ = db::set_user_capability(
+        &state.pool,
+        target_id,
+        Capability::UnfilteredHtml,
+        form.unfiltered_html.is_some(),
+    )
+    .await;
 
     // Optional password change
     if let Some(pw) = &form.new_password {
@@ -228,7 +253,7 @@ pub async fn users_update(
                 return render_edit(
                     &state.pool,
                     user,
-                    is_admin,
+                    &req,
                     Some(msg),
                     None,
                 )
@@ -250,7 +275,7 @@ pub async fn users_update(
                         return render_edit(
                             &state.pool,
                             user,
-                            is_admin,
+                            &req,
                             Some(generic_error_message(
                                 "password update",
                             )),
@@ -258,6 +283,20 @@ pub async fn users_update(
                         )
                         .await;
                     }
+
+                    // A password reset by an admin signs the user out everywhere.
+                    if let Err(e) = db::delete_sessions_for_user(
+                        &state.pool,
+                        target_id,
+                    )
+                    .await
+                    {
+                        log::error!(
+                            "Failed to revoke sessions for {}: {}",
+                            target_id,
+                            e
+                        );
+                    }
                 }
                 Err(e) => {
                     log::error!("Password hashing error: {}", e);
@@ -267,7 +306,7 @@ pub async fn users_update(
                     return render_edit(
                         &state.pool,
                         user,
-                        is_admin,
+                        &req,
                         Some(generic_error_message(
                             "password hashing",
                         )),
@@ -285,7 +324,7 @@ pub async fn users_update(
     render_edit(
         &state.pool,
         updated_user,
-        is_admin,
+        &req,
         None,
         Some("User updated".into()),
     )
@@ -303,14 +342,13 @@ pub async fn users_delete(
         Err(resp) => return resp,
     };
 
-    let is_admin = get_is_admin(&req);
     let target_id = path.into_inner();
 
     if target_id == uid {
         return render_list(
             &state.pool,
             uid,
-            is_admin,
+            &req,
             Some("You cannot delete your own account".into()),
             None,
         )
@@ -327,7 +365,7 @@ pub async fn users_delete(
         return render_list(
             &state.pool,
             uid,
-            is_admin,
+            &req,
             Some("Cannot delete the last admin".into()),
             None,
         )
@@ -340,17 +378,27 @@ pub async fn users_delete(
         return render_list(
             &state.pool,
             uid,
-            is_admin,
+            &req,
             Some(generic_error_message("user deletion")),
             None,
         )
         .await;
     }
 
+    if let Err(e) =
+        db::delete_sessions_for_user(&state.pool, target_id).await
+    {
+        log::error!(
+            "Failed to revoke sessions for {}: {}",
+            target_id,
+            e
+        );
+    }
+
     render_list(
         &state.pool,
         uid,
-        is_admin,
+        &req,
         None,
         Some("User deleted".into()),
     )
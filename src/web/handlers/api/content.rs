@@ -0,0 +1,682 @@
+use actix_web::{
+    HttpRequest, HttpResponse, delete, get, patch, post, put, web,
+};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::{
+    ContentCreate, ContentFilter, ContentItem, ContentItemRevision,
+    ContentItemRevisionMeta, ContentKind, ContentSchedule,
+    ContentSeo, ContentStatus, ContentUpdate, PageMove, page_offset,
+};
+use rustpress::services::convert_content;
+
+use super::{ApiError, ApiErrorBody, api_user};
+use crate::web::forms::{
+    AdminCreateForm, ApiContentListQuery, MAX_MENU_ORDER,
+    validate_content_update, validate_schedule,
+};
+use crate::web::handlers::admin_content::{
+    TRASHED_NOTE, publish_denied_reason, record_note,
+    save_denied_reason, validate_page_parent,
+};
+use crate::web::handlers::admin_history::RevisionsQuery;
+use crate::web::helpers::{authored_content, is_unique_violation};
+use crate::web::state::AppState;
+
+const SLUG_CONFLICT_MESSAGE: &str =
+    "Slug already exists for this content type";
+
+/// Collection named in the path; each holds one kind of content.
+#[derive(Debug, Clone, Copy, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ContentCollection {
+    Posts,
+    Pages,
+}
+
+impl ContentCollection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Posts => "posts",
+            Self::Pages => "pages",
+        }
+    }
+
+    pub fn kind(&self) -> ContentKind {
+        match self {
+            Self::Posts => ContentKind::Post,
+            Self::Pages => ContentKind::Page,
+        }
+    }
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct ContentListResponse {
+    pub items: Vec<ContentItem>,
+    pub page: i64,
+    pub per_page: i64,
+    pub total: i64,
+}
+
+fn slug_conflict(e: sqlx::Error) -> ApiError {
+    if is_unique_violation(&e) {
+        ApiError::conflict(SLUG_CONFLICT_MESSAGE)
+    } else {
+        e.into()
+    }
+}
+
+/// Load an item of the collection's kind that `uid` may see. Items of
+/// the other kind, or hidden from the user, are reported as missing.
+async fn load_viewable_item(
+    pool: &db::PgPool,
+    collection: ContentCollection,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<ContentItem, ApiError> {
+    let item = db::get_content_by_id(pool, id)
+        .await?
+        .filter(|item| item.kind == collection.kind())
+        .ok_or_else(ApiError::not_found)?;
+    if !db::can_view_content(pool, &item, uid).await? {
+        return Err(ApiError::not_found());
+    }
+    Ok(item)
+}
+
+async fn load_editable_item(
+    pool: &db::PgPool,
+    collection: ContentCollection,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<ContentItem, ApiError> {
+    let item = load_viewable_item(pool, collection, id, uid).await?;
+    if !db::can_edit_content(pool, &item, uid).await? {
+        return Err(ApiError::forbidden(
+            "You do not have permission to edit this item",
+        ));
+    }
+    Ok(item)
+}
+
+/// Load an item for a change by `uid` that leaves its status alone.
+/// Live items also need the publish permission.
+async fn load_changeable_item(
+    pool: &db::PgPool,
+    collection: ContentCollection,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<ContentItem, ApiError> {
+    let item = load_editable_item(pool, collection, id, uid).await?;
+    ensure_can_save(pool, uid, &item, item.status).await?;
+    Ok(item)
+}
+
+async fn ensure_can_publish(
+    pool: &db::PgPool,
+    uid: Uuid,
+) -> Result<(), ApiError> {
+    match publish_denied_reason(pool, uid).await? {
+        None => Ok(()),
+        Some(reason) => Err(ApiError::forbidden(reason)),
+    }
+}
+
+/// Refuse changes to `item` that need the publish permission `uid`
+/// lacks; `next` is the status it is saved with.
+async fn ensure_can_save(
+    pool: &db::PgPool,
+    uid: Uuid,
+    item: &ContentItem,
+    next: ContentStatus,
+) -> Result<(), ApiError> {
+    match save_denied_reason(pool, uid, item.status, next).await? {
+        None => Ok(()),
+        Some(reason) => Err(ApiError::forbidden(reason)),
+    }
+}
+
+/// Record an explicit change in the item's revision history, noting a
+/// move away from the `previous` status.
+async fn record_revision(
+    pool: &db::PgPool,
+    item: &mut ContentItem,
+    previous: ContentStatus,
+    uid: Uuid,
+) -> Result<(), ApiError> {
+    db::ensure_initial_revision(pool, item.id, Some(uid)).await?;
+    item.current_rev =
+        db::record_change(pool, item, previous, Some(uid)).await?;
+    Ok(())
+}
+
+/// List posts or pages visible to the caller, newest first.
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path), ApiContentListQuery),
+    responses(
+        (status = 200, body = ContentListResponse),
+        (status = 400, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:read"]))
+)]
+#[get("/{kind:posts|pages}")]
+pub async fn api_content_list(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<ContentCollection>,
+    query: web::Query<ApiContentListQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let kind = path.kind();
+
+    let filter = ContentFilter {
+        status: query.status,
+        owner_user_id: query.owner,
+        slug: query
+            .slug
+            .as_deref()
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string),
+    };
+    let (page, per_page) = (query.page(), query.per_page());
+
+    let items = db::list_filtered_content_for_user(
+        &state.pool,
+        kind,
+        &filter,
+        uid,
+        per_page,
+        page_offset(page, per_page),
+    )
+    .await?;
+    let total = db::count_filtered_content_for_user(
+        &state.pool,
+        kind,
+        &filter,
+        uid,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(ContentListResponse {
+        items,
+        page,
+        per_page,
+        total,
+    }))
+}
+
+/// Create a draft owned by the caller.
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path)),
+    request_body = AdminCreateForm,
+    responses(
+        (status = 201, body = ContentItem),
+        (status = 400, body = ApiErrorBody),
+        (status = 409, description = "Slug already taken", body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:write"]))
+)]
+#[post("/{kind:posts|pages}")]
+pub async fn api_content_create(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<ContentCollection>,
+    body: web::Json<AdminCreateForm>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    body.validate().map_err(ApiError::bad_request)?;
+
+    let authored =
+        authored_content(&state, uid, body.format, &body.content)
+            .await?;
+    let data = ContentCreate {
+        owner_user_id: Some(uid),
+        kind: path.kind(),
+        title: body.title.trim().to_string(),
+        slug: body.slug.trim().to_string(),
+        content: authored.content,
+        template: body
+            .template
+            .as_deref()
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .unwrap_or("default")
+            .to_string(),
+        unfiltered_html: authored.unfiltered_html,
+        format: authored.format,
+        rendered_html: authored.rendered_html,
+    };
+
+    let created = db::create_content(&state.pool, &data)
+        .await
+        .map_err(slug_conflict)?;
+    db::ensure_initial_revision(&state.pool, created.id, Some(uid))
+        .await?;
+
+    Ok(HttpResponse::Created()
+        .insert_header((
+            "Location",
+            format!("/api/v1/{}/{}", path.as_str(), created.id),
+        ))
+        .json(created))
+}
+
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path), ("id" = Uuid, Path)),
+    responses(
+        (status = 200, body = ContentItem),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:read"]))
+)]
+#[get("/{kind:posts|pages}/{id}")]
+pub async fn api_content_get(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id) = path.into_inner();
+
+    let item =
+        load_viewable_item(&state.pool, collection, id, uid).await?;
+    Ok(HttpResponse::Ok().json(item))
+}
+
+/// Change the fields present in the body; others keep their value.
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path), ("id" = Uuid, Path)),
+    request_body = ContentUpdate,
+    responses(
+        (status = 200, body = ContentItem),
+        (status = 400, body = ApiErrorBody),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+        (status = 409, description = "Slug already taken", body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:write"]))
+)]
+#[patch("/{kind:posts|pages}/{id}")]
+pub async fn api_content_update(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid)>,
+    body: web::Json<ContentUpdate>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id) = path.into_inner();
+    validate_content_update(&body).map_err(ApiError::bad_request)?;
+
+    let existing =
+        load_editable_item(&state.pool, collection, id, uid).await?;
+
+    ensure_can_save(
+        &state.pool,
+        uid,
+        &existing,
+        body.status.unwrap_or(existing.status),
+    )
+    .await?;
+
+    let trimmed = |s: &Option<String>| {
+        s.as_deref().map(|s| s.trim().to_string())
+    };
+    // A new format without new content converts the stored content.
+    let format = body.format.unwrap_or(existing.format);
+    let source = match body.content.as_deref() {
+        Some(source) => Some(source.to_string()),
+        None if format != existing.format => Some(convert_content(
+            &existing.content,
+            existing.format,
+            format,
+        )),
+        None => None,
+    };
+    let (content, unfiltered_html, rendered_html) = match source {
+        Some(source) => {
+            let authored =
+                authored_content(&state, uid, format, &source)
+                    .await?;
+            (
+                Some(authored.content),
+                authored.unfiltered_html,
+                authored.rendered_html,
+            )
+        }
+        None => (None, false, None),
+    };
+    let update = ContentUpdate {
+        title: trimmed(&body.title),
+        slug: trimmed(&body.slug),
+        content,
+        template: trimmed(&body.template),
+        status: body.status,
+        unfiltered_html,
+        format: Some(format),
+        rendered_html,
+    };
+
+    let mut updated = db::update_content(&state.pool, id, &update)
+        .await
+        .map_err(slug_conflict)?
+        .ok_or_else(ApiError::not_found)?;
+    record_revision(&state.pool, &mut updated, existing.status, uid)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path), ("id" = Uuid, Path)),
+    responses(
+        (status = 200, body = ContentItem),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:write"]))
+)]
+#[post("/{kind:posts|pages}/{id}/publish")]
+pub async fn api_content_publish(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id) = path.into_inner();
+
+    let existing =
+        load_editable_item(&state.pool, collection, id, uid).await?;
+    ensure_can_publish(&state.pool, uid).await?;
+
+    let mut published = db::publish_content(&state.pool, id)
+        .await?
+        .ok_or_else(ApiError::not_found)?;
+    record_revision(
+        &state.pool,
+        &mut published,
+        existing.status,
+        uid,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(published))
+}
+
+/// Schedule an unpublished item to go live at `publish_at`, and
+/// optionally to be taken down at `unpublish_at`. A published item only
+/// accepts `unpublish_at`.
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path), ("id" = Uuid, Path)),
+    request_body = ContentSchedule,
+    responses(
+        (status = 200, body = ContentItem),
+        (status = 400, body = ApiErrorBody),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:write"]))
+)]
+#[put("/{kind:posts|pages}/{id}/schedule")]
+pub async fn api_content_schedule(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid)>,
+    body: web::Json<ContentSchedule>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id) = path.into_inner();
+
+    let existing =
+        load_changeable_item(&state.pool, collection, id, uid)
+            .await?;
+    validate_schedule(existing.status, &body, Utc::now())
+        .map_err(ApiError::bad_request)?;
+    if body.publish_at.is_some() {
+        ensure_can_publish(&state.pool, uid).await?;
+    }
+
+    let mut scheduled = db::schedule_content(&state.pool, id, &body)
+        .await?
+        .ok_or_else(ApiError::not_found)?;
+    record_revision(
+        &state.pool,
+        &mut scheduled,
+        existing.status,
+        uid,
+    )
+    .await?;
+
+    Ok(HttpResponse::Ok().json(scheduled))
+}
+
+/// Move a page under `parent_id`, or to the top level when it is
+/// missing, and optionally set its `menu_order` among its siblings. The
+/// page's old paths redirect to the new ones.
+#[utoipa::path(
+    tag = "content",
+    params(("id" = Uuid, Path)),
+    request_body = PageMove,
+    responses(
+        (status = 200, body = ContentItem),
+        (status = 400, body = ApiErrorBody),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:write"]))
+)]
+#[put("/pages/{id}/parent")]
+pub async fn api_page_parent(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<PageMove>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let id = path.into_inner();
+
+    let existing = load_changeable_item(
+        &state.pool,
+        ContentCollection::Pages,
+        id,
+        uid,
+    )
+    .await?;
+    if body.menu_order.is_some_and(|o| o.abs() > MAX_MENU_ORDER) {
+        return Err(ApiError::bad_request(
+            "menu_order is out of range",
+        ));
+    }
+    validate_page_parent(&state.pool, &existing, body.parent_id, uid)
+        .await
+        .map_err(ApiError::bad_request)?;
+
+    let moved = db::move_page(&state.pool, id, &body)
+        .await?
+        .ok_or_else(ApiError::not_found)?;
+    Ok(HttpResponse::Ok().json(moved))
+}
+
+/// Drop the schedule. A scheduled item goes back to draft.
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path), ("id" = Uuid, Path)),
+    responses(
+        (status = 200, body = ContentItem),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:write"]))
+)]
+#[delete("/{kind:posts|pages}/{id}/schedule")]
+pub async fn api_content_schedule_clear(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id) = path.into_inner();
+
+    let existing =
+        load_changeable_item(&state.pool, collection, id, uid)
+            .await?;
+
+    let mut cleared = db::clear_schedule(&state.pool, id)
+        .await?
+        .ok_or_else(ApiError::not_found)?;
+    record_revision(&state.pool, &mut cleared, existing.status, uid)
+        .await?;
+
+    Ok(HttpResponse::Ok().json(cleared))
+}
+
+/// Replace an item's SEO fields. Empty strings fall back to values
+/// derived from the content.
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path), ("id" = Uuid, Path)),
+    request_body = ContentSeo,
+    responses(
+        (status = 200, body = ContentItem),
+        (status = 400, body = ApiErrorBody),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:write"]))
+)]
+#[put("/{kind:posts|pages}/{id}/seo")]
+pub async fn api_content_seo(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid)>,
+    body: web::Json<ContentSeo>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id) = path.into_inner();
+
+    load_changeable_item(&state.pool, collection, id, uid).await?;
+    let seo = body.trimmed();
+    seo.validate().map_err(ApiError::bad_request)?;
+
+    let updated = db::update_content_seo(&state.pool, id, &seo)
+        .await?
+        .ok_or_else(ApiError::not_found)?;
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+/// Move an item to the trash. Trashed items no longer show up in the
+/// API and are deleted for good after the site's retention period.
+#[utoipa::path(
+    tag = "content",
+    params(("kind" = ContentCollection, Path), ("id" = Uuid, Path)),
+    responses(
+        (status = 204, description = "Moved to the trash"),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:write"]))
+)]
+#[delete("/{kind:posts|pages}/{id}")]
+pub async fn api_content_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid)>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id) = path.into_inner();
+
+    load_changeable_item(&state.pool, collection, id, uid).await?;
+
+    let trashed = db::trash_content(&state.pool, id)
+        .await?
+        .ok_or_else(ApiError::not_found)?;
+    record_note(&state.pool, &trashed, uid, TRASHED_NOTE).await?;
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Revision history of an item, newest first.
+#[utoipa::path(
+    tag = "content",
+    params(
+        ("kind" = ContentCollection, Path),
+        ("id" = Uuid, Path),
+        RevisionsQuery,
+    ),
+    responses(
+        (status = 200, body = Vec<ContentItemRevisionMeta>),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:read"]))
+)]
+#[get("/{kind:posts|pages}/{id}/revisions")]
+pub async fn api_content_revisions(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid)>,
+    query: web::Query<RevisionsQuery>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id) = path.into_inner();
+
+    load_viewable_item(&state.pool, collection, id, uid).await?;
+    db::ensure_initial_revision(&state.pool, id, Some(uid)).await?;
+
+    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let revisions =
+        db::list_revisions(&state.pool, id, limit).await?;
+    Ok(HttpResponse::Ok().json(revisions))
+}
+
+#[utoipa::path(
+    tag = "content",
+    params(
+        ("kind" = ContentCollection, Path),
+        ("id" = Uuid, Path),
+        ("rev" = i32, Path),
+    ),
+    responses(
+        (status = 200, body = ContentItemRevision),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:read"]))
+)]
+#[get("/{kind:posts|pages}/{id}/revisions/{rev}")]
+pub async fn api_content_revision(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(ContentCollection, Uuid, i32)>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let (collection, id, rev) = path.into_inner();
+
+    load_viewable_item(&state.pool, collection, id, uid).await?;
+    let revision = db::get_revision(&state.pool, id, rev)
+        .await?
+        .ok_or_else(ApiError::not_found)?;
+    Ok(HttpResponse::Ok().json(revision))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(api_content_list)
+        .service(api_content_create)
+        .service(api_content_get)
+        .service(api_content_update)
+        .service(api_content_publish)
+        .service(api_content_schedule)
+        .service(api_content_schedule_clear)
+        .service(api_content_seo)
+        .service(api_page_parent)
+        .service(api_content_delete)
+        .service(api_content_revisions)
+        .service(api_content_revision);
+}
@@ -0,0 +1,71 @@
+//! Versioned JSON API under `/api/v1`.
+//!
+//! Requests authenticate with a personal API token (see
+//! [`crate::web::api_token`]); errors are JSON [`ApiError`] bodies.
+
+pub mod content;
+pub mod error;
+pub mod openapi;
+pub mod sites;
+pub mod templates;
+
+pub use error::{ApiError, ApiErrorBody};
+
+use actix_web::{HttpRequest, HttpResponse, ResponseError, web};
+use uuid::Uuid;
+
+use crate::web::forms::MAX_CONTENT_LENGTH;
+use crate::web::helpers::current_user_id;
+
+pub const API_PREFIX: &str = "/api/";
+pub const API_V1_PATH: &str = "/api/v1";
+/// Served without authentication so clients can be generated from it.
+pub const OPENAPI_PATH: &str = "/api/v1/openapi.json";
+
+/// Largest JSON body accepted; escaping can double the size of content.
+const MAX_JSON_BYTES: usize = 2 * MAX_CONTENT_LENGTH;
+
+/// Report a malformed body, query or path as a JSON error.
+fn extractor_error(e: impl ResponseError) -> actix_web::Error {
+    ApiError::new(e.status_code(), e.to_string()).into()
+}
+
+/// The token owner; the auth guard has already checked the token.
+fn api_user(req: &HttpRequest) -> Result<Uuid, ApiError> {
+    current_user_id(req).ok_or_else(|| {
+        ApiError::unauthorized("Authentication required")
+    })
+}
+
+async fn not_found() -> Result<HttpResponse, ApiError> {
+    Err(ApiError::not_found())
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::scope(API_V1_PATH)
+            .app_data(
+                web::JsonConfig::default()
+                    .limit(MAX_JSON_BYTES)
+                    .error_handler(|e, _| extractor_error(e)),
+            )
+            .app_data(
+                web::QueryConfig::default()
+                    .error_handler(|e, _| extractor_error(e)),
+            )
+            .app_data(
+                web::PathConfig::default()
+                    .error_handler(|e, _| extractor_error(e)),
+            )
+            .configure(openapi::configure)
+            .configure(content::configure)
+            .configure(templates::configure)
+            .configure(sites::configure)
+            .default_service(web::to(not_found)),
+    );
+}
+
+/// Whether a request path belongs to the JSON API.
+pub fn is_api_path(path: &str) -> bool {
+    path.starts_with(API_PREFIX)
+}
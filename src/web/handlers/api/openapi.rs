@@ -0,0 +1,90 @@
+//! OpenAPI 3 description of the JSON API, generated from the handler
+//! annotations and model types.
+
+use actix_web::{HttpResponse, get, web};
+use utoipa::openapi::security::{
+    HttpAuthScheme, HttpBuilder, SecurityScheme,
+};
+use utoipa::{Modify, OpenApi};
+
+use super::{content, sites, templates};
+
+#[derive(OpenApi)]
+#[openapi(
+    info(title = "RustPress API"),
+    servers((url = "/api/v1")),
+    paths(
+        openapi_json,
+        content::api_content_list,
+        content::api_content_create,
+        content::api_content_get,
+        content::api_content_update,
+        content::api_content_publish,
+        content::api_content_schedule,
+        content::api_content_schedule_clear,
+        content::api_content_seo,
+        content::api_page_parent,
+        content::api_content_delete,
+        content::api_content_revisions,
+        content::api_content_revision,
+        templates::api_template_list,
+        templates::api_template_create,
+        templates::api_template_get,
+        templates::api_template_update,
+        templates::api_template_delete,
+        sites::api_site_list,
+        sites::api_site_get,
+        sites::api_site_update,
+    ),
+    components(schemas(content::ContentCollection)),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "content", description = "Posts and pages"),
+        (name = "templates", description = "Site templates"),
+        (name = "sites", description = "Sites and their settings"),
+        (name = "meta", description = "This document"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Personal API tokens, sent as `Authorization: Bearer rp_pat_…`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_default();
+        components.add_security_scheme(
+            "bearer",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .description(Some(
+                        "Personal API token; each operation lists the scope it needs",
+                    ))
+                    .build(),
+            ),
+        );
+    }
+}
+
+/// The document as pretty-printed JSON.
+pub fn openapi_json_string() -> String {
+    ApiDoc::openapi()
+        .to_pretty_json()
+        .expect("OpenAPI document serializes")
+}
+
+/// This document. Public, so clients can be generated without a token.
+#[utoipa::path(
+    tag = "meta",
+    responses((status = 200, description = "OpenAPI 3 document", content_type = "application/json")),
+    security(())
+)]
+#[get("/openapi.json")]
+pub async fn openapi_json() -> HttpResponse {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(openapi_json);
+}
@@ -0,0 +1,112 @@
+use actix_web::{HttpRequest, HttpResponse, get, patch, web};
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::{Site, SiteUpdate};
+
+use super::{ApiError, ApiErrorBody, api_user};
+use crate::web::helpers::get_is_admin;
+use crate::web::state::AppState;
+
+/// Load a site owned by the user; other sites are reported as missing.
+async fn load_site(
+    pool: &db::PgPool,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<Site, ApiError> {
+    db::get_site_by_id(pool, id)
+        .await?
+        .filter(|s| s.owner_user_id == uid)
+        .ok_or_else(ApiError::not_found)
+}
+
+/// Sites owned by the caller, newest first.
+#[utoipa::path(
+    tag = "sites",
+    responses((status = 200, body = Vec<Site>)),
+    security(("bearer" = ["content:read"]))
+)]
+#[get("/sites")]
+pub async fn api_site_list(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let sites =
+        db::list_sites_for_user(&state.pool, uid, None).await?;
+    Ok(HttpResponse::Ok().json(sites))
+}
+
+#[utoipa::path(
+    tag = "sites",
+    params(("id" = Uuid, Path)),
+    responses(
+        (status = 200, body = Site),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:read"]))
+)]
+#[get("/sites/{id}")]
+pub async fn api_site_get(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let site = load_site(&state.pool, path.into_inner(), uid).await?;
+    Ok(HttpResponse::Ok().json(site))
+}
+
+/// Change site settings. Admin only, like the configuration page.
+#[utoipa::path(
+    tag = "sites",
+    params(("id" = Uuid, Path)),
+    request_body = SiteUpdate,
+    responses(
+        (status = 200, body = Site),
+        (status = 400, body = ApiErrorBody),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["users:admin"]))
+)]
+#[patch("/sites/{id}")]
+pub async fn api_site_update(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<SiteUpdate>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    if !get_is_admin(&req) {
+        return Err(ApiError::forbidden(
+            "Only admins can change site settings",
+        ));
+    }
+    let id = path.into_inner();
+    body.validate_homepage().map_err(ApiError::bad_request)?;
+    body.validate_trash_retention()
+        .map_err(ApiError::bad_request)?;
+    body.validate_robots_txt().map_err(ApiError::bad_request)?;
+    body.validate_posts_per_page()
+        .map_err(ApiError::bad_request)?;
+    if body.search_language.is_some() {
+        let languages =
+            db::list_search_languages(&state.pool).await?;
+        body.validate_search_language(&languages)
+            .map_err(ApiError::bad_request)?;
+    }
+
+    load_site(&state.pool, id, uid).await?;
+
+    let updated = db::update_site(&state.pool, id, uid, &body)
+        .await?
+        .ok_or_else(ApiError::not_found)?;
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(api_site_list)
+        .service(api_site_get)
+        .service(api_site_update);
+}
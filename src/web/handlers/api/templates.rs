@@ -0,0 +1,208 @@
+use actix_web::{
+    HttpRequest, HttpResponse, delete, get, patch, post, web,
+};
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::{
+    SiteTemplate, SiteTemplateCreate, SiteTemplateUpdate,
+};
+
+use super::{ApiError, ApiErrorBody, api_user};
+use crate::web::forms::{
+    AdminTemplateCreateForm, validate_template_update,
+};
+use crate::web::helpers::is_unique_violation;
+use crate::web::state::AppState;
+
+const NAME_CONFLICT_MESSAGE: &str = "Template name already exists";
+
+fn name_conflict(e: sqlx::Error) -> ApiError {
+    if is_unique_violation(&e) {
+        ApiError::conflict(NAME_CONFLICT_MESSAGE)
+    } else {
+        e.into()
+    }
+}
+
+/// Load a template the user can see: built-in, global or their own.
+async fn load_template(
+    pool: &db::PgPool,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<SiteTemplate, ApiError> {
+    db::get_site_template_by_id(pool, id)
+        .await?
+        .filter(|t| t.owner_user_id.is_none_or(|owner| owner == uid))
+        .ok_or_else(ApiError::not_found)
+}
+
+/// Load a template the user may change; built-ins are read-only.
+async fn load_owned_template(
+    pool: &db::PgPool,
+    id: Uuid,
+    uid: Uuid,
+) -> Result<SiteTemplate, ApiError> {
+    let template = load_template(pool, id, uid).await?;
+    if template.is_builtin {
+        return Err(ApiError::forbidden(
+            "Built-in templates are read-only",
+        ));
+    }
+    if template.owner_user_id != Some(uid) {
+        return Err(ApiError::forbidden(
+            "You do not have permission to change this template",
+        ));
+    }
+    Ok(template)
+}
+
+/// Built-in templates followed by the caller's own.
+#[utoipa::path(
+    tag = "templates",
+    responses((status = 200, body = Vec<SiteTemplate>)),
+    security(("bearer" = ["content:read"]))
+)]
+#[get("/templates")]
+pub async fn api_template_list(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let templates =
+        db::list_site_templates_for_user(&state.pool, uid).await?;
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+#[utoipa::path(
+    tag = "templates",
+    request_body = AdminTemplateCreateForm,
+    responses(
+        (status = 201, body = SiteTemplate),
+        (status = 400, body = ApiErrorBody),
+        (status = 409, description = "Name already taken", body = ApiErrorBody),
+    ),
+    security(("bearer" = ["templates:write"]))
+)]
+#[post("/templates")]
+pub async fn api_template_create(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Json<AdminTemplateCreateForm>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    body.validate().map_err(ApiError::bad_request)?;
+
+    let data = SiteTemplateCreate {
+        owner_user_id: uid,
+        name: body.name.trim().to_string(),
+        description: body.description.clone().unwrap_or_default(),
+        html: body.html.clone(),
+    };
+    let created = db::create_site_template(&state.pool, &data)
+        .await
+        .map_err(name_conflict)?;
+
+    Ok(HttpResponse::Created()
+        .insert_header((
+            "Location",
+            format!("/api/v1/templates/{}", created.id),
+        ))
+        .json(created))
+}
+
+#[utoipa::path(
+    tag = "templates",
+    params(("id" = Uuid, Path)),
+    responses(
+        (status = 200, body = SiteTemplate),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["content:read"]))
+)]
+#[get("/templates/{id}")]
+pub async fn api_template_get(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let template =
+        load_template(&state.pool, path.into_inner(), uid).await?;
+    Ok(HttpResponse::Ok().json(template))
+}
+
+/// Change the fields present in the body; others keep their value.
+#[utoipa::path(
+    tag = "templates",
+    params(("id" = Uuid, Path)),
+    request_body = SiteTemplateUpdate,
+    responses(
+        (status = 200, body = SiteTemplate),
+        (status = 400, body = ApiErrorBody),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+        (status = 409, description = "Name already taken", body = ApiErrorBody),
+    ),
+    security(("bearer" = ["templates:write"]))
+)]
+#[patch("/templates/{id}")]
+pub async fn api_template_update(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+    body: web::Json<SiteTemplateUpdate>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let id = path.into_inner();
+    validate_template_update(&body).map_err(ApiError::bad_request)?;
+
+    load_owned_template(&state.pool, id, uid).await?;
+
+    let update = SiteTemplateUpdate {
+        name: body.name.as_deref().map(|s| s.trim().to_string()),
+        description: body.description.clone(),
+        html: body.html.clone(),
+    };
+    let updated = db::update_site_template(&state.pool, id, &update)
+        .await
+        .map_err(name_conflict)?
+        .ok_or_else(ApiError::not_found)?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+#[utoipa::path(
+    tag = "templates",
+    params(("id" = Uuid, Path)),
+    responses(
+        (status = 204, description = "Deleted"),
+        (status = 403, body = ApiErrorBody),
+        (status = 404, body = ApiErrorBody),
+    ),
+    security(("bearer" = ["templates:write"]))
+)]
+#[delete("/templates/{id}")]
+pub async fn api_template_delete(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ApiError> {
+    let uid = api_user(&req)?;
+    let id = path.into_inner();
+
+    load_owned_template(&state.pool, id, uid).await?;
+
+    if !db::delete_site_template(&state.pool, id).await? {
+        return Err(ApiError::not_found());
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(api_template_list)
+        .service(api_template_create)
+        .service(api_template_get)
+        .service(api_template_update)
+        .service(api_template_delete);
+}
@@ -0,0 +1,152 @@
+use actix_web::{HttpRequest, HttpResponse, Responder, post, web};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::ApiTokenCreate;
+
+use crate::web::api_token::generate_api_token;
+use crate::web::forms::ApiTokenCreateForm;
+use crate::web::handlers::account::{
+    grantable_api_scopes, render_account_page,
+};
+use crate::web::helpers::{get_is_admin, load_user, require_user};
+use crate::web::security::generic_error_message;
+use crate::web::state::AppState;
+
+async fn account_response(
+    state: &AppState,
+    req: &HttpRequest,
+    uid: Uuid,
+    error: Option<String>,
+    success: Option<String>,
+    new_token: Option<String>,
+) -> HttpResponse {
+    match load_user(&state.pool, uid).await {
+        Ok(user) => {
+            render_account_page(
+                &state.pool,
+                req,
+                user,
+                error,
+                success,
+                new_token,
+            )
+            .await
+        }
+        Err(resp) => resp,
+    }
+}
+
+#[post("/admin/me/account/tokens")]
+pub async fn api_token_create(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<ApiTokenCreateForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+
+    if let Err(e) = form.validate() {
+        return account_response(
+            &state,
+            &req,
+            uid,
+            Some(e.to_string()),
+            None,
+            None,
+        )
+        .await;
+    }
+
+    let scopes = form.scopes();
+    let grantable = grantable_api_scopes(get_is_admin(&req));
+    if scopes.iter().any(|s| !grantable.contains(s)) {
+        return account_response(
+            &state,
+            &req,
+            uid,
+            Some("You cannot grant that scope".into()),
+            None,
+            None,
+        )
+        .await;
+    }
+
+    let (token, token_hash) = generate_api_token();
+    let data = ApiTokenCreate {
+        user_id: uid,
+        name: form.name.trim().to_string(),
+        token_hash,
+        scopes,
+        expires_at: form
+            .expires_in_days()
+            .ok()
+            .flatten()
+            .map(|days| Utc::now() + Duration::days(days)),
+    };
+
+    match db::create_api_token(&state.pool, &data).await {
+        Ok(_) => {
+            account_response(
+                &state,
+                &req,
+                uid,
+                None,
+                Some(
+                    "Token created. Copy it now; it will not be shown again."
+                        .into(),
+                ),
+                Some(token),
+            )
+            .await
+        }
+        Err(e) => {
+            log::error!("Failed to create API token: {}", e);
+            account_response(
+                &state,
+                &req,
+                uid,
+                Some(generic_error_message("token creation")),
+                None,
+                None,
+            )
+            .await
+        }
+    }
+}
+
+#[post("/admin/me/account/tokens/{id}/revoke")]
+pub async fn api_token_revoke(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+
+    let (error, success) = match db::delete_api_token_for_user(
+        &state.pool,
+        path.into_inner(),
+        uid,
+    )
+    .await
+    {
+        Ok(true) => (None, Some("Token revoked".to_string())),
+        Ok(false) => (Some("Token not found".to_string()), None),
+        Err(e) => {
+            log::error!("Failed to revoke API token: {}", e);
+            (Some(generic_error_message("token revocation")), None)
+        }
+    };
+
+    account_response(&state, &req, uid, error, success, None).await
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(api_token_create).service(api_token_revoke);
+}
@@ -1,4 +1,3 @@
-use actix_web::cookie::{Cookie, SameSite};
 use actix_web::{
     HttpRequest, HttpResponse, Responder, get, post, web,
 };
@@ -9,7 +8,16 @@ use rustpress::models::{RoleName, SiteCreate};
 use rustpress::services::PasswordManager;
 
 use crate::web::forms::{AuthQuery, LoginForm, RegisterForm};
-use crate::web::helpers::{is_htmx, render};
+use crate::web::handlers::email_verification::send_verification_email;
+use crate::web::handlers::two_factor::{
+    begin_login_challenge, two_factor_enabled,
+};
+use crate::web::helpers::{
+    csrf_token, current_session, is_htmx, render,
+};
+use crate::web::session::{
+    client_ip, session_removal_cookie, start_session,
+};
 use crate::web::state::AppState;
 use crate::web::templates::{
     AdminLoginTemplate, AdminRegisterTemplate,
@@ -17,6 +25,7 @@ use crate::web::templates::{
 
 #[get("/admin/login")]
 pub async fn login_form(
+    req: HttpRequest,
     query: web::Query<AuthQuery>,
 ) -> impl Responder {
     let error = query.error.as_deref().map(|code| match code {
@@ -25,10 +34,26 @@ pub async fn login_form(
         "db" => "Database error. Please try again.".to_string(),
         "internal" => "An internal error occurred. Please try again."
             .to_string(),
+        "expired" => {
+            "Your sign-in attempt expired. Please sign in again."
+                .to_string()
+        }
         other => other.to_string(),
     });
+    let notice =
+        query.notice.as_deref().and_then(|code| match code {
+            "password_reset" => Some(
+                "Your password has been reset. Please sign in."
+                    .to_string(),
+            ),
+            _ => None,
+        });
 
-    render(AdminLoginTemplate { error })
+    render(AdminLoginTemplate {
+        error,
+        notice,
+        csrf_token: csrf_token(&req),
+    })
 }
 
 #[post("/admin/login")]
@@ -38,14 +63,10 @@ pub async fn login_submit(
     form: web::Form<LoginForm>,
 ) -> impl Responder {
     // Rate limiting
-    let client_ip = req
-        .connection_info()
-        .realip_remote_addr()
-        .unwrap_or("unknown")
-        .to_string();
+    let ip = client_ip(&req).unwrap_or_else(|| "unknown".to_string());
 
     if !state.rate_limiter.check_rate_limit(
-        &format!("login:{}", client_ip),
+        &format!("login:{}", ip),
         5,                        // 5 attempts
         Duration::from_secs(300), // per 5 minutes
     ) {
@@ -119,13 +140,50 @@ pub async fn login_submit(
         .expect("Database error re-fetching user")
         .expect("User should exist");
 
-    let cookie = Cookie::build("rp_uid", user.id.to_string())
-        .path("/")
-        .http_only(true)
-        .secure(true)
-        .same_site(SameSite::Lax)
-        .max_age(actix_web::cookie::time::Duration::days(7))
-        .finish();
+    // With two-factor enabled the password only earns a challenge; the
+    // session is created once the code step succeeds.
+    match two_factor_enabled(&state.pool, user.id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return begin_login_challenge(&state.pool, user.id)
+                .await
+                .unwrap_or_else(|e| {
+                    log::error!(
+                        "Failed to start 2FA challenge: {}",
+                        e
+                    );
+                    HttpResponse::SeeOther()
+                        .insert_header((
+                            "Location",
+                            "/admin/login?error=internal",
+                        ))
+                        .finish()
+                });
+        }
+        Err(e) => {
+            log::error!("Two-factor lookup failed: {}", e);
+            return HttpResponse::SeeOther()
+                .insert_header((
+                    "Location",
+                    "/admin/login?error=internal",
+                ))
+                .finish();
+        }
+    }
+
+    let cookie = match start_session(&state.pool, &req, user.id).await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Failed to create session: {}", e);
+            return HttpResponse::SeeOther()
+                .insert_header((
+                    "Location",
+                    "/admin/login?error=internal",
+                ))
+                .finish();
+        }
+    };
 
     HttpResponse::SeeOther()
         .cookie(cookie)
@@ -135,6 +193,7 @@ pub async fn login_submit(
 
 #[get("/admin/register")]
 pub async fn register_form(
+    req: HttpRequest,
     query: web::Query<AuthQuery>,
 ) -> impl Responder {
     let error = query.error.as_deref().map(|code| match code {
@@ -155,7 +214,10 @@ pub async fn register_form(
         other => other.to_string(),
     });
 
-    render(AdminRegisterTemplate { error })
+    render(AdminRegisterTemplate {
+        error,
+        csrf_token: csrf_token(&req),
+    })
 }
 
 #[post("/admin/register")]
@@ -178,14 +240,10 @@ pub async fn register_submit(
     }
 
     // Rate limiting
-    let client_ip = req
-        .connection_info()
-        .realip_remote_addr()
-        .unwrap_or("unknown")
-        .to_string();
+    let ip = client_ip(&req).unwrap_or_else(|| "unknown".to_string());
 
     if !state.rate_limiter.check_rate_limit(
-        &format!("register:{}", client_ip),
+        &format!("register:{}", ip),
         3,                         // 3 attempts
         Duration::from_secs(3600), // per hour
     ) {
@@ -255,6 +313,12 @@ pub async fn register_submit(
         log::error!("Failed to set user role: {}", e);
     }
 
+    if let Err(e) =
+        send_verification_email(&state, user.id, &user.email).await
+    {
+        log::error!("Failed to send verification email: {}", e);
+    }
+
     // Create default site if none exists
     if db::get_default_site(&state.pool)
         .await
@@ -278,13 +342,19 @@ pub async fn register_submit(
         }
     }
 
-    let cookie = Cookie::build("rp_uid", user.id.to_string())
-        .path("/")
-        .http_only(true)
-        .secure(true)
-        .same_site(SameSite::Lax)
-        .max_age(actix_web::cookie::time::Duration::days(7))
-        .finish();
+    let cookie = match start_session(&state.pool, &req, user.id).await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            log::error!("Failed to create session: {}", e);
+            return HttpResponse::SeeOther()
+                .insert_header((
+                    "Location",
+                    "/admin/login?error=internal",
+                ))
+                .finish();
+        }
+    };
 
     HttpResponse::SeeOther()
         .cookie(cookie)
@@ -293,14 +363,18 @@ pub async fn register_submit(
 }
 
 #[post("/admin/logout")]
-pub async fn logout(req: HttpRequest) -> impl Responder {
-    let mut cookie = Cookie::build("rp_uid", "")
-        .path("/")
-        .http_only(true)
-        .secure(true)
-        .same_site(SameSite::Lax)
-        .finish();
-    cookie.make_removal();
+pub async fn logout(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
+    if let Some(session) = current_session(&req)
+        && let Err(e) =
+            db::delete_session(&state.pool, session.id).await
+    {
+        log::error!("Failed to revoke session on logout: {}", e);
+    }
+
+    let cookie = session_removal_cookie();
 
     if is_htmx(&req) {
         HttpResponse::Ok()
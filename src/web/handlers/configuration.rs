@@ -7,7 +7,9 @@ use uuid::Uuid;
 use rustpress::db;
 use rustpress::models::{ContentKind, HomepageType};
 
-use crate::web::helpers::{get_is_admin, render, require_user};
+use crate::web::helpers::{
+    csrf_token, get_is_admin, render, require_user,
+};
 
 use crate::web::state::AppState;
 use crate::web::templates::ConfigurationTemplate;
@@ -16,6 +18,16 @@ use crate::web::templates::ConfigurationTemplate;
 pub struct ConfigurationForm {
     pub homepage_type: Option<String>,
     pub homepage_page_id: Option<String>,
+    /// Checkbox: present when ticked, absent otherwise.
+    pub require_verified_email: Option<String>,
+    pub require_admin_2fa: Option<String>,
+    #[serde(default)]
+    pub trash_retention_days: String,
+    #[serde(default)]
+    pub posts_per_page: String,
+    pub feed_full_text: Option<String>,
+    pub robots_txt: Option<String>,
+    pub search_language: Option<String>,
 }
 
 #[get("/admin/configuration")]
@@ -34,13 +46,18 @@ pub async fn configuration_page(
         db::list_content(&state.pool, ContentKind::Page, false)
             .await
             .unwrap_or_default();
+    let search_languages = db::list_search_languages(&state.pool)
+        .await
+        .unwrap_or_default();
 
     render(ConfigurationTemplate {
         site,
         pages,
+        search_languages,
         error: None,
         success: None,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
@@ -56,6 +73,9 @@ pub async fn configuration_update(
     };
 
     let is_admin = get_is_admin(&req);
+    let search_languages = db::list_search_languages(&state.pool)
+        .await
+        .unwrap_or_default();
 
     let site = match db::get_default_site(&state.pool).await {
         Ok(Some(s)) => s,
@@ -70,9 +90,11 @@ pub async fn configuration_update(
             return render(ConfigurationTemplate {
                 site: None,
                 pages,
+                search_languages,
                 error: Some("No site configured".to_string()),
                 success: None,
                 is_admin,
+                csrf_token: csrf_token(&req),
             });
         }
         Err(e) => {
@@ -103,6 +125,30 @@ pub async fn configuration_update(
         default_template: None,
         homepage_type,
         homepage_page_id,
+        require_verified_email: Some(
+            form.require_verified_email.is_some(),
+        ),
+        require_admin_2fa: Some(form.require_admin_2fa.is_some()),
+        // Anything but a number fails the range check below.
+        trash_retention_days: match form.trash_retention_days.trim() {
+            "" => None,
+            days => Some(days.parse().unwrap_or(0)),
+        },
+        feed_full_text: Some(form.feed_full_text.is_some()),
+        // Anything but a number fails the range check below.
+        posts_per_page: match form.posts_per_page.trim() {
+            "" => None,
+            n => Some(n.parse().unwrap_or(0)),
+        },
+        robots_txt: form
+            .robots_txt
+            .as_ref()
+            .map(|rules| rules.replace("\r\n", "\n")),
+        search_language: form
+            .search_language
+            .as_ref()
+            .map(|language| language.trim().to_string())
+            .filter(|language| !language.is_empty()),
     };
 
     let pages =
@@ -110,30 +156,55 @@ pub async fn configuration_update(
             .await
             .unwrap_or_default();
 
+    if let Err(e) = update
+        .validate_trash_retention()
+        .and_then(|()| update.validate_robots_txt())
+        .and_then(|()| update.validate_posts_per_page())
+        .and_then(|()| {
+            update.validate_search_language(&search_languages)
+        })
+    {
+        return render(ConfigurationTemplate {
+            site: Some(site),
+            pages,
+            search_languages,
+            error: Some(e),
+            success: None,
+            is_admin,
+            csrf_token: csrf_token(&req),
+        });
+    }
+
     match db::update_site(&state.pool, site.id, uid, &update).await {
         Ok(Some(updated)) => render(ConfigurationTemplate {
             site: Some(updated),
             pages,
+            search_languages,
             error: None,
             success: Some("Configuration saved".to_string()),
             is_admin,
+            csrf_token: csrf_token(&req),
         }),
         Ok(None) => render(ConfigurationTemplate {
             site: Some(site),
             pages,
+            search_languages,
             error: Some(
                 "Update failed - site not found or no permission"
                     .to_string(),
             ),
             success: None,
             is_admin,
+            csrf_token: csrf_token(&req),
         }),
         Err(e) => render(ConfigurationTemplate {
             site: Some(site),
             pages,
+            search_languages,
             error: Some(format!("Update failed: {e}")),
             success: None,
             is_admin,
+            csrf_token: csrf_token(&req),
         }),
     }
 }
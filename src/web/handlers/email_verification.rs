@@ -0,0 +1,118 @@
+use actix_web::{HttpRequest, Responder, get, web};
+use chrono::{Duration, Utc};
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::EmailVerificationTokenCreate;
+use rustpress::services::TokenManager;
+
+use crate::web::helpers::{
+    csrf_token, is_unique_violation, render, send_email,
+};
+use crate::web::state::AppState;
+use crate::web::templates::{
+    AdminVerifyEmailTemplate, VerifyEmailEmailTemplate,
+};
+
+/// Verification links stop working after this long.
+pub const EMAIL_VERIFICATION_TTL_HOURS: i64 = 48;
+
+/// Issue a verification token for `email` on behalf of `user_id` and mail
+/// the link to that address.
+///
+/// `email` is the address to confirm. For an email change it is the new
+/// address; the account keeps its current one until the link is followed.
+/// Only the token's hash is kept; the outbox erases the mail, link
+/// included, once it is delivered.
+pub async fn send_verification_email(
+    state: &AppState,
+    user_id: Uuid,
+    email: &str,
+) -> Result<(), String> {
+    if let Err(e) =
+        db::delete_stale_email_verification_tokens(&state.pool).await
+    {
+        log::warn!(
+            "Failed to purge stale verification tokens: {}",
+            e
+        );
+    }
+
+    let token = TokenManager::generate();
+    let data = EmailVerificationTokenCreate {
+        user_id,
+        email: email.to_string(),
+        token_hash: TokenManager::hash(&token),
+        expires_at: Utc::now()
+            + Duration::hours(EMAIL_VERIFICATION_TTL_HOURS),
+    };
+    db::create_email_verification_token(&state.pool, &data)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let link =
+        format!("{}/admin/verify-email/{}", state.public_url, token);
+    send_email(
+        state,
+        email,
+        "Confirm your email address",
+        VerifyEmailEmailTemplate {
+            email,
+            link,
+            ttl_hours: EMAIL_VERIFICATION_TTL_HOURS,
+        },
+    )
+    .await
+}
+
+#[get("/admin/verify-email/{token}")]
+pub async fn verify_email(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    let token = path.into_inner();
+
+    let (verified_email, error) = match db::verify_email_with_token(
+        &state.pool,
+        &TokenManager::hash(&token),
+    )
+    .await
+    {
+        Ok(Some(user)) => (Some(user.email), None),
+        Ok(None) => (
+            None,
+            Some(
+                "This verification link is invalid or has expired."
+                    .to_string(),
+            ),
+        ),
+        Err(e) if is_unique_violation(&e) => (
+            None,
+            Some(
+                "This address is already used by another account."
+                    .to_string(),
+            ),
+        ),
+        Err(e) => {
+            log::error!("Email verification failed: {}", e);
+            (
+                None,
+                Some(
+                    "An internal error occurred. Please try again."
+                        .to_string(),
+                ),
+            )
+        }
+    };
+
+    render(AdminVerifyEmailTemplate {
+        verified_email,
+        error,
+        csrf_token: csrf_token(&req),
+    })
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(verify_email);
+}
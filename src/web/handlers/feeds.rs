@@ -0,0 +1,304 @@
+use actix_web::{
+    HttpRequest, HttpResponse, HttpResponseBuilder, Responder, get,
+    web,
+};
+use askama::Template;
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use rustpress::db::{self, Database};
+use rustpress::models::{Site, UserIden};
+use rustpress::services::{
+    FEED_EXCERPT_WORDS, FEED_ITEM_LIMIT, absolute_urls, etag_matches,
+    excerpt, feed_etag, feed_published, feed_updated,
+};
+
+use crate::web::helpers::render_not_found;
+use crate::web::state::AppState;
+use crate::web::templates::{
+    AtomFeedTemplate, Feed, FeedEntry, RssFeedTemplate,
+};
+
+/// Feed readers poll often; let them reuse a copy for a few minutes.
+const FEED_CACHE_CONTROL: &str = "public, max-age=300";
+
+#[derive(Clone, Copy)]
+enum FeedFormat {
+    Rss,
+    Atom,
+}
+
+impl FeedFormat {
+    fn file_name(self) -> &'static str {
+        match self {
+            Self::Rss => "feed.xml",
+            Self::Atom => "atom.xml",
+        }
+    }
+
+    fn content_type(self) -> &'static str {
+        match self {
+            Self::Rss => "application/rss+xml; charset=utf-8",
+            Self::Atom => "application/atom+xml; charset=utf-8",
+        }
+    }
+}
+
+/// An HTTP date, as used by `Last-Modified` and `If-Modified-Since`.
+fn http_date(at: DateTime<Utc>) -> String {
+    at.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Whether the client's cached copy is still current. `If-None-Match`
+/// wins over `If-Modified-Since` when both are sent.
+fn is_not_modified(
+    req: &HttpRequest,
+    etag: &str,
+    last_modified: DateTime<Utc>,
+) -> bool {
+    let header =
+        |name| req.headers().get(name).and_then(|v| v.to_str().ok());
+    if let Some(tags) = header("If-None-Match") {
+        return etag_matches(tags, etag);
+    }
+    header("If-Modified-Since")
+        .and_then(|since| DateTime::parse_from_rfc2822(since).ok())
+        .is_some_and(|since| {
+            last_modified.timestamp() <= since.timestamp()
+        })
+}
+
+/// Where a feed is served from, relative to the site root.
+fn feed_path(format: FeedFormat, author: Option<Uuid>) -> String {
+    match author {
+        Some(id) => format!("/author/{id}/{}", format.file_name()),
+        None => format!("/{}", format.file_name()),
+    }
+}
+
+/// Everything a feed is built from besides its posts: the build that
+/// renders it, where it lives and the site settings.
+fn feed_version(
+    state: &AppState,
+    site: Option<&Site>,
+    path: &str,
+) -> String {
+    format!(
+        "{} {}{path} {:?}",
+        env!("CARGO_PKG_VERSION"),
+        state.public_url,
+        site.map(|s| s.edited_at),
+    )
+}
+
+/// When a feed last changed: its newest post change, or for an empty
+/// feed, the last change to the site itself.
+fn feed_last_modified(
+    posts: impl IntoIterator<Item = DateTime<Utc>>,
+    site: Option<&Site>,
+) -> DateTime<Utc> {
+    posts
+        .into_iter()
+        .max()
+        .or(site.map(|s| s.edited_at))
+        .unwrap_or(DateTime::UNIX_EPOCH)
+}
+
+/// The latest published posts, optionally only those by `author`.
+async fn build_feed(
+    state: &AppState,
+    site: Option<&Site>,
+    path: &str,
+    author: Option<Uuid>,
+) -> Result<Feed, sqlx::Error> {
+    let full_text = site.is_none_or(|s| s.feed_full_text);
+    let base = state.public_url.as_str();
+
+    let posts =
+        db::list_feed_posts(&state.pool, author, FEED_ITEM_LIMIT)
+            .await?;
+    let entries: Vec<FeedEntry> = posts
+        .into_iter()
+        .map(|post| {
+            let html = state.html_policy.for_display(
+                post.html(),
+                post.format,
+                post.unfiltered_html,
+            );
+            FeedEntry {
+                id: post.id,
+                url: format!("{base}/blog/{}", post.slug),
+                published: feed_published(&post),
+                updated: feed_updated(&post),
+                summary: excerpt(&html, FEED_EXCERPT_WORDS),
+                content: full_text
+                    .then(|| absolute_urls(&html, base)),
+                title: post.title,
+            }
+        })
+        .collect();
+
+    let name = site.map_or("RustPress", |s| s.name.as_str());
+    let title = match author {
+        Some(_) => format!("{name}: posts by one author"),
+        None => name.to_string(),
+    };
+    let updated =
+        feed_last_modified(entries.iter().map(|e| e.updated), site);
+
+    Ok(Feed {
+        title,
+        subtitle: format!("Latest posts from {name}"),
+        site_url: format!("{base}/blog"),
+        self_url: format!("{base}{path}"),
+        updated,
+        entries,
+    })
+}
+
+/// The validators and caching headers every feed response carries.
+fn feed_headers(
+    mut response: HttpResponseBuilder,
+    etag: &str,
+    last_modified: DateTime<Utc>,
+) -> HttpResponseBuilder {
+    response
+        .insert_header(("ETag", etag))
+        .insert_header(("Last-Modified", http_date(last_modified)))
+        .insert_header(("Cache-Control", FEED_CACHE_CONTROL));
+    response
+}
+
+/// A rendered feed, or `304 Not Modified` when the client's copy is
+/// current. That is checked against the posts' ids and dates alone,
+/// so a revalidation loads no post bodies and renders nothing.
+async fn feed_response(
+    state: &AppState,
+    req: &HttpRequest,
+    format: FeedFormat,
+    author: Option<Uuid>,
+) -> HttpResponse {
+    let site = match db::get_default_site(&state.pool).await {
+        Ok(site) => site,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let path = feed_path(format, author);
+    let version = feed_version(state, site.as_ref(), &path);
+
+    let stamps = match db::list_feed_stamps(
+        &state.pool,
+        author,
+        FEED_ITEM_LIMIT,
+    )
+    .await
+    {
+        Ok(stamps) => stamps,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let etag = feed_etag(&version, stamps.iter().copied());
+    let updated = feed_last_modified(
+        stamps.iter().map(|(_, at)| *at),
+        site.as_ref(),
+    );
+    if is_not_modified(req, &etag, updated) {
+        return feed_headers(
+            HttpResponse::NotModified(),
+            &etag,
+            updated,
+        )
+        .finish();
+    }
+
+    let feed =
+        match build_feed(state, site.as_ref(), &path, author).await {
+            Ok(feed) => feed,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+    let body = match format {
+        FeedFormat::Rss => RssFeedTemplate { feed: &feed }.render(),
+        FeedFormat::Atom => AtomFeedTemplate { feed: &feed }.render(),
+    };
+    let body = match body {
+        Ok(body) => body,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .content_type("text/plain; charset=utf-8")
+                .body(format!("Template error: {e}"));
+        }
+    };
+
+    // Posts may have changed since the check; describe what was sent.
+    let etag = feed_etag(
+        &version,
+        feed.entries.iter().map(|e| (e.id, e.updated)),
+    );
+    feed_headers(HttpResponse::Ok(), &etag, feed.updated)
+        .content_type(format.content_type())
+        .body(body)
+}
+
+/// A feed of one author's posts; unknown authors get a 404.
+async fn author_feed_response(
+    state: &AppState,
+    req: &HttpRequest,
+    format: FeedFormat,
+    author: Uuid,
+) -> HttpResponse {
+    let user = Database::from_pool(state.pool.clone())
+        .get_user(&UserIden::Id(author), false)
+        .await;
+    if user.is_err() {
+        return render_not_found(req);
+    }
+    feed_response(state, req, format, Some(author)).await
+}
+
+#[get("/feed.xml")]
+pub async fn rss_feed(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
+    feed_response(&state, &req, FeedFormat::Rss, None).await
+}
+
+#[get("/atom.xml")]
+pub async fn atom_feed(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
+    feed_response(&state, &req, FeedFormat::Atom, None).await
+}
+
+#[get("/author/{id}/feed.xml")]
+pub async fn author_rss_feed(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    author_feed_response(&state, &req, FeedFormat::Rss, *path).await
+}
+
+#[get("/author/{id}/atom.xml")]
+pub async fn author_atom_feed(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    author_feed_response(&state, &req, FeedFormat::Atom, *path).await
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(rss_feed)
+        .service(atom_feed)
+        .service(author_rss_feed)
+        .service(author_atom_feed);
+}
@@ -0,0 +1,135 @@
+use actix_web::http::header::{
+    ContentDisposition, DispositionParam, DispositionType,
+};
+use actix_web::{HttpRequest, HttpResponse, Responder, get, web};
+use uuid::Uuid;
+
+use rustpress::common::MediaError;
+use rustpress::db;
+use rustpress::models::Media;
+use rustpress::services::{
+    media_variant_key, resize_image, variant_widths,
+};
+
+use crate::web::helpers::render_not_found;
+use crate::web::state::AppState;
+
+/// A file never changes under its id, so it can be cached for good.
+const MEDIA_CACHE_CONTROL: &str =
+    "public, max-age=31536000, immutable";
+
+async fn load_media(
+    state: &AppState,
+    req: &HttpRequest,
+    id: Uuid,
+) -> Result<Media, HttpResponse> {
+    match db::get_media(&state.pool, id).await {
+        Ok(Some(media)) => Ok(media),
+        Ok(None) => Err(render_not_found(req)),
+        Err(e) => {
+            Err(HttpResponse::InternalServerError()
+                .body(e.to_string()))
+        }
+    }
+}
+
+/// Send `data` as the contents of `media`, or an error page when
+/// reading it failed.
+fn file_response(
+    req: &HttpRequest,
+    media: Media,
+    data: Result<Result<Vec<u8>, MediaError>, actix_web::Error>,
+) -> HttpResponse {
+    let data = match data {
+        Ok(Ok(data)) => data,
+        Ok(Err(MediaError::NotFound)) => {
+            return render_not_found(req);
+        }
+        Ok(Err(e)) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    HttpResponse::Ok()
+        .content_type(media.mime_type.as_str())
+        .insert_header(ContentDisposition {
+            disposition: DispositionType::Inline,
+            parameters: vec![DispositionParam::Filename(
+                media.filename,
+            )],
+        })
+        .insert_header(("Cache-Control", MEDIA_CACHE_CONTROL))
+        .body(data)
+}
+
+/// Serve an uploaded file with the type sniffed at upload time.
+#[get("/media/{id}")]
+pub async fn media_file(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> impl Responder {
+    let media = match load_media(&state, &req, *path).await {
+        Ok(media) => media,
+        Err(resp) => return resp,
+    };
+    let storage = state.media.clone();
+    let key = media.storage_key.clone();
+    let data = web::block(move || storage.get(&key))
+        .await
+        .map_err(Into::into);
+    file_response(&req, media, data)
+}
+
+/// Serve an image scaled down to one of the configured widths. The
+/// copy is made on first request and kept in storage next to the
+/// original. Widths the image has no copy at send the original.
+#[get("/media/{id}/{width:[0-9]+}")]
+pub async fn media_variant(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(Uuid, u32)>,
+) -> impl Responder {
+    let (id, width) = path.into_inner();
+    if !state.image_widths.contains(&width) {
+        return render_not_found(&req);
+    }
+    let media = match load_media(&state, &req, id).await {
+        Ok(media) => media,
+        Err(resp) => return resp,
+    };
+    if !variant_widths(&media, &state.image_widths).contains(&width) {
+        return HttpResponse::Found()
+            .insert_header(("Location", media.url()))
+            .finish();
+    }
+
+    let storage = state.media.clone();
+    let key = media.storage_key.clone();
+    let mime_type = media.mime_type.clone();
+    let data = web::block(move || {
+        let variant_key = media_variant_key(&key, width);
+        match storage.get(&variant_key) {
+            Err(MediaError::NotFound) => {}
+            cached => return cached,
+        }
+        let resized =
+            resize_image(&storage.get(&key)?, &mime_type, width)?;
+        // A failed write only means the copy is made again next time.
+        if let Err(e) = storage.put(&variant_key, &resized) {
+            log::error!("Failed to store {variant_key}: {e}");
+        }
+        Ok(resized)
+    })
+    .await
+    .map_err(Into::into);
+    file_response(&req, media, data)
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(media_file).service(media_variant);
+}
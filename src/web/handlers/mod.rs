@@ -2,13 +2,26 @@ pub mod account;
 pub mod admin_collaborators;
 pub mod admin_content;
 pub mod admin_history;
+pub mod admin_media;
+pub mod admin_redirects;
+pub mod admin_review;
 pub mod admin_roles;
 pub mod admin_templates;
+pub mod admin_terms;
+pub mod admin_trash;
 pub mod admin_users;
+pub mod api;
+pub mod api_tokens;
 pub mod auth;
 pub mod configuration;
+pub mod email_verification;
+pub mod feeds;
+pub mod media;
+pub mod password_reset;
 pub mod public;
+pub mod sitemaps;
 pub mod themes;
+pub mod two_factor;
 
 use actix_web::web;
 
@@ -16,14 +29,27 @@ use actix_web::web;
 /// The catch-all must be registered last to avoid matching before specific routes.
 pub fn configure(cfg: &mut web::ServiceConfig) {
     public::configure(cfg);
+    feeds::configure(cfg);
+    sitemaps::configure(cfg);
+    media::configure(cfg);
     auth::configure(cfg);
+    two_factor::configure(cfg);
+    password_reset::configure(cfg);
+    email_verification::configure(cfg);
     admin_content::configure(cfg);
     admin_history::configure(cfg);
+    admin_media::configure(cfg);
+    admin_redirects::configure(cfg);
+    admin_review::configure(cfg);
     admin_collaborators::configure(cfg);
     admin_roles::configure(cfg);
     admin_templates::configure(cfg);
+    admin_terms::configure(cfg);
+    admin_trash::configure(cfg);
     admin_users::configure(cfg);
     account::configure(cfg);
+    api_tokens::configure(cfg);
+    api::configure(cfg);
     configuration::configure(cfg);
     themes::configure(cfg);
 }
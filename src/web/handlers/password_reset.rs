@@ -0,0 +1,250 @@
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, get, post, web,
+};
+use chrono::{Duration, Utc};
+use std::time::Duration as StdDuration;
+
+use rustpress::db;
+use rustpress::models::PasswordResetTokenCreate;
+use rustpress::services::{PasswordManager, TokenManager};
+
+use crate::web::forms::{ForgotPasswordForm, ResetPasswordForm};
+use crate::web::helpers::{csrf_token, render, send_email};
+use crate::web::security::{
+    PasswordValidator, generic_error_message, validate_email,
+};
+use crate::web::session::client_ip;
+use crate::web::state::AppState;
+use crate::web::templates::{
+    AdminForgotPasswordTemplate, AdminResetPasswordTemplate,
+    PasswordResetEmailTemplate,
+};
+
+/// Reset links stop working after this long.
+pub const PASSWORD_RESET_TTL_MINUTES: i64 = 60;
+
+const RESET_SENT_MESSAGE: &str = "If an account exists for that address, \
+     we've sent a link to reset its password.";
+
+fn render_forgot(
+    req: &HttpRequest,
+    error: Option<String>,
+    success: Option<String>,
+) -> HttpResponse {
+    render(AdminForgotPasswordTemplate {
+        error,
+        success,
+        csrf_token: csrf_token(req),
+    })
+}
+
+fn render_reset(
+    req: &HttpRequest,
+    token: String,
+    valid: bool,
+    error: Option<String>,
+) -> HttpResponse {
+    render(AdminResetPasswordTemplate {
+        token,
+        valid,
+        error,
+        csrf_token: csrf_token(req),
+    })
+}
+
+#[get("/admin/forgot-password")]
+pub async fn forgot_password_form(
+    req: HttpRequest,
+) -> impl Responder {
+    render_forgot(&req, None, None)
+}
+
+#[post("/admin/forgot-password")]
+pub async fn forgot_password_submit(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<ForgotPasswordForm>,
+) -> impl Responder {
+    let ip = client_ip(&req).unwrap_or_else(|| "unknown".to_string());
+    if !state.rate_limiter.check_rate_limit(
+        &format!("forgot_password:{}", ip),
+        5,                           // 5 requests
+        StdDuration::from_secs(900), // per 15 minutes
+    ) {
+        return render_forgot(
+            &req,
+            Some(
+                "Too many reset requests. Please try again later."
+                    .into(),
+            ),
+            None,
+        );
+    }
+
+    let email = form.email.trim().to_string();
+    if !validate_email(&email) {
+        return render_forgot(
+            &req,
+            Some("Invalid email address".into()),
+            None,
+        );
+    }
+
+    // Cap mails per address as well, so one client cannot flood a
+    // mailbox by rotating IPs. Over the limit we silently skip sending;
+    // the response must look the same either way.
+    let may_send = state.rate_limiter.check_rate_limit(
+        &format!("forgot_password_email:{}", email.to_lowercase()),
+        3,                            // 3 emails
+        StdDuration::from_secs(3600), // per hour
+    );
+
+    if may_send && let Err(e) = send_reset_link(&state, &email).await
+    {
+        log::error!("Password reset request failed: {}", e);
+        return render_forgot(
+            &req,
+            Some(generic_error_message("reset request")),
+            None,
+        );
+    }
+
+    render_forgot(&req, None, Some(RESET_SENT_MESSAGE.into()))
+}
+
+/// Issue a reset token for `email` and mail the link. Unknown or deleted
+/// accounts are a silent no-op so the endpoint cannot be used to probe
+/// for registered addresses.
+async fn send_reset_link(
+    state: &AppState,
+    email: &str,
+) -> Result<(), String> {
+    let user = match db::get_user_by_email(&state.pool, email).await {
+        Ok(Some(u)) if u.deleted_at.is_none() => u,
+        Ok(_) => return Ok(()),
+        Err(e) => return Err(e.to_string()),
+    };
+
+    if let Err(e) =
+        db::delete_stale_password_reset_tokens(&state.pool).await
+    {
+        log::warn!("Failed to purge stale reset tokens: {}", e);
+    }
+
+    let token = TokenManager::generate();
+    let data = PasswordResetTokenCreate {
+        user_id: user.id,
+        token_hash: TokenManager::hash(&token),
+        expires_at: Utc::now()
+            + Duration::minutes(PASSWORD_RESET_TTL_MINUTES),
+    };
+    db::create_password_reset_token(&state.pool, &data)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let link = format!(
+        "{}/admin/reset-password/{}",
+        state.public_url, token
+    );
+    send_email(
+        state,
+        &user.email,
+        "Reset your RustPress password",
+        PasswordResetEmailTemplate {
+            link,
+            ttl_minutes: PASSWORD_RESET_TTL_MINUTES,
+        },
+    )
+    .await
+}
+
+#[get("/admin/reset-password/{token}")]
+pub async fn reset_password_form(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<String>,
+) -> impl Responder {
+    let token = path.into_inner();
+    let valid = match db::get_usable_password_reset_token(
+        &state.pool,
+        &TokenManager::hash(&token),
+    )
+    .await
+    {
+        Ok(found) => found.is_some(),
+        Err(e) => {
+            log::error!("Reset token lookup failed: {}", e);
+            return render_reset(
+                &req,
+                token,
+                true,
+                Some(generic_error_message("request")),
+            );
+        }
+    };
+
+    render_reset(&req, token, valid, None)
+}
+
+#[post("/admin/reset-password/{token}")]
+pub async fn reset_password_submit(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<String>,
+    form: web::Form<ResetPasswordForm>,
+) -> impl Responder {
+    let token = path.into_inner();
+
+    if let Err(e) = form.validate() {
+        return render_reset(&req, token, true, Some(e.to_string()));
+    }
+    if let Err(e) = PasswordValidator::validate(&form.password) {
+        return render_reset(&req, token, true, Some(e));
+    }
+
+    let password_hash =
+        match PasswordManager::hash_password(&form.password) {
+            Ok(h) => h,
+            Err(e) => {
+                log::error!("Password hashing error: {}", e);
+                return render_reset(
+                    &req,
+                    token,
+                    true,
+                    Some(generic_error_message("password hashing")),
+                );
+            }
+        };
+
+    match db::reset_password_with_token(
+        &state.pool,
+        &TokenManager::hash(&token),
+        &password_hash,
+    )
+    .await
+    {
+        Ok(Some(_)) => HttpResponse::SeeOther()
+            .insert_header((
+                "Location",
+                "/admin/login?notice=password_reset",
+            ))
+            .finish(),
+        Ok(None) => render_reset(&req, token, false, None),
+        Err(e) => {
+            log::error!("Password reset failed: {}", e);
+            render_reset(
+                &req,
+                token,
+                true,
+                Some(generic_error_message("password reset")),
+            )
+        }
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(forgot_password_form)
+        .service(forgot_password_submit)
+        .service(reset_password_form)
+        .service(reset_password_submit);
+}
@@ -1,23 +1,47 @@
-use actix_web::{HttpResponse, Responder, get, web};
+use actix_web::http::StatusCode;
+use actix_web::http::header::{
+    CACHE_CONTROL, HeaderName, HeaderValue,
+};
+use actix_web::{HttpRequest, HttpResponse, Responder, get, web};
+use chrono::{Months, NaiveDate, Utc};
 
 use rustpress::db;
-use rustpress::models::{ContentItem, ContentKind, HomepageType};
+use rustpress::models::{
+    ContentItem, ContentKind, ContentStatus, DEFAULT_POSTS_PER_PAGE,
+    HomepageType, PostListFilter, SEARCH_RESULTS_PER_PAGE, Taxonomy,
+    Term, page_offset, search_terms,
+};
+use rustpress::services::SeoMeta;
+
+use crate::web::forms::{PageQuery, PublicSearchQuery};
 
 use crate::web::helpers::{
-    apply_site_template, normalize_builtin_template_html, render,
-    render_not_found,
+    apply_breadcrumb_placeholder, apply_seo_placeholders,
+    apply_site_template, apply_term_placeholders, content_public_url,
+    normalize_builtin_template_html, numbered_page_links, page_links,
+    render, render_not_found, responsive_content, seo_head_meta,
 };
+use crate::web::session::session_user_id;
 use crate::web::state::AppState;
 use crate::web::templates::{
     PublicContentTemplate, PublicFallbackTemplate,
-    PublicIndexTemplate,
+    PublicIndexTemplate, PublicSearchTemplate,
 };
 
 async fn render_content(
-    pool: &db::PgPool,
+    state: &AppState,
     item: &ContentItem,
 ) -> HttpResponse {
-    match get_template_for_item(pool, item).await {
+    let pool = &state.pool;
+    let url = content_public_url(pool, item, &state.public_url).await;
+    let seo = SeoMeta::for_item(item, &url, &state.public_url);
+    let content = state.html_policy.for_display(
+        item.html(),
+        item.format,
+        item.unfiltered_html,
+    );
+    let content = responsive_content(state, &content).await;
+    let mut response = match get_template_for_item(pool, item).await {
         Some(tpl) => {
             let tpl_html = if tpl.is_builtin {
                 normalize_builtin_template_html(&tpl.html)
@@ -27,17 +51,73 @@ async fn render_content(
             let html = apply_site_template(
                 tpl_html.as_ref(),
                 &item.title,
-                &item.content,
+                &content,
                 &item.slug,
                 item.kind.as_str(),
             );
+            let terms = db::list_terms_for_content(pool, item.id)
+                .await
+                .unwrap_or_default();
+            let html = apply_term_placeholders(&html, &terms);
+            let ancestors = match item.parent_id {
+                Some(_) => db::list_page_ancestors(pool, item.id)
+                    .await
+                    .unwrap_or_default(),
+                None => Vec::new(),
+            };
+            let html = apply_breadcrumb_placeholder(
+                &html,
+                &ancestors,
+                &item.title,
+            );
+            let html = apply_seo_placeholders(&html, &seo);
             render(PublicContentTemplate { html })
         }
         None => render(PublicFallbackTemplate {
             title: &item.title,
-            content: &item.content,
+            content: &content,
+            head_meta: seo_head_meta(&seo),
         }),
+    };
+    if seo.noindex {
+        response.headers_mut().insert(
+            HeaderName::from_static("x-robots-tag"),
+            HeaderValue::from_static("noindex"),
+        );
+    }
+    // Private items are only shown to some signed-in users.
+    if item.status == ContentStatus::Private {
+        response.headers_mut().insert(
+            CACHE_CONTROL,
+            HeaderValue::from_static("private"),
+        );
     }
+    response
+}
+
+/// The response for a manual redirect configured at the request path,
+/// if there is one. Public routes check this before their own lookups.
+async fn manual_redirect(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+) -> Option<HttpResponse> {
+    let redirect =
+        db::find_redirect(pool, req.path()).await.ok().flatten()?;
+    let response =
+        match redirect.location(req.path(), req.query_string()) {
+            Some(location) => HttpResponse::build(
+                StatusCode::from_u16(redirect.status_code as u16)
+                    .unwrap_or(StatusCode::MOVED_PERMANENTLY),
+            )
+            .insert_header(("Location", location))
+            .finish(),
+            None => {
+                let mut response = render_not_found(req);
+                *response.status_mut() = StatusCode::GONE;
+                response
+            }
+        };
+    Some(response)
 }
 
 async fn get_template_for_item(
@@ -77,40 +157,168 @@ async fn get_template_for_item(
     }
 }
 
-async fn render_posts_index(pool: &db::PgPool) -> HttpResponse {
-    let posts = db::list_content(pool, ContentKind::Post, false)
-        .await
-        .unwrap_or_default();
-    render(PublicIndexTemplate { posts })
+/// Page size of the blog and its archives.
+async fn posts_per_page(pool: &db::PgPool) -> i64 {
+    let per_page = match db::get_default_site(pool).await {
+        Ok(Some(site)) => site.posts_per_page,
+        _ => DEFAULT_POSTS_PER_PAGE,
+    };
+    per_page.into()
+}
+
+/// A page of published posts matching `filter`, laid out like the blog
+/// index. `archive` and `month` name what is being browsed.
+async fn render_post_list(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    query: &PageQuery,
+    filter: PostListFilter,
+    archive: Option<Term>,
+    month: Option<NaiveDate>,
+) -> HttpResponse {
+    let page = match query.request(posts_per_page(pool).await) {
+        Ok(page) => page,
+        Err(e) => return HttpResponse::BadRequest().body(e),
+    };
+    let posts =
+        match db::list_published_posts(pool, &filter, &page).await {
+            Ok(posts) => posts,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+    let (prev_url, next_url) =
+        page_links(req.path(), query.number(), &posts);
+    // Public pages carry no forms, so no CSRF token is issued for them.
+    render(PublicIndexTemplate {
+        posts: posts.items,
+        archive,
+        month,
+        page: query.number(),
+        prev_url,
+        next_url,
+        csrf_token: String::new(),
+    })
+}
+
+async fn render_posts_index(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    query: &PageQuery,
+) -> HttpResponse {
+    render_post_list(
+        pool,
+        req,
+        query,
+        PostListFilter::default(),
+        None,
+        None,
+    )
+    .await
+}
+
+/// Posts filed under a category or tag, laid out like the blog index.
+async fn render_term_archive(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    query: &PageQuery,
+    taxonomy: Taxonomy,
+    slug: &str,
+) -> HttpResponse {
+    if let Some(resp) = manual_redirect(pool, req).await {
+        return resp;
+    }
+    let term = match db::get_term_by_slug(pool, taxonomy, slug).await
+    {
+        Ok(Some(term)) => term,
+        Ok(None) => return render_not_found(req),
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let filter = PostListFilter {
+        term_id: Some(term.id),
+        ..Default::default()
+    };
+    render_post_list(pool, req, query, filter, Some(term), None).await
 }
 
 #[get("/")]
-pub async fn home_page(state: web::Data<AppState>) -> impl Responder {
+pub async fn home_page(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<PageQuery>,
+) -> impl Responder {
     if let Ok(Some(site)) = db::get_default_site(&state.pool).await {
         match site.homepage_type {
             HomepageType::Posts => {
-                return render_posts_index(&state.pool).await;
+                return render_posts_index(&state.pool, &req, &query)
+                    .await;
             }
             HomepageType::Page => {
                 if let Some(page_id) = site.homepage_page_id
                     && let Ok(Some(page)) =
                         db::get_content_by_id(&state.pool, page_id)
                             .await
+                    && page.is_live(Utc::now())
                 {
-                    return render_content(&state.pool, &page).await;
+                    return render_content(&state, &page).await;
                 }
             }
         }
     }
 
-    render_posts_index(&state.pool).await
+    render_posts_index(&state.pool, &req, &query).await
 }
 
 #[get("/blog")]
 pub async fn blog_index(
     state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<PageQuery>,
 ) -> impl Responder {
-    render_posts_index(&state.pool).await
+    if let Some(resp) = manual_redirect(&state.pool, &req).await {
+        return resp;
+    }
+    render_posts_index(&state.pool, &req, &query).await
+}
+
+/// Posts published in a month, e.g. `/blog/2026/10`.
+#[get("/blog/{year:\\d+}/{month:\\d+}")]
+pub async fn blog_month_archive(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(i32, u32)>,
+    query: web::Query<PageQuery>,
+) -> impl Responder {
+    if let Some(resp) = manual_redirect(&state.pool, &req).await {
+        return resp;
+    }
+    let (year, month) = path.into_inner();
+    let Some(first) = NaiveDate::from_ymd_opt(year, month, 1) else {
+        return render_not_found(&req);
+    };
+    let start = |day: NaiveDate| {
+        day.and_hms_opt(0, 0, 0).map(|t| t.and_utc())
+    };
+    let filter = PostListFilter {
+        published_from: start(first),
+        published_before: first
+            .checked_add_months(Months::new(1))
+            .and_then(start),
+        ..Default::default()
+    };
+    render_post_list(
+        &state.pool,
+        &req,
+        &query,
+        filter,
+        None,
+        Some(first),
+    )
+    .await
 }
 
 #[get("/blog/{slug}")]
@@ -120,21 +328,152 @@ pub async fn blog_post(
     path: web::Path<String>,
 ) -> impl Responder {
     let slug = path.into_inner();
+    if let Some(resp) = manual_redirect(&state.pool, &req).await {
+        return resp;
+    }
 
-    match db::get_published_by_slug(
+    let viewer = session_user_id(&state.pool, &req).await;
+    match db::get_viewable_by_slug(
         &state.pool,
         ContentKind::Post,
         &slug,
+        viewer,
     )
     .await
     .ok()
     .flatten()
     {
-        Some(item) => render_content(&state.pool, &item).await,
-        None => render_not_found(&req),
+        Some(item) => render_content(&state, &item).await,
+        None => {
+            // A renamed post keeps answering on its old slug.
+            let moved = db::find_moved_content(
+                &state.pool,
+                ContentKind::Post,
+                &slug,
+            )
+            .await
+            .ok()
+            .flatten()
+            .filter(|item| item.is_live(Utc::now()));
+            match moved {
+                Some(item) if item.slug != slug => {
+                    HttpResponse::MovedPermanently()
+                        .insert_header((
+                            "Location",
+                            format!("/blog/{}", item.slug),
+                        ))
+                        .finish()
+                }
+                _ => render_not_found(&req),
+            }
+        }
     }
 }
 
+#[get("/category/{slug}")]
+pub async fn category_archive(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<PageQuery>,
+) -> impl Responder {
+    render_term_archive(
+        &state.pool,
+        &req,
+        &query,
+        Taxonomy::Category,
+        &path,
+    )
+    .await
+}
+
+#[get("/tag/{slug}")]
+pub async fn tag_archive(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<String>,
+    query: web::Query<PageQuery>,
+) -> impl Responder {
+    render_term_archive(
+        &state.pool,
+        &req,
+        &query,
+        Taxonomy::Tag,
+        &path,
+    )
+    .await
+}
+
+/// Published posts and pages matching `q`, best first.
+#[get("/search")]
+pub async fn search_page(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    query: web::Query<PublicSearchQuery>,
+) -> impl Responder {
+    if let Some(resp) = manual_redirect(&state.pool, &req).await {
+        return resp;
+    }
+    let pool = &state.pool;
+    let page = query.page.unwrap_or(1).max(1);
+    let Some(terms) = search_terms(query.q.as_deref()) else {
+        return render(PublicSearchTemplate {
+            query: String::new(),
+            results: Vec::new(),
+            total: 0,
+            page: 1,
+            prev_url: None,
+            next_url: None,
+            csrf_token: String::new(),
+        });
+    };
+
+    let total = match db::count_published_search_results(pool, &terms)
+        .await
+    {
+        Ok(total) => total,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let hits = match db::search_published_content(
+        pool,
+        &terms,
+        SEARCH_RESULTS_PER_PAGE,
+        page_offset(page, SEARCH_RESULTS_PER_PAGE),
+    )
+    .await
+    {
+        Ok(hits) => hits,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let mut results = Vec::with_capacity(hits.len());
+    for hit in hits {
+        let url = content_public_url(pool, &hit.item, "").await;
+        results.push((url, hit));
+    }
+
+    let (prev_url, next_url) = numbered_page_links(
+        &format!("/search?q={}", urlencoding::encode(&terms)),
+        page,
+        total,
+        SEARCH_RESULTS_PER_PAGE,
+    );
+    render(PublicSearchTemplate {
+        prev_url,
+        next_url,
+        query: terms,
+        results,
+        total,
+        page,
+        csrf_token: String::new(),
+    })
+}
+
 #[get("/{path:.*}")]
 pub async fn page_page(
     state: web::Data<AppState>,
@@ -146,28 +485,53 @@ pub async fn page_page(
     if slug.is_empty()
         || slug == "admin"
         || slug.starts_with("admin/")
-        || slug == "blog"
-        || slug.starts_with("blog/")
     {
         return render_not_found(&req);
     }
+    if let Some(resp) = manual_redirect(&state.pool, &req).await {
+        return resp;
+    }
+    if slug == "blog" || slug.starts_with("blog/") {
+        return render_not_found(&req);
+    }
 
-    match db::get_published_by_slug(
-        &state.pool,
-        ContentKind::Page,
-        &slug,
-    )
-    .await
-    .ok()
-    .flatten()
+    let path = slug.trim_end_matches('/');
+    let viewer = session_user_id(&state.pool, &req).await;
+    match db::get_viewable_page_by_path(&state.pool, path, viewer)
+        .await
+    {
+        Ok(Some(item)) => {
+            return render_content(&state, &item).await;
+        }
+        Ok(None) => {}
+        Err(_) => return render_not_found(&req),
+    }
+
+    // A page that moved or was renamed keeps answering on its old path.
+    let moved =
+        db::find_moved_content(&state.pool, ContentKind::Page, path)
+            .await
+            .ok()
+            .flatten()
+            .filter(|item| item.is_live(Utc::now()));
+    if let Some(item) = moved
+        && let Ok(current) =
+            db::get_page_path(&state.pool, &item).await
+        && current != path
     {
-        Some(item) => render_content(&state.pool, &item).await,
-        None => render_not_found(&req),
+        return HttpResponse::MovedPermanently()
+            .insert_header(("Location", format!("/{current}")))
+            .finish();
     }
+    render_not_found(&req)
 }
 
 pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(home_page)
         .service(blog_index)
-        .service(blog_post);
+        .service(blog_month_archive)
+        .service(blog_post)
+        .service(category_archive)
+        .service(tag_archive)
+        .service(search_page);
 }
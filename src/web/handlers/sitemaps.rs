@@ -0,0 +1,155 @@
+use actix_web::{HttpRequest, HttpResponse, Responder, get, web};
+use askama::Template;
+
+use rustpress::db;
+use rustpress::models::{
+    ContentKind, DEFAULT_ROBOTS_TXT, SITEMAP_MAX_URLS,
+};
+
+use crate::web::helpers::render_not_found;
+use crate::web::state::AppState;
+use crate::web::templates::{
+    SitemapIndexTemplate, SitemapTemplate, SitemapUrl,
+};
+
+/// Kinds with a sitemap, by the name used in its file name.
+const SITEMAP_KINDS: [(&str, ContentKind); 2] =
+    [("posts", ContentKind::Post), ("pages", ContentKind::Page)];
+
+fn xml_response<T: Template>(t: T) -> HttpResponse {
+    match t.render() {
+        Ok(body) => HttpResponse::Ok()
+            .content_type("application/xml; charset=utf-8")
+            .body(body),
+        Err(e) => HttpResponse::InternalServerError()
+            .content_type("text/plain; charset=utf-8")
+            .body(format!("Template error: {e}")),
+    }
+}
+
+/// Lists one sitemap file per 50,000 posts or pages.
+#[get("/sitemap.xml")]
+pub async fn sitemap_index(
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let mut sitemaps = Vec::new();
+    for (name, kind) in SITEMAP_KINDS {
+        let chunks = match db::list_sitemap_chunks(
+            &state.pool,
+            kind,
+            SITEMAP_MAX_URLS,
+        )
+        .await
+        {
+            Ok(chunks) => chunks,
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        };
+        sitemaps.extend(chunks.into_iter().map(|chunk| SitemapUrl {
+            loc: format!(
+                "{}/sitemap-{name}-{}.xml",
+                state.public_url, chunk.page
+            ),
+            lastmod: chunk.lastmod,
+        }));
+    }
+    xml_response(SitemapIndexTemplate { sitemaps })
+}
+
+#[get("/sitemap-{kind:posts|pages}-{page:[0-9]+}.xml")]
+pub async fn sitemap_file(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    path: web::Path<(String, i64)>,
+) -> impl Responder {
+    let (name, page) = path.into_inner();
+    let Some((_, kind)) =
+        SITEMAP_KINDS.iter().find(|(n, _)| *n == name)
+    else {
+        return render_not_found(&req);
+    };
+    if page < 1 {
+        return render_not_found(&req);
+    }
+
+    let items = match db::list_sitemap_content(
+        &state.pool,
+        *kind,
+        page,
+        SITEMAP_MAX_URLS,
+    )
+    .await
+    {
+        Ok(items) => items,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    // The first file always exists, so an empty site still has one.
+    if items.is_empty() && page > 1 {
+        return render_not_found(&req);
+    }
+
+    let paths = match kind {
+        ContentKind::Page => {
+            match db::list_page_paths(&state.pool).await {
+                Ok(paths) => paths,
+                Err(e) => {
+                    return HttpResponse::InternalServerError()
+                        .body(e.to_string());
+                }
+            }
+        }
+        ContentKind::Post => Default::default(),
+    };
+    let base = &state.public_url;
+    let urls = items
+        .into_iter()
+        .map(|item| SitemapUrl {
+            loc: match item.kind {
+                ContentKind::Post => {
+                    format!("{base}/blog/{}", item.slug)
+                }
+                ContentKind::Page => format!(
+                    "{base}/{}",
+                    paths.get(&item.id).unwrap_or(&item.slug)
+                ),
+            },
+            lastmod: item.edited_at,
+        })
+        .collect();
+    xml_response(SitemapTemplate { urls })
+}
+
+/// The site's crawler rules, pointing crawlers at the sitemap.
+#[get("/robots.txt")]
+pub async fn robots_txt(
+    state: web::Data<AppState>,
+) -> impl Responder {
+    let site = db::get_default_site(&state.pool).await.ok().flatten();
+    let rules = site
+        .as_ref()
+        .map_or(DEFAULT_ROBOTS_TXT, |s| s.robots_txt.as_str())
+        .trim_end();
+    let mut body = String::new();
+    if !rules.is_empty() {
+        body.push_str(rules);
+        body.push_str("\n\n");
+    }
+    body.push_str(&format!(
+        "Sitemap: {}/sitemap.xml\n",
+        state.public_url
+    ));
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .body(body)
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(sitemap_index)
+        .service(sitemap_file)
+        .service(robots_txt);
+}
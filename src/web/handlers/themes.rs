@@ -3,7 +3,9 @@ use actix_web::{HttpRequest, Responder, get, web};
 use rustpress::db;
 
 use crate::web::forms::ThemesQuery;
-use crate::web::helpers::{get_is_admin, render, require_user};
+use crate::web::helpers::{
+    csrf_token, get_is_admin, render, require_user,
+};
 use crate::web::state::AppState;
 use crate::web::templates::ThemesTemplate;
 
@@ -47,6 +49,7 @@ pub async fn themes_list(
         query: q,
         category,
         is_admin,
+        csrf_token: csrf_token(&req),
     })
 }
 
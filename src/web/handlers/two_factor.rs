@@ -0,0 +1,574 @@
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::{
+    HttpRequest, HttpResponse, Responder, get, post, web,
+};
+use chrono::{Duration, Utc};
+use std::time::Duration as StdDuration;
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::{LoginChallengeCreate, User};
+use rustpress::services::{
+    PasswordManager, TokenManager, TotpManager,
+};
+
+use crate::web::forms::{TwoFactorCodeForm, TwoFactorPasswordForm};
+use crate::web::handlers::account::render_security;
+use crate::web::helpers::{
+    csrf_token, get_is_admin, load_user, render, require_user,
+};
+use crate::web::security::generic_error_message;
+use crate::web::session::{client_ip, start_session};
+use crate::web::state::AppState;
+use crate::web::templates::{
+    AdminTwoFactorLoginTemplate, MeRecoveryCodesTemplate,
+    MeTwoFactorSetupTemplate,
+};
+
+/// Carries the login challenge between the password and code steps.
+pub const LOGIN_CHALLENGE_COOKIE: &str = "rp_2fa";
+
+/// Enrollment page; admins are sent here while the site policy requires
+/// two-factor and they have not enabled it.
+pub const TWO_FACTOR_SETUP_PATH: &str = "/admin/me/security/2fa";
+
+/// Time allowed between the password and the code step.
+const LOGIN_CHALLENGE_TTL_MINUTES: i64 = 10;
+
+/// Wrong codes accepted per challenge before the password is asked again.
+const MAX_CHALLENGE_ATTEMPTS: i32 = 5;
+
+fn challenge_cookie(token: &str) -> Cookie<'static> {
+    Cookie::build(LOGIN_CHALLENGE_COOKIE, token.to_string())
+        .path("/admin/login")
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .max_age(actix_web::cookie::time::Duration::minutes(
+            LOGIN_CHALLENGE_TTL_MINUTES,
+        ))
+        .finish()
+}
+
+fn challenge_removal_cookie() -> Cookie<'static> {
+    let mut cookie = Cookie::build(LOGIN_CHALLENGE_COOKIE, "")
+        .path("/admin/login")
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .finish();
+    cookie.make_removal();
+    cookie
+}
+
+fn challenge_token_hash(req: &HttpRequest) -> Option<String> {
+    req.cookie(LOGIN_CHALLENGE_COOKIE)
+        .map(|c| c.value().trim().to_string())
+        .filter(|s| !s.is_empty())
+        .map(|token| TokenManager::hash(&token))
+}
+
+fn restart_login() -> HttpResponse {
+    HttpResponse::SeeOther()
+        .cookie(challenge_removal_cookie())
+        .insert_header(("Location", "/admin/login?error=expired"))
+        .finish()
+}
+
+/// Whether `user_id` must pass the code step before getting a session.
+pub async fn two_factor_enabled(
+    pool: &db::PgPool,
+    user_id: Uuid,
+) -> Result<bool, sqlx::Error> {
+    Ok(db::get_user_totp(pool, user_id)
+        .await?
+        .is_some_and(|t| t.is_enabled()))
+}
+
+/// Issue a login challenge for a user whose password was just verified
+/// and return the response that continues with the code step.
+pub async fn begin_login_challenge(
+    pool: &db::PgPool,
+    user_id: Uuid,
+) -> Result<HttpResponse, sqlx::Error> {
+    if let Err(e) = db::delete_expired_login_challenges(pool).await {
+        log::warn!("Failed to purge expired login challenges: {}", e);
+    }
+
+    let token = TokenManager::generate();
+    db::create_login_challenge(
+        pool,
+        &LoginChallengeCreate {
+            user_id,
+            token_hash: TokenManager::hash(&token),
+            expires_at: Utc::now()
+                + Duration::minutes(LOGIN_CHALLENGE_TTL_MINUTES),
+        },
+    )
+    .await?;
+
+    Ok(HttpResponse::SeeOther()
+        .cookie(challenge_cookie(&token))
+        .insert_header(("Location", "/admin/login/2fa"))
+        .finish())
+}
+
+#[get("/admin/login/2fa")]
+pub async fn login_two_factor_form(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
+    let Some(hash) = challenge_token_hash(&req) else {
+        return restart_login();
+    };
+    match db::get_login_challenge(&state.pool, &hash).await {
+        Ok(Some(_)) => render(AdminTwoFactorLoginTemplate {
+            error: None,
+            csrf_token: csrf_token(&req),
+        }),
+        Ok(None) => restart_login(),
+        Err(e) => {
+            log::error!("Login challenge lookup failed: {}", e);
+            restart_login()
+        }
+    }
+}
+
+#[post("/admin/login/2fa")]
+pub async fn login_two_factor_submit(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<TwoFactorCodeForm>,
+) -> impl Responder {
+    let render_error = |msg: &str| {
+        render(AdminTwoFactorLoginTemplate {
+            error: Some(msg.to_string()),
+            csrf_token: csrf_token(&req),
+        })
+    };
+
+    let ip = client_ip(&req).unwrap_or_else(|| "unknown".to_string());
+    if !state.rate_limiter.check_rate_limit(
+        &format!("login_2fa:{}", ip),
+        10,                          // 10 attempts
+        StdDuration::from_secs(300), // per 5 minutes
+    ) {
+        return render_error(
+            "Too many attempts. Please try again later.",
+        );
+    }
+
+    let Some(hash) = challenge_token_hash(&req) else {
+        return restart_login();
+    };
+    let challenge = match db::attempt_login_challenge(
+        &state.pool,
+        &hash,
+        MAX_CHALLENGE_ATTEMPTS,
+    )
+    .await
+    {
+        Ok(Some(c)) => c,
+        Ok(None) => return restart_login(),
+        Err(e) => {
+            log::error!("Login challenge lookup failed: {}", e);
+            return render_error(&generic_error_message("sign in"));
+        }
+    };
+
+    match verify_second_factor(
+        &state.pool,
+        challenge.user_id,
+        &form.code,
+    )
+    .await
+    {
+        Ok(true) => {}
+        Ok(false) => {
+            return render_error("Invalid authentication code");
+        }
+        Err(e) => {
+            log::error!("Two-factor verification failed: {}", e);
+            return render_error(&generic_error_message("sign in"));
+        }
+    }
+
+    if let Err(e) =
+        db::delete_login_challenge(&state.pool, challenge.id).await
+    {
+        log::warn!("Failed to delete login challenge: {}", e);
+    }
+
+    match start_session(&state.pool, &req, challenge.user_id).await {
+        Ok(cookie) => HttpResponse::SeeOther()
+            .cookie(cookie)
+            .cookie(challenge_removal_cookie())
+            .insert_header(("Location", "/admin"))
+            .finish(),
+        Err(e) => {
+            log::error!("Failed to create session: {}", e);
+            HttpResponse::SeeOther()
+                .insert_header((
+                    "Location",
+                    "/admin/login?error=internal",
+                ))
+                .finish()
+        }
+    }
+}
+
+/// Accept either a current authenticator code or an unused recovery code.
+async fn verify_second_factor(
+    pool: &db::PgPool,
+    user_id: Uuid,
+    code: &str,
+) -> Result<bool, sqlx::Error> {
+    let Some(totp) = db::get_user_totp(pool, user_id).await? else {
+        return Ok(false);
+    };
+    if !totp.is_enabled() {
+        return Ok(false);
+    }
+
+    if let Some(step) = TotpManager::verify(&totp.secret, code) {
+        return db::record_totp_step(pool, user_id, step).await;
+    }
+
+    db::use_recovery_code(
+        pool,
+        user_id,
+        &TotpManager::hash_recovery_code(code),
+    )
+    .await
+}
+
+/// Site policy: admins must have two-factor enabled.
+async fn required_for(pool: &db::PgPool, req: &HttpRequest) -> bool {
+    get_is_admin(req)
+        && db::get_default_site(pool)
+            .await
+            .ok()
+            .flatten()
+            .is_some_and(|s| s.require_admin_2fa)
+}
+
+/// Verify the current password before a change to two-factor settings.
+fn check_password(user: &User, password: &str) -> Result<(), String> {
+    match PasswordManager::verify_password(
+        password,
+        &user.password_hash,
+    ) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err("Current password is incorrect".to_string()),
+        Err(e) => {
+            log::error!("Password verification error: {}", e);
+            Err(generic_error_message("password verification"))
+        }
+    }
+}
+
+/// Render the enrollment page for a pending secret.
+async fn render_setup(
+    pool: &db::PgPool,
+    req: &HttpRequest,
+    user: &User,
+    secret: String,
+    error: Option<String>,
+) -> HttpResponse {
+    let otpauth_uri =
+        match TotpManager::provisioning_uri(&secret, &user.email) {
+            Ok(uri) => uri,
+            Err(e) => {
+                return HttpResponse::InternalServerError().body(e);
+            }
+        };
+    let qr_svg =
+        TotpManager::qr_svg(&otpauth_uri).unwrap_or_default();
+
+    render(MeTwoFactorSetupTemplate {
+        secret,
+        otpauth_uri,
+        qr_svg,
+        required: required_for(pool, req).await,
+        error,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
+    })
+}
+
+fn render_recovery_codes(
+    req: &HttpRequest,
+    codes: Vec<String>,
+) -> HttpResponse {
+    render(MeRecoveryCodesTemplate {
+        codes,
+        is_admin: get_is_admin(req),
+        csrf_token: csrf_token(req),
+    })
+}
+
+fn hash_codes(codes: &[String]) -> Vec<String> {
+    codes
+        .iter()
+        .map(|c| TotpManager::hash_recovery_code(c))
+        .collect()
+}
+
+#[get("/admin/me/security/2fa")]
+pub async fn two_factor_setup(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let user = match load_user(&state.pool, uid).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+
+    // Reuse a pending secret so reloading the page does not invalidate
+    // a QR code that was already scanned.
+    let existing = match db::get_user_totp(&state.pool, uid).await {
+        Ok(t) => t,
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+    let secret = match existing {
+        Some(t) if t.is_enabled() => {
+            return HttpResponse::SeeOther()
+                .insert_header(("Location", "/admin/me/security"))
+                .finish();
+        }
+        Some(t) => t.secret,
+        None => match db::start_totp_enrollment(
+            &state.pool,
+            uid,
+            &TotpManager::generate_secret(),
+        )
+        .await
+        {
+            Ok(Some(t)) => t.secret,
+            Ok(None) => {
+                return HttpResponse::SeeOther()
+                    .insert_header(("Location", "/admin/me/security"))
+                    .finish();
+            }
+            Err(e) => {
+                return HttpResponse::InternalServerError()
+                    .body(e.to_string());
+            }
+        },
+    };
+
+    render_setup(&state.pool, &req, &user, secret, None).await
+}
+
+#[post("/admin/me/security/2fa/enable")]
+pub async fn two_factor_enable(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<TwoFactorCodeForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let user = match load_user(&state.pool, uid).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+
+    let totp = match db::get_user_totp(&state.pool, uid).await {
+        Ok(Some(t)) if !t.is_enabled() => t,
+        Ok(_) => {
+            return HttpResponse::SeeOther()
+                .insert_header(("Location", TWO_FACTOR_SETUP_PATH))
+                .finish();
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    };
+
+    let Some(step) = TotpManager::verify(&totp.secret, &form.code)
+    else {
+        return render_setup(
+            &state.pool,
+            &req,
+            &user,
+            totp.secret,
+            Some(
+                "That code is not valid. Check the time on your device and try again."
+                    .to_string(),
+            ),
+        )
+        .await;
+    };
+
+    let codes = TotpManager::generate_recovery_codes();
+    match db::enable_totp(&state.pool, uid, step, &hash_codes(&codes))
+        .await
+    {
+        Ok(true) => render_recovery_codes(&req, codes),
+        Ok(false) => HttpResponse::SeeOther()
+            .insert_header(("Location", "/admin/me/security"))
+            .finish(),
+        Err(e) => {
+            log::error!("Failed to enable two-factor: {}", e);
+            render_setup(
+                &state.pool,
+                &req,
+                &user,
+                totp.secret,
+                Some(generic_error_message("two-factor setup")),
+            )
+            .await
+        }
+    }
+}
+
+#[post("/admin/me/security/2fa/recovery-codes")]
+pub async fn two_factor_regenerate_codes(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<TwoFactorPasswordForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let user = match load_user(&state.pool, uid).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+
+    if let Err(msg) = check_password(&user, &form.password) {
+        return render_security(
+            &state.pool,
+            &req,
+            &user,
+            Some(msg),
+            None,
+        )
+        .await;
+    }
+    match two_factor_enabled(&state.pool, uid).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return render_security(
+                &state.pool,
+                &req,
+                &user,
+                Some(
+                    "Two-factor authentication is not enabled".into(),
+                ),
+                None,
+            )
+            .await;
+        }
+        Err(e) => {
+            return HttpResponse::InternalServerError()
+                .body(e.to_string());
+        }
+    }
+
+    let codes = TotpManager::generate_recovery_codes();
+    match db::replace_recovery_codes(
+        &state.pool,
+        uid,
+        &hash_codes(&codes),
+    )
+    .await
+    {
+        Ok(()) => render_recovery_codes(&req, codes),
+        Err(e) => {
+            log::error!("Failed to replace recovery codes: {}", e);
+            render_security(
+                &state.pool,
+                &req,
+                &user,
+                Some(generic_error_message("recovery code update")),
+                None,
+            )
+            .await
+        }
+    }
+}
+
+#[post("/admin/me/security/2fa/disable")]
+pub async fn two_factor_disable(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    form: web::Form<TwoFactorPasswordForm>,
+) -> impl Responder {
+    let uid = match require_user(&req) {
+        Ok(uid) => uid,
+        Err(resp) => return resp,
+    };
+    let user = match load_user(&state.pool, uid).await {
+        Ok(u) => u,
+        Err(resp) => return resp,
+    };
+
+    if let Err(msg) = check_password(&user, &form.password) {
+        return render_security(
+            &state.pool,
+            &req,
+            &user,
+            Some(msg),
+            None,
+        )
+        .await;
+    }
+    if required_for(&state.pool, &req).await {
+        return render_security(
+            &state.pool,
+            &req,
+            &user,
+            Some(
+                "This site requires administrators to use two-factor authentication."
+                    .into(),
+            ),
+            None,
+        )
+        .await;
+    }
+
+    match db::disable_totp(&state.pool, uid).await {
+        Ok(()) => {
+            render_security(
+                &state.pool,
+                &req,
+                &user,
+                None,
+                Some("Two-factor authentication disabled".into()),
+            )
+            .await
+        }
+        Err(e) => {
+            log::error!("Failed to disable two-factor: {}", e);
+            render_security(
+                &state.pool,
+                &req,
+                &user,
+                Some(generic_error_message("two-factor update")),
+                None,
+            )
+            .await
+        }
+    }
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(login_two_factor_form)
+        .service(login_two_factor_submit)
+        .service(two_factor_setup)
+        .service(two_factor_enable)
+        .service(two_factor_regenerate_codes)
+        .service(two_factor_disable);
+}
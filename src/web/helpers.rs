@@ -3,8 +3,20 @@ use askama::Template;
 use sqlx::PgPool;
 use uuid::Uuid;
 
+use crate::web::api_token::ApiTokenAuth;
+use crate::web::security::CsrfTokenValue;
+use crate::web::session::CurrentSession;
+use crate::web::state::AppState;
 use crate::web::templates::{NotFoundTemplate, UnauthorizedTemplate};
-use rustpress::models::User;
+use rustpress::db;
+use rustpress::models::{
+    ContentFormat, ContentItem, ContentKind, ContentPage, Taxonomy,
+    Term, User,
+};
+use rustpress::services::{
+    AuthoredContent, EmailMessage, SeoMeta, add_responsive_images,
+    media_ids_in,
+};
 
 /// Marker stored in request extensions by the admin middleware.
 #[derive(Clone, Copy)]
@@ -18,6 +30,15 @@ pub fn get_is_admin(req: &HttpRequest) -> bool {
         .unwrap_or(false)
 }
 
+/// CSRF token issued by the middleware, for embedding in templates.
+/// Empty outside `/admin`, where no token is issued.
+pub fn csrf_token(req: &HttpRequest) -> String {
+    req.extensions()
+        .get::<CsrfTokenValue>()
+        .map(|t| t.0.clone())
+        .unwrap_or_default()
+}
+
 pub fn is_htmx(req: &HttpRequest) -> bool {
     req.headers()
         .get("HX-Request")
@@ -25,37 +46,19 @@ pub fn is_htmx(req: &HttpRequest) -> bool {
         .is_some_and(|s| s.eq_ignore_ascii_case("true"))
 }
 
+/// User id of the session validated by the admin middleware, if any.
+/// The signed-in user, authenticated by either a session cookie or an
+/// API token.
 pub fn current_user_id(req: &HttpRequest) -> Option<Uuid> {
-    // MVP auth/session.
-    // Priority: cookie -> request header -> env var.
-    let cookie_val = req
-        .cookie("rp_uid")
-        .map(|c| c.value().trim().to_string())
-        .filter(|s| !s.is_empty())
-        .and_then(|s| Uuid::parse_str(&s).ok());
-
-    if cookie_val.is_some() {
-        return cookie_val;
-    }
-
-    let header_val = req
-        .headers()
-        .get("X-Rustpress-User-Id")
-        .or_else(|| req.headers().get("X-User-Id"))
-        .and_then(|v| v.to_str().ok())
-        .map(str::trim)
-        .filter(|s| !s.is_empty())
-        .and_then(|s| Uuid::parse_str(s).ok());
-
-    header_val.or_else(|| {
-        std::env::var("RUSTPRESS_USER_ID")
-            .ok()
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .and_then(|s| Uuid::parse_str(&s).ok())
+    current_session(req).map(|s| s.user_id).or_else(|| {
+        req.extensions().get::<ApiTokenAuth>().map(|t| t.user_id)
     })
 }
 
+pub fn current_session(req: &HttpRequest) -> Option<CurrentSession> {
+    req.extensions().get::<CurrentSession>().copied()
+}
+
 pub fn require_user(req: &HttpRequest) -> Result<Uuid, HttpResponse> {
     match current_user_id(req) {
         Some(uid) => Ok(uid),
@@ -94,6 +97,29 @@ pub async fn load_user(
     }
 }
 
+/// Render a plain-text email template and queue it in the outbox.
+///
+/// Returns once the message is stored; delivery happens in the
+/// background worker.
+pub async fn send_email<T: Template>(
+    state: &AppState,
+    to: &str,
+    subject: &str,
+    body: T,
+) -> Result<(), String> {
+    let message = EmailMessage {
+        to: to.to_string(),
+        subject: subject.to_string(),
+        body: body.render().map_err(|e| e.to_string())?,
+    };
+    state
+        .outbox
+        .enqueue(&message)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
 pub fn render<T: Template>(t: T) -> HttpResponse {
     match t.render() {
         Ok(body) => HttpResponse::Ok()
@@ -206,7 +232,8 @@ pub fn apply_site_template(
 ) -> String {
     // Very small placeholder system.
     // - title/slug/kind are escaped
-    // - content is inserted as-is (admin-authored)
+    // - content is inserted as-is; callers pass it through
+    //   `HtmlPolicy::for_display` first
     let title_e = escape_html(title);
     let slug_e = escape_html(slug);
     let kind_e = escape_html(kind);
@@ -229,9 +256,266 @@ pub fn apply_site_template(
     out
 }
 
+/// Fill `{{categories}}` and `{{tags}}` with link lists to the term
+/// archives. Items without terms of a taxonomy get an empty string.
+pub fn apply_term_placeholders(
+    template_html: &str,
+    terms: &[Term],
+) -> String {
+    let mut out = template_html.to_string();
+    for taxonomy in Taxonomy::ALL {
+        let links: String = terms
+            .iter()
+            .filter(|t| t.taxonomy == taxonomy)
+            .map(|t| {
+                format!(
+                    "<li><a href=\"{}\">{}</a></li>",
+                    escape_html(&t.url()),
+                    escape_html(&t.name)
+                )
+            })
+            .collect();
+        let list = if links.is_empty() {
+            String::new()
+        } else {
+            format!(
+                "<ul class=\"rp-terms rp-{}\">{links}</ul>",
+                taxonomy.plural_label().to_lowercase()
+            )
+        };
+        let name = taxonomy.plural_label().to_lowercase();
+        out = out
+            .replace(&format!("{{{{{name}}}}}"), &list)
+            .replace(&format!("{{{{ {name} }}}}"), &list);
+    }
+    out
+}
+
+/// Fill `{{breadcrumbs}}` with a trail from the home page through a
+/// page's `ancestors` (top-level first) to `title`, the current page.
+pub fn apply_breadcrumb_placeholder(
+    template_html: &str,
+    ancestors: &[ContentItem],
+    title: &str,
+) -> String {
+    let mut path = String::new();
+    let mut crumbs = String::from("<li><a href=\"/\">Home</a></li>");
+    for page in ancestors {
+        path.push('/');
+        path.push_str(&page.slug);
+        crumbs.push_str(&format!(
+            "<li><a href=\"{}\">{}</a></li>",
+            escape_html(&path),
+            escape_html(&page.title)
+        ));
+    }
+    crumbs.push_str(&format!(
+        "<li aria-current=\"page\">{}</li>",
+        escape_html(title)
+    ));
+    let nav = format!(
+        "<nav class=\"rp-breadcrumbs\" aria-label=\"Breadcrumb\"><ol>{crumbs}</ol></nav>"
+    );
+    template_html
+        .replace("{{breadcrumbs}}", &nav)
+        .replace("{{ breadcrumbs }}", &nav)
+}
+
+/// Links to the pages before and after `page`, which is page `number`
+/// of the listing at `path`. The link back to the first page is `path`
+/// itself.
+pub fn page_links(
+    path: &str,
+    number: i64,
+    page: &ContentPage,
+) -> (Option<String>, Option<String>) {
+    let prev = page.prev.map(|cursor| {
+        if number <= 2 {
+            path.to_string()
+        } else {
+            format!("{path}?page={}&before={cursor}", number - 1)
+        }
+    });
+    let next = page.next.map(|cursor| {
+        format!(
+            "{path}?page={}&after={cursor}",
+            number.saturating_add(1)
+        )
+    });
+    (prev, next)
+}
+
+/// Links to the pages before and after page `number` of a listing of
+/// `total` items, `per_page` to a page, that is paged by offset. `url`
+/// may carry the listing's query, e.g. `/search?q=rust`.
+pub fn numbered_page_links(
+    url: &str,
+    number: i64,
+    total: i64,
+    per_page: i64,
+) -> (Option<String>, Option<String>) {
+    let last = ((total + per_page - 1) / per_page).max(1);
+    let sep = if url.contains('?') { '&' } else { '?' };
+    let link = |n: i64| format!("{url}{sep}page={n}");
+    (
+        (number > 1).then(|| link((number - 1).min(last))),
+        (number < last).then(|| link(number + 1)),
+    )
+}
+
+/// Absolute public URL of `item`: `/blog/{slug}` for a post, the full
+/// path for a page.
+pub async fn content_public_url(
+    pool: &PgPool,
+    item: &ContentItem,
+    base_url: &str,
+) -> String {
+    match item.kind {
+        ContentKind::Post => format!("{base_url}/blog/{}", item.slug),
+        ContentKind::Page => {
+            let path = db::get_page_path(pool, item)
+                .await
+                .unwrap_or_else(|_| item.slug.clone());
+            format!("{base_url}/{path}")
+        }
+    }
+}
+
+/// Content as it is stored when `uid` saves `source` written in
+/// `format`: kept unfiltered for admins and holders of the
+/// `unfiltered_html` capability, sanitized for everyone else.
+pub async fn authored_content(
+    state: &AppState,
+    uid: Uuid,
+    format: ContentFormat,
+    source: &str,
+) -> Result<AuthoredContent, sqlx::Error> {
+    let unfiltered =
+        db::user_can_use_unfiltered_html(&state.pool, uid).await?;
+    Ok(AuthoredContent::new(
+        &state.html_policy,
+        format,
+        source,
+        unfiltered,
+    ))
+}
+
+/// `html` with library images given `srcset`, `sizes` and lazy
+/// loading (see [`add_responsive_images`]).
+pub async fn responsive_content(
+    state: &AppState,
+    html: &str,
+) -> String {
+    let ids = media_ids_in(html);
+    let media = if ids.is_empty() {
+        Vec::new()
+    } else {
+        db::list_media_by_ids(&state.pool, &ids)
+            .await
+            .unwrap_or_default()
+    };
+    let media = media.into_iter().map(|m| (m.id, m)).collect();
+    add_responsive_images(html, &media, &state.image_widths)
+}
+
+/// The `<meta>`, canonical link, Open Graph and Twitter Card tags that
+/// `{{head_meta}}` expands to.
+pub fn seo_head_meta(seo: &SeoMeta) -> String {
+    let title = escape_html(&seo.title);
+    let description = escape_html(&seo.description);
+    let url = escape_html(&seo.canonical_url);
+    let mut tags = vec![
+        format!(
+            "<meta name=\"description\" content=\"{description}\"/>"
+        ),
+        format!("<link rel=\"canonical\" href=\"{url}\"/>"),
+    ];
+    if seo.noindex {
+        tags.push(
+            "<meta name=\"robots\" content=\"noindex\"/>".into(),
+        );
+    }
+    let og_type = match seo.kind {
+        ContentKind::Post => "article",
+        ContentKind::Page => "website",
+    };
+    tags.extend([
+        format!("<meta property=\"og:type\" content=\"{og_type}\"/>"),
+        format!("<meta property=\"og:title\" content=\"{title}\"/>"),
+        format!(
+            "<meta property=\"og:description\" content=\"{description}\"/>"
+        ),
+        format!("<meta property=\"og:url\" content=\"{url}\"/>"),
+    ]);
+    if seo.kind == ContentKind::Post {
+        for (property, at) in [
+            ("published_time", seo.published_at),
+            ("modified_time", seo.modified_at),
+        ] {
+            if let Some(at) = at {
+                tags.push(format!(
+                    "<meta property=\"article:{property}\" content=\"{}\"/>",
+                    at.to_rfc3339()
+                ));
+            }
+        }
+    }
+    let card = match &seo.image_url {
+        Some(image) => {
+            let image = escape_html(image);
+            tags.push(format!(
+                "<meta property=\"og:image\" content=\"{image}\"/>"
+            ));
+            tags.push(format!(
+                "<meta name=\"twitter:image\" content=\"{image}\"/>"
+            ));
+            "summary_large_image"
+        }
+        None => "summary",
+    };
+    tags.extend([
+        format!("<meta name=\"twitter:card\" content=\"{card}\"/>"),
+        format!("<meta name=\"twitter:title\" content=\"{title}\"/>"),
+        format!(
+            "<meta name=\"twitter:description\" content=\"{description}\"/>"
+        ),
+    ]);
+    tags.join("\n")
+}
+
+/// Fill `{{head_meta}}` and the single-value SEO placeholders
+/// (`{{meta_title}}`, `{{meta_description}}`, `{{canonical_url}}` and
+/// `{{og_image}}`).
+pub fn apply_seo_placeholders(
+    template_html: &str,
+    seo: &SeoMeta,
+) -> String {
+    let head_meta = seo_head_meta(seo);
+    let values = [
+        ("meta_title", escape_html(&seo.title)),
+        ("meta_description", escape_html(&seo.description)),
+        ("canonical_url", escape_html(&seo.canonical_url)),
+        (
+            "og_image",
+            escape_html(seo.image_url.as_deref().unwrap_or_default()),
+        ),
+        ("head_meta", head_meta),
+    ];
+    let mut out = template_html.to_string();
+    for (name, value) in values {
+        out = out
+            .replace(&format!("{{{{{name}}}}}"), &value)
+            .replace(&format!("{{{{ {name} }}}}"), &value);
+    }
+    out
+}
+
 pub fn render_not_found(req: &HttpRequest) -> HttpResponse {
     let is_admin = get_is_admin(req);
-    let template = NotFoundTemplate { is_admin };
+    let template = NotFoundTemplate {
+        is_admin,
+        csrf_token: csrf_token(req),
+    };
 
     match template.render() {
         Ok(body) => HttpResponse::NotFound()
@@ -245,7 +529,10 @@ pub fn render_not_found(req: &HttpRequest) -> HttpResponse {
 
 pub fn render_unauthorized(req: &HttpRequest) -> HttpResponse {
     let is_admin = get_is_admin(req);
-    let template = UnauthorizedTemplate { is_admin };
+    let template = UnauthorizedTemplate {
+        is_admin,
+        csrf_token: csrf_token(req),
+    };
 
     match template.render() {
         Ok(body) => HttpResponse::Unauthorized()
@@ -1,12 +1,25 @@
 use actix_web::{
-    Error,
-    dev::{Service, ServiceRequest, ServiceResponse, Transform},
+    Error, HttpMessage, HttpResponse,
+    body::{BoxBody, MessageBody},
+    dev::{
+        Payload, Service, ServiceRequest, ServiceResponse, Transform,
+    },
+    http::Method,
+    web::{Bytes, BytesMut},
 };
+use futures_util::StreamExt;
 use futures_util::future::{Ready, ok};
+use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
+use std::rc::Rc;
 use std::task::{Context, Poll};
 
+use crate::web::api_token::bearer_token;
+use crate::web::security::{
+    CSRF_COOKIE, CSRF_FIELD, CSRF_HEADER, CsrfToken, CsrfTokenValue,
+};
+
 /// Security headers middleware
 pub struct SecurityHeaders;
 
@@ -128,3 +141,176 @@ where
         })
     }
 }
+
+/// Largest urlencoded body the CSRF middleware will buffer to look for
+/// the token field. Larger forms have to send the header instead.
+const CSRF_MAX_FORM_BYTES: usize = 2 * 1024 * 1024;
+
+/// CSRF protection for `/admin`.
+///
+/// Issues the token cookie when missing, exposes the token to handlers
+/// via [`CsrfTokenValue`], and rejects unsafe requests whose
+/// `X-CSRF-Token` header or `csrf_token` form field does not match it.
+pub struct CsrfProtection;
+
+impl<S, B> Transform<S, ServiceRequest> for CsrfProtection
+where
+    S: Service<
+            ServiceRequest,
+            Response = ServiceResponse<B>,
+            Error = Error,
+        > + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = CsrfProtectionMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ok(CsrfProtectionMiddleware {
+            service: Rc::new(service),
+        })
+    }
+}
+
+pub struct CsrfProtectionMiddleware<S> {
+    service: Rc<S>,
+}
+
+impl<S, B> Service<ServiceRequest> for CsrfProtectionMiddleware<S>
+where
+    S: Service<
+            ServiceRequest,
+            Response = ServiceResponse<B>,
+            Error = Error,
+        > + 'static,
+    S::Future: 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = Pin<
+        Box<dyn Future<Output = Result<Self::Response, Self::Error>>>,
+    >;
+
+    fn poll_ready(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        self.service.poll_ready(cx)
+    }
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+
+        Box::pin(async move {
+            // Bearer-authenticated calls carry no ambient credentials
+            // a third-party page could ride on, so need no token.
+            if !req.path().starts_with("/admin")
+                || bearer_token(req.headers()).is_some()
+            {
+                return service
+                    .call(req)
+                    .await
+                    .map(ServiceResponse::map_into_boxed_body);
+            }
+
+            let existing = req
+                .cookie(CSRF_COOKIE)
+                .map(|c| c.value().to_string())
+                .filter(|v| !v.is_empty());
+
+            if is_unsafe_method(req.method()) {
+                let submitted = match header_token(&req) {
+                    Some(token) => Some(token),
+                    None => match form_token(&mut req).await {
+                        Ok(token) => token,
+                        Err(response) => {
+                            return Ok(req.into_response(response));
+                        }
+                    },
+                };
+
+                let valid = submitted.is_some_and(|token| {
+                    CsrfToken::validate(req.request(), &token)
+                });
+
+                if !valid {
+                    log::warn!(
+                        "Rejected {} {}: missing or invalid CSRF token",
+                        req.method(),
+                        req.path()
+                    );
+                    return Ok(req.into_response(
+                        HttpResponse::Forbidden()
+                            .content_type("text/plain; charset=utf-8")
+                            .body("Invalid or missing CSRF token"),
+                    ));
+                }
+            }
+
+            let issued = existing.is_none();
+            let token = existing.unwrap_or_else(CsrfToken::generate);
+            req.extensions_mut()
+                .insert(CsrfTokenValue(token.clone()));
+
+            let mut res =
+                service.call(req).await?.map_into_boxed_body();
+
+            if issued {
+                res.response_mut()
+                    .add_cookie(&CsrfToken::create_cookie(&token))?;
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+fn is_unsafe_method(method: &Method) -> bool {
+    !matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS)
+}
+
+fn header_token(req: &ServiceRequest) -> Option<String> {
+    req.headers()
+        .get(CSRF_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+/// Pull the token out of an urlencoded body, then put the body back so
+/// the handler's `web::Form` extractor still sees it.
+async fn form_token(
+    req: &mut ServiceRequest,
+) -> Result<Option<String>, HttpResponse> {
+    if req.content_type() != "application/x-www-form-urlencoded" {
+        return Ok(None);
+    }
+
+    let mut payload = req.take_payload();
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|_| {
+            HttpResponse::BadRequest()
+                .body("Failed to read request body")
+        })?;
+        if body.len() + chunk.len() > CSRF_MAX_FORM_BYTES {
+            return Err(HttpResponse::PayloadTooLarge().finish());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    let body: Bytes = body.freeze();
+
+    let token =
+        serde_urlencoded::from_bytes::<HashMap<String, String>>(
+            &body,
+        )
+        .ok()
+        .and_then(|mut fields| fields.remove(CSRF_FIELD));
+
+    req.set_payload(Payload::from(body));
+    Ok(token)
+}
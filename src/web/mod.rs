@@ -1,8 +1,10 @@
+pub mod api_token;
 pub mod forms;
 pub mod handlers;
 pub mod helpers;
 pub mod middleware;
 pub mod security;
+pub mod session;
 pub mod state;
 pub mod templates;
 
@@ -2,24 +2,38 @@ use actix_web::{HttpRequest, cookie::Cookie};
 use std::collections::HashMap;
 use std::sync::Mutex;
 use std::time::{Duration, SystemTime};
+use subtle::ConstantTimeEq;
 use uuid::Uuid;
 
+/// Name of the cookie carrying the CSRF token.
+pub const CSRF_COOKIE: &str = "csrf_token";
+
+/// Form field checked on submitted forms.
+pub const CSRF_FIELD: &str = "csrf_token";
+
+/// Header checked on HTMX requests (set via `hx-headers` on `<body>`).
+pub const CSRF_HEADER: &str = "X-CSRF-Token";
+
 /// CSRF token management
-#[allow(dead_code)]
 pub struct CsrfToken;
 
-#[allow(dead_code)]
 impl CsrfToken {
     /// Generate a new CSRF token
     pub fn generate() -> String {
         Uuid::new_v4().to_string()
     }
 
-    /// Validate CSRF token from request
+    /// Validate CSRF token from request. The tokens are compared in
+    /// constant time so response timing reveals nothing about the cookie.
     pub fn validate(req: &HttpRequest, form_token: &str) -> bool {
-        if let Some(session_token) = req.cookie("csrf_token") {
+        if let Some(session_token) = req.cookie(CSRF_COOKIE) {
             let session_value = session_token.value();
-            !session_value.is_empty() && session_value == form_token
+            !session_value.is_empty()
+                && bool::from(
+                    session_value
+                        .as_bytes()
+                        .ct_eq(form_token.as_bytes()),
+                )
         } else {
             false
         }
@@ -27,7 +41,7 @@ impl CsrfToken {
 
     /// Create a CSRF token cookie
     pub fn create_cookie(token: &str) -> Cookie<'static> {
-        Cookie::build("csrf_token", token.to_string())
+        Cookie::build(CSRF_COOKIE, token.to_string())
             .path("/")
             .http_only(true)
             .secure(true)
@@ -37,6 +51,11 @@ impl CsrfToken {
     }
 }
 
+/// Stored in request extensions by the CSRF middleware so handlers can
+/// hand the token to templates.
+#[derive(Clone, Debug)]
+pub struct CsrfTokenValue(pub String);
+
 /// Simple in-memory rate limiter
 pub struct RateLimiter {
     requests: Mutex<HashMap<String, Vec<SystemTime>>>,
@@ -0,0 +1,125 @@
+use actix_web::cookie::{Cookie, SameSite};
+use actix_web::{HttpRequest, web};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use rustpress::db;
+use rustpress::models::SessionCreate;
+use rustpress::services::TokenManager;
+
+use crate::web::state::AppState;
+
+pub const SESSION_COOKIE: &str = "rp_session";
+
+/// Sessions expire after this long without activity.
+pub const SESSION_TTL_DAYS: i64 = 7;
+
+/// Minimum gap between two `last_seen` writes for the same session,
+/// so that busy editors don't turn every request into an UPDATE.
+pub const SESSION_TOUCH_INTERVAL_SECS: i64 = 60;
+
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Stored in request extensions by the admin middleware once the session
+/// cookie has been validated against the database.
+#[derive(Clone, Copy, Debug)]
+pub struct CurrentSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+}
+
+pub fn session_expiry() -> DateTime<Utc> {
+    Utc::now() + Duration::days(SESSION_TTL_DAYS)
+}
+
+pub fn session_cookie(token: &str) -> Cookie<'static> {
+    Cookie::build(SESSION_COOKIE, token.to_string())
+        .path("/")
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .max_age(actix_web::cookie::time::Duration::days(
+            SESSION_TTL_DAYS,
+        ))
+        .finish()
+}
+
+pub fn session_removal_cookie() -> Cookie<'static> {
+    let mut cookie = Cookie::build(SESSION_COOKIE, "")
+        .path("/")
+        .http_only(true)
+        .secure(true)
+        .same_site(SameSite::Lax)
+        .finish();
+    cookie.make_removal();
+    cookie
+}
+
+/// Address of the client, for rate limits and the session list. The
+/// forwarding headers are only believed behind a trusted proxy, since
+/// anyone can send them.
+pub fn client_ip(req: &HttpRequest) -> Option<String> {
+    let trust_proxy = req
+        .app_data::<web::Data<AppState>>()
+        .is_some_and(|state| state.trust_proxy);
+    if trust_proxy {
+        req.connection_info()
+            .realip_remote_addr()
+            .map(str::to_string)
+    } else {
+        req.peer_addr().map(|addr| addr.ip().to_string())
+    }
+}
+
+/// The user signed in on this request, for public pages, which the
+/// admin middleware does not cover. Does not renew the session.
+pub async fn session_user_id(
+    pool: &PgPool,
+    req: &HttpRequest,
+) -> Option<Uuid> {
+    let cookie = req.cookie(SESSION_COOKIE)?;
+    let token = cookie.value().trim();
+    if token.is_empty() {
+        return None;
+    }
+    db::get_active_session(pool, &TokenManager::hash(token))
+        .await
+        .unwrap_or_else(|e| {
+            log::error!("Session lookup failed: {}", e);
+            None
+        })
+        .map(|session| session.user_id)
+}
+
+pub fn user_agent(req: &HttpRequest) -> Option<String> {
+    req.headers()
+        .get("User-Agent")
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.chars().take(MAX_USER_AGENT_LENGTH).collect())
+}
+
+/// Create a server-side session for `user_id` and return the cookie that
+/// carries its token.
+pub async fn start_session(
+    pool: &PgPool,
+    req: &HttpRequest,
+    user_id: Uuid,
+) -> Result<Cookie<'static>, sqlx::Error> {
+    // Opportunistic cleanup; failures here must not block the login.
+    if let Err(e) = db::delete_expired_sessions(pool).await {
+        log::warn!("Failed to purge expired sessions: {}", e);
+    }
+
+    let token = TokenManager::generate();
+    let data = SessionCreate {
+        user_id,
+        token_hash: TokenManager::hash(&token),
+        expires_at: session_expiry(),
+        ip: client_ip(req),
+        user_agent: user_agent(req),
+    };
+    db::create_session(pool, &data).await?;
+
+    Ok(session_cookie(&token))
+}
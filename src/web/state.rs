@@ -1,4 +1,5 @@
 use crate::web::security::RateLimiter;
+use rustpress::services::{HtmlPolicy, MailOutbox, MediaStorage};
 use sqlx::PgPool;
 use std::sync::Arc;
 
@@ -6,4 +7,17 @@ use std::sync::Arc;
 pub struct AppState {
     pub pool: PgPool,
     pub rate_limiter: Arc<RateLimiter>,
+    pub outbox: MailOutbox,
+    /// Absolute URL of the site without trailing slash, for links in emails.
+    pub public_url: String,
+    /// Where uploaded media files are kept.
+    pub media: Arc<dyn MediaStorage>,
+    /// Widths, in pixels, of the resized copies offered for images.
+    pub image_widths: Vec<u32>,
+    /// What content saved without the `unfiltered_html` capability may
+    /// contain.
+    pub html_policy: Arc<HtmlPolicy>,
+    /// Whether a reverse proxy in front of the server sets
+    /// `X-Forwarded-For`, so client addresses are taken from it.
+    pub trust_proxy: bool,
 }
@@ -1,18 +1,42 @@
 use std::collections::HashMap;
 
 use askama::Template;
+use chrono::{DateTime, NaiveDate, Utc};
 use uuid::Uuid;
 
 use rustpress::db::UserWithRoles;
 use rustpress::models::{
-    ContentItem, ContentItemRevision, ContentItemRevisionMeta, Site,
-    SiteTemplate, User,
+    ApiScope, ApiToken, ContentFormat, ContentItem,
+    ContentItemRevision, ContentItemRevisionMeta, ContentKind, Media,
+    PageNode, Redirect, RedirectMatch, SearchHit, Session, Site,
+    SiteTemplate, SlugHistoryEntry, Taxonomy, Term, TermNode, User,
 };
 
 #[derive(Template)]
 #[template(path = "public/index.html")]
 pub struct PublicIndexTemplate {
     pub posts: Vec<ContentItem>,
+    /// The category or tag being browsed; `None` for the blog index.
+    pub archive: Option<Term>,
+    /// The first day of the month being browsed.
+    pub month: Option<NaiveDate>,
+    pub page: i64,
+    pub prev_url: Option<String>,
+    pub next_url: Option<String>,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "public/search.html")]
+pub struct PublicSearchTemplate {
+    pub query: String,
+    /// Each hit with its public URL.
+    pub results: Vec<(String, SearchHit)>,
+    pub total: i64,
+    pub page: i64,
+    pub prev_url: Option<String>,
+    pub next_url: Option<String>,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -21,11 +45,66 @@ pub struct PublicContentTemplate {
     pub html: String,
 }
 
+/// A post as listed in a feed, with absolute URLs.
+pub struct FeedEntry {
+    pub id: Uuid,
+    pub title: String,
+    pub url: String,
+    pub published: DateTime<Utc>,
+    pub updated: DateTime<Utc>,
+    /// Excerpt, as HTML.
+    pub summary: String,
+    /// Full text, as HTML; `None` when the site publishes excerpts.
+    pub content: Option<String>,
+}
+
+/// What the RSS and Atom templates share.
+pub struct Feed {
+    pub title: String,
+    pub subtitle: String,
+    /// The HTML page the feed mirrors.
+    pub site_url: String,
+    pub self_url: String,
+    pub updated: DateTime<Utc>,
+    pub entries: Vec<FeedEntry>,
+}
+
+#[derive(Template)]
+#[template(path = "feeds/rss.xml")]
+pub struct RssFeedTemplate<'a> {
+    pub feed: &'a Feed,
+}
+
+#[derive(Template)]
+#[template(path = "feeds/atom.xml")]
+pub struct AtomFeedTemplate<'a> {
+    pub feed: &'a Feed,
+}
+
+/// An absolute URL with its last change, as sitemaps list them.
+pub struct SitemapUrl {
+    pub loc: String,
+    pub lastmod: DateTime<Utc>,
+}
+
+#[derive(Template)]
+#[template(path = "sitemaps/index.xml")]
+pub struct SitemapIndexTemplate {
+    pub sitemaps: Vec<SitemapUrl>,
+}
+
+#[derive(Template)]
+#[template(path = "sitemaps/urlset.xml")]
+pub struct SitemapTemplate {
+    pub urls: Vec<SitemapUrl>,
+}
+
 #[derive(Template)]
 #[template(path = "public/fallback.html")]
 pub struct PublicFallbackTemplate<'a> {
     pub title: &'a str,
     pub content: &'a str,
+    pub head_meta: String,
 }
 
 #[derive(Template)]
@@ -34,6 +113,7 @@ pub struct AdminDashboardTemplate {
     pub posts: Vec<ContentItem>,
     pub pages: Vec<ContentItem>,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -42,16 +122,58 @@ pub struct AdminPostsListTemplate {
     pub posts: Vec<ContentItem>,
     pub authors: HashMap<Uuid, String>,
     pub query: String,
+    /// Posts in the whole list, or matches of a search.
+    pub total: i64,
+    pub page: i64,
+    pub prev_url: Option<String>,
+    pub next_url: Option<String>,
     pub is_admin: bool,
+    pub csrf_token: String,
+}
+
+/// Items waiting for review. Reviewers see every pending item; other
+/// users only their own submissions.
+#[derive(Template)]
+#[template(path = "admin/review_queue.html")]
+pub struct AdminReviewQueueTemplate {
+    pub items: Vec<ContentItem>,
+    pub authors: HashMap<Uuid, String>,
+    pub can_review: bool,
+    pub is_admin: bool,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub csrf_token: String,
+}
+
+/// Trashed items of one kind, most recently trashed first.
+#[derive(Template)]
+#[template(path = "admin/trash.html")]
+pub struct AdminTrashTemplate {
+    pub kind: ContentKind,
+    pub items: Vec<ContentItem>,
+    pub authors: HashMap<Uuid, String>,
+    pub retention_days: i32,
+    pub is_admin: bool,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "admin/pages_list.html")]
 pub struct AdminPagesListTemplate {
-    pub pages: Vec<ContentItem>,
+    /// One page of the tree, children after their parent, or of search
+    /// results in order of relevance.
+    pub pages: Vec<PageNode>,
     pub authors: HashMap<Uuid, String>,
     pub query: String,
+    /// Pages in the whole tree, or matches of a search.
+    pub total: i64,
+    pub page: i64,
+    pub prev_url: Option<String>,
+    pub next_url: Option<String>,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -60,7 +182,22 @@ pub struct AdminEditTemplate {
     pub item: ContentItem,
     pub author: String,
     pub templates: Vec<SiteTemplate>,
+    /// Every category, parents before children.
+    pub categories: Vec<TermNode>,
+    /// Categories assigned to the item.
+    pub assigned_category_ids: Vec<Uuid>,
+    /// The item's tag names, comma-separated for the tags input.
+    pub tags_input: String,
+    /// Whether the viewer may publish directly or only submit for review.
+    pub can_publish: bool,
+    /// The reviewer's note when changes were requested on this revision.
+    pub review_note: Option<String>,
+    pub review_note_by: String,
+    /// Pages this page may be moved under: everything outside its own
+    /// subtree, in tree order. Empty for posts.
+    pub parent_options: Vec<PageNode>,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -71,15 +208,18 @@ pub struct AdminRevisionPreviewTemplate {
     pub revision_author: String,
     pub preview_html: String,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "admin/new.html")]
 pub struct AdminNewTemplate {
     pub kind: String,
+    pub format: ContentFormat,
     pub default_template: String,
     pub templates: Vec<SiteTemplate>,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -87,18 +227,56 @@ pub struct AdminNewTemplate {
 pub struct AdminTemplatesListTemplate {
     pub templates: Vec<SiteTemplate>,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "admin/login.html")]
 pub struct AdminLoginTemplate {
     pub error: Option<String>,
+    pub notice: Option<String>,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/login_2fa.html")]
+pub struct AdminTwoFactorLoginTemplate {
+    pub error: Option<String>,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/forgot_password.html")]
+pub struct AdminForgotPasswordTemplate {
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/reset_password.html")]
+pub struct AdminResetPasswordTemplate {
+    pub token: String,
+    /// `false` when the link is unknown, expired or already used.
+    pub valid: bool,
+    pub error: Option<String>,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/verify_email.html")]
+pub struct AdminVerifyEmailTemplate {
+    /// Set when the link was valid: the address now on the account.
+    pub verified_email: Option<String>,
+    pub error: Option<String>,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "admin/register.html")]
 pub struct AdminRegisterTemplate {
     pub error: Option<String>,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -107,6 +285,7 @@ pub struct AdminTemplateNewTemplate {
     pub starter_html: String,
     pub content_items: Vec<ContentItem>,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -115,15 +294,24 @@ pub struct AdminTemplateEditTemplate {
     pub template: SiteTemplate,
     pub content_items: Vec<ContentItem>,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "admin/account.html")]
 pub struct MeAccountTemplate {
     pub user: User,
+    /// New address awaiting confirmation, if an email change is pending.
+    pub pending_email: Option<String>,
+    pub api_tokens: Vec<ApiToken>,
+    /// Scopes this user may grant to a new token.
+    pub api_scopes: Vec<ApiScope>,
+    /// Plaintext of a token just created; shown only this once.
+    pub new_api_token: Option<String>,
     pub error: Option<String>,
     pub success: Option<String>,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -131,9 +319,36 @@ pub struct MeAccountTemplate {
 pub struct MeSecurityTemplate {
     pub password_set: bool,
     pub email_verified: bool,
+    pub two_factor_enabled: bool,
+    pub recovery_codes_left: i64,
+    pub sessions: Vec<Session>,
+    pub current_session_id: Option<Uuid>,
     pub error: Option<String>,
     pub success: Option<String>,
     pub is_admin: bool,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/two_factor_setup.html")]
+pub struct MeTwoFactorSetupTemplate {
+    /// Base32 secret for manual entry.
+    pub secret: String,
+    pub otpauth_uri: String,
+    pub qr_svg: String,
+    /// The site policy requires this admin to enroll.
+    pub required: bool,
+    pub error: Option<String>,
+    pub is_admin: bool,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/recovery_codes.html")]
+pub struct MeRecoveryCodesTemplate {
+    pub codes: Vec<String>,
+    pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -143,6 +358,7 @@ pub struct ThemesTemplate {
     pub query: String,
     pub category: String,
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -150,9 +366,86 @@ pub struct ThemesTemplate {
 pub struct ConfigurationTemplate {
     pub site: Option<Site>,
     pub pages: Vec<ContentItem>,
+    /// Choices for the site's search language.
+    pub search_languages: Vec<String>,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub is_admin: bool,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/terms_list.html")]
+pub struct AdminTermsTemplate {
+    pub taxonomy: Taxonomy,
+    pub terms: Vec<TermNode>,
+    pub is_admin: bool,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub csrf_token: String,
+}
+
+#[derive(Template)]
+#[template(path = "admin/redirects.html")]
+pub struct AdminRedirectsTemplate {
+    pub redirects: Vec<Redirect>,
+    /// Old addresses of renamed posts and moved pages.
+    pub history: Vec<SlugHistoryEntry>,
+    pub is_admin: bool,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub csrf_token: String,
+}
+
+/// A file in the media library as the current user sees it.
+pub struct MediaEntry {
+    pub media: Media,
+    /// Whether the user may edit the alt text or delete the file.
+    pub can_manage: bool,
+}
+
+#[derive(Template)]
+#[template(path = "admin/media.html")]
+pub struct AdminMediaTemplate {
+    pub entries: Vec<MediaEntry>,
+    pub query: String,
+    /// The listing stopped at its limit; older files need a search.
+    pub truncated: bool,
+    pub is_admin: bool,
     pub error: Option<String>,
     pub success: Option<String>,
+    pub csrf_token: String,
+}
+
+/// The file grid of the media library, swapped in after an upload.
+#[derive(Template)]
+#[template(path = "admin/media_grid.html")]
+pub struct AdminMediaGridTemplate {
+    pub entries: Vec<MediaEntry>,
+    pub truncated: bool,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub csrf_token: String,
+}
+
+/// Images offered by the editor's "Insert image" picker.
+#[derive(Template)]
+#[template(path = "admin/media_picker_items.html")]
+pub struct AdminMediaPickerTemplate {
+    pub images: Vec<Media>,
+    pub error: Option<String>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/term_edit.html")]
+pub struct AdminTermEditTemplate {
+    pub term: Term,
+    /// Categories the term may be moved under.
+    pub parents: Vec<TermNode>,
     pub is_admin: bool,
+    pub error: Option<String>,
+    pub success: Option<String>,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -163,6 +456,7 @@ pub struct AdminUsersListTemplate {
     pub is_admin: bool,
     pub error: Option<String>,
     pub success: Option<String>,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -170,6 +464,7 @@ pub struct AdminUsersListTemplate {
 pub struct AdminUserNewTemplate {
     pub is_admin: bool,
     pub error: Option<String>,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -177,21 +472,26 @@ pub struct AdminUserNewTemplate {
 pub struct AdminUserEditTemplate {
     pub target_user: User,
     pub target_roles: Vec<String>,
+    pub target_can_publish: bool,
+    pub target_unfiltered_html: bool,
     pub is_admin: bool,
     pub error: Option<String>,
     pub success: Option<String>,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "404.html")]
 pub struct NotFoundTemplate {
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
 #[template(path = "401.html")]
 pub struct UnauthorizedTemplate {
     pub is_admin: bool,
+    pub csrf_token: String,
 }
 
 #[derive(Template)]
@@ -202,3 +502,18 @@ pub struct AdminHistoryPartialTemplate {
     pub current_rev: i32,
     pub content_item_id: Uuid,
 }
+
+#[derive(Template)]
+#[template(path = "email/password_reset.txt")]
+pub struct PasswordResetEmailTemplate {
+    pub link: String,
+    pub ttl_minutes: i64,
+}
+
+#[derive(Template)]
+#[template(path = "email/verify_email.txt")]
+pub struct VerifyEmailEmailTemplate<'a> {
+    pub email: &'a str,
+    pub link: String,
+    pub ttl_hours: i64,
+}
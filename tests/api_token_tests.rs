@@ -0,0 +1,190 @@
+mod common;
+
+#[cfg(test)]
+pub mod api_token_tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::TokenManager;
+
+    fn new_token(
+        user: &User,
+        token: &str,
+        scopes: Vec<ApiScope>,
+        expires_at: Option<chrono::DateTime<Utc>>,
+    ) -> ApiTokenCreate {
+        ApiTokenCreate {
+            user_id: user.id,
+            name: "deploy script".into(),
+            token_hash: TokenManager::hash(token),
+            scopes,
+            expires_at,
+        }
+    }
+
+    #[test]
+    fn test_api_scope_round_trip() {
+        for scope in ApiScope::ALL {
+            assert_eq!(scope.as_str().parse::<ApiScope>(), Ok(scope));
+        }
+        assert!("content:delete".parse::<ApiScope>().is_err());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_create_and_lookup_api_token(pool: PgPool) {
+        let user = get_seed_user_0();
+        let data = new_token(
+            &user,
+            "tok-a",
+            vec![ApiScope::ContentRead, ApiScope::ContentWrite],
+            None,
+        );
+        let created = create_api_token(&pool, &data)
+            .await
+            .expect("Failed to create token");
+
+        assert_eq!(created.user_id, user.id);
+        assert!(created.has_scope(ApiScope::ContentWrite));
+        assert!(!created.has_scope(ApiScope::UsersAdmin));
+        assert!(created.last_used_at.is_none());
+
+        let found =
+            get_active_api_token(&pool, &TokenManager::hash("tok-a"))
+                .await
+                .expect("Failed to look up token")
+                .expect("Token should be active");
+        assert_eq!(found.id, created.id);
+
+        let missing =
+            get_active_api_token(&pool, &TokenManager::hash("tok-b"))
+                .await
+                .expect("Failed to look up token");
+        assert!(missing.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_expired_api_token_is_inactive(pool: PgPool) {
+        let user = get_seed_user_0();
+        let data = new_token(
+            &user,
+            "tok-old",
+            vec![ApiScope::ContentRead],
+            Some(Utc::now() - Duration::minutes(1)),
+        );
+        let created = create_api_token(&pool, &data)
+            .await
+            .expect("Failed to create token");
+        assert!(created.is_expired());
+
+        let found = get_active_api_token(
+            &pool,
+            &TokenManager::hash("tok-old"),
+        )
+        .await
+        .expect("Failed to look up token");
+        assert!(found.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_deleted_user_api_token_is_inactive(pool: PgPool) {
+        let user = get_seed_user_deleted();
+        let data = new_token(
+            &user,
+            "tok-del",
+            vec![ApiScope::ContentRead],
+            None,
+        );
+        create_api_token(&pool, &data)
+            .await
+            .expect("Failed to create token");
+
+        let found = get_active_api_token(
+            &pool,
+            &TokenManager::hash("tok-del"),
+        )
+        .await
+        .expect("Failed to look up token");
+        assert!(found.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_touch_api_token_sets_last_used(pool: PgPool) {
+        let user = get_seed_user_0();
+        let data = new_token(
+            &user,
+            "tok-t",
+            vec![ApiScope::ContentRead],
+            None,
+        );
+        let created = create_api_token(&pool, &data)
+            .await
+            .expect("Failed to create token");
+
+        touch_api_token(&pool, created.id)
+            .await
+            .expect("Failed to touch token");
+        let first =
+            get_active_api_token(&pool, &TokenManager::hash("tok-t"))
+                .await
+                .expect("Failed to look up token")
+                .expect("Token should be active")
+                .last_used_at
+                .expect("last_used_at should be set");
+
+        // A second use within the throttle window is not written.
+        touch_api_token(&pool, created.id)
+            .await
+            .expect("Failed to touch token");
+        let second =
+            get_active_api_token(&pool, &TokenManager::hash("tok-t"))
+                .await
+                .expect("Failed to look up token")
+                .expect("Token should be active")
+                .last_used_at;
+        assert_eq!(second, Some(first));
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_delete_api_token_scoped_to_owner(pool: PgPool) {
+        let user = get_seed_user_0();
+        let other = get_seed_user_1();
+        let data = new_token(
+            &user,
+            "tok-r",
+            vec![ApiScope::ContentRead],
+            None,
+        );
+        let created = create_api_token(&pool, &data)
+            .await
+            .expect("Failed to create token");
+
+        assert!(
+            !delete_api_token_for_user(&pool, created.id, other.id)
+                .await
+                .expect("Failed to delete token")
+        );
+        assert_eq!(
+            list_api_tokens_for_user(&pool, user.id)
+                .await
+                .expect("Failed to list tokens")
+                .len(),
+            1
+        );
+
+        assert!(
+            delete_api_token_for_user(&pool, created.id, user.id)
+                .await
+                .expect("Failed to delete token")
+        );
+        assert!(
+            list_api_tokens_for_user(&pool, user.id)
+                .await
+                .expect("Failed to list tokens")
+                .is_empty()
+        );
+    }
+}
@@ -1,6 +1,10 @@
+#![allow(dead_code)]
+
 use chrono::{DateTime, Utc};
+use sqlx::PgPool;
 use uuid::Uuid;
 
+use rustpress::db::create_content;
 use rustpress::models::*;
 
 const SQL_TIME_FMT: &str = "%Y-%m-%d %H:%M:%S%#z";
@@ -77,3 +81,54 @@ pub fn get_seed_user_nonexisting() -> User {
         deleted_at: None,
     }
 }
+
+/// A draft of `kind` owned by `owner`, titled after `slug`, with a short
+/// HTML body. Override fields with struct update syntax as needed.
+pub fn new_content(
+    owner: Option<Uuid>,
+    kind: ContentKind,
+    slug: &str,
+) -> ContentCreate {
+    ContentCreate {
+        owner_user_id: owner,
+        kind,
+        title: format!("Title {slug}"),
+        slug: slug.into(),
+        content: "<p>Body</p>".into(),
+        template: "default".into(),
+        unfiltered_html: false,
+        format: ContentFormat::Html,
+        rendered_html: None,
+    }
+}
+
+/// Create `data`, panicking if that fails.
+pub async fn create_from(
+    pool: &PgPool,
+    data: &ContentCreate,
+) -> ContentItem {
+    create_content(pool, data)
+        .await
+        .expect("Failed to create content")
+}
+
+/// Create the draft described by [`new_content`].
+pub async fn create_item(
+    pool: &PgPool,
+    owner: Option<Uuid>,
+    kind: ContentKind,
+    slug: &str,
+) -> ContentItem {
+    create_from(pool, &new_content(owner, kind, slug)).await
+}
+
+/// Create a draft post owned by seed user 0.
+pub async fn create_post(pool: &PgPool, slug: &str) -> ContentItem {
+    create_item(
+        pool,
+        Some(get_seed_user_0().id),
+        ContentKind::Post,
+        slug,
+    )
+    .await
+}
@@ -0,0 +1,412 @@
+mod common;
+
+#[cfg(test)]
+pub mod content_tests {
+    use chrono::Utc;
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_create_content_starts_as_draft(pool: PgPool) {
+        let user = get_seed_user_0();
+        let item = create_item(
+            &pool,
+            Some(user.id),
+            ContentKind::Post,
+            "hello",
+        )
+        .await;
+
+        assert_eq!(item.status, ContentStatus::Draft);
+        assert_eq!(item.owner_user_id, Some(user.id));
+        assert!(item.published_at.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_duplicate_slug_per_owner_and_kind_fails(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let other = get_seed_user_1();
+        create_item(&pool, Some(user.id), ContentKind::Post, "hello")
+            .await;
+
+        let dup = create_content(
+            &pool,
+            &new_content(Some(user.id), ContentKind::Post, "hello"),
+        )
+        .await;
+        assert!(dup.is_err());
+
+        // Same slug is fine for another kind or another owner.
+        create_item(&pool, Some(user.id), ContentKind::Page, "hello")
+            .await;
+        create_item(
+            &pool,
+            Some(other.id),
+            ContentKind::Post,
+            "hello",
+        )
+        .await;
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_update_content_applies_only_given_fields(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let item = create_item(
+            &pool,
+            Some(user.id),
+            ContentKind::Post,
+            "hello",
+        )
+        .await;
+
+        let update = ContentUpdate {
+            title: Some("Renamed".into()),
+            slug: None,
+            content: None,
+            template: None,
+            status: None,
+            unfiltered_html: false,
+            format: None,
+            rendered_html: None,
+        };
+        let updated = update_content(&pool, item.id, &update)
+            .await
+            .expect("Failed to update content")
+            .expect("Content should exist");
+
+        assert_eq!(updated.title, "Renamed");
+        assert_eq!(updated.slug, item.slug);
+        assert_eq!(updated.content, item.content);
+        assert_eq!(updated.status, ContentStatus::Draft);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_going_live_sets_published_at_once(pool: PgPool) {
+        let user = get_seed_user_0();
+        let set_status = |id, status| {
+            let pool = pool.clone();
+            async move {
+                let update = ContentUpdate {
+                    title: None,
+                    slug: None,
+                    content: None,
+                    template: None,
+                    status: Some(status),
+                    unfiltered_html: false,
+                    format: None,
+                    rendered_html: None,
+                };
+                update_content(&pool, id, &update)
+                    .await
+                    .expect("Failed to update content")
+                    .expect("Content should exist")
+            }
+        };
+
+        let post = create_item(
+            &pool,
+            Some(user.id),
+            ContentKind::Post,
+            "post",
+        )
+        .await;
+        let pending =
+            set_status(post.id, ContentStatus::Pending).await;
+        assert!(pending.published_at.is_none());
+        let start = Utc::now();
+        let published =
+            set_status(post.id, ContentStatus::Published).await;
+        let end = Utc::now();
+        let first = published.published_at.expect("Should be dated");
+        assert!(
+            first >= start && first <= end,
+            "Timestamp outside test window"
+        );
+
+        // Editing the live item leaves its date alone.
+        let edit = ContentUpdate {
+            title: Some("Edited".into()),
+            slug: None,
+            content: None,
+            template: None,
+            status: None,
+            unfiltered_html: false,
+            format: None,
+            rendered_html: None,
+        };
+        let edited = update_content(&pool, post.id, &edit)
+            .await
+            .expect("Failed to update content")
+            .expect("Content should exist");
+        assert_eq!(edited.published_at, Some(first));
+
+        // Going back to draft and live again keeps the first date.
+        set_status(post.id, ContentStatus::Draft).await;
+        let republished =
+            set_status(post.id, ContentStatus::Published).await;
+        assert_eq!(republished.published_at, Some(first));
+
+        let page = create_item(
+            &pool,
+            Some(user.id),
+            ContentKind::Page,
+            "page",
+        )
+        .await;
+        let private =
+            set_status(page.id, ContentStatus::Private).await;
+        assert!(private.published_at.is_some());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_filtered_listing_respects_visibility(pool: PgPool) {
+        let user = get_seed_user_0();
+        let other = get_seed_user_1();
+        create_item(&pool, Some(user.id), ContentKind::Post, "mine")
+            .await;
+        let shared = create_item(
+            &pool,
+            Some(other.id),
+            ContentKind::Post,
+            "shared",
+        )
+        .await;
+        create_item(
+            &pool,
+            Some(other.id),
+            ContentKind::Post,
+            "private",
+        )
+        .await;
+        create_item(&pool, None, ContentKind::Post, "global").await;
+        create_item(
+            &pool,
+            Some(user.id),
+            ContentKind::Page,
+            "a-page",
+        )
+        .await;
+
+        add_collaborator(
+            &pool,
+            shared.id,
+            &user.email,
+            RoleName::Editor,
+            Some(other.id),
+        )
+        .await
+        .expect("Failed to add collaborator");
+
+        let filter = ContentFilter::default();
+        let items = list_filtered_content_for_user(
+            &pool,
+            ContentKind::Post,
+            &filter,
+            user.id,
+            50,
+            0,
+        )
+        .await
+        .expect("Failed to list content");
+        let mut slugs: Vec<_> =
+            items.iter().map(|i| i.slug.as_str()).collect();
+        slugs.sort();
+        assert_eq!(slugs, ["global", "mine", "shared"]);
+
+        let total = count_filtered_content_for_user(
+            &pool,
+            ContentKind::Post,
+            &filter,
+            user.id,
+        )
+        .await
+        .expect("Failed to count content");
+        assert_eq!(total, 3);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_filtered_listing_by_status_owner_and_slug(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let draft = create_item(
+            &pool,
+            Some(user.id),
+            ContentKind::Post,
+            "draft",
+        )
+        .await;
+        let published = create_item(
+            &pool,
+            Some(user.id),
+            ContentKind::Post,
+            "live",
+        )
+        .await;
+        publish_content(&pool, published.id)
+            .await
+            .expect("Failed to publish");
+        create_item(&pool, None, ContentKind::Post, "global").await;
+
+        let list = |filter: ContentFilter| {
+            let pool = pool.clone();
+            async move {
+                list_filtered_content_for_user(
+                    &pool,
+                    ContentKind::Post,
+                    &filter,
+                    user.id,
+                    50,
+                    0,
+                )
+                .await
+                .expect("Failed to list content")
+            }
+        };
+
+        let items = list(ContentFilter {
+            status: Some(ContentStatus::Published),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, published.id);
+
+        let items = list(ContentFilter {
+            owner_user_id: Some(user.id),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(items.len(), 2);
+
+        let items = list(ContentFilter {
+            slug: Some("draft".into()),
+            ..Default::default()
+        })
+        .await;
+        assert_eq!(items.len(), 1);
+        assert_eq!(items[0].id, draft.id);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_filtered_listing_pages(pool: PgPool) {
+        let user = get_seed_user_0();
+        for i in 0..5 {
+            create_item(
+                &pool,
+                Some(user.id),
+                ContentKind::Post,
+                &format!("p{i}"),
+            )
+            .await;
+        }
+
+        let filter = ContentFilter::default();
+        let mut seen = Vec::new();
+        for offset in [0, 2, 4] {
+            let page = list_filtered_content_for_user(
+                &pool,
+                ContentKind::Post,
+                &filter,
+                user.id,
+                2,
+                offset,
+            )
+            .await
+            .expect("Failed to list content");
+            seen.extend(page.into_iter().map(|i| i.id));
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 5);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_listing_past_the_last_page(pool: PgPool) {
+        let user = get_seed_user_0();
+        create_item(&pool, Some(user.id), ContentKind::Post, "p")
+            .await;
+
+        assert_eq!(page_offset(0, 20), 0);
+        assert_eq!(page_offset(3, 20), 40);
+        assert_eq!(page_offset(i64::MAX, 100), i64::MAX);
+
+        let items = list_filtered_content_for_user(
+            &pool,
+            ContentKind::Post,
+            &ContentFilter::default(),
+            user.id,
+            100,
+            page_offset(i64::MAX, 100),
+        )
+        .await
+        .expect("Failed to list content");
+        assert!(items.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_collaborators_gate_access(pool: PgPool) {
+        let owner = get_seed_user_0();
+        let user = get_seed_user_1();
+        let item = create_item(
+            &pool,
+            Some(owner.id),
+            ContentKind::Post,
+            "doc",
+        )
+        .await;
+
+        assert!(
+            !can_view_content(&pool, &item, user.id).await.unwrap()
+        );
+        assert!(
+            !can_edit_content(&pool, &item, user.id).await.unwrap()
+        );
+
+        add_collaborator(
+            &pool,
+            item.id,
+            &user.email,
+            RoleName::Editor,
+            Some(owner.id),
+        )
+        .await
+        .expect("Failed to add collaborator");
+        assert!(
+            can_view_content(&pool, &item, user.id).await.unwrap()
+        );
+        assert!(
+            can_edit_content(&pool, &item, user.id).await.unwrap()
+        );
+
+        remove_collaborator(&pool, item.id, user.id)
+            .await
+            .expect("Failed to remove collaborator");
+        assert!(
+            !can_edit_content(&pool, &item, user.id).await.unwrap()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_is_email_verified(pool: PgPool) {
+        let user = get_seed_user_0();
+        assert!(!is_email_verified(&pool, user.id).await.unwrap());
+
+        sqlx::query(
+            "UPDATE users SET email_verified_at = now() WHERE id = $1",
+        )
+        .bind(user.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        assert!(is_email_verified(&pool, user.id).await.unwrap());
+    }
+}
@@ -57,10 +57,6 @@ pub mod db_tests {
         assert!(fetched_n.is_none());
     }
 
-    async fn test_database_new(pool: PgPool) {
-        todo!();
-    }
-
     #[sqlx::test(migrations = "./tests/migrations")]
     async fn test_from_pool(pool: PgPool) {
         let db = Database::from_pool(pool.clone());
@@ -0,0 +1,326 @@
+mod common;
+
+#[cfg(test)]
+pub mod email_verification_tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::common::MailError;
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::{
+        EmailMessage, MailOutbox, Mailer, TokenManager,
+    };
+
+    /// Accepts every message.
+    struct NullMailer;
+
+    impl Mailer for NullMailer {
+        fn send(&self, _: &EmailMessage) -> Result<(), MailError> {
+            Ok(())
+        }
+    }
+
+    fn new_verification(
+        user: &User,
+        email: &str,
+        token: &str,
+    ) -> EmailVerificationTokenCreate {
+        EmailVerificationTokenCreate {
+            user_id: user.id,
+            email: email.to_string(),
+            token_hash: TokenManager::hash(token),
+            expires_at: Utc::now() + Duration::hours(48),
+        }
+    }
+
+    async fn load(pool: &PgPool, user: &User) -> User {
+        sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = $1")
+            .bind(user.id)
+            .fetch_one(pool)
+            .await
+            .expect("Failed to load user")
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_verify_email_with_token_success(pool: PgPool) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        create_email_verification_token(
+            &pool,
+            &new_verification(&user, &user.email, &token),
+        )
+        .await
+        .expect("Failed to create verification token");
+
+        let verified = verify_email_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await
+        .expect("Failed to verify email")
+        .expect("Token should be usable");
+
+        assert_eq!(verified.id, user.id);
+        assert_eq!(verified.email, user.email);
+        assert!(verified.email_verified_at.is_some());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_verify_email_with_token_switches_to_new_address(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        create_email_verification_token(
+            &pool,
+            &new_verification(&user, "changed@test.com", &token),
+        )
+        .await
+        .expect("Failed to create verification token");
+
+        let before = load(&pool, &user).await;
+        assert_eq!(
+            before.email, user.email,
+            "Old address must stay active until confirmed"
+        );
+
+        let pending = get_pending_email_verification(&pool, user.id)
+            .await
+            .expect("Failed to get pending verification")
+            .expect("Verification should be pending");
+        assert_eq!(pending.email, "changed@test.com");
+
+        let verified = verify_email_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await
+        .expect("Failed to verify email")
+        .expect("Token should be usable");
+
+        assert_eq!(verified.email, "changed@test.com");
+        assert!(verified.email_verified_at.is_some());
+        assert!(
+            get_pending_email_verification(&pool, user.id)
+                .await
+                .expect("Failed to get pending verification")
+                .is_none()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_verify_email_with_token_is_single_use(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        create_email_verification_token(
+            &pool,
+            &new_verification(&user, &user.email, &token),
+        )
+        .await
+        .expect("Failed to create verification token");
+
+        let first = verify_email_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await
+        .expect("Failed to verify email");
+        let second = verify_email_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await
+        .expect("Failed to verify email");
+
+        assert!(first.is_some());
+        assert!(second.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_verify_email_with_token_fails_on_expired(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        let mut data = new_verification(&user, &user.email, &token);
+        data.expires_at = Utc::now() - Duration::minutes(1);
+        create_email_verification_token(&pool, &data)
+            .await
+            .expect("Failed to create verification token");
+
+        let result = verify_email_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await
+        .expect("Failed to verify email");
+
+        assert!(result.is_none());
+        assert!(load(&pool, &user).await.email_verified_at.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_verify_email_with_token_fails_on_taken_address(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let other = get_seed_user_1();
+        let token = TokenManager::generate();
+
+        create_email_verification_token(
+            &pool,
+            &new_verification(&user, &other.email, &token),
+        )
+        .await
+        .expect("Failed to create verification token");
+
+        let result = verify_email_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await;
+
+        assert!(result.is_err(), "Unique email must be enforced");
+
+        let after = load(&pool, &user).await;
+        assert_eq!(after.email, user.email);
+        assert!(after.email_verified_at.is_none());
+        assert!(
+            get_pending_email_verification(&pool, user.id)
+                .await
+                .expect("Failed to get pending verification")
+                .is_some(),
+            "Failed verification must not spend the token"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_create_token_replaces_earlier_unused_tokens(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let first = TokenManager::generate();
+        let second = TokenManager::generate();
+
+        create_email_verification_token(
+            &pool,
+            &new_verification(&user, "first@test.com", &first),
+        )
+        .await
+        .expect("Failed to create verification token");
+        create_email_verification_token(
+            &pool,
+            &new_verification(&user, "second@test.com", &second),
+        )
+        .await
+        .expect("Failed to create verification token");
+
+        let stale = verify_email_with_token(
+            &pool,
+            &TokenManager::hash(&first),
+        )
+        .await
+        .expect("Failed to verify email");
+        assert!(stale.is_none());
+
+        let pending = get_pending_email_verification(&pool, user.id)
+            .await
+            .expect("Failed to get pending verification")
+            .expect("Verification should be pending");
+        assert_eq!(pending.email, "second@test.com");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_update_user_email_clears_verification(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        create_email_verification_token(
+            &pool,
+            &new_verification(&user, &user.email, &token),
+        )
+        .await
+        .expect("Failed to create verification token");
+        verify_email_with_token(&pool, &TokenManager::hash(&token))
+            .await
+            .expect("Failed to verify email");
+
+        update_user_email(&pool, user.id, &user.email)
+            .await
+            .expect("Failed to update email");
+        assert!(
+            load(&pool, &user).await.email_verified_at.is_some(),
+            "Unchanged address keeps its verification"
+        );
+
+        update_user_email(&pool, user.id, "admin-set@test.com")
+            .await
+            .expect("Failed to update email");
+        let after = load(&pool, &user).await;
+        assert_eq!(after.email, "admin-set@test.com");
+        assert!(after.email_verified_at.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_verification_link_does_not_outlive_delivery(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+        create_email_verification_token(
+            &pool,
+            &new_verification(&user, &user.email, &token),
+        )
+        .await
+        .expect("Failed to create verification token");
+
+        let outbox = MailOutbox::new(pool.clone());
+        outbox
+            .enqueue(&EmailMessage {
+                to: user.email.clone(),
+                subject: "Confirm your email address".into(),
+                body: format!(
+                    "https://example.com/admin/verify-email/{token}"
+                ),
+            })
+            .await
+            .expect("Failed to enqueue mail");
+        let mailer: std::sync::Arc<dyn Mailer> =
+            std::sync::Arc::new(NullMailer);
+        outbox
+            .deliver_due(&mailer)
+            .await
+            .expect("Failed to deliver mail");
+
+        let stored: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM mail_outbox WHERE body LIKE '%' || $1 || '%'",
+        )
+        .bind(&token)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to query");
+        assert_eq!(stored, 0, "The token must not stay stored");
+
+        // The link in the delivered mail still works.
+        assert!(
+            verify_email_with_token(
+                &pool,
+                &TokenManager::hash(&token)
+            )
+            .await
+            .expect("Failed to verify email")
+            .is_some()
+        );
+    }
+}
@@ -0,0 +1,123 @@
+mod common;
+
+#[cfg(test)]
+pub mod feed_tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::*;
+
+    #[test]
+    fn test_excerpt_strips_markup_and_truncates() {
+        let html = "<h2>Title</h2><p>One <b>two</b>&amp; three</p>";
+        assert_eq!(excerpt(html, 10), "Title One two &amp; three");
+        assert_eq!(excerpt(html, 2), "Title One…");
+        assert_eq!(excerpt("", 5), "");
+    }
+
+    #[test]
+    fn test_absolute_urls() {
+        let base = "https://example.com";
+        assert_eq!(
+            absolute_urls(
+                r#"<a href="/blog/x">x</a><img src='/static/a.png'>"#,
+                base
+            ),
+            r#"<a href="https://example.com/blog/x">x</a><img src='https://example.com/static/a.png'>"#
+        );
+        // Protocol-relative and absolute links are left alone.
+        let untouched = r#"<a href="//cdn.example.org/a">a</a><a href="https://b.org/">b</a>"#;
+        assert_eq!(absolute_urls(untouched, base), untouched);
+    }
+
+    #[test]
+    fn test_etag_matching() {
+        let etag = etag_for("<rss/>");
+        assert_eq!(etag, etag_for("<rss/>"));
+        assert_ne!(etag, etag_for("<rss></rss>"));
+        assert!(etag_matches(&etag, &etag));
+        assert!(etag_matches(&format!("\"a\", W/{etag}"), &etag));
+        assert!(etag_matches("*", &etag));
+        assert!(!etag_matches("\"a\", \"b\"", &etag));
+    }
+
+    #[test]
+    fn test_feed_etag() {
+        let id = uuid::Uuid::nil();
+        let at = Utc::now();
+        let etag = feed_etag("v1 /feed.xml", [(id, at)]);
+        assert_eq!(etag, feed_etag("v1 /feed.xml", [(id, at)]));
+        assert_ne!(etag, feed_etag("v1 /atom.xml", [(id, at)]));
+        assert_ne!(
+            etag,
+            feed_etag(
+                "v1 /feed.xml",
+                [(id, at + Duration::seconds(1))]
+            )
+        );
+        assert_ne!(etag, feed_etag("v1 /feed.xml", []));
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_feed_lists_live_posts_by_author(pool: PgPool) {
+        let other = get_seed_user_1().id;
+        let mut live = Vec::new();
+        for slug in ["one", "two", "three"] {
+            let post = create_post(&pool, slug).await;
+            publish_content(&pool, post.id).await.unwrap();
+            live.push(post.id);
+        }
+        create_post(&pool, "draft").await;
+        let theirs = create_item(
+            &pool,
+            Some(other),
+            ContentKind::Post,
+            "theirs",
+        )
+        .await;
+        publish_content(&pool, theirs.id).await.unwrap();
+        live.push(theirs.id);
+        live.reverse();
+
+        let ids = |posts: Vec<ContentItem>| {
+            posts.into_iter().map(|p| p.id).collect::<Vec<_>>()
+        };
+        let all = list_feed_posts(&pool, None, 10).await.unwrap();
+        assert_eq!(ids(all), live);
+        let newest = list_feed_posts(&pool, None, 2).await.unwrap();
+        assert_eq!(ids(newest), live[..2]);
+        let by_other =
+            list_feed_posts(&pool, Some(other), 10).await.unwrap();
+        assert_eq!(ids(by_other), [theirs.id]);
+
+        // The stamps describe the same posts, dated as the feed does.
+        let posts = list_feed_posts(&pool, None, 3).await.unwrap();
+        let stamps = list_feed_stamps(&pool, None, 3).await.unwrap();
+        let expected: Vec<_> =
+            posts.iter().map(|p| (p.id, feed_updated(p))).collect();
+        assert_eq!(stamps, expected);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_feed_dates(pool: PgPool) {
+        let post = create_post(&pool, "hello").await;
+        // Never published: the creation date stands in.
+        assert_eq!(feed_published(&post), post.created_at);
+
+        let mut post =
+            publish_content(&pool, post.id).await.unwrap().unwrap();
+        let published = post.published_at.unwrap();
+        assert_eq!(feed_published(&post), published);
+        assert!(feed_updated(&post) >= published);
+
+        post.edited_at = published + Duration::hours(1);
+        assert_eq!(feed_updated(&post), post.edited_at);
+        post.edited_at = published - Duration::hours(1);
+        assert_eq!(feed_updated(&post), published);
+        assert!(feed_updated(&post) <= Utc::now());
+    }
+}
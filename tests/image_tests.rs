@@ -0,0 +1,160 @@
+#[cfg(test)]
+pub mod image_tests {
+    use std::collections::HashMap;
+    use std::io::Cursor;
+
+    use chrono::Utc;
+    use image::{ImageFormat, RgbImage};
+    use uuid::Uuid;
+
+    use rustpress::common::MediaError;
+    use rustpress::models::*;
+    use rustpress::services::*;
+
+    const WIDTHS: [u32; 3] = [320, 640, 1024];
+
+    fn encoded(
+        format: ImageFormat,
+        width: u32,
+        height: u32,
+    ) -> Vec<u8> {
+        let mut out = Cursor::new(Vec::new());
+        RgbImage::new(width, height)
+            .write_to(&mut out, format)
+            .expect("Failed to encode image");
+        out.into_inner()
+    }
+
+    fn image(mime_type: &str, width: i32, height: i32) -> Media {
+        let id = Uuid::new_v4();
+        Media {
+            id,
+            owner_user_id: None,
+            filename: "photo".into(),
+            storage_key: media_storage_key(id, "jpg"),
+            mime_type: mime_type.into(),
+            size_bytes: 42,
+            width: Some(width),
+            height: Some(height),
+            alt_text: String::new(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_image_widths() {
+        assert_eq!(
+            parse_image_widths(" 640, 320,,640 ").unwrap(),
+            [320, 640]
+        );
+        assert!(parse_image_widths("").unwrap().is_empty());
+        for invalid in ["wide", "8", "10000", "-320"] {
+            assert!(parse_image_widths(invalid).is_err());
+        }
+    }
+
+    #[test]
+    fn test_variant_widths() {
+        let photo = image("image/jpeg", 800, 600);
+        assert_eq!(variant_widths(&photo, &WIDTHS), [320, 640]);
+        let small = image("image/png", 320, 200);
+        assert!(variant_widths(&small, &WIDTHS).is_empty());
+        // GIFs may be animated and are never resized.
+        let gif = image("image/gif", 2000, 1000);
+        assert!(variant_widths(&gif, &WIDTHS).is_empty());
+
+        assert_eq!(media_variant_key("abc.jpg", 640), "abc-640w.jpg");
+        assert_eq!(media_variant_key("abc", 320), "abc-320w");
+    }
+
+    #[test]
+    fn test_resize_image() {
+        for (format, mime_type) in [
+            (ImageFormat::Png, "image/png"),
+            (ImageFormat::Jpeg, "image/jpeg"),
+        ] {
+            let resized = resize_image(
+                &encoded(format, 800, 400),
+                mime_type,
+                200,
+            )
+            .unwrap();
+            let sniffed = sniff_media(&resized).unwrap();
+            assert_eq!(sniffed.mime_type, mime_type);
+            assert_eq!(sniffed.dimensions, Some((200, 100)));
+        }
+        assert!(matches!(
+            resize_image(b"not an image", "image/png", 200),
+            Err(MediaError::Processing(_))
+        ));
+        assert!(matches!(
+            resize_image(b"GIF89a", "image/gif", 200),
+            Err(MediaError::UnsupportedType)
+        ));
+    }
+
+    #[test]
+    fn test_media_ids_in() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let html = format!(
+            "<p><img src=\"/media/{a}\"><IMG alt=x SRC='/media/{b}'>\
+             <img src=\"/media/{a}\"><img src=\"/media/nope\">\
+             <img src=\"https://example.com/media/{b}\"></p>"
+        );
+        assert_eq!(media_ids_in(&html), [a, b]);
+    }
+
+    #[test]
+    fn test_add_responsive_images() {
+        let photo = image("image/jpeg", 800, 600);
+        let url = photo.url();
+        let media = HashMap::from([(photo.id, photo)]);
+
+        let html =
+            format!("<p><img src=\"{url}\" alt=\"a > b\"></p>");
+        assert_eq!(
+            add_responsive_images(&html, &media, &WIDTHS),
+            format!(
+                "<p><img src=\"{url}\" alt=\"a > b\" \
+                 loading=\"lazy\" srcset=\"{url}/320 320w, \
+                 {url}/640 640w, {url} 800w\" sizes=\"(max-width: \
+                 800px) 100vw, 800px\" width=\"800\" \
+                 height=\"600\"></p>"
+            )
+        );
+
+        // Attributes the author set win.
+        let html = format!(
+            "<img src=\"{url}\" loading=\"eager\" sizes=\"50vw\" \
+             width=\"400\" />"
+        );
+        assert_eq!(
+            add_responsive_images(&html, &media, &WIDTHS),
+            format!(
+                "<img src=\"{url}\" loading=\"eager\" sizes=\"50vw\" \
+                 width=\"400\" srcset=\"{url}/320 320w, \
+                 {url}/640 640w, {url} 800w\" />"
+            )
+        );
+
+        // Other images are only loaded lazily.
+        assert_eq!(
+            add_responsive_images(
+                "<img src=\"https://example.com/a.png\"/><imgs>",
+                &media,
+                &WIDTHS
+            ),
+            "<img src=\"https://example.com/a.png\" \
+             loading=\"lazy\" /><imgs>"
+        );
+        assert_eq!(
+            add_responsive_images(
+                "<p>a < b</p><img",
+                &media,
+                &WIDTHS
+            ),
+            "<p>a < b</p><img"
+        );
+    }
+}
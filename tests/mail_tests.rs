@@ -0,0 +1,335 @@
+mod common;
+
+#[cfg(test)]
+pub mod mail_tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use rustpress::common::MailError;
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::{
+        EmailMessage, FileMailer, MAX_MAIL_ATTEMPTS, MailOutbox,
+        Mailer, TokenManager, mail_retry_delay,
+    };
+
+    /// Records successful sends; fails while `fail` is set.
+    struct CountingMailer {
+        sent: AtomicUsize,
+        fail: bool,
+    }
+
+    impl Mailer for CountingMailer {
+        fn send(&self, _: &EmailMessage) -> Result<(), MailError> {
+            if self.fail {
+                return Err(MailError::Transport("refused".into()));
+            }
+            self.sent.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn counting_mailer(fail: bool) -> Arc<CountingMailer> {
+        Arc::new(CountingMailer {
+            sent: AtomicUsize::new(0),
+            fail,
+        })
+    }
+
+    fn message(to: &str) -> EmailMessage {
+        EmailMessage {
+            to: to.to_string(),
+            subject: "Hello".to_string(),
+            body: "Line one\nLine two".to_string(),
+        }
+    }
+
+    async fn load(pool: &PgPool, mail: &OutboxMail) -> OutboxMail {
+        sqlx::query_as::<_, OutboxMail>(
+            "SELECT * FROM mail_outbox WHERE id = $1",
+        )
+        .bind(mail.id)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to load mail")
+    }
+
+    /// Make a rescheduled message due again.
+    async fn make_due(pool: &PgPool, mail: &OutboxMail) {
+        sqlx::query(
+            "UPDATE mail_outbox SET next_attempt_at = now() WHERE id = $1",
+        )
+        .bind(mail.id)
+        .execute(pool)
+        .await
+        .expect("Failed to reschedule mail");
+    }
+
+    #[test]
+    fn test_file_mailer_writes_eml_success() {
+        let dir = std::env::temp_dir()
+            .join(format!("rustpress-mail-{}", uuid::Uuid::new_v4()));
+        let mailer =
+            FileMailer::new(&dir, "RustPress <noreply@example.com>")
+                .expect("Failed to create file mailer");
+
+        mailer
+            .send(&message("user@example.com"))
+            .expect("Failed to send");
+
+        let files: Vec<_> = std::fs::read_dir(&dir)
+            .expect("Failed to read mail dir")
+            .map(|e| e.expect("Failed to read entry").path())
+            .collect();
+        assert_eq!(files.len(), 1);
+        assert_eq!(
+            files[0].extension().and_then(|e| e.to_str()),
+            Some("eml")
+        );
+
+        let eml = std::fs::read_to_string(&files[0])
+            .expect("Failed to read eml");
+        assert!(eml.contains("To: user@example.com"));
+        assert!(eml.contains("Subject: Hello"));
+        assert!(eml.contains("Line two"));
+
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_file_mailer_fails_on_invalid_address() {
+        let dir = std::env::temp_dir()
+            .join(format!("rustpress-mail-{}", uuid::Uuid::new_v4()));
+        let mailer = FileMailer::new(&dir, "noreply@example.com")
+            .expect("Failed to create file mailer");
+
+        let result = mailer.send(&message("not an address"));
+
+        assert!(matches!(result, Err(MailError::InvalidAddress(_))));
+        std::fs::remove_dir_all(&dir).ok();
+    }
+
+    #[test]
+    fn test_mail_retry_delay_backs_off() {
+        assert_eq!(mail_retry_delay(1), Duration::minutes(1));
+        assert_eq!(mail_retry_delay(2), Duration::minutes(2));
+        assert_eq!(mail_retry_delay(4), Duration::minutes(8));
+        assert_eq!(mail_retry_delay(50), Duration::hours(1));
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_claim_due_mail_leases_rows(pool: PgPool) {
+        let mail = enqueue_mail(
+            &pool,
+            &OutboxMailCreate {
+                recipient: "user@example.com".into(),
+                subject: "Hello".into(),
+                body: "Body".into(),
+            },
+        )
+        .await
+        .expect("Failed to enqueue mail");
+        assert_eq!(mail.status, MailStatus::Pending);
+        assert_eq!(mail.attempts, 0);
+
+        let claimed = claim_due_mail(&pool, 10, Duration::minutes(5))
+            .await
+            .expect("Failed to claim mail");
+        assert_eq!(claimed.len(), 1);
+        assert_eq!(claimed[0].attempts, 1);
+        assert!(claimed[0].next_attempt_at > Utc::now());
+
+        let again = claim_due_mail(&pool, 10, Duration::minutes(5))
+            .await
+            .expect("Failed to claim mail");
+        assert!(
+            again.is_empty(),
+            "Leased mail must not be reclaimed"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_outbox_delivers_queued_mail(pool: PgPool) {
+        let outbox = MailOutbox::new(pool.clone());
+        let counter = counting_mailer(false);
+        let mailer: Arc<dyn Mailer> = counter.clone();
+
+        let mail = outbox
+            .enqueue(&message("user@example.com"))
+            .await
+            .expect("Failed to enqueue mail");
+
+        let delivered = outbox
+            .deliver_due(&mailer)
+            .await
+            .expect("Failed to deliver mail");
+
+        assert_eq!(delivered, 1);
+        assert_eq!(counter.sent.load(Ordering::SeqCst), 1);
+        let stored = load(&pool, &mail).await;
+        assert_eq!(stored.status, MailStatus::Sent);
+        assert!(stored.sent_at.is_some());
+        assert_eq!(
+            stored.body, "",
+            "Sent mail must not keep its body"
+        );
+
+        let delivered = outbox
+            .deliver_due(&mailer)
+            .await
+            .expect("Failed to deliver mail");
+        assert_eq!(delivered, 0, "Sent mail must not be resent");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_outbox_retries_then_gives_up(pool: PgPool) {
+        let outbox = MailOutbox::new(pool.clone());
+        let mailer: Arc<dyn Mailer> = counting_mailer(true);
+
+        let mail = outbox
+            .enqueue(&message("user@example.com"))
+            .await
+            .expect("Failed to enqueue mail");
+
+        outbox
+            .deliver_due(&mailer)
+            .await
+            .expect("Failed to deliver mail");
+        let stored = load(&pool, &mail).await;
+        assert_eq!(stored.status, MailStatus::Pending);
+        assert_eq!(stored.body, mail.body, "Retries need the body");
+        assert_eq!(stored.attempts, 1);
+        assert_eq!(
+            stored.last_error.as_deref(),
+            Some("Mail transport error: refused")
+        );
+        assert!(stored.next_attempt_at > Utc::now());
+
+        for _ in 1..MAX_MAIL_ATTEMPTS {
+            make_due(&pool, &mail).await;
+            outbox
+                .deliver_due(&mailer)
+                .await
+                .expect("Failed to deliver mail");
+        }
+
+        let stored = load(&pool, &mail).await;
+        assert_eq!(stored.status, MailStatus::Failed);
+        assert_eq!(stored.attempts, MAX_MAIL_ATTEMPTS);
+        assert_eq!(stored.body, "");
+
+        make_due(&pool, &mail).await;
+        let claimed = claim_due_mail(&pool, 10, Duration::minutes(5))
+            .await
+            .expect("Failed to claim mail");
+        assert!(
+            claimed.is_empty(),
+            "Failed mail must not be retried"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_reset_link_does_not_outlive_delivery(pool: PgPool) {
+        let outbox = MailOutbox::new(pool.clone());
+        let mailer: Arc<dyn Mailer> = counting_mailer(false);
+        let token = TokenManager::generate();
+
+        let mail = outbox
+            .enqueue(&EmailMessage {
+                to: "user@example.com".into(),
+                subject: "Reset your password".into(),
+                body: format!(
+                    "https://example.com/admin/reset-password/{token}"
+                ),
+            })
+            .await
+            .expect("Failed to enqueue mail");
+        outbox
+            .deliver_due(&mailer)
+            .await
+            .expect("Failed to deliver mail");
+
+        let leaked: bool = sqlx::query_scalar(
+            "SELECT EXISTS(SELECT 1 FROM mail_outbox WHERE body LIKE '%' || $1 || '%')",
+        )
+        .bind(&token)
+        .fetch_one(&pool)
+        .await
+        .expect("Failed to query");
+        assert!(!leaked, "The reset token must not stay stored");
+        assert_eq!(
+            load(&pool, &mail).await.subject,
+            "Reset your password"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_delete_finished_mail_keeps_pending(pool: PgPool) {
+        let outbox = MailOutbox::new(pool.clone());
+        let mailer: Arc<dyn Mailer> = counting_mailer(false);
+
+        let sent = outbox
+            .enqueue(&message("sent@example.com"))
+            .await
+            .expect("Failed to enqueue mail");
+        outbox
+            .deliver_due(&mailer)
+            .await
+            .expect("Failed to deliver mail");
+        let failed = outbox
+            .enqueue(&message("failed@example.com"))
+            .await
+            .expect("Failed to enqueue mail");
+        mark_mail_failed(&pool, failed.id, "refused", None)
+            .await
+            .expect("Failed to fail mail");
+        let pending = outbox
+            .enqueue(&message("pending@example.com"))
+            .await
+            .expect("Failed to enqueue mail");
+
+        let deleted = delete_finished_mail(
+            &pool,
+            Utc::now() + Duration::minutes(1),
+        )
+        .await
+        .expect("Failed to delete finished mail");
+
+        assert_eq!(deleted, 2);
+        for gone in [&sent, &failed] {
+            assert!(
+                sqlx::query(
+                    "SELECT 1 FROM mail_outbox WHERE id = $1"
+                )
+                .bind(gone.id)
+                .fetch_optional(&pool)
+                .await
+                .expect("Failed to query")
+                .is_none()
+            );
+        }
+        assert_eq!(
+            load(&pool, &pending).await.status,
+            MailStatus::Pending
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_outbox_rejects_unknown_status(pool: PgPool) {
+        let mail = MailOutbox::new(pool.clone())
+            .enqueue(&message("a@example.com"))
+            .await
+            .expect("Failed to enqueue mail");
+        let result = sqlx::query(
+            "UPDATE mail_outbox SET status = $1 WHERE id = $2",
+        )
+        .bind("bounced")
+        .bind(mail.id)
+        .execute(&pool)
+        .await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,236 @@
+mod common;
+
+#[cfg(test)]
+pub mod markdown_tests {
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::*;
+
+    const GFM: &str = "\
+| Name | Qty |
+|:-----|:---:|
+| Tea  | 2   |
+
+Brewed twice[^steep], ~~never~~ rarely cold.
+
+- [x] boil water
+- [ ] steep
+
+[^steep]: Three minutes each.
+";
+
+    fn authored(source: &str, unfiltered: bool) -> AuthoredContent {
+        AuthoredContent::new(
+            &HtmlPolicy::default(),
+            ContentFormat::Markdown,
+            source,
+            unfiltered,
+        )
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let html = render_markdown(GFM);
+        for expected in [
+            "<table>",
+            "<th style=\"text-align: left\">Name</th>",
+            "<td style=\"text-align: center\">2</td>",
+            "<del>never</del>",
+            "<sup class=\"footnote-reference\"><a href=\"#fn-1\">1</a></sup>",
+            "<div class=\"footnote-definition\" id=\"fn-1\">",
+            "<input disabled=\"\" type=\"checkbox\" checked=\"\"/>",
+            "<input disabled=\"\" type=\"checkbox\"/>",
+        ] {
+            assert!(html.contains(expected), "{expected} in {html}");
+        }
+        assert_eq!(
+            render_markdown("# Title\n\nSome *text*."),
+            "<h1>Title</h1>\n<p>Some <em>text</em>.</p>\n"
+        );
+    }
+
+    #[test]
+    fn test_markdown_is_sanitized_after_rendering() {
+        let source = format!(
+            "{GFM}\n<script>alert(1)</script>\n\n\
+             <div id=\"login\" class=\"admin\">x</div>\n\n\
+             <input type=\"text\" name=\"q\">\n"
+        );
+        let cleaned = authored(&source, false);
+        // The source survives, only its rendering is cleaned.
+        assert_eq!(cleaned.content, source);
+        let html = cleaned.html();
+        for expected in [
+            "<th style=\"text-align: left\">Name</th>",
+            "<a href=\"#fn-1\" rel=\"noopener noreferrer nofollow\">1</a>",
+            "<div class=\"footnote-definition\" id=\"fn-1\">",
+            "<input disabled=\"\" type=\"checkbox\" checked=\"\">",
+            "<del>never</del>",
+            "<div>x</div>",
+            "<input>",
+        ] {
+            assert!(html.contains(expected), "{expected} in {html}");
+        }
+        assert!(!html.contains("script"), "{html}");
+        assert!(!cleaned.unfiltered_html);
+
+        let unfiltered = authored(&source, true);
+        assert!(
+            unfiltered.html().contains("<script>alert(1)</script>")
+        );
+        assert!(unfiltered.unfiltered_html);
+
+        // Markdown's extra markup is not allowed in HTML content.
+        let policy = HtmlPolicy::default();
+        let rendered = render_markdown(GFM);
+        assert!(!policy.clean(&rendered).contains("<table>"));
+        assert_eq!(
+            policy.for_display(
+                cleaned.html(),
+                ContentFormat::Markdown,
+                false
+            ),
+            cleaned.html()
+        );
+
+        // HTML content is sanitized and has no rendering.
+        let html = AuthoredContent::new(
+            &policy,
+            ContentFormat::Html,
+            "<p onclick=\"x\">a</p>",
+            false,
+        );
+        assert_eq!(html.content, "<p>a</p>");
+        assert_eq!(html.rendered_html, None);
+        assert_eq!(html.html(), "<p>a</p>");
+    }
+
+    #[test]
+    fn test_convert_content() {
+        let html = "<h2>Notes</h2><p>Some <strong>bold</strong> and \
+                    <a href=\"https://example.com\">a link</a>.</p>\
+                    <ul><li><p>one</p></li><li><p>two</p></li></ul>\
+                    <pre><code class=\"language-rust\">fn main() {}\
+                    </code></pre>";
+        let markdown = convert_content(
+            html,
+            ContentFormat::Html,
+            ContentFormat::Markdown,
+        );
+        assert_eq!(
+            markdown,
+            "## Notes\n\nSome **bold** and \
+             [a link](https://example.com).\n\n- one\n\n- two\n\n\
+             ```rust\nfn main() {}\n```\n"
+        );
+
+        // Converting back renders the same structure.
+        let back = convert_content(
+            &markdown,
+            ContentFormat::Markdown,
+            ContentFormat::Html,
+        );
+        assert_eq!(
+            back,
+            "<h2>Notes</h2>\n<p>Some <strong>bold</strong> and \
+             <a href=\"https://example.com\">a link</a>.</p>\n<ul>\n\
+             <li>\n<p>one</p>\n</li>\n<li>\n<p>two</p>\n</li>\n</ul>\n\
+             <pre><code class=\"language-rust\">fn main() {}\n\
+             </code></pre>\n"
+        );
+
+        assert_eq!(
+            convert_content(
+                "<p>x</p>",
+                ContentFormat::Html,
+                ContentFormat::Html
+            ),
+            "<p>x</p>"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_revisions_store_format(pool: PgPool) {
+        let uid = get_seed_user_0().id;
+        let source = "# Hello\n\n| a |\n|---|\n| b |\n";
+        let md = authored(source, false);
+        let item = create_from(
+            &pool,
+            &ContentCreate {
+                content: md.content,
+                unfiltered_html: md.unfiltered_html,
+                format: md.format,
+                rendered_html: md.rendered_html,
+                ..new_content(Some(uid), ContentKind::Post, "hello")
+            },
+        )
+        .await;
+        assert_eq!(item.format, ContentFormat::Markdown);
+        assert_eq!(item.content, source);
+        assert!(item.html().starts_with("<h1>Hello</h1>"));
+        ensure_initial_revision(&pool, item.id, Some(uid))
+            .await
+            .unwrap();
+
+        let update = |content: Option<&str>, format| ContentUpdate {
+            title: None,
+            slug: None,
+            content: content.map(Into::into),
+            template: None,
+            status: None,
+            unfiltered_html: false,
+            format,
+            rendered_html: None,
+        };
+        // A format without content changes nothing.
+        let item = update_content(
+            &pool,
+            item.id,
+            &update(None, Some(ContentFormat::Html)),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(item.format, ContentFormat::Markdown);
+        assert!(item.rendered_html.is_some());
+
+        let item = update_content(
+            &pool,
+            item.id,
+            &update(
+                Some("<h1>Hello</h1>"),
+                Some(ContentFormat::Html),
+            ),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(item.format, ContentFormat::Html);
+        assert_eq!(item.rendered_html, None);
+        assert_eq!(item.html(), "<h1>Hello</h1>");
+        record_revision(&pool, &item, Some(uid)).await.unwrap();
+
+        let rev1 =
+            get_revision(&pool, item.id, 1).await.unwrap().unwrap();
+        assert_eq!(rev1.format, ContentFormat::Markdown);
+        assert_eq!(rev1.content, source);
+        assert!(rev1.html().contains("<table>"));
+        let rev2 =
+            get_revision(&pool, item.id, 2).await.unwrap().unwrap();
+        assert_eq!(rev2.format, ContentFormat::Html);
+        assert_eq!(rev2.rendered_html, None);
+
+        // Restoring brings back the format and its rendering.
+        let item = restore_revision(&pool, item.id, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(item.format, ContentFormat::Markdown);
+        assert_eq!(item.content, source);
+        assert_eq!(item.rendered_html, rev1.rendered_html);
+    }
+}
@@ -0,0 +1,170 @@
+mod common;
+
+#[cfg(test)]
+pub mod media_tests {
+    use std::io::Cursor;
+
+    use image::{ImageFormat, RgbImage};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::common::*;
+
+    use rustpress::common::MediaError;
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::*;
+
+    fn encoded(
+        format: ImageFormat,
+        width: u32,
+        height: u32,
+    ) -> Vec<u8> {
+        let mut out = Cursor::new(Vec::new());
+        RgbImage::new(width, height)
+            .write_to(&mut out, format)
+            .expect("Failed to encode image");
+        out.into_inner()
+    }
+
+    fn media(
+        filename: &str,
+        mime_type: &str,
+        alt: &str,
+    ) -> MediaCreate {
+        MediaCreate {
+            owner_user_id: Some(get_seed_user_0().id),
+            filename: filename.into(),
+            storage_key: media_storage_key(Uuid::new_v4(), "bin"),
+            mime_type: mime_type.into(),
+            size_bytes: 42,
+            width: None,
+            height: None,
+            alt_text: alt.into(),
+        }
+    }
+
+    #[test]
+    fn test_sniff_by_contents() {
+        let png =
+            sniff_media(&encoded(ImageFormat::Png, 3, 2)).unwrap();
+        assert_eq!(png.mime_type, "image/png");
+        assert_eq!(png.extension, "png");
+        assert_eq!(png.dimensions, Some((3, 2)));
+
+        let jpeg =
+            sniff_media(&encoded(ImageFormat::Jpeg, 8, 5)).unwrap();
+        assert_eq!(jpeg.mime_type, "image/jpeg");
+        assert_eq!(jpeg.dimensions, Some((8, 5)));
+
+        let pdf = sniff_media(b"%PDF-1.7\n...").unwrap();
+        assert_eq!(pdf.mime_type, "application/pdf");
+        assert_eq!(pdf.dimensions, None);
+
+        for rejected in [
+            &b"<script>alert(1)</script>"[..],
+            b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>",
+            b"",
+            // A PNG signature with nothing after it.
+            b"\x89PNG\r\n\x1a\n",
+        ] {
+            assert!(matches!(
+                sniff_media(rejected),
+                Err(MediaError::UnsupportedType)
+            ));
+        }
+    }
+
+    #[test]
+    fn test_clean_filename() {
+        assert_eq!(clean_media_filename("photo.jpg"), "photo.jpg");
+        assert_eq!(
+            clean_media_filename("C:\\Users\\me\\cat.png"),
+            "cat.png"
+        );
+        assert_eq!(
+            clean_media_filename("../../etc/passwd"),
+            "passwd"
+        );
+        assert_eq!(clean_media_filename("a\nb.png"), "ab.png");
+        assert_eq!(clean_media_filename("dir/"), "upload");
+        assert_eq!(
+            clean_media_filename(&"x".repeat(300)).len(),
+            MAX_MEDIA_FILENAME_LENGTH
+        );
+    }
+
+    #[test]
+    fn test_local_storage() {
+        let dir = std::env::temp_dir()
+            .join(format!("rustpress-media-{}", Uuid::new_v4()));
+        let storage = LocalMediaStorage::new(&dir).unwrap();
+
+        storage.put("a.png", b"bytes").unwrap();
+        assert_eq!(storage.get("a.png").unwrap(), b"bytes");
+        storage.delete("a.png").unwrap();
+        assert!(matches!(
+            storage.get("a.png"),
+            Err(MediaError::NotFound)
+        ));
+        // Deleting twice is fine.
+        storage.delete("a.png").unwrap();
+
+        for key in ["../a.png", "a/b.png", ".hidden", ""] {
+            assert!(matches!(
+                storage.put(key, b"x"),
+                Err(MediaError::InvalidKey(_))
+            ));
+        }
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_media_records(pool: PgPool) {
+        let cat =
+            create_media(&pool, &media("cat.png", "image/png", ""))
+                .await
+                .unwrap();
+        create_media(
+            &pool,
+            &media("menu.pdf", "application/pdf", "Lunch menu"),
+        )
+        .await
+        .unwrap();
+
+        let list = |query: Option<&str>, images_only: bool| {
+            let filter = MediaFilter {
+                query: query.map(Into::into),
+                images_only,
+                limit: 10,
+            };
+            let pool = pool.clone();
+            async move {
+                list_media(&pool, &filter)
+                    .await
+                    .unwrap()
+                    .into_iter()
+                    .map(|m| m.filename)
+                    .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(list(None, false).await, ["menu.pdf", "cat.png"]);
+        assert_eq!(list(None, true).await, ["cat.png"]);
+        assert_eq!(list(Some("LUNCH"), false).await, ["menu.pdf"]);
+        // Wildcards in the search are taken literally.
+        assert!(list(Some("%"), false).await.is_empty());
+
+        let cat = update_media_alt(&pool, cat.id, "A sleeping cat")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(cat.alt_text, "A sleeping cat");
+        assert_eq!(cat.url(), format!("/media/{}", cat.id));
+        assert!(cat.is_image());
+
+        let deleted =
+            delete_media(&pool, cat.id).await.unwrap().unwrap();
+        assert_eq!(deleted.storage_key, cat.storage_key);
+        assert!(get_media(&pool, cat.id).await.unwrap().is_none());
+    }
+}
@@ -0,0 +1,11 @@
+/* Session table definition */
+CREATE TABLE IF NOT EXISTS sessions (
+    id          uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash  text        NOT NULL UNIQUE,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    last_seen   timestamptz NOT NULL DEFAULT now(),
+    expires_at  timestamptz NOT NULL,
+    ip          text,
+    user_agent  text
+);
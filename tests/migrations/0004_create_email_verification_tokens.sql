@@ -0,0 +1,10 @@
+/* Email verification token table definition */
+CREATE TABLE IF NOT EXISTS email_verification_tokens (
+    id          uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    email       text        NOT NULL,
+    token_hash  text        NOT NULL UNIQUE,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    expires_at  timestamptz NOT NULL,
+    used_at     timestamptz DEFAULT NULL
+);
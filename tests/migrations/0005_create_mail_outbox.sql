@@ -0,0 +1,18 @@
+/* Mail outbox table definition */
+CREATE TABLE IF NOT EXISTS mail_outbox (
+    id               uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+    recipient        text        NOT NULL,
+    subject          text        NOT NULL,
+    body             text        NOT NULL,
+    status           text        NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'sent', 'failed')),
+    attempts         integer     NOT NULL DEFAULT 0,
+    last_error       text        DEFAULT NULL,
+    created_at       timestamptz NOT NULL DEFAULT now(),
+    next_attempt_at  timestamptz NOT NULL DEFAULT now(),
+    sent_at          timestamptz DEFAULT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_mail_outbox_due
+    ON mail_outbox(next_attempt_at)
+    WHERE status = 'pending';
@@ -0,0 +1,25 @@
+/* Two-factor authentication table definitions */
+CREATE TABLE IF NOT EXISTS user_totp (
+    user_id         uuid        PRIMARY KEY REFERENCES users(id) ON DELETE CASCADE,
+    secret          text        NOT NULL,
+    enabled_at      timestamptz DEFAULT NULL,
+    last_used_step  bigint      DEFAULT NULL,
+    created_at      timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE TABLE IF NOT EXISTS user_recovery_codes (
+    id          uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    code_hash   text        NOT NULL,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    used_at     timestamptz DEFAULT NULL
+);
+
+CREATE TABLE IF NOT EXISTS login_challenges (
+    id          uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    token_hash  text        NOT NULL UNIQUE,
+    attempts    integer     NOT NULL DEFAULT 0,
+    created_at  timestamptz NOT NULL DEFAULT now(),
+    expires_at  timestamptz NOT NULL
+);
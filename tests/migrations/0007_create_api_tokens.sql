@@ -0,0 +1,11 @@
+/* API token table definition */
+CREATE TABLE IF NOT EXISTS api_tokens (
+    id            uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id       uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    name          text        NOT NULL,
+    token_hash    text        NOT NULL UNIQUE,
+    scopes        text[]      NOT NULL DEFAULT '{}',
+    created_at    timestamptz NOT NULL DEFAULT now(),
+    expires_at    timestamptz DEFAULT NULL,
+    last_used_at  timestamptz DEFAULT NULL
+);
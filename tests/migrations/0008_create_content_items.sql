@@ -0,0 +1,48 @@
+/* Content, revision and collaborator table definitions */
+CREATE TABLE IF NOT EXISTS content_items (
+    id             uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_user_id  uuid        REFERENCES users(id) ON DELETE SET NULL,
+    kind           text        NOT NULL CHECK (kind IN ('page', 'post')),
+    status         text        NOT NULL CHECK (status IN ('draft', 'published')),
+    title          text        NOT NULL,
+    slug           text        NOT NULL,
+    content        text        NOT NULL DEFAULT '',
+    template       text        NOT NULL DEFAULT 'default',
+    current_rev    integer     NOT NULL DEFAULT 1,
+    created_at     timestamptz NOT NULL DEFAULT now(),
+    edited_at      timestamptz NOT NULL DEFAULT now(),
+    published_at   timestamptz
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_items_global_kind_slug
+ON content_items(kind, slug)
+WHERE owner_user_id IS NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_content_items_owner_kind_slug
+ON content_items(owner_user_id, kind, slug)
+WHERE owner_user_id IS NOT NULL;
+
+CREATE TABLE IF NOT EXISTS content_item_revisions (
+    id                  uuid        PRIMARY KEY DEFAULT gen_random_uuid(),
+    content_item_id     uuid        NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    rev                 integer     NOT NULL,
+    title               text        NOT NULL,
+    slug                text        NOT NULL,
+    content             text        NOT NULL,
+    template            text        NOT NULL,
+    status              text        NOT NULL,
+    created_by_user_id  uuid        REFERENCES users(id) ON DELETE SET NULL,
+    created_at          timestamptz NOT NULL DEFAULT now(),
+    owner_user_id       uuid,
+    kind                text,
+    UNIQUE (content_item_id, rev)
+);
+
+CREATE TABLE IF NOT EXISTS content_item_collaborators (
+    content_item_id     uuid        NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    user_id             uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    role                text        NOT NULL CHECK (role IN ('viewer', 'editor')),
+    invited_by_user_id  uuid        REFERENCES users(id) ON DELETE SET NULL,
+    created_at          timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (content_item_id, user_id)
+);
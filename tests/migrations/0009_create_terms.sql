@@ -0,0 +1,32 @@
+-- Categories and tags.
+--
+-- Terms are site-wide and grouped by taxonomy. Categories may nest via
+-- `parent_id`; tags are flat. `content_terms` assigns terms to posts.
+
+CREATE TABLE IF NOT EXISTS terms
+(
+    id           uuid PRIMARY KEY     DEFAULT gen_random_uuid(),
+    -------------------------------------------------------------
+    taxonomy     text        NOT NULL CHECK (taxonomy IN ('category', 'tag')),
+    slug         text        NOT NULL,
+    name         text        NOT NULL,
+    description  text        NOT NULL DEFAULT '',
+    parent_id    uuid                 DEFAULT NULL REFERENCES terms(id) ON DELETE SET NULL,
+    created_at   timestamptz NOT NULL DEFAULT now(),
+    UNIQUE (taxonomy, slug),
+    CHECK (parent_id IS NULL OR taxonomy = 'category'),
+    CHECK (parent_id IS DISTINCT FROM id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_terms_parent_id
+    ON terms(parent_id);
+
+CREATE TABLE IF NOT EXISTS content_terms
+(
+    content_item_id  uuid NOT NULL REFERENCES content_items(id) ON DELETE CASCADE,
+    term_id          uuid NOT NULL REFERENCES terms(id) ON DELETE CASCADE,
+    PRIMARY KEY (content_item_id, term_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_content_terms_term_id
+    ON content_terms(term_id);
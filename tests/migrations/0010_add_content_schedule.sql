@@ -0,0 +1,28 @@
+-- Scheduled publishing.
+--
+-- A `scheduled` item goes live once `publish_at` has passed; the server
+-- promotes it to `published`. A published item with `unpublish_at` set
+-- drops out of public listings at that time and is reverted to `draft`.
+
+ALTER TABLE content_items
+    DROP CONSTRAINT IF EXISTS content_items_status_check;
+
+ALTER TABLE content_items
+    ADD CONSTRAINT content_items_status_check
+    CHECK (status IN ('draft', 'scheduled', 'published'));
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS publish_at   timestamptz DEFAULT NULL,
+    ADD COLUMN IF NOT EXISTS unpublish_at timestamptz DEFAULT NULL;
+
+ALTER TABLE content_items
+    ADD CONSTRAINT content_items_schedule_check
+    CHECK (status <> 'scheduled' OR publish_at IS NOT NULL);
+
+CREATE INDEX IF NOT EXISTS idx_content_items_publish_at
+ON content_items(publish_at)
+WHERE status = 'scheduled';
+
+CREATE INDEX IF NOT EXISTS idx_content_items_unpublish_at
+ON content_items(unpublish_at)
+WHERE unpublish_at IS NOT NULL;
@@ -0,0 +1,39 @@
+-- Editorial review.
+--
+-- `pending` items have been submitted for review; `private` items are
+-- published only to signed-in users who can see them. Publishing needs
+-- the admin role or the `publish` capability; everyone else submits
+-- their work for review. Review decisions are kept on the revision they
+-- create, in `note`.
+
+ALTER TABLE content_items
+    DROP CONSTRAINT IF EXISTS content_items_status_check;
+
+ALTER TABLE content_items
+    ADD CONSTRAINT content_items_status_check
+    CHECK (status IN ('draft', 'pending', 'scheduled', 'private', 'published'));
+
+CREATE INDEX IF NOT EXISTS idx_content_items_pending
+ON content_items(edited_at)
+WHERE status = 'pending';
+
+ALTER TABLE content_item_revisions
+    ADD COLUMN IF NOT EXISTS note text DEFAULT NULL;
+
+CREATE TABLE IF NOT EXISTS user_capabilities
+(
+    user_id     uuid        NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    capability  text        NOT NULL CHECK (capability IN ('publish')),
+    granted_at  timestamptz NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, capability)
+);
+
+-- Holders of `publish` review every user's pending items, so it is only
+-- carried over for admins. Other existing accounts, like new ones, submit
+-- for review until an admin grants it.
+INSERT INTO user_capabilities (user_id, capability)
+SELECT ur.user_id, 'publish'
+FROM user_roles ur
+JOIN roles r ON r.id = ur.role_id
+WHERE r.name = 'admin'
+ON CONFLICT DO NOTHING;
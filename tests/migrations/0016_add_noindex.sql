@@ -0,0 +1,7 @@
+-- Search engines.
+--
+-- `noindex` keeps an item out of the sitemaps and asks crawlers not to
+-- index it.
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS noindex boolean NOT NULL DEFAULT false;
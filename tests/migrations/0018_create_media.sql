@@ -0,0 +1,22 @@
+-- Media library.
+--
+-- One row per uploaded file. The bytes live in the configured storage
+-- backend under `storage_key`, a name RustPress picks itself; the
+-- uploader's file name is kept only for display and search. Width and
+-- height are set for images.
+
+CREATE TABLE IF NOT EXISTS media (
+    id uuid PRIMARY KEY DEFAULT gen_random_uuid(),
+    owner_user_id uuid REFERENCES users(id) ON DELETE SET NULL,
+    filename text NOT NULL,
+    storage_key text NOT NULL UNIQUE,
+    mime_type text NOT NULL,
+    size_bytes bigint NOT NULL CHECK (size_bytes >= 0),
+    width integer,
+    height integer,
+    alt_text text NOT NULL DEFAULT '',
+    created_at timestamptz NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS media_created_at_idx
+    ON media (created_at DESC);
@@ -0,0 +1,20 @@
+-- HTML sanitization.
+--
+-- Content is cleaned against an allowlist when it is saved, unless the
+-- author holds the `unfiltered_html` capability (admins always do).
+-- `unfiltered_html` records which case applies; content without it is
+-- cleaned again when shown, which also covers rows saved before this
+-- migration. Revisions keep the flag so restoring one restores it too.
+
+ALTER TABLE user_capabilities
+    DROP CONSTRAINT IF EXISTS user_capabilities_capability_check;
+
+ALTER TABLE user_capabilities
+    ADD CONSTRAINT user_capabilities_capability_check
+    CHECK (capability IN ('publish', 'unfiltered_html'));
+
+ALTER TABLE content_items
+    ADD COLUMN IF NOT EXISTS unfiltered_html boolean NOT NULL DEFAULT false;
+
+ALTER TABLE content_item_revisions
+    ADD COLUMN IF NOT EXISTS unfiltered_html boolean NOT NULL DEFAULT false;
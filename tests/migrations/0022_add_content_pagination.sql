@@ -0,0 +1,19 @@
+-- Pagination.
+--
+-- Listings are paged by keyset: each page continues after the sort key
+-- and id of the last item on the previous one. These indexes serve the
+-- public post listings, sorted by publication time, and the admin
+-- lists, sorted by creation time. `posts_per_page` sets the page size
+-- of the blog and its archives.
+
+ALTER TABLE sites
+    ADD COLUMN IF NOT EXISTS posts_per_page integer NOT NULL DEFAULT 10
+        CHECK (posts_per_page BETWEEN 1 AND 100);
+
+CREATE INDEX IF NOT EXISTS content_items_published_order_idx
+    ON content_items (kind, (COALESCE(published_at, created_at)) DESC, id DESC)
+    WHERE status = 'published' AND deleted_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS content_items_created_order_idx
+    ON content_items (kind, created_at DESC, id DESC)
+    WHERE deleted_at IS NULL;
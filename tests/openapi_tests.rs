@@ -0,0 +1,168 @@
+#[cfg(test)]
+pub mod openapi_tests {
+    use std::collections::BTreeSet;
+    use std::path::Path;
+    use std::process::Command;
+
+    use serde_json::Value;
+
+    const API_HANDLERS_DIR: &str = "src/web/handlers/api";
+    const METHODS: &[&str] =
+        &["get", "post", "put", "patch", "delete"];
+
+    /// The document printed by `rustpress openapi`.
+    fn spec() -> Value {
+        let output = Command::new(env!("CARGO_BIN_EXE_rustpress"))
+            .arg("openapi")
+            .output()
+            .expect("Failed to run rustpress openapi");
+        assert!(output.status.success(), "rustpress openapi failed");
+        serde_json::from_slice(&output.stdout)
+            .expect("OpenAPI document is not valid JSON")
+    }
+
+    /// `{kind:posts|pages}` becomes `{kind}`, as in the document.
+    fn strip_patterns(path: &str) -> String {
+        let mut out = String::new();
+        let (mut in_segment, mut in_pattern) = (false, false);
+        for c in path.chars() {
+            match c {
+                '{' => in_segment = true,
+                '}' => (in_segment, in_pattern) = (false, false),
+                ':' if in_segment => in_pattern = true,
+                _ if in_pattern => continue,
+                _ => {}
+            }
+            if !in_pattern {
+                out.push(c);
+            }
+        }
+        out
+    }
+
+    /// `(METHOD, path)` for every actix route attribute under the API
+    /// handlers, e.g. `#[get("/templates/{id}")]`.
+    fn declared_routes() -> BTreeSet<(String, String)> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join(API_HANDLERS_DIR);
+        let mut routes = BTreeSet::new();
+        for entry in std::fs::read_dir(dir).expect("API handlers dir")
+        {
+            let path = entry.expect("dir entry").path();
+            if path.extension().is_none_or(|e| e != "rs") {
+                continue;
+            }
+            let source =
+                std::fs::read_to_string(&path).expect("read source");
+            for line in source.lines().map(str::trim) {
+                for method in METHODS {
+                    let prefix = format!("#[{method}(\"");
+                    if let Some(rest) = line.strip_prefix(&prefix)
+                        && let Some(route) = rest.strip_suffix("\")]")
+                    {
+                        routes.insert((
+                            method.to_uppercase(),
+                            strip_patterns(route),
+                        ));
+                    }
+                }
+            }
+        }
+        routes
+    }
+
+    fn documented_routes(spec: &Value) -> BTreeSet<(String, String)> {
+        let paths = spec["paths"].as_object().expect("paths object");
+        paths
+            .iter()
+            .flat_map(|(path, item)| {
+                let item = item.as_object().expect("path item");
+                METHODS
+                    .iter()
+                    .filter(|m| item.contains_key(**m))
+                    .map(|m| (m.to_uppercase(), path.clone()))
+            })
+            .collect()
+    }
+
+    fn collect_refs<'a>(value: &'a Value, refs: &mut Vec<&'a str>) {
+        match value {
+            Value::Object(map) => {
+                for (key, v) in map {
+                    match (key.as_str(), v) {
+                        ("$ref", Value::String(r)) => refs.push(r),
+                        _ => collect_refs(v, refs),
+                    }
+                }
+            }
+            Value::Array(items) => {
+                items.iter().for_each(|v| collect_refs(v, refs))
+            }
+            _ => {}
+        }
+    }
+
+    #[test]
+    fn test_strip_patterns() {
+        assert_eq!(strip_patterns("/{kind:posts|pages}"), "/{kind}");
+        assert_eq!(
+            strip_patterns("/{kind:posts|pages}/{id}/publish"),
+            "/{kind}/{id}/publish"
+        );
+        assert_eq!(strip_patterns("/openapi.json"), "/openapi.json");
+    }
+
+    #[test]
+    fn test_every_api_route_is_documented() {
+        let declared = declared_routes();
+        assert!(!declared.is_empty(), "No API routes found");
+        let documented = documented_routes(&spec());
+
+        let missing: Vec<_> =
+            declared.difference(&documented).collect();
+        assert!(
+            missing.is_empty(),
+            "Routes missing from the OpenAPI document: {missing:?}"
+        );
+        let stale: Vec<_> =
+            documented.difference(&declared).collect();
+        assert!(
+            stale.is_empty(),
+            "Documented routes without a handler: {stale:?}"
+        );
+    }
+
+    #[test]
+    fn test_document_describes_models_and_auth() {
+        let spec = spec();
+        assert!(spec["openapi"].as_str().unwrap().starts_with("3."));
+        assert_eq!(spec["servers"][0]["url"], "/api/v1");
+        assert_eq!(
+            spec["components"]["securitySchemes"]["bearer"]["scheme"],
+            "bearer"
+        );
+
+        let schemas = spec["components"]["schemas"]
+            .as_object()
+            .expect("schemas object");
+        for name in [
+            "ContentItem",
+            "NewContent",
+            "ContentUpdate",
+            "SiteTemplate",
+            "Site",
+            "ContentItemRevision",
+        ] {
+            assert!(schemas.contains_key(name), "{name} missing");
+        }
+
+        let mut refs = Vec::new();
+        collect_refs(&spec, &mut refs);
+        for r in refs {
+            let name = r
+                .strip_prefix("#/components/schemas/")
+                .unwrap_or_else(|| panic!("Unexpected $ref {r}"));
+            assert!(schemas.contains_key(name), "Dangling $ref {r}");
+        }
+    }
+}
@@ -0,0 +1,318 @@
+mod common;
+
+#[cfg(test)]
+pub mod page_tests {
+    use std::collections::HashMap;
+
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+
+    async fn create_page(
+        pool: &PgPool,
+        slug: &str,
+        parent: Option<&ContentItem>,
+    ) -> ContentItem {
+        let page = create_item(
+            pool,
+            Some(get_seed_user_0().id),
+            ContentKind::Page,
+            slug,
+        )
+        .await;
+        let page = publish_content(pool, page.id)
+            .await
+            .expect("Failed to publish")
+            .expect("Content not found");
+        match parent {
+            Some(parent) => {
+                move_to(pool, &page, Some(parent), 0).await
+            }
+            None => page,
+        }
+    }
+
+    async fn move_to(
+        pool: &PgPool,
+        page: &ContentItem,
+        parent: Option<&ContentItem>,
+        menu_order: i32,
+    ) -> ContentItem {
+        move_page(
+            pool,
+            page.id,
+            &PageMove {
+                parent_id: parent.map(|p| p.id),
+                menu_order: Some(menu_order),
+            },
+        )
+        .await
+        .expect("Failed to move")
+        .expect("Page not found")
+    }
+
+    async fn resolve(pool: &PgPool, path: &str) -> Option<Uuid> {
+        get_published_page_by_path(pool, path)
+            .await
+            .expect("Failed to resolve")
+            .map(|p| p.id)
+    }
+
+    async fn moved_to(pool: &PgPool, path: &str) -> Option<Uuid> {
+        find_moved_content(pool, ContentKind::Page, path)
+            .await
+            .expect("Failed to look up history")
+            .map(|p| p.id)
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_nested_paths_resolve(pool: PgPool) {
+        let about = create_page(&pool, "about", None).await;
+        let team = create_page(&pool, "team", Some(&about)).await;
+        let lead =
+            create_page(&pool, "leadership", Some(&team)).await;
+
+        assert_eq!(
+            resolve(&pool, "about/team/leadership").await,
+            Some(lead.id)
+        );
+        assert_eq!(resolve(&pool, "about/team").await, Some(team.id));
+        assert_eq!(resolve(&pool, "about").await, Some(about.id));
+        for wrong in
+            ["leadership", "team/leadership", "about/leadership"]
+        {
+            assert_eq!(resolve(&pool, wrong).await, None, "{wrong}");
+        }
+
+        let crumbs: Vec<_> = list_page_ancestors(&pool, lead.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.slug)
+            .collect();
+        assert_eq!(crumbs, vec!["about", "team"]);
+        assert_eq!(
+            get_page_path(&pool, &lead).await.unwrap(),
+            "about/team/leadership"
+        );
+
+        let paths = list_page_paths(&pool).await.unwrap();
+        assert_eq!(paths[&team.id], "about/team");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_cycles_are_detected(pool: PgPool) {
+        let about = create_page(&pool, "about", None).await;
+        let team = create_page(&pool, "team", Some(&about)).await;
+        let lead =
+            create_page(&pool, "leadership", Some(&team)).await;
+
+        assert!(
+            is_page_ancestor_or_self(&pool, about.id, lead.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            is_page_ancestor_or_self(&pool, lead.id, lead.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !is_page_ancestor_or_self(&pool, lead.id, about.id)
+                .await
+                .unwrap()
+        );
+        assert_eq!(get_page_depth(&pool, lead.id).await.unwrap(), 3);
+        assert_eq!(
+            get_subtree_height(&pool, about.id).await.unwrap(),
+            2
+        );
+
+        // The database refuses a page as its own parent outright.
+        let own_parent = PageMove {
+            parent_id: Some(about.id),
+            menu_order: None,
+        };
+        assert!(
+            move_page(&pool, about.id, &own_parent).await.is_err()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_moving_a_page_keeps_old_paths(pool: PgPool) {
+        let about = create_page(&pool, "about", None).await;
+        let team = create_page(&pool, "team", Some(&about)).await;
+        let lead =
+            create_page(&pool, "leadership", Some(&team)).await;
+
+        move_to(&pool, &team, None, 0).await;
+        assert_eq!(
+            resolve(&pool, "team/leadership").await,
+            Some(lead.id)
+        );
+        assert_eq!(
+            resolve(&pool, "about/team/leadership").await,
+            None
+        );
+        assert_eq!(
+            moved_to(&pool, "about/team").await,
+            Some(team.id)
+        );
+        assert_eq!(
+            moved_to(&pool, "about/team/leadership").await,
+            Some(lead.id)
+        );
+
+        // Renaming records the subtree's old paths as well.
+        let update = ContentUpdate {
+            title: None,
+            slug: Some("people".into()),
+            content: None,
+            template: None,
+            status: None,
+            unfiltered_html: false,
+            format: None,
+            rendered_html: None,
+        };
+        update_content(&pool, team.id, &update)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            moved_to(&pool, "team/leadership").await,
+            Some(lead.id)
+        );
+        assert_eq!(
+            resolve(&pool, "people/leadership").await,
+            Some(lead.id)
+        );
+
+        // Changing only the order leaves no history behind.
+        move_to(&pool, &lead, Some(&team), 5).await;
+        assert_eq!(moved_to(&pool, "people/leadership").await, None);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_trashing_a_parent_promotes_its_children(
+        pool: PgPool,
+    ) {
+        let about = create_page(&pool, "about", None).await;
+        let team = create_page(&pool, "team", Some(&about)).await;
+        let lead =
+            create_page(&pool, "leadership", Some(&team)).await;
+
+        trash_content(&pool, team.id).await.unwrap().unwrap();
+        let lead =
+            get_content_by_id(&pool, lead.id).await.unwrap().unwrap();
+        assert_eq!(lead.parent_id, Some(about.id));
+        assert_eq!(
+            resolve(&pool, "about/leadership").await,
+            Some(lead.id)
+        );
+        assert_eq!(
+            moved_to(&pool, "about/team/leadership").await,
+            Some(lead.id)
+        );
+
+        // A page restored while its parent is trashed lands at the top.
+        trash_content(&pool, about.id).await.unwrap().unwrap();
+        let restored = restore_trashed_content(&pool, team.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(restored.parent_id, None);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_page_tree_orders_siblings(pool: PgPool) {
+        let about = create_page(&pool, "about", None).await;
+        let contact = create_page(&pool, "contact", None).await;
+        let team = create_page(&pool, "team", Some(&about)).await;
+        create_page(&pool, "history", Some(&about)).await;
+        move_to(&pool, &contact, None, -1).await;
+        move_to(&pool, &team, Some(&about), 1).await;
+
+        let pages = list_content(&pool, ContentKind::Page, true)
+            .await
+            .unwrap();
+        let paths = list_page_paths(&pool).await.unwrap();
+        let tree: Vec<_> = page_tree(pages, &paths)
+            .into_iter()
+            .map(|n| (n.path, n.depth))
+            .collect();
+        assert_eq!(
+            tree,
+            vec![
+                ("contact".to_string(), 0),
+                ("about".to_string(), 0),
+                ("about/history".to_string(), 1),
+                ("about/team".to_string(), 1),
+            ]
+        );
+
+        // Without paths a page falls back to its slug.
+        let tree = page_tree(
+            list_content(&pool, ContentKind::Page, true)
+                .await
+                .unwrap(),
+            &HashMap::new(),
+        );
+        assert!(
+            tree.iter().any(|n| n.path == "team" && n.depth == 1)
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_page_tree_for_user_pages_the_tree(pool: PgPool) {
+        let about = create_page(&pool, "about", None).await;
+        let contact = create_page(&pool, "contact", None).await;
+        let team = create_page(&pool, "team", Some(&about)).await;
+        create_page(&pool, "history", Some(&about)).await;
+        move_to(&pool, &contact, None, -1).await;
+        move_to(&pool, &team, Some(&about), 1).await;
+        let user = get_seed_user_0();
+
+        let pages = list_content(&pool, ContentKind::Page, true)
+            .await
+            .unwrap();
+        let paths = list_page_paths(&pool).await.unwrap();
+        let whole: Vec<_> = page_tree(pages, &paths)
+            .into_iter()
+            .map(|n| (n.path, n.depth))
+            .collect();
+
+        let mut paged = Vec::new();
+        for number in 1..=3 {
+            let rows = list_page_tree_for_user(
+                &pool,
+                user.id,
+                2,
+                page_offset(number, 2),
+            )
+            .await
+            .expect("Failed to list pages");
+            assert!(rows.len() <= 2);
+            paged.extend(
+                page_nodes(rows, &paths)
+                    .into_iter()
+                    .map(|n| (n.path, n.depth)),
+            );
+        }
+        assert_eq!(paged, whole);
+
+        // Pages owned by someone else stay out of the listing.
+        let other = list_page_tree_for_user(
+            &pool,
+            get_seed_user_1().id,
+            50,
+            0,
+        )
+        .await
+        .unwrap();
+        assert!(other.is_empty());
+    }
+}
@@ -0,0 +1,242 @@
+mod common;
+
+#[cfg(test)]
+pub mod pagination_tests {
+    use chrono::{DateTime, TimeZone, Utc};
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+
+    async fn post(
+        pool: &PgPool,
+        owner: Uuid,
+        slug: &str,
+        published_at: Option<DateTime<Utc>>,
+    ) -> ContentItem {
+        let item =
+            create_item(pool, Some(owner), ContentKind::Post, slug)
+                .await;
+        let Some(at) = published_at else {
+            return item;
+        };
+        publish_content(pool, item.id).await.unwrap().unwrap();
+        sqlx::query(
+            "UPDATE content_items SET published_at = $2 WHERE id = $1",
+        )
+        .bind(item.id)
+        .bind(at)
+        .execute(pool)
+        .await
+        .expect("Failed to set published_at");
+        get_content_by_id(pool, item.id).await.unwrap().unwrap()
+    }
+
+    fn day(month: u32, day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, month, day, 12, 0, 0).unwrap()
+    }
+
+    fn slugs(page: &ContentPage) -> Vec<&str> {
+        page.items.iter().map(|i| i.slug.as_str()).collect()
+    }
+
+    fn request(anchor: PageAnchor) -> PageRequest {
+        PageRequest { anchor, limit: 2 }
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = ContentCursor {
+            at: Utc.timestamp_micros(1_790_000_000_123_456).unwrap(),
+            id: Uuid::nil(),
+        };
+        let text = cursor.to_string();
+        assert_eq!(
+            text,
+            "1790000000123456_00000000-0000-0000-0000-000000000000"
+        );
+        assert_eq!(text.parse::<ContentCursor>(), Ok(cursor));
+        for invalid in ["", "123", "abc_def", "1_not-a-uuid"] {
+            assert!(invalid.parse::<ContentCursor>().is_err());
+        }
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_published_posts_keyset(pool: PgPool) {
+        let uid = get_seed_user_0().id;
+        // `c` and `d` share a time, so the id breaks the tie.
+        for (slug, at) in [
+            ("a", day(9, 1)),
+            ("b", day(9, 20)),
+            ("c", day(10, 5)),
+            ("d", day(10, 5)),
+            ("e", day(10, 30)),
+        ] {
+            post(&pool, uid, slug, Some(at)).await;
+        }
+        post(&pool, uid, "draft", None).await;
+        let filter = PostListFilter::default();
+
+        // Walk forward with cursors and collect every post once.
+        let first = list_published_posts(
+            &pool,
+            &filter,
+            &request(PageAnchor::Offset(0)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(first.items.len(), 2);
+        assert_eq!(first.items[0].slug, "e");
+        assert_eq!(first.prev, None);
+        let mut seen = slugs(&first)
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>();
+        let mut pages = vec![first];
+        while let Some(next) = pages.last().unwrap().next {
+            let page = list_published_posts(
+                &pool,
+                &filter,
+                &request(PageAnchor::After(next)),
+            )
+            .await
+            .unwrap();
+            assert!(page.prev.is_some());
+            seen.extend(slugs(&page).into_iter().map(String::from));
+            pages.push(page);
+        }
+        assert_eq!(pages.len(), 3);
+        assert_eq!(slugs(&pages[2]), ["a"]);
+        seen.sort();
+        assert_eq!(seen, ["a", "b", "c", "d", "e"]);
+
+        // Going back lands on the same pages.
+        let back = list_published_posts(
+            &pool,
+            &filter,
+            &request(PageAnchor::Before(pages[2].prev.unwrap())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slugs(&back), slugs(&pages[1]));
+        assert!(back.prev.is_some() && back.next.is_some());
+        let back = list_published_posts(
+            &pool,
+            &filter,
+            &request(PageAnchor::Before(back.prev.unwrap())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slugs(&back), slugs(&pages[0]));
+        assert_eq!(back.prev, None);
+
+        // Offsets give the same pages as cursors.
+        let offset = list_published_posts(
+            &pool,
+            &filter,
+            &request(PageAnchor::Offset(2)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slugs(&offset), slugs(&pages[1]));
+        assert!(offset.prev.is_some() && offset.next.is_some());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_published_posts_filters(pool: PgPool) {
+        let uid = get_seed_user_0().id;
+        let mut november = None;
+        for (slug, at) in [
+            ("september", day(9, 30)),
+            ("october-1", day(10, 1)),
+            ("october-31", day(10, 31)),
+            ("november", day(11, 1)),
+        ] {
+            november = Some(post(&pool, uid, slug, Some(at)).await);
+        }
+        let october = PostListFilter {
+            published_from: Some(
+                day(10, 1) - chrono::Duration::hours(12),
+            ),
+            published_before: Some(
+                day(11, 1) - chrono::Duration::hours(12),
+            ),
+            ..Default::default()
+        };
+        let page = list_published_posts(
+            &pool,
+            &october,
+            &PageRequest::first(10),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slugs(&page), ["october-31", "october-1"]);
+        assert_eq!((page.prev, page.next), (None, None));
+
+        let tag =
+            get_or_create_tag(&pool, "rust", "Rust").await.unwrap();
+        let november = november.unwrap();
+        set_content_terms(
+            &pool,
+            november.id,
+            Taxonomy::Tag,
+            &[tag.id],
+        )
+        .await
+        .unwrap();
+        let page = list_published_posts(
+            &pool,
+            &PostListFilter {
+                term_id: Some(tag.id),
+                ..Default::default()
+            },
+            &PageRequest::first(10),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slugs(&page), ["november"]);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_content_page_for_user(pool: PgPool) {
+        let owner = get_seed_user_0().id;
+        let other = get_seed_user_1().id;
+        for slug in ["one", "two", "three"] {
+            post(&pool, owner, slug, None).await;
+        }
+        post(&pool, other, "theirs", Some(day(10, 1))).await;
+
+        let first = list_content_page_for_user(
+            &pool,
+            ContentKind::Post,
+            owner,
+            &request(PageAnchor::Offset(0)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slugs(&first), ["three", "two"]);
+        let rest = list_content_page_for_user(
+            &pool,
+            ContentKind::Post,
+            owner,
+            &request(PageAnchor::After(first.next.unwrap())),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slugs(&rest), ["one"]);
+        assert_eq!(rest.next, None);
+
+        let theirs = list_content_page_for_user(
+            &pool,
+            ContentKind::Post,
+            other,
+            &PageRequest::first(10),
+        )
+        .await
+        .unwrap();
+        assert_eq!(slugs(&theirs), ["theirs"]);
+    }
+}
@@ -0,0 +1,370 @@
+mod common;
+
+#[cfg(test)]
+pub mod password_reset_tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::TokenManager;
+
+    fn new_reset_token(
+        user: &User,
+        token: &str,
+    ) -> PasswordResetTokenCreate {
+        PasswordResetTokenCreate {
+            user_id: user.id,
+            token_hash: TokenManager::hash(token),
+            expires_at: Utc::now() + Duration::hours(1),
+        }
+    }
+
+    async fn create_user_session(pool: &PgPool, user: &User) {
+        create_session(
+            pool,
+            &SessionCreate {
+                user_id: user.id,
+                token_hash: TokenManager::hash(
+                    &TokenManager::generate(),
+                ),
+                expires_at: Utc::now() + Duration::days(7),
+                ip: None,
+                user_agent: None,
+            },
+        )
+        .await
+        .expect("Failed to create session");
+    }
+
+    async fn stored_password_hash(
+        pool: &PgPool,
+        user: &User,
+    ) -> String {
+        sqlx::query_scalar::<_, String>(
+            "SELECT password_hash FROM users WHERE id = $1",
+        )
+        .bind(user.id)
+        .fetch_one(pool)
+        .await
+        .expect("Failed to fetch password hash")
+    }
+
+    #[test]
+    fn test_reset_token_is_usable_success() {
+        let token = PasswordResetToken {
+            id: uuid::Uuid::new_v4(),
+            user_id: get_seed_user_0().id,
+            token_hash: String::new(),
+            created_at: Utc::now(),
+            expires_at: Utc::now() + Duration::hours(1),
+            used_at: None,
+        };
+        assert!(token.is_usable());
+
+        let used = PasswordResetToken {
+            used_at: Some(Utc::now()),
+            ..token.clone()
+        };
+        assert!(!used.is_usable());
+
+        let expired = PasswordResetToken {
+            expires_at: Utc::now() - Duration::seconds(1),
+            ..token
+        };
+        assert!(!expired.is_usable());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_create_and_get_usable_token_success(pool: PgPool) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        let created = create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &token),
+        )
+        .await
+        .expect("Failed to create reset token");
+
+        let found = get_usable_password_reset_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await
+        .expect("Failed to get reset token")
+        .expect("Reset token should exist");
+
+        assert_eq!(found, created);
+        assert_eq!(found.user_id, user.id);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_get_usable_token_fails_on_raw_token(pool: PgPool) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &token),
+        )
+        .await
+        .expect("Failed to create reset token");
+
+        let found = get_usable_password_reset_token(&pool, &token)
+            .await
+            .expect("Failed to get reset token");
+
+        assert!(found.is_none(), "Only the hash must be stored");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_get_usable_token_fails_on_expired(pool: PgPool) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        let mut data = new_reset_token(&user, &token);
+        data.expires_at = Utc::now() - Duration::minutes(1);
+        create_password_reset_token(&pool, &data)
+            .await
+            .expect("Failed to create reset token");
+
+        let found = get_usable_password_reset_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await
+        .expect("Failed to get reset token");
+
+        assert!(found.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_get_usable_token_fails_on_deleted_user(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_deleted();
+        let token = TokenManager::generate();
+
+        create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &token),
+        )
+        .await
+        .expect("Failed to create reset token");
+
+        let found = get_usable_password_reset_token(
+            &pool,
+            &TokenManager::hash(&token),
+        )
+        .await
+        .expect("Failed to get reset token");
+
+        assert!(found.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_create_token_replaces_earlier_unused_tokens(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let first = TokenManager::generate();
+        let second = TokenManager::generate();
+
+        create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &first),
+        )
+        .await
+        .expect("Failed to create first reset token");
+        create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &second),
+        )
+        .await
+        .expect("Failed to create second reset token");
+
+        let first_found = get_usable_password_reset_token(
+            &pool,
+            &TokenManager::hash(&first),
+        )
+        .await
+        .expect("Failed to get reset token");
+        let second_found = get_usable_password_reset_token(
+            &pool,
+            &TokenManager::hash(&second),
+        )
+        .await
+        .expect("Failed to get reset token");
+
+        assert!(first_found.is_none());
+        assert!(second_found.is_some());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_reset_password_with_token_success(pool: PgPool) {
+        let user = get_seed_user_0();
+        let other = get_seed_user_1();
+        let token = TokenManager::generate();
+
+        create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &token),
+        )
+        .await
+        .expect("Failed to create reset token");
+        create_user_session(&pool, &user).await;
+        create_user_session(&pool, &other).await;
+
+        let reset_user_id = reset_password_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+            "new-hash",
+        )
+        .await
+        .expect("Failed to reset password");
+
+        assert_eq!(reset_user_id, Some(user.id));
+        assert_eq!(
+            stored_password_hash(&pool, &user).await,
+            "new-hash"
+        );
+
+        let sessions = list_sessions_for_user(&pool, user.id)
+            .await
+            .expect("Failed to list sessions");
+        assert!(sessions.is_empty(), "Sessions must be revoked");
+
+        let other_sessions = list_sessions_for_user(&pool, other.id)
+            .await
+            .expect("Failed to list sessions");
+        assert_eq!(other_sessions.len(), 1);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_reset_password_with_token_is_single_use(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &token),
+        )
+        .await
+        .expect("Failed to create reset token");
+
+        let first = reset_password_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+            "first-hash",
+        )
+        .await
+        .expect("Failed to reset password");
+        let second = reset_password_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+            "second-hash",
+        )
+        .await
+        .expect("Failed to reset password");
+
+        assert_eq!(first, Some(user.id));
+        assert_eq!(second, None);
+        assert_eq!(
+            stored_password_hash(&pool, &user).await,
+            "first-hash"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_reset_password_with_token_fails_on_expired(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+        let original_hash = stored_password_hash(&pool, &user).await;
+
+        let mut data = new_reset_token(&user, &token);
+        data.expires_at = Utc::now() - Duration::minutes(1);
+        create_password_reset_token(&pool, &data)
+            .await
+            .expect("Failed to create reset token");
+        create_user_session(&pool, &user).await;
+
+        let result = reset_password_with_token(
+            &pool,
+            &TokenManager::hash(&token),
+            "new-hash",
+        )
+        .await
+        .expect("Failed to reset password");
+
+        assert_eq!(result, None);
+        assert_eq!(
+            stored_password_hash(&pool, &user).await,
+            original_hash
+        );
+
+        let sessions = list_sessions_for_user(&pool, user.id)
+            .await
+            .expect("Failed to list sessions");
+        assert_eq!(sessions.len(), 1, "Sessions must be kept");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_delete_stale_password_reset_tokens_success(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let other = get_seed_user_1();
+        let used = TokenManager::generate();
+        let expired = TokenManager::generate();
+        let live = TokenManager::generate();
+
+        create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &used),
+        )
+        .await
+        .expect("Failed to create reset token");
+        reset_password_with_token(
+            &pool,
+            &TokenManager::hash(&used),
+            "new-hash",
+        )
+        .await
+        .expect("Failed to reset password");
+
+        let mut data = new_reset_token(&other, &expired);
+        data.expires_at = Utc::now() - Duration::minutes(1);
+        create_password_reset_token(&pool, &data)
+            .await
+            .expect("Failed to create reset token");
+
+        create_password_reset_token(
+            &pool,
+            &new_reset_token(&user, &live),
+        )
+        .await
+        .expect("Failed to create reset token");
+
+        let deleted = delete_stale_password_reset_tokens(&pool)
+            .await
+            .expect("Failed to delete stale tokens");
+
+        assert_eq!(deleted, 2);
+        assert!(
+            get_usable_password_reset_token(
+                &pool,
+                &TokenManager::hash(&live)
+            )
+            .await
+            .expect("Failed to get reset token")
+            .is_some()
+        );
+    }
+}
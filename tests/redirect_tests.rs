@@ -0,0 +1,205 @@
+mod common;
+
+#[cfg(test)]
+pub mod redirect_tests {
+    use chrono::Utc;
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+
+    fn redirect(
+        source: &str,
+        match_type: RedirectMatch,
+        target: Option<&str>,
+    ) -> Redirect {
+        Redirect {
+            id: Uuid::new_v4(),
+            source_path: source.into(),
+            match_type,
+            target_url: target.map(Into::into),
+            status_code: if target.is_some() { 301 } else { 410 },
+            created_by_user_id: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    async fn add(
+        pool: &PgPool,
+        source: &str,
+        match_type: RedirectMatch,
+        target: &str,
+    ) -> Redirect {
+        create_redirect(
+            pool,
+            &RedirectCreate {
+                source_path: source.into(),
+                match_type,
+                target_url: Some(target.into()),
+                status_code: 301,
+                created_by_user_id: Some(get_seed_user_0().id),
+            },
+        )
+        .await
+        .expect("Failed to create redirect")
+    }
+
+    async fn target_for(pool: &PgPool, path: &str) -> Option<String> {
+        find_redirect(pool, path)
+            .await
+            .expect("Failed to find redirect")
+            .and_then(|r| r.target_url)
+    }
+
+    #[test]
+    fn test_location_carries_path_and_query() {
+        let exact =
+            redirect("/old", RedirectMatch::Exact, Some("/new"));
+        assert_eq!(exact.location("/old", ""), Some("/new".into()));
+        assert_eq!(
+            exact.location("/old", "a=1"),
+            Some("/new?a=1".into())
+        );
+
+        let prefix = redirect(
+            "/docs/",
+            RedirectMatch::Prefix,
+            Some("/guide/"),
+        );
+        assert_eq!(
+            prefix.location("/docs/setup/linux", ""),
+            Some("/guide/setup/linux".into())
+        );
+        assert_eq!(
+            prefix.location("/docs", ""),
+            Some("/guide".into())
+        );
+
+        let external = redirect(
+            "/shop",
+            RedirectMatch::Prefix,
+            Some("https://shop.example.com/?ref=blog"),
+        );
+        assert_eq!(
+            external.location("/shop", "a=1"),
+            Some("https://shop.example.com/?ref=blog".into())
+        );
+
+        let gone = redirect("/gone", RedirectMatch::Exact, None);
+        assert_eq!(gone.location("/gone", ""), None);
+    }
+
+    #[test]
+    fn test_location_stays_on_site() {
+        let root = redirect("/old", RedirectMatch::Prefix, Some("/"));
+        assert_eq!(
+            root.location("/old//evil.com", ""),
+            Some("/evil.com".into())
+        );
+        assert_eq!(
+            root.location("/old/\\evil.com", ""),
+            Some("/evil.com".into())
+        );
+        assert_eq!(
+            root.location("/old/about", ""),
+            Some("/about".into())
+        );
+        assert_eq!(root.location("/old", ""), Some("/".into()));
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_exact_wins_then_longest_prefix(pool: PgPool) {
+        add(&pool, "/old", RedirectMatch::Prefix, "/archive").await;
+        add(&pool, "/old/news", RedirectMatch::Prefix, "/news").await;
+        add(&pool, "/old/news/today", RedirectMatch::Exact, "/today")
+            .await;
+
+        assert_eq!(
+            target_for(&pool, "/old/news/today").await.as_deref(),
+            Some("/today")
+        );
+        assert_eq!(
+            target_for(&pool, "/old/news/2024").await.as_deref(),
+            Some("/news")
+        );
+        assert_eq!(
+            target_for(&pool, "/old/about").await.as_deref(),
+            Some("/archive")
+        );
+        assert_eq!(
+            target_for(&pool, "/old").await.as_deref(),
+            Some("/archive")
+        );
+        // A prefix stops at a segment boundary.
+        assert_eq!(target_for(&pool, "/older").await, None);
+
+        // One redirect per source and match type.
+        let duplicate = create_redirect(
+            &pool,
+            &RedirectCreate {
+                source_path: "/old".into(),
+                match_type: RedirectMatch::Prefix,
+                target_url: None,
+                status_code: 410,
+                created_by_user_id: None,
+            },
+        )
+        .await;
+        assert!(duplicate.is_err());
+
+        let listed = list_redirects(&pool).await.unwrap();
+        assert_eq!(listed.len(), 3);
+        assert!(delete_redirect(&pool, listed[0].id).await.unwrap());
+        assert_eq!(list_redirects(&pool).await.unwrap().len(), 2);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_renamed_post_keeps_old_slug(pool: PgPool) {
+        let post = create_post(&pool, "hello").await;
+        let rename = |slug: &str| ContentUpdate {
+            title: None,
+            slug: Some(slug.into()),
+            content: None,
+            template: None,
+            status: None,
+            unfiltered_html: false,
+            format: None,
+            rendered_html: None,
+        };
+
+        // Saving with the same slug records nothing.
+        update_content(&pool, post.id, &rename("hello"))
+            .await
+            .unwrap();
+        assert!(list_slug_history(&pool).await.unwrap().is_empty());
+
+        update_content(&pool, post.id, &rename("hello-world"))
+            .await
+            .unwrap();
+        let moved =
+            find_moved_content(&pool, ContentKind::Post, "hello")
+                .await
+                .unwrap()
+                .expect("Old slug not recorded");
+        assert_eq!(moved.id, post.id);
+        assert_eq!(moved.slug, "hello-world");
+
+        let history = list_slug_history(&pool).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].url(), "/blog/hello");
+        assert!(
+            delete_slug_history(&pool, ContentKind::Post, "hello")
+                .await
+                .unwrap()
+        );
+        assert!(
+            find_moved_content(&pool, ContentKind::Post, "hello")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,379 @@
+mod common;
+
+#[cfg(test)]
+pub mod review_tests {
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::SeoMeta;
+
+    async fn set_status(
+        pool: &PgPool,
+        item: &ContentItem,
+        status: ContentStatus,
+    ) -> ContentItem {
+        let update = ContentUpdate {
+            title: None,
+            slug: None,
+            content: None,
+            template: None,
+            status: Some(status),
+            unfiltered_html: false,
+            format: None,
+            rendered_html: None,
+        };
+        update_content(pool, item.id, &update)
+            .await
+            .expect("Failed to update")
+            .expect("Content not found")
+    }
+
+    #[test]
+    fn test_status_round_trip_and_permissions() {
+        for status in [
+            ContentStatus::Draft,
+            ContentStatus::Pending,
+            ContentStatus::Scheduled,
+            ContentStatus::Private,
+            ContentStatus::Published,
+        ] {
+            assert_eq!(status.as_str().parse(), Ok(status));
+        }
+        assert!(!ContentStatus::Draft.needs_publish_permission());
+        assert!(!ContentStatus::Pending.needs_publish_permission());
+        assert!(ContentStatus::Private.needs_publish_permission());
+        assert!(ContentStatus::Published.needs_publish_permission());
+
+        assert_eq!(
+            ContentStatus::Pending
+                .transition_note(ContentStatus::Published),
+            Some("Approved and published")
+        );
+        assert_eq!(
+            ContentStatus::Draft
+                .transition_note(ContentStatus::Pending),
+            Some("Submitted for review")
+        );
+        assert_eq!(
+            ContentStatus::Draft
+                .transition_note(ContentStatus::Draft),
+            None
+        );
+    }
+
+    #[test]
+    fn test_live_content_changes_need_publish_permission() {
+        use ContentStatus::*;
+        // Drafts and pending items can be edited and submitted freely.
+        for (current, next) in [
+            (Draft, Draft),
+            (Draft, Pending),
+            (Pending, Pending),
+            (Pending, Draft),
+        ] {
+            assert!(
+                !current.save_needs_publish_permission(next),
+                "{current} -> {next}"
+            );
+        }
+        // Making an item live, or changing one that is, skips review.
+        for (current, next) in [
+            (Draft, Published),
+            (Pending, Private),
+            (Published, Published),
+            (Published, Pending),
+            (Private, Draft),
+            (Scheduled, Scheduled),
+        ] {
+            assert!(
+                current.save_needs_publish_permission(next),
+                "{current} -> {next}"
+            );
+        }
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_publish_requires_admin_or_capability(pool: PgPool) {
+        let uid = get_seed_user_1().id;
+
+        // Only admins kept publishing through the upgrade.
+        assert!(!user_can_publish(&pool, uid).await.unwrap());
+
+        set_user_role(&pool, uid, RoleName::Admin)
+            .await
+            .expect("Failed to set role");
+        assert!(user_can_publish(&pool, uid).await.unwrap());
+
+        set_user_role(&pool, uid, RoleName::Editor)
+            .await
+            .expect("Failed to set role");
+        set_user_capability(&pool, uid, Capability::Publish, true)
+            .await
+            .expect("Failed to grant");
+        assert!(user_can_publish(&pool, uid).await.unwrap());
+
+        set_user_capability(&pool, uid, Capability::Publish, false)
+            .await
+            .expect("Failed to revoke");
+        assert!(
+            !user_has_capability(&pool, uid, Capability::Publish)
+                .await
+                .unwrap()
+        );
+        assert!(!user_can_publish(&pool, uid).await.unwrap());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_pending_content_is_open_to_reviewers(pool: PgPool) {
+        // The reviewer is neither the author nor a collaborator; the
+        // publish capability alone opens every user's pending items.
+        let reviewer = get_seed_user_1().id;
+        set_user_capability(
+            &pool,
+            reviewer,
+            Capability::Publish,
+            true,
+        )
+        .await
+        .unwrap();
+        let draft = create_post(&pool, "draft").await;
+        assert!(
+            !can_edit_content(&pool, &draft, reviewer).await.unwrap()
+        );
+        assert!(
+            !can_view_content(&pool, &draft, reviewer).await.unwrap()
+        );
+
+        let pending =
+            set_status(&pool, &draft, ContentStatus::Pending).await;
+        assert!(
+            can_view_content(&pool, &pending, reviewer)
+                .await
+                .unwrap()
+        );
+        assert!(
+            can_edit_content(&pool, &pending, reviewer)
+                .await
+                .unwrap()
+        );
+        let edited = update_content(
+            &pool,
+            pending.id,
+            &ContentUpdate {
+                title: Some("Touched up".into()),
+                slug: None,
+                content: None,
+                template: None,
+                status: None,
+                unfiltered_html: false,
+                format: None,
+                rendered_html: None,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(edited.title, "Touched up");
+        assert_eq!(edited.owner_user_id, Some(get_seed_user_0().id));
+
+        let queue = list_pending_content(&pool).await.unwrap();
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue[0].id, pending.id);
+
+        // Without the capability a user is not a reviewer.
+        set_user_capability(
+            &pool,
+            reviewer,
+            Capability::Publish,
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(
+            !can_view_content(&pool, &pending, reviewer)
+                .await
+                .unwrap()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_review_decisions_are_recorded_in_history(
+        pool: PgPool,
+    ) {
+        let author = get_seed_user_0().id;
+        let reviewer = get_seed_user_1().id;
+        let item = create_post(&pool, "reviewed").await;
+        ensure_initial_revision(&pool, item.id, Some(author))
+            .await
+            .unwrap();
+
+        let pending =
+            set_status(&pool, &item, ContentStatus::Pending).await;
+        record_change(&pool, &pending, item.status, Some(author))
+            .await
+            .unwrap();
+
+        let returned =
+            set_status(&pool, &pending, ContentStatus::Draft).await;
+        record_transition(
+            &pool,
+            &returned,
+            Some(reviewer),
+            "Changes requested: Add a summary",
+        )
+        .await
+        .unwrap();
+
+        let published =
+            publish_content(&pool, item.id).await.unwrap().unwrap();
+        let approved_rev = record_transition(
+            &pool,
+            &published,
+            Some(reviewer),
+            "Approved and published",
+        )
+        .await
+        .unwrap();
+
+        let revisions = list_revisions(&pool, item.id, 10)
+            .await
+            .expect("Failed to list revisions");
+        let notes: Vec<_> = revisions
+            .iter()
+            .map(|r| {
+                (r.status, r.note.as_deref(), r.created_by_user_id)
+            })
+            .collect();
+        assert_eq!(
+            notes,
+            vec![
+                (
+                    ContentStatus::Published,
+                    Some("Approved and published"),
+                    Some(reviewer),
+                ),
+                (
+                    ContentStatus::Draft,
+                    Some("Changes requested: Add a summary"),
+                    Some(reviewer),
+                ),
+                (
+                    ContentStatus::Pending,
+                    Some("Submitted for review"),
+                    Some(author),
+                ),
+                (ContentStatus::Draft, None, Some(author)),
+            ]
+        );
+
+        let current = get_revision(&pool, item.id, approved_rev)
+            .await
+            .unwrap()
+            .expect("Current revision missing");
+        assert_eq!(
+            current.note.as_deref(),
+            Some("Approved and published")
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_private_and_pending_content_is_not_public(
+        pool: PgPool,
+    ) {
+        let private = create_post(&pool, "private").await;
+        set_status(&pool, &private, ContentStatus::Private).await;
+        let pending = create_post(&pool, "pending").await;
+        set_status(&pool, &pending, ContentStatus::Pending).await;
+
+        for slug in ["private", "pending"] {
+            assert!(
+                get_published_by_slug(&pool, ContentKind::Post, slug)
+                    .await
+                    .unwrap()
+                    .is_none()
+            );
+        }
+        let listed = list_content(&pool, ContentKind::Post, false)
+            .await
+            .unwrap();
+        assert!(listed.is_empty());
+
+        // Both still show up in the admin listings.
+        let all = list_content(&pool, ContentKind::Post, true)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_private_content_is_shown_to_viewers(pool: PgPool) {
+        let owner = get_seed_user_0().id;
+        let collaborator = get_seed_user_1().id;
+        let post = create_post(&pool, "members").await;
+        let post =
+            set_status(&pool, &post, ContentStatus::Private).await;
+
+        // The public site answers 404 unless the viewer may see it.
+        let viewable = |viewer| {
+            let pool = pool.clone();
+            async move {
+                get_viewable_by_slug(
+                    &pool,
+                    ContentKind::Post,
+                    "members",
+                    viewer,
+                )
+                .await
+                .unwrap()
+                .map(|item| item.id)
+            }
+        };
+        assert_eq!(viewable(None).await, None);
+        assert_eq!(viewable(Some(collaborator)).await, None);
+        assert_eq!(viewable(Some(owner)).await, Some(post.id));
+        add_collaborator(
+            &pool,
+            post.id,
+            &get_seed_user_1().email,
+            RoleName::Editor,
+            Some(owner),
+        )
+        .await
+        .unwrap();
+        assert_eq!(viewable(Some(collaborator)).await, Some(post.id));
+        assert_eq!(viewable(None).await, None);
+
+        // Private items are kept out of search engines.
+        let seo = SeoMeta::for_item(&post, "/blog/members", "");
+        assert!(seo.noindex);
+
+        let page = create_item(
+            &pool,
+            Some(owner),
+            ContentKind::Page,
+            "team",
+        )
+        .await;
+        set_status(&pool, &page, ContentStatus::Private).await;
+        for (viewer, found) in [
+            (None, false),
+            (Some(collaborator), false),
+            (Some(owner), true),
+        ] {
+            let shown =
+                get_viewable_page_by_path(&pool, "team", viewer)
+                    .await
+                    .unwrap();
+            assert_eq!(shown.is_some(), found, "{viewer:?}");
+        }
+        assert!(
+            get_published_page_by_path(&pool, "team")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,282 @@
+mod common;
+
+#[cfg(test)]
+pub mod sanitize_tests {
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::*;
+
+    fn clean(html: &str) -> String {
+        HtmlPolicy::default().clean(html)
+    }
+
+    #[test]
+    fn test_editor_markup_is_kept() {
+        let html = "<h2 style=\"text-align: center\">Title</h2>\
+            <p style=\"text-align: right\">Some <strong>bold</strong>, \
+            <em>italic</em>, <u>underlined</u> and <s>struck</s> \
+            text<br></p><ul><li><p>one</p></li></ul>\
+            <ol start=\"3\"><li><p>three</p></li></ol>\
+            <blockquote><p>quote</p></blockquote>\
+            <pre><code class=\"language-rust\">fn main() {}</code></pre>\
+            <hr><p><img src=\"/media/abc\" alt=\"Cat\"></p>";
+        assert_eq!(clean(html), html);
+
+        // Links keep their target and always get a safe rel.
+        assert_eq!(
+            clean(
+                "<a href=\"https://example.com\" target=\"_blank\" \
+                 rel=\"opener\">x</a>"
+            ),
+            "<a href=\"https://example.com\" target=\"_blank\" \
+             rel=\"noopener noreferrer nofollow\">x</a>"
+        );
+        assert_eq!(
+            clean("<a href=\"mailto:a@b.c\">m</a>"),
+            "<a href=\"mailto:a@b.c\" \
+             rel=\"noopener noreferrer nofollow\">m</a>"
+        );
+    }
+
+    #[test]
+    fn test_scripts_and_handlers_are_removed() {
+        for (html, expected) in [
+            ("<script>alert(1)</script><p>a</p>", "<p>a</p>"),
+            ("<SCRIPT SRC=//evil.test/x.js></SCRIPT>", ""),
+            ("<style>body{display:none}</style>", ""),
+            (
+                "<img src=\"/media/x\" onerror=\"alert(1)\">",
+                "<img src=\"/media/x\">",
+            ),
+            (
+                "<p onclick=\"alert(1)\" onmouseover=x>p</p>",
+                "<p>p</p>",
+            ),
+            ("<svg onload=alert(1)><circle/></svg>", ""),
+            ("<iframe src=\"//evil.test\"></iframe>", ""),
+            ("<object data=\"x.swf\"></object>", ""),
+            ("<embed src=\"x.swf\">", ""),
+            (
+                "<meta http-equiv=\"refresh\" content=\"0;url=//evil\">",
+                "",
+            ),
+            (
+                "<form action=\"/x\"><button \
+                 formaction=\"javascript:alert(1)\">b</button></form>",
+                "b",
+            ),
+            (
+                "<p>a<!-- <script>alert(1)</script> --></p>",
+                "<p>a</p>",
+            ),
+            // Unclosed and misnested markup is parsed like a browser
+            // would before it is cleaned.
+            ("<p>open <b>bold", "<p>open <b>bold</b></p>"),
+            // The attribute value that hides a tag stays inert text.
+            (
+                "<noscript><p title=\"</noscript><img src=x \
+                 onerror=alert(1)>\">",
+                "&lt;p title=\"<img src=\"x\">\"&gt;",
+            ),
+        ] {
+            assert_eq!(clean(html), expected, "{html}");
+        }
+    }
+
+    #[test]
+    fn test_dangerous_urls_are_removed() {
+        for html in [
+            "<a href=\"javascript:alert(1)\">x</a>",
+            "<a href=\"JaVaScRiPt:alert(1)\">x</a>",
+            "<a href=\"java&#x09;script:alert(1)\">x</a>",
+            "<a href=\" javascript:alert(1)\">x</a>",
+            "<a href=\"&#106;avascript:alert(1)\">x</a>",
+            "<a href=\"vbscript:msgbox(1)\">x</a>",
+            "<a href=\"data:text/html,<script>alert(1)</script>\">x</a>",
+        ] {
+            assert_eq!(
+                clean(html),
+                "<a rel=\"noopener noreferrer nofollow\">x</a>",
+                "{html}"
+            );
+        }
+        assert_eq!(
+            clean("<img src=\"data:image/svg+xml;base64,PHN2Zz4=\">"),
+            "<img>"
+        );
+    }
+
+    #[test]
+    fn test_partly_safe_attributes_are_narrowed() {
+        assert_eq!(
+            clean(
+                "<p style=\"color: red; text-align: center\">a</p>\
+                 <p style=\"background: url(javascript:alert(1))\">b</p>\
+                 <p style=\"text-align: expression(alert(1))\">c</p>"
+            ),
+            "<p style=\"text-align: center\">a</p><p>b</p><p>c</p>"
+        );
+        assert_eq!(
+            clean("<code class=\"language-js x\">c</code>"),
+            "<code class=\"language-js\">c</code>"
+        );
+        assert_eq!(
+            clean("<p class=\"admin\" id=\"x\">p</p>"),
+            "<p>p</p>"
+        );
+        assert_eq!(
+            clean("<a href=\"/\" target=\"_top\">x</a>"),
+            "<a href=\"/\" rel=\"noopener noreferrer nofollow\">x</a>"
+        );
+    }
+
+    #[test]
+    fn test_configured_allowlist() {
+        let policy = HtmlPolicy::default()
+            .allow_tags("table, tr,TD")
+            .unwrap()
+            .allow_attributes("td:colspan,*:lang")
+            .unwrap();
+        assert_eq!(
+            policy.clean(
+                "<table><tr><td colspan=\"2\" rowspan=\"2\" \
+                 lang=\"en\">x</td></tr></table>"
+            ),
+            "<table><tr><td colspan=\"2\" lang=\"en\">x</td></tr>\
+             </table>"
+        );
+        // Without the tags the table is reduced to its text.
+        assert_eq!(clean("<table><tr><td>x</td></tr></table>"), "x");
+
+        for tags in ["script", "iframe", "svg", "bad-name"] {
+            assert!(HtmlPolicy::default().allow_tags(tags).is_err());
+        }
+        for attributes in [
+            "img:onerror",
+            "*:style",
+            "a:rel",
+            "img:srcset",
+            "colspan",
+        ] {
+            assert!(
+                HtmlPolicy::default()
+                    .allow_attributes(attributes)
+                    .is_err()
+            );
+        }
+    }
+
+    #[test]
+    fn test_for_display() {
+        let policy = HtmlPolicy::default();
+        let html = "<p>a</p><script>alert(1)</script>";
+        assert_eq!(
+            policy.for_display(html, ContentFormat::Html, true),
+            html
+        );
+        assert_eq!(
+            policy.for_display(html, ContentFormat::Html, false),
+            "<p>a</p>"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_unfiltered_html_capability(pool: PgPool) {
+        let uid = get_seed_user_1().id;
+        assert!(
+            !user_can_use_unfiltered_html(&pool, uid).await.unwrap()
+        );
+
+        set_user_capability(
+            &pool,
+            uid,
+            Capability::UnfilteredHtml,
+            true,
+        )
+        .await
+        .expect("Failed to grant");
+        assert!(
+            user_can_use_unfiltered_html(&pool, uid).await.unwrap()
+        );
+
+        set_user_capability(
+            &pool,
+            uid,
+            Capability::UnfilteredHtml,
+            false,
+        )
+        .await
+        .expect("Failed to revoke");
+        set_user_role(&pool, uid, RoleName::Admin)
+            .await
+            .expect("Failed to set role");
+        assert!(
+            user_can_use_unfiltered_html(&pool, uid).await.unwrap()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_unfiltered_flag_follows_content(pool: PgPool) {
+        let uid = get_seed_user_0().id;
+        let item = create_from(
+            &pool,
+            &ContentCreate {
+                content:
+                    "<iframe src=\"https://video.test\"></iframe>"
+                        .into(),
+                unfiltered_html: true,
+                ..new_content(Some(uid), ContentKind::Post, "embed")
+            },
+        )
+        .await;
+        assert!(item.unfiltered_html);
+        ensure_initial_revision(&pool, item.id, Some(uid))
+            .await
+            .unwrap();
+
+        let update =
+            |content: Option<&str>, unfiltered_html| ContentUpdate {
+                title: Some("Embed".into()),
+                slug: None,
+                content: content.map(Into::into),
+                template: None,
+                status: None,
+                unfiltered_html,
+                format: None,
+                rendered_html: None,
+            };
+        // Changes that leave the content alone keep the flag.
+        let item =
+            update_content(&pool, item.id, &update(None, false))
+                .await
+                .unwrap()
+                .unwrap();
+        assert!(item.unfiltered_html);
+
+        let item = update_content(
+            &pool,
+            item.id,
+            &update(Some("<p>cleaned</p>"), false),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(!item.unfiltered_html);
+        record_revision(&pool, &item, Some(uid)).await.unwrap();
+        let rev2 =
+            get_revision(&pool, item.id, 2).await.unwrap().unwrap();
+        assert!(!rev2.unfiltered_html);
+
+        // Restoring the first revision brings its flag back with it.
+        let item = restore_revision(&pool, item.id, 1)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(item.unfiltered_html);
+        assert!(item.content.contains("<iframe"));
+    }
+}
@@ -0,0 +1,201 @@
+mod common;
+
+#[cfg(test)]
+pub mod schedule_tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::ContentScheduler;
+
+    async fn schedule(
+        pool: &PgPool,
+        item: &ContentItem,
+        publish_at: Option<Duration>,
+        unpublish_at: Option<Duration>,
+    ) -> ContentItem {
+        let now = Utc::now();
+        schedule_content(
+            pool,
+            item.id,
+            &ContentSchedule {
+                publish_at: publish_at.map(|d| now + d),
+                unpublish_at: unpublish_at.map(|d| now + d),
+            },
+        )
+        .await
+        .expect("Failed to schedule")
+        .expect("Content not found")
+    }
+
+    async fn is_public(pool: &PgPool, slug: &str) -> bool {
+        let by_slug =
+            get_published_by_slug(pool, ContentKind::Post, slug)
+                .await
+                .unwrap()
+                .is_some();
+        let listed = list_content(pool, ContentKind::Post, false)
+            .await
+            .unwrap()
+            .iter()
+            .any(|item| item.slug == slug);
+        assert_eq!(
+            by_slug, listed,
+            "Slug lookup and listing disagree"
+        );
+        by_slug
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_scheduled_content_is_not_public(pool: PgPool) {
+        let item = create_post(&pool, "later").await;
+        let item =
+            schedule(&pool, &item, Some(Duration::hours(1)), None)
+                .await;
+
+        assert_eq!(item.status, ContentStatus::Scheduled);
+        assert!(item.publish_at.is_some());
+        assert!(item.published_at.is_none());
+        assert!(!is_public(&pool, "later").await);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_promote_due_content_publishes_only_due_items(
+        pool: PgPool,
+    ) {
+        let due = create_post(&pool, "due").await;
+        let due =
+            schedule(&pool, &due, Some(-Duration::minutes(5)), None)
+                .await;
+        let future = create_post(&pool, "future").await;
+        schedule(&pool, &future, Some(Duration::hours(1)), None)
+            .await;
+
+        let promoted = promote_due_content(&pool, Utc::now())
+            .await
+            .expect("Failed to promote");
+
+        assert_eq!(promoted.len(), 1);
+        assert_eq!(promoted[0].id, due.id);
+        assert_eq!(promoted[0].status, ContentStatus::Published);
+        assert_eq!(promoted[0].published_at, due.publish_at);
+        assert!(promoted[0].publish_at.is_none());
+        assert!(is_public(&pool, "due").await);
+        assert!(!is_public(&pool, "future").await);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_scheduler_records_revision_for_promotion(
+        pool: PgPool,
+    ) {
+        let item = create_post(&pool, "due").await;
+        schedule(&pool, &item, Some(-Duration::minutes(1)), None)
+            .await;
+
+        let changed = ContentScheduler::new(pool.clone())
+            .apply_due()
+            .await
+            .expect("Failed to apply schedules");
+        assert_eq!(changed.len(), 1);
+
+        let revisions = list_revisions(&pool, item.id, 10)
+            .await
+            .expect("Failed to list revisions");
+        let latest = revisions.first().expect("No revision recorded");
+        assert_eq!(latest.rev, changed[0].current_rev);
+        assert_eq!(latest.status, ContentStatus::Published);
+        assert!(latest.created_by_user_id.is_none());
+
+        // Nothing left to do on the next pass.
+        let again = ContentScheduler::new(pool.clone())
+            .apply_due()
+            .await
+            .expect("Failed to apply schedules");
+        assert!(again.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_expired_content_is_hidden_then_unpublished(
+        pool: PgPool,
+    ) {
+        let item = create_post(&pool, "expiring").await;
+        publish_content(&pool, item.id).await.unwrap().unwrap();
+        let item =
+            schedule(&pool, &item, None, Some(-Duration::seconds(1)))
+                .await;
+
+        // Still marked published, but already out of public view.
+        assert_eq!(item.status, ContentStatus::Published);
+        assert!(!item.is_live(Utc::now()));
+        assert!(!is_public(&pool, "expiring").await);
+
+        let unpublished = unpublish_due_content(&pool, Utc::now())
+            .await
+            .expect("Failed to unpublish");
+        assert_eq!(unpublished.len(), 1);
+        assert_eq!(unpublished[0].status, ContentStatus::Draft);
+        assert!(unpublished[0].unpublish_at.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_publish_now_and_clear_drop_the_schedule(
+        pool: PgPool,
+    ) {
+        let first = create_post(&pool, "first").await;
+        schedule(&pool, &first, Some(Duration::hours(1)), None).await;
+        let cleared =
+            clear_schedule(&pool, first.id).await.unwrap().unwrap();
+        assert_eq!(cleared.status, ContentStatus::Draft);
+        assert!(cleared.publish_at.is_none());
+
+        let second = create_post(&pool, "second").await;
+        schedule(
+            &pool,
+            &second,
+            Some(Duration::hours(1)),
+            Some(Duration::hours(2)),
+        )
+        .await;
+        let published =
+            publish_content(&pool, second.id).await.unwrap().unwrap();
+        assert_eq!(published.status, ContentStatus::Published);
+        assert!(published.publish_at.is_none());
+        assert!(published.unpublish_at.is_some());
+        assert!(is_public(&pool, "second").await);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_status_change_clears_publish_time(pool: PgPool) {
+        let item = create_post(&pool, "later").await;
+        schedule(&pool, &item, Some(Duration::hours(1)), None).await;
+
+        let update = ContentUpdate {
+            title: None,
+            slug: None,
+            content: None,
+            template: None,
+            status: Some(ContentStatus::Draft),
+            unfiltered_html: false,
+            format: None,
+            rendered_html: None,
+        };
+        let updated = update_content(&pool, item.id, &update)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(updated.status, ContentStatus::Draft);
+        assert!(updated.publish_at.is_none());
+
+        // A scheduled item cannot lose its publish time.
+        let invalid = sqlx::query(
+            "UPDATE content_items SET status = 'scheduled' WHERE id = $1",
+        )
+        .bind(item.id)
+        .execute(&pool)
+        .await;
+        assert!(invalid.is_err());
+    }
+}
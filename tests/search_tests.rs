@@ -0,0 +1,413 @@
+mod common;
+
+#[cfg(test)]
+pub mod search_tests {
+    use sqlx::PgPool;
+    use uuid::Uuid;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::*;
+
+    async fn create(
+        pool: &PgPool,
+        owner: Uuid,
+        kind: ContentKind,
+        title: &str,
+        content: &str,
+    ) -> ContentItem {
+        let slug = title.to_lowercase().replace(' ', "-");
+        create_from(
+            pool,
+            &ContentCreate {
+                title: title.into(),
+                content: content.into(),
+                ..new_content(Some(owner), kind, &slug)
+            },
+        )
+        .await
+    }
+
+    async fn published(
+        pool: &PgPool,
+        kind: ContentKind,
+        title: &str,
+        content: &str,
+    ) -> ContentItem {
+        let item =
+            create(pool, get_seed_user_0().id, kind, title, content)
+                .await;
+        publish_content(pool, item.id).await.unwrap().unwrap()
+    }
+
+    fn language_update(language: &str) -> SiteUpdate {
+        SiteUpdate {
+            name: None,
+            slug: None,
+            status: None,
+            default_template: None,
+            homepage_type: None,
+            homepage_page_id: None,
+            require_verified_email: None,
+            require_admin_2fa: None,
+            trash_retention_days: None,
+            feed_full_text: None,
+            robots_txt: None,
+            search_language: Some(language.into()),
+            posts_per_page: None,
+        }
+    }
+
+    async fn titles(pool: &PgPool, query: &str) -> Vec<String> {
+        search_published_content(pool, query, 10, 0)
+            .await
+            .expect("Failed to search")
+            .into_iter()
+            .map(|hit| hit.item.title)
+            .collect()
+    }
+
+    #[test]
+    fn test_search_terms() {
+        assert_eq!(search_terms(None), None);
+        assert_eq!(search_terms(Some("   ")), None);
+        assert_eq!(
+            search_terms(Some("  rust web ")).as_deref(),
+            Some("rust web")
+        );
+        let long = "é".repeat(MAX_SEARCH_QUERY_LENGTH + 10);
+        assert_eq!(
+            search_terms(Some(&long)).unwrap().chars().count(),
+            MAX_SEARCH_QUERY_LENGTH
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_search_published_content(pool: PgPool) {
+        published(
+            &pool,
+            ContentKind::Post,
+            "Gardening notes",
+            "<p>The tomatoes are <strong>growing</strong> fast.</p>",
+        )
+        .await;
+        published(
+            &pool,
+            ContentKind::Page,
+            "Tomato growing guide",
+            "<p>Everything about soil.</p>",
+        )
+        .await;
+        create(
+            &pool,
+            get_seed_user_0().id,
+            ContentKind::Post,
+            "Draft tomatoes",
+            "<p>Not out yet.</p>",
+        )
+        .await;
+        let trashed = published(
+            &pool,
+            ContentKind::Post,
+            "Old tomatoes",
+            "<p>Gone.</p>",
+        )
+        .await;
+        trash_content(&pool, trashed.id).await.unwrap();
+
+        // Words are stemmed, titles outrank content, and only live
+        // items are found.
+        assert_eq!(
+            titles(&pool, "tomato grows").await,
+            ["Tomato growing guide", "Gardening notes"]
+        );
+        assert_eq!(
+            count_published_search_results(&pool, "tomato grows")
+                .await
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            titles(&pool, "\"growing guide\"").await,
+            ["Tomato growing guide"]
+        );
+        assert_eq!(
+            titles(&pool, "tomatoes -soil").await,
+            ["Gardening notes"]
+        );
+        assert!(titles(&pool, "cucumber").await.is_empty());
+        assert!(titles(&pool, "the").await.is_empty());
+
+        // Tags are not indexed, and matches are marked in snippets,
+        // which leave out stop words and punctuation at their ends.
+        assert!(titles(&pool, "strong").await.is_empty());
+        let hits = search_published_content(&pool, "fast", 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].snippet,
+            format!(
+                "tomatoes are growing {}fast{}",
+                SNIPPET_MATCH_START, SNIPPET_MATCH_END
+            )
+        );
+
+        let second =
+            search_published_content(&pool, "tomato grows", 1, 1)
+                .await
+                .unwrap();
+        assert_eq!(second.len(), 1);
+        assert_eq!(second[0].item.title, "Gardening notes");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_search_vector_follows_content(pool: PgPool) {
+        let item = published(
+            &pool,
+            ContentKind::Post,
+            "Weekly update",
+            "<p>Nothing new.</p><script>var secret = 1;</script>",
+        )
+        .await;
+        assert!(titles(&pool, "secret").await.is_empty());
+
+        // Snippets split into runs; marks never come from the text.
+        let mut hit =
+            search_published_content(&pool, "weekly", 10, 0)
+                .await
+                .unwrap()
+                .remove(0);
+        let (s, e) = (SNIPPET_MATCH_START, SNIPPET_MATCH_END);
+        hit.snippet = format!("a {s}<b>{e} c {s}d{e}");
+        assert_eq!(
+            hit.snippet_parts(),
+            [
+                (false, "a "),
+                (true, "<b>"),
+                (false, " c "),
+                (true, "d")
+            ]
+        );
+        hit.snippet = "plain".into();
+        assert_eq!(hit.snippet_parts(), [(false, "plain")]);
+        hit.snippet.clear();
+        assert!(hit.snippet_parts().is_empty());
+
+        let md = AuthoredContent::new(
+            &HtmlPolicy::default(),
+            ContentFormat::Markdown,
+            "| Fruit |\n|---|\n| Apricots &amp; plums |\n",
+            false,
+        );
+        update_content(
+            &pool,
+            item.id,
+            &ContentUpdate {
+                title: Some("Harvest report".into()),
+                slug: None,
+                content: Some(md.content),
+                template: None,
+                status: None,
+                unfiltered_html: false,
+                format: Some(md.format),
+                rendered_html: md.rendered_html,
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+        assert!(titles(&pool, "weekly").await.is_empty());
+        assert_eq!(
+            titles(&pool, "harvest").await,
+            ["Harvest report"]
+        );
+        assert_eq!(
+            titles(&pool, "apricot").await,
+            ["Harvest report"]
+        );
+        let hits = search_published_content(&pool, "plums", 10, 0)
+            .await
+            .unwrap();
+        assert!(hits[0].snippet.contains("Apricots & "), "{hits:?}");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_search_content_for_user(pool: PgPool) {
+        let owner = get_seed_user_0().id;
+        let other = get_seed_user_1().id;
+        let draft = create(
+            &pool,
+            owner,
+            ContentKind::Post,
+            "Rust draft",
+            "<p>About borrowing.</p>",
+        )
+        .await;
+        published(
+            &pool,
+            ContentKind::Post,
+            "Borrowing explained",
+            "<p>Rust lifetimes.</p>",
+        )
+        .await;
+        published(
+            &pool,
+            ContentKind::Page,
+            "Rust page",
+            "<p>A page.</p>",
+        )
+        .await;
+
+        // Drafts are included and ranked with the rest.
+        let found = |limit, offset| {
+            let pool = pool.clone();
+            async move {
+                search_content_for_user(
+                    &pool,
+                    ContentKind::Post,
+                    owner,
+                    "borrow",
+                    limit,
+                    offset,
+                )
+                .await
+                .unwrap()
+                .into_iter()
+                .map(|item| item.title)
+                .collect::<Vec<_>>()
+            }
+        };
+        assert_eq!(
+            found(10, 0).await,
+            ["Borrowing explained", "Rust draft"]
+        );
+        // Results come a page at a time.
+        assert_eq!(found(1, 0).await, ["Borrowing explained"]);
+        assert_eq!(found(1, 1).await, ["Rust draft"]);
+        assert_eq!(
+            count_content_search_results_for_user(
+                &pool,
+                ContentKind::Post,
+                owner,
+                "borrow",
+            )
+            .await
+            .unwrap(),
+            2
+        );
+
+        // Other users only see what they collaborate on.
+        let visible = |uid| {
+            let pool = pool.clone();
+            async move {
+                count_content_search_results_for_user(
+                    &pool,
+                    ContentKind::Post,
+                    uid,
+                    "rust",
+                )
+                .await
+                .unwrap()
+            }
+        };
+        assert_eq!(visible(owner).await, 2);
+        assert_eq!(visible(other).await, 0);
+        add_collaborator(
+            &pool,
+            draft.id,
+            &get_seed_user_1().email,
+            RoleName::Editor,
+            Some(owner),
+        )
+        .await
+        .unwrap();
+        assert_eq!(visible(other).await, 1);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_search_follows_site_language(pool: PgPool) {
+        let owner = get_seed_user_0().id;
+        published(
+            &pool,
+            ContentKind::Post,
+            "Race day",
+            "<p>The runners were running.</p>",
+        )
+        .await;
+        // Without a site, words are stemmed as English.
+        assert_eq!(titles(&pool, "run").await, ["Race day"]);
+
+        let site = create_site(
+            &pool,
+            &SiteCreate {
+                owner_user_id: owner,
+                name: "Blog".into(),
+                slug: "blog".into(),
+                default_template: "default".into(),
+            },
+        )
+        .await
+        .unwrap();
+        publish_site(&pool, site.id, owner).await.unwrap().unwrap();
+        assert_eq!(titles(&pool, "run").await, ["Race day"]);
+
+        // `simple` matches words exactly, for old and new content.
+        update_site(
+            &pool,
+            site.id,
+            owner,
+            &language_update("simple"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(titles(&pool, "run").await.is_empty());
+        assert_eq!(titles(&pool, "running").await, ["Race day"]);
+        published(
+            &pool,
+            ContentKind::Post,
+            "Long jumps",
+            "<p>Jumping practice.</p>",
+        )
+        .await;
+        assert!(titles(&pool, "jump").await.is_empty());
+        assert_eq!(titles(&pool, "jumping").await, ["Long jumps"]);
+
+        // Other languages have their own stems and stop words.
+        update_site(
+            &pool,
+            site.id,
+            owner,
+            &language_update("german"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        published(
+            &pool,
+            ContentKind::Post,
+            "Bergwandern",
+            "<p>Wir wanderten durch die Berge.</p>",
+        )
+        .await;
+        assert_eq!(titles(&pool, "Berg").await, ["Bergwandern"]);
+        assert!(titles(&pool, "durch").await.is_empty());
+
+        update_site(
+            &pool,
+            site.id,
+            owner,
+            &language_update("english"),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(titles(&pool, "run").await, ["Race day"]);
+        assert_eq!(titles(&pool, "jump").await, ["Long jumps"]);
+        assert_eq!(titles(&pool, "durch").await, ["Bergwandern"]);
+    }
+}
@@ -0,0 +1,144 @@
+mod common;
+
+#[cfg(test)]
+pub mod seo_tests {
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::*;
+
+    const BASE: &str = "https://example.com";
+
+    #[test]
+    fn test_html_to_text_decodes_entities() {
+        assert_eq!(
+            html_to_text(
+                "<h2>Fish &amp; chips</h2>\n<p>Caf&#233; &lt;open&gt;&nbsp;daily &#x2014; 5&ndash;9</p>"
+            ),
+            "Fish & chips Café <open> daily — 5&ndash;9"
+        );
+        assert_eq!(html_to_text("a & b"), "a & b");
+    }
+
+    #[test]
+    fn test_truncate_text_at_word_boundary() {
+        assert_eq!(truncate_text("short", 10), "short");
+        assert_eq!(
+            truncate_text("one two, three four", 12),
+            "one two…"
+        );
+        assert_eq!(truncate_text("abcdefgh", 4), "abcd…");
+    }
+
+    #[test]
+    fn test_first_image_src() {
+        assert_eq!(
+            first_image_src(
+                r#"<p>x</p><IMG alt="a" SRC="/static/a.png"><img src="/b.png">"#
+            )
+            .as_deref(),
+            Some("/static/a.png")
+        );
+        assert_eq!(
+            first_image_src("<img src=/static/c.png/>").as_deref(),
+            Some("/static/c.png")
+        );
+        assert_eq!(first_image_src("<p>No images</p>"), None);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_fallbacks_and_overrides(pool: PgPool) {
+        let post = create_from(
+            &pool,
+            &ContentCreate {
+                title: "Hello".into(),
+                content: r#"<p>First words.</p><img src="/static/cover.png">"#
+                    .into(),
+                ..new_content(
+                    Some(get_seed_user_0().id),
+                    ContentKind::Post,
+                    "hello",
+                )
+            },
+        )
+        .await;
+        let url = format!("{BASE}/blog/hello");
+
+        let derived = SeoMeta::for_item(&post, &url, BASE);
+        assert_eq!(derived.title, "Hello");
+        assert_eq!(derived.description, "First words.");
+        assert_eq!(derived.canonical_url, url);
+        assert_eq!(
+            derived.image_url.as_deref(),
+            Some("https://example.com/static/cover.png")
+        );
+        assert!(!derived.noindex);
+
+        let seo = ContentSeo {
+            meta_title: "  Hello, world  ".into(),
+            meta_description: "A greeting.".into(),
+            canonical_url: "https://other.example/hello".into(),
+            og_image_url: "/static/share.png".into(),
+            noindex: true,
+        };
+        let post = update_content_seo(&pool, post.id, &seo)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            ContentSeo::from_item(&post).meta_title,
+            "Hello, world"
+        );
+
+        let meta = SeoMeta::for_item(&post, &url, BASE);
+        assert_eq!(meta.title, "Hello, world");
+        assert_eq!(meta.description, "A greeting.");
+        assert_eq!(meta.canonical_url, "https://other.example/hello");
+        assert_eq!(
+            meta.image_url.as_deref(),
+            Some("https://example.com/static/share.png")
+        );
+        assert!(meta.noindex);
+
+        // Clearing the fields brings the derived values back.
+        let post = update_content_seo(
+            &pool,
+            post.id,
+            &ContentSeo::default(),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            SeoMeta::for_item(&post, &url, BASE).title,
+            "Hello"
+        );
+    }
+
+    #[test]
+    fn test_validate_seo() {
+        let seo = |canonical: &str, image: &str| ContentSeo {
+            canonical_url: canonical.into(),
+            og_image_url: image.into(),
+            ..Default::default()
+        };
+        assert!(seo("", "").validate().is_ok());
+        assert!(
+            seo("https://a.example/x", "/static/a.png")
+                .validate()
+                .is_ok()
+        );
+        assert!(seo("javascript:alert(1)", "").validate().is_err());
+        assert!(seo("", "//cdn.example/a.png").validate().is_err());
+        assert!(seo("/a b", "").validate().is_err());
+
+        let long = ContentSeo {
+            meta_title: "x".repeat(MAX_META_TITLE_LENGTH + 1),
+            ..Default::default()
+        };
+        assert!(long.validate().is_err());
+    }
+}
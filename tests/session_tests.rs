@@ -0,0 +1,373 @@
+mod common;
+
+#[cfg(test)]
+pub mod session_tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::TokenManager;
+
+    fn new_session(user: &User, token: &str) -> SessionCreate {
+        SessionCreate {
+            user_id: user.id,
+            token_hash: TokenManager::hash(token),
+            expires_at: Utc::now() + Duration::days(7),
+            ip: Some("127.0.0.1".to_string()),
+            user_agent: Some("test-agent".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_token_generate_is_random_hex() {
+        let a = TokenManager::generate();
+        let b = TokenManager::generate();
+
+        assert_eq!(a.len(), 64);
+        assert!(a.chars().all(|c| c.is_ascii_hexdigit()));
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_token_hash_is_deterministic_and_opaque() {
+        let token = TokenManager::generate();
+
+        assert_eq!(
+            TokenManager::hash(&token),
+            TokenManager::hash(&token)
+        );
+        assert_ne!(TokenManager::hash(&token), token);
+        assert_ne!(
+            TokenManager::hash(&token),
+            TokenManager::hash(&TokenManager::generate())
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_create_and_get_active_session_success(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        let created =
+            create_session(&pool, &new_session(&user, &token))
+                .await
+                .expect("Failed to create session");
+
+        assert_eq!(created.user_id, user.id);
+        assert_ne!(created.token_hash, token);
+
+        let fetched =
+            get_active_session(&pool, &TokenManager::hash(&token))
+                .await
+                .expect("Failed database query");
+
+        assert_eq!(fetched, Some(created));
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_get_active_session_ignores_raw_token(pool: PgPool) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+
+        create_session(&pool, &new_session(&user, &token))
+            .await
+            .expect("Failed to create session");
+
+        let fetched = get_active_session(&pool, &token)
+            .await
+            .expect("Failed database query");
+
+        assert!(fetched.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_get_active_session_fails_when_expired(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+        let mut data = new_session(&user, &token);
+        data.expires_at = Utc::now() - Duration::seconds(1);
+
+        create_session(&pool, &data)
+            .await
+            .expect("Failed to create session");
+
+        let fetched =
+            get_active_session(&pool, &TokenManager::hash(&token))
+                .await
+                .expect("Failed database query");
+
+        assert!(fetched.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_get_active_session_fails_for_deleted_user(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_deleted();
+        let token = TokenManager::generate();
+
+        create_session(&pool, &new_session(&user, &token))
+            .await
+            .expect("Failed to create session");
+
+        let fetched =
+            get_active_session(&pool, &TokenManager::hash(&token))
+                .await
+                .expect("Failed database query");
+
+        assert!(fetched.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_touch_session_extends_expiry(pool: PgPool) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+        let created =
+            create_session(&pool, &new_session(&user, &token))
+                .await
+                .expect("Failed to create session");
+
+        let new_expiry = created.expires_at + Duration::days(1);
+        touch_session(&pool, created.id, new_expiry)
+            .await
+            .expect("Failed to touch session");
+
+        let fetched =
+            get_active_session(&pool, &TokenManager::hash(&token))
+                .await
+                .expect("Failed database query")
+                .expect("Session should still be active");
+
+        assert!(fetched.expires_at > created.expires_at);
+        assert!(fetched.last_seen >= created.last_seen);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_delete_session_revokes_only_that_session(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let token_a = TokenManager::generate();
+        let token_b = TokenManager::generate();
+        let a = create_session(&pool, &new_session(&user, &token_a))
+            .await
+            .expect("Failed to create session");
+        create_session(&pool, &new_session(&user, &token_b))
+            .await
+            .expect("Failed to create session");
+
+        assert!(delete_session(&pool, a.id).await.unwrap());
+        assert!(!delete_session(&pool, a.id).await.unwrap());
+
+        let hash_a = TokenManager::hash(&token_a);
+        let hash_b = TokenManager::hash(&token_b);
+        assert!(
+            get_active_session(&pool, &hash_a)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            get_active_session(&pool, &hash_b)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_delete_sessions_for_user_success(pool: PgPool) {
+        let user0 = get_seed_user_0();
+        let user1 = get_seed_user_1();
+        let token_other = TokenManager::generate();
+
+        for _ in 0..3 {
+            create_session(
+                &pool,
+                &new_session(&user0, &TokenManager::generate()),
+            )
+            .await
+            .expect("Failed to create session");
+        }
+        create_session(&pool, &new_session(&user1, &token_other))
+            .await
+            .expect("Failed to create session");
+
+        let removed = delete_sessions_for_user(&pool, user0.id)
+            .await
+            .expect("Failed to delete sessions");
+
+        assert_eq!(removed, 3);
+        assert!(
+            get_active_session(
+                &pool,
+                &TokenManager::hash(&token_other)
+            )
+            .await
+            .unwrap()
+            .is_some()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_delete_expired_sessions_success(pool: PgPool) {
+        let user = get_seed_user_0();
+        let mut expired =
+            new_session(&user, &TokenManager::generate());
+        expired.expires_at = Utc::now() - Duration::hours(1);
+
+        create_session(&pool, &expired)
+            .await
+            .expect("Failed to create session");
+        create_session(
+            &pool,
+            &new_session(&user, &TokenManager::generate()),
+        )
+        .await
+        .expect("Failed to create session");
+
+        let removed = delete_expired_sessions(&pool)
+            .await
+            .expect("Failed to purge sessions");
+
+        assert_eq!(removed, 1);
+    }
+
+    fn session_with_agent(user_agent: Option<&str>) -> Session {
+        let user = get_seed_user_0();
+        Session {
+            id: user.id,
+            user_id: user.id,
+            token_hash: String::new(),
+            created_at: Utc::now(),
+            last_seen: Utc::now(),
+            expires_at: Utc::now() + Duration::days(7),
+            ip: None,
+            user_agent: user_agent.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_session_device_label_common_browsers() {
+        let firefox = "Mozilla/5.0 (X11; Linux x86_64; rv:128.0) Gecko/20100101 Firefox/128.0";
+        let chrome = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0 Safari/537.36";
+        let edge = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/126.0 Safari/537.36 Edg/126.0";
+        let safari_ios = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_5 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.5 Mobile/15E148 Safari/604.1";
+
+        assert_eq!(
+            session_with_agent(Some(firefox)).device_label(),
+            "Firefox on Linux"
+        );
+        assert_eq!(
+            session_with_agent(Some(chrome)).device_label(),
+            "Chrome on Windows"
+        );
+        assert_eq!(
+            session_with_agent(Some(edge)).device_label(),
+            "Edge on Windows"
+        );
+        assert_eq!(
+            session_with_agent(Some(safari_ios)).device_label(),
+            "Safari on iOS"
+        );
+    }
+
+    #[test]
+    fn test_session_device_label_unknown() {
+        assert_eq!(
+            session_with_agent(None).device_label(),
+            "Unknown device"
+        );
+        assert_eq!(
+            session_with_agent(Some("SomeBot")).device_label(),
+            "Unknown device"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_list_sessions_for_user_skips_expired(pool: PgPool) {
+        let user = get_seed_user_0();
+        let mut expired =
+            new_session(&user, &TokenManager::generate());
+        expired.expires_at = Utc::now() - Duration::hours(1);
+
+        create_session(&pool, &expired)
+            .await
+            .expect("Failed to create session");
+        let live = create_session(
+            &pool,
+            &new_session(&user, &TokenManager::generate()),
+        )
+        .await
+        .expect("Failed to create session");
+
+        let listed = list_sessions_for_user(&pool, user.id)
+            .await
+            .expect("Failed database query");
+
+        assert_eq!(listed, vec![live]);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_delete_session_for_user_fails_for_other_user(
+        pool: PgPool,
+    ) {
+        let owner = get_seed_user_0();
+        let other = get_seed_user_1();
+        let session = create_session(
+            &pool,
+            &new_session(&owner, &TokenManager::generate()),
+        )
+        .await
+        .expect("Failed to create session");
+
+        let revoked =
+            delete_session_for_user(&pool, session.id, other.id)
+                .await
+                .expect("Failed database query");
+        assert!(!revoked);
+
+        let revoked =
+            delete_session_for_user(&pool, session.id, owner.id)
+                .await
+                .expect("Failed database query");
+        assert!(revoked);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_delete_other_sessions_keeps_current(pool: PgPool) {
+        let user = get_seed_user_0();
+        let current = create_session(
+            &pool,
+            &new_session(&user, &TokenManager::generate()),
+        )
+        .await
+        .expect("Failed to create session");
+        for _ in 0..2 {
+            create_session(
+                &pool,
+                &new_session(&user, &TokenManager::generate()),
+            )
+            .await
+            .expect("Failed to create session");
+        }
+
+        let removed =
+            delete_other_sessions(&pool, user.id, current.id)
+                .await
+                .expect("Failed database query");
+
+        assert_eq!(removed, 2);
+        let remaining = list_sessions_for_user(&pool, user.id)
+            .await
+            .expect("Failed database query");
+        assert_eq!(remaining, vec![current]);
+    }
+}
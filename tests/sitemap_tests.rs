@@ -0,0 +1,117 @@
+mod common;
+
+#[cfg(test)]
+pub mod sitemap_tests {
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+
+    async fn post(
+        pool: &PgPool,
+        slug: &str,
+        publish: bool,
+    ) -> ContentItem {
+        let post = create_post(pool, slug).await;
+        if !publish {
+            return post;
+        }
+        publish_content(pool, post.id)
+            .await
+            .expect("Failed to publish")
+            .expect("Content not found")
+    }
+
+    async fn listed(
+        pool: &PgPool,
+        page: i64,
+        per_page: i64,
+    ) -> Vec<String> {
+        list_sitemap_content(pool, ContentKind::Post, page, per_page)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|p| p.slug)
+            .collect()
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_drafts_and_noindex_are_left_out(pool: PgPool) {
+        post(&pool, "one", true).await;
+        post(&pool, "draft", false).await;
+        let hidden = post(&pool, "hidden", true).await;
+        let trashed = post(&pool, "trashed", true).await;
+        trash_content(&pool, trashed.id).await.unwrap().unwrap();
+
+        let hidden = update_content_seo(
+            &pool,
+            hidden.id,
+            &ContentSeo {
+                noindex: true,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert!(hidden.noindex);
+        assert_eq!(listed(&pool, 1, SITEMAP_MAX_URLS).await, ["one"]);
+
+        update_content_seo(
+            &pool,
+            hidden.id,
+            &ContentSeo {
+                noindex: false,
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            listed(&pool, 1, SITEMAP_MAX_URLS).await,
+            ["one", "hidden"]
+        );
+        assert!(
+            list_sitemap_content(&pool, ContentKind::Page, 1, 10)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_sitemaps_are_split_into_files(pool: PgPool) {
+        assert!(
+            list_sitemap_chunks(&pool, ContentKind::Post, 2)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+
+        for slug in ["a", "b", "c", "d", "e"] {
+            post(&pool, slug, true).await;
+        }
+        let chunks = list_sitemap_chunks(&pool, ContentKind::Post, 2)
+            .await
+            .unwrap();
+        assert_eq!(
+            chunks.iter().map(|c| c.page).collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+
+        assert_eq!(listed(&pool, 1, 2).await, ["a", "b"]);
+        assert_eq!(listed(&pool, 2, 2).await, ["c", "d"]);
+        assert_eq!(listed(&pool, 3, 2).await, ["e"]);
+        assert!(listed(&pool, 4, 2).await.is_empty());
+
+        // A file's lastmod is its most recent edit.
+        let posts =
+            list_sitemap_content(&pool, ContentKind::Post, 3, 2)
+                .await
+                .unwrap();
+        assert_eq!(chunks[2].lastmod, posts[0].edited_at);
+    }
+}
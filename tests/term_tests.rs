@@ -0,0 +1,235 @@
+mod common;
+
+#[cfg(test)]
+pub mod term_tests {
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+
+    async fn category(
+        pool: &PgPool,
+        slug: &str,
+        parent: Option<&Term>,
+    ) -> Term {
+        create_term(
+            pool,
+            &TermCreate {
+                taxonomy: Taxonomy::Category,
+                slug: slug.into(),
+                name: slug.to_uppercase(),
+                description: String::new(),
+                parent_id: parent.map(|p| p.id),
+            },
+        )
+        .await
+        .expect("Failed to create category")
+    }
+
+    async fn post(
+        pool: &PgPool,
+        slug: &str,
+        publish: bool,
+    ) -> ContentItem {
+        let item = create_post(pool, slug).await;
+        if publish {
+            publish_content(pool, item.id)
+                .await
+                .expect("Failed to publish")
+                .expect("Post should exist")
+        } else {
+            item
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Rust & Web"), "rust-web");
+        assert_eq!(slugify("  Hello,  World!  "), "hello-world");
+        assert_eq!(slugify("Ünïcode 2"), "n-code-2");
+        assert_eq!(slugify("!!!"), "");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_term_tree_orders_children_after_parent(
+        pool: PgPool,
+    ) {
+        let news = category(&pool, "news", None).await;
+        let local = category(&pool, "local", Some(&news)).await;
+        let city = category(&pool, "city", Some(&local)).await;
+        let arts = category(&pool, "arts", None).await;
+
+        let terms = list_terms(&pool, Taxonomy::Category)
+            .await
+            .expect("Failed to list terms");
+        let tree: Vec<_> = term_tree(terms)
+            .into_iter()
+            .map(|n| (n.term.id, n.depth))
+            .collect();
+
+        assert_eq!(
+            tree,
+            [(arts.id, 0), (news.id, 0), (local.id, 1), (city.id, 2)]
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_duplicate_slug_per_taxonomy_fails(pool: PgPool) {
+        category(&pool, "rust", None).await;
+        let dup = create_term(
+            &pool,
+            &TermCreate {
+                taxonomy: Taxonomy::Category,
+                slug: "rust".into(),
+                name: "Rust again".into(),
+                description: String::new(),
+                parent_id: None,
+            },
+        )
+        .await;
+        assert!(dup.is_err());
+
+        // A tag may share the slug.
+        get_or_create_tag(&pool, "rust", "Rust")
+            .await
+            .expect("Failed to create tag");
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_get_or_create_tag_reuses_existing(pool: PgPool) {
+        let first = get_or_create_tag(&pool, "web", "Web")
+            .await
+            .expect("Failed to create tag");
+        let second = get_or_create_tag(&pool, "web", "WEB")
+            .await
+            .expect("Failed to fetch tag");
+
+        assert_eq!(first.id, second.id);
+        assert_eq!(second.name, "Web");
+        assert_eq!(second.taxonomy, Taxonomy::Tag);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_set_content_terms_replaces_one_taxonomy(
+        pool: PgPool,
+    ) {
+        let item = post(&pool, "hello", false).await;
+        let news = category(&pool, "news", None).await;
+        let arts = category(&pool, "arts", None).await;
+        let tag =
+            get_or_create_tag(&pool, "web", "Web").await.unwrap();
+
+        set_content_terms(
+            &pool,
+            item.id,
+            Taxonomy::Category,
+            &[news.id],
+        )
+        .await
+        .expect("Failed to set categories");
+        // Ids from the other taxonomy are ignored.
+        set_content_terms(
+            &pool,
+            item.id,
+            Taxonomy::Tag,
+            &[tag.id, arts.id],
+        )
+        .await
+        .expect("Failed to set tags");
+        set_content_terms(
+            &pool,
+            item.id,
+            Taxonomy::Category,
+            &[arts.id],
+        )
+        .await
+        .expect("Failed to replace categories");
+
+        let slugs: Vec<_> = list_terms_for_content(&pool, item.id)
+            .await
+            .expect("Failed to list terms")
+            .into_iter()
+            .map(|t| t.slug)
+            .collect();
+        assert_eq!(slugs, ["arts", "web"]);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_category_archive_includes_subcategories(
+        pool: PgPool,
+    ) {
+        let news = category(&pool, "news", None).await;
+        let local = category(&pool, "local", Some(&news)).await;
+        let in_news = post(&pool, "in-news", true).await;
+        let in_local = post(&pool, "in-local", true).await;
+        let draft = post(&pool, "draft", false).await;
+        for (item, term) in
+            [(&in_news, &news), (&in_local, &local), (&draft, &news)]
+        {
+            set_content_terms(
+                &pool,
+                item.id,
+                Taxonomy::Category,
+                &[term.id],
+            )
+            .await
+            .unwrap();
+        }
+
+        let mut ids: Vec<_> =
+            list_published_posts_for_term(&pool, news.id)
+                .await
+                .expect("Failed to list archive")
+                .into_iter()
+                .map(|i| i.id)
+                .collect();
+        ids.sort();
+        let mut expected = vec![in_news.id, in_local.id];
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        let ids: Vec<_> =
+            list_published_posts_for_term(&pool, local.id)
+                .await
+                .expect("Failed to list archive")
+                .into_iter()
+                .map(|i| i.id)
+                .collect();
+        assert_eq!(ids, [in_local.id]);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_ancestor_check_and_delete_detaches_children(
+        pool: PgPool,
+    ) {
+        let news = category(&pool, "news", None).await;
+        let local = category(&pool, "local", Some(&news)).await;
+        let city = category(&pool, "city", Some(&local)).await;
+
+        // Moving `news` below `city` would form a cycle.
+        assert!(
+            is_term_ancestor_or_self(&pool, news.id, city.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            is_term_ancestor_or_self(&pool, city.id, city.id)
+                .await
+                .unwrap()
+        );
+        assert!(
+            !is_term_ancestor_or_self(&pool, city.id, news.id)
+                .await
+                .unwrap()
+        );
+
+        assert!(delete_term(&pool, local.id).await.unwrap());
+        let city = get_term_by_id(&pool, city.id)
+            .await
+            .unwrap()
+            .expect("Child should survive");
+        assert_eq!(city.parent_id, None);
+    }
+}
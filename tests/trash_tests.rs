@@ -0,0 +1,182 @@
+mod common;
+
+#[cfg(test)]
+pub mod trash_tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+
+    async fn trash(pool: &PgPool, item: &ContentItem) -> ContentItem {
+        trash_content(pool, item.id)
+            .await
+            .expect("Failed to trash")
+            .expect("Content not found")
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_trashed_content_is_hidden(pool: PgPool) {
+        let uid = get_seed_user_0().id;
+        let item = create_post(&pool, "gone").await;
+        publish_content(&pool, item.id).await.unwrap().unwrap();
+        let trashed = trash(&pool, &item).await;
+        assert!(trashed.deleted_at.is_some());
+        assert_eq!(trashed.status, ContentStatus::Published);
+
+        assert!(
+            get_content_by_id(&pool, item.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            get_published_by_slug(&pool, ContentKind::Post, "gone")
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            list_content_for_user(
+                &pool,
+                ContentKind::Post,
+                true,
+                uid
+            )
+            .await
+            .unwrap()
+            .is_empty()
+        );
+        assert!(
+            publish_content(&pool, item.id).await.unwrap().is_none()
+        );
+        // Trashing twice is a no-op.
+        assert!(
+            trash_content(&pool, item.id).await.unwrap().is_none()
+        );
+
+        let listed = list_trashed_content_for_user(
+            &pool,
+            ContentKind::Post,
+            uid,
+        )
+        .await
+        .unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].id, item.id);
+
+        // Other users only see trash they could edit.
+        let other = list_trashed_content_for_user(
+            &pool,
+            ContentKind::Post,
+            get_seed_user_1().id,
+        )
+        .await
+        .unwrap();
+        assert!(other.is_empty());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_restore_returns_a_draft(pool: PgPool) {
+        let item = create_post(&pool, "back").await;
+        publish_content(&pool, item.id).await.unwrap().unwrap();
+        trash(&pool, &item).await;
+
+        let restored = restore_trashed_content(&pool, item.id)
+            .await
+            .expect("Failed to restore")
+            .expect("Not in the trash");
+        assert!(restored.deleted_at.is_none());
+        assert_eq!(restored.status, ContentStatus::Draft);
+        assert!(
+            get_trashed_content_by_id(&pool, item.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            get_content_by_id(&pool, item.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_trashed_slug_is_free_until_restore(pool: PgPool) {
+        let old = create_post(&pool, "reused").await;
+        let old = trash(&pool, &old).await;
+
+        let new = create_post(&pool, "reused").await;
+        let conflict = find_slug_conflict(&pool, &old)
+            .await
+            .unwrap()
+            .expect("Conflict not found");
+        assert_eq!(conflict.id, new.id);
+
+        let err = restore_trashed_content(&pool, old.id)
+            .await
+            .expect_err("Restore should conflict");
+        assert!(matches!(
+            err,
+            sqlx::Error::Database(ref e) if e.is_unique_violation()
+        ));
+
+        // Once the slug is free again the item comes back.
+        trash(&pool, &new).await;
+        assert!(
+            find_slug_conflict(&pool, &old).await.unwrap().is_none()
+        );
+        assert!(
+            restore_trashed_content(&pool, old.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_purge_removes_only_expired_items(pool: PgPool) {
+        let expired = create_post(&pool, "expired").await;
+        trash(&pool, &expired).await;
+        sqlx::query(
+            "UPDATE content_items SET deleted_at = now() - interval '31 days' WHERE id = $1",
+        )
+        .bind(expired.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let recent = create_post(&pool, "recent").await;
+        trash(&pool, &recent).await;
+        let live = create_post(&pool, "live").await;
+
+        let purged = purge_trashed_content(
+            &pool,
+            Utc::now() - Duration::days(30),
+        )
+        .await
+        .expect("Failed to purge");
+        assert_eq!(purged, 1);
+
+        assert!(
+            get_trashed_content_by_id(&pool, expired.id)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            get_trashed_content_by_id(&pool, recent.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            get_content_by_id(&pool, live.id)
+                .await
+                .unwrap()
+                .is_some()
+        );
+    }
+}
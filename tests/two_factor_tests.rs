@@ -0,0 +1,355 @@
+mod common;
+
+#[cfg(test)]
+pub mod two_factor_tests {
+    use chrono::{Duration, Utc};
+    use sqlx::PgPool;
+
+    use super::common::*;
+
+    use rustpress::db::*;
+    use rustpress::models::*;
+    use rustpress::services::{TokenManager, TotpManager};
+
+    const NOW: u64 = 1_750_000_000;
+
+    async fn enroll(
+        pool: &PgPool,
+        user: &User,
+        codes: &[String],
+    ) -> String {
+        let secret = TotpManager::generate_secret();
+        start_totp_enrollment(pool, user.id, &secret)
+            .await
+            .expect("Failed to start enrollment")
+            .expect("Enrollment should start");
+        let hashes: Vec<String> = codes
+            .iter()
+            .map(|c| TotpManager::hash_recovery_code(c))
+            .collect();
+        assert!(
+            enable_totp(pool, user.id, 1, &hashes)
+                .await
+                .expect("Failed to enable 2FA")
+        );
+        secret
+    }
+
+    fn new_challenge(
+        user: &User,
+        token: &str,
+    ) -> LoginChallengeCreate {
+        LoginChallengeCreate {
+            user_id: user.id,
+            token_hash: TokenManager::hash(token),
+            expires_at: Utc::now() + Duration::minutes(10),
+        }
+    }
+
+    #[test]
+    fn test_totp_verify_at_success() {
+        let secret = TotpManager::generate_secret();
+        let code = TotpManager::generate_code_at(&secret, NOW)
+            .expect("Failed to generate code");
+
+        let step = TotpManager::verify_at(&secret, &code, NOW);
+        assert_eq!(step, Some((NOW / 30) as i64));
+
+        // One step of clock drift either way is tolerated.
+        assert!(
+            TotpManager::verify_at(&secret, &code, NOW + 30)
+                .is_some()
+        );
+        assert!(
+            TotpManager::verify_at(&secret, &code, NOW - 30)
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_totp_verify_at_fails_outside_window() {
+        let secret = TotpManager::generate_secret();
+        let code = TotpManager::generate_code_at(&secret, NOW)
+            .expect("Failed to generate code");
+
+        assert!(
+            TotpManager::verify_at(&secret, &code, NOW + 90)
+                .is_none()
+        );
+        assert!(
+            TotpManager::verify_at(&secret, "abcdef", NOW).is_none()
+        );
+        assert!(
+            TotpManager::verify_at(&secret, "12345", NOW).is_none()
+        );
+    }
+
+    #[test]
+    fn test_provisioning_uri_contains_secret() {
+        let secret = TotpManager::generate_secret();
+        let uri =
+            TotpManager::provisioning_uri(&secret, "admin@test.com")
+                .expect("Failed to build URI");
+
+        assert!(uri.starts_with("otpauth://totp/RustPress:"));
+        assert!(uri.contains(&format!("secret={secret}")));
+        assert!(
+            TotpManager::qr_svg(&uri)
+                .expect("Failed to render QR")
+                .contains("<svg")
+        );
+    }
+
+    #[test]
+    fn test_recovery_codes_are_unique_and_normalized() {
+        let codes = TotpManager::generate_recovery_codes();
+        assert_eq!(codes.len(), 10);
+
+        let mut unique = codes.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), codes.len());
+
+        let code = &codes[0];
+        assert_eq!(
+            TotpManager::hash_recovery_code(code),
+            TotpManager::hash_recovery_code(&format!(
+                " {} ",
+                code.to_uppercase().replace('-', "")
+            ))
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_enable_totp_success(pool: PgPool) {
+        let user = get_seed_user_0();
+        let secret = TotpManager::generate_secret();
+
+        let pending = start_totp_enrollment(&pool, user.id, &secret)
+            .await
+            .expect("Failed to start enrollment")
+            .expect("Enrollment should start");
+        assert!(!pending.is_enabled());
+
+        let enabled = enable_totp(&pool, user.id, 42, &[])
+            .await
+            .expect("Failed to enable 2FA");
+        assert!(enabled);
+
+        let totp = get_user_totp(&pool, user.id)
+            .await
+            .expect("Failed to get 2FA")
+            .expect("2FA should exist");
+        assert!(totp.is_enabled());
+        assert_eq!(totp.secret, secret);
+        assert_eq!(totp.last_used_step, Some(42));
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_start_enrollment_keeps_enabled_secret(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let secret = enroll(&pool, &user, &[]).await;
+
+        let restarted = start_totp_enrollment(
+            &pool,
+            user.id,
+            &TotpManager::generate_secret(),
+        )
+        .await
+        .expect("Failed to start enrollment");
+        assert!(restarted.is_none());
+
+        let totp = get_user_totp(&pool, user.id)
+            .await
+            .expect("Failed to get 2FA")
+            .expect("2FA should exist");
+        assert_eq!(totp.secret, secret);
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_record_totp_step_rejects_replay(pool: PgPool) {
+        let user = get_seed_user_0();
+        enroll(&pool, &user, &[]).await;
+
+        assert!(
+            record_totp_step(&pool, user.id, 100)
+                .await
+                .expect("Failed to record step")
+        );
+        assert!(
+            !record_totp_step(&pool, user.id, 100)
+                .await
+                .expect("Failed to record step"),
+            "A code must not be accepted twice"
+        );
+        assert!(
+            !record_totp_step(&pool, user.id, 99)
+                .await
+                .expect("Failed to record step")
+        );
+        assert!(
+            record_totp_step(&pool, user.id, 101)
+                .await
+                .expect("Failed to record step")
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_use_recovery_code_is_single_use(pool: PgPool) {
+        let user = get_seed_user_0();
+        let codes = TotpManager::generate_recovery_codes();
+        enroll(&pool, &user, &codes).await;
+
+        let hash = TotpManager::hash_recovery_code(&codes[0]);
+        assert!(
+            use_recovery_code(&pool, user.id, &hash)
+                .await
+                .expect("Failed to use recovery code")
+        );
+        assert!(
+            !use_recovery_code(&pool, user.id, &hash)
+                .await
+                .expect("Failed to use recovery code")
+        );
+        assert_eq!(
+            count_unused_recovery_codes(&pool, user.id)
+                .await
+                .expect("Failed to count codes"),
+            9
+        );
+
+        let other = get_seed_user_1();
+        let other_hash = TotpManager::hash_recovery_code(&codes[1]);
+        assert!(
+            !use_recovery_code(&pool, other.id, &other_hash)
+                .await
+                .expect("Failed to use recovery code"),
+            "Codes belong to one user"
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_replace_recovery_codes_invalidates_old(
+        pool: PgPool,
+    ) {
+        let user = get_seed_user_0();
+        let old = TotpManager::generate_recovery_codes();
+        enroll(&pool, &user, &old).await;
+
+        let new = TotpManager::generate_recovery_codes();
+        let hashes: Vec<String> = new
+            .iter()
+            .map(|c| TotpManager::hash_recovery_code(c))
+            .collect();
+        replace_recovery_codes(&pool, user.id, &hashes)
+            .await
+            .expect("Failed to replace codes");
+
+        assert!(
+            !use_recovery_code(
+                &pool,
+                user.id,
+                &TotpManager::hash_recovery_code(&old[0])
+            )
+            .await
+            .expect("Failed to use recovery code")
+        );
+        assert!(
+            use_recovery_code(
+                &pool,
+                user.id,
+                &TotpManager::hash_recovery_code(&new[0])
+            )
+            .await
+            .expect("Failed to use recovery code")
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_disable_totp_removes_codes(pool: PgPool) {
+        let user = get_seed_user_0();
+        enroll(&pool, &user, &TotpManager::generate_recovery_codes())
+            .await;
+
+        disable_totp(&pool, user.id)
+            .await
+            .expect("Failed to disable 2FA");
+
+        assert!(
+            get_user_totp(&pool, user.id)
+                .await
+                .expect("Failed to get 2FA")
+                .is_none()
+        );
+        assert_eq!(
+            count_unused_recovery_codes(&pool, user.id)
+                .await
+                .expect("Failed to count codes"),
+            0
+        );
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_login_challenge_attempts_are_limited(pool: PgPool) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+        create_login_challenge(&pool, &new_challenge(&user, &token))
+            .await
+            .expect("Failed to create challenge");
+
+        for attempt in 1..=3 {
+            let challenge = attempt_login_challenge(
+                &pool,
+                &TokenManager::hash(&token),
+                3,
+            )
+            .await
+            .expect("Failed to attempt challenge")
+            .expect("Challenge should be usable");
+            assert_eq!(challenge.attempts, attempt);
+            assert_eq!(challenge.user_id, user.id);
+        }
+
+        let exhausted = attempt_login_challenge(
+            &pool,
+            &TokenManager::hash(&token),
+            3,
+        )
+        .await
+        .expect("Failed to attempt challenge");
+        assert!(exhausted.is_none());
+    }
+
+    #[sqlx::test(migrations = "./tests/migrations")]
+    async fn test_login_challenge_fails_on_expired(pool: PgPool) {
+        let user = get_seed_user_0();
+        let token = TokenManager::generate();
+        let mut data = new_challenge(&user, &token);
+        data.expires_at = Utc::now() - Duration::minutes(1);
+        create_login_challenge(&pool, &data)
+            .await
+            .expect("Failed to create challenge");
+
+        let hash = TokenManager::hash(&token);
+        assert!(
+            get_login_challenge(&pool, &hash)
+                .await
+                .expect("Failed to get challenge")
+                .is_none()
+        );
+        assert!(
+            attempt_login_challenge(&pool, &hash, 5)
+                .await
+                .expect("Failed to attempt challenge")
+                .is_none()
+        );
+        assert_eq!(
+            delete_expired_login_challenges(&pool)
+                .await
+                .expect("Failed to delete challenges"),
+            1
+        );
+    }
+}
@@ -2,12 +2,10 @@ mod common;
 
 #[cfg(test)]
 pub mod user_tests {
-    use chrono::{DateTime, TimeZone, Utc};
     use uuid::Uuid;
 
     use super::common::*;
 
-    use rustpress::common::*;
     use rustpress::models::*;
 
     #[test]
@@ -164,7 +162,7 @@ pub mod user_tests {
 
     #[test]
     fn test_user_query_fields_success() {
-        let original_fields = UserQuery::fields().clone();
+        let original_fields = UserQuery::fields();
 
         assert_eq!(original_fields.len(), 8);
 